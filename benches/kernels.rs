@@ -0,0 +1,92 @@
+// compares the old kronecker-operator path with the in-place kernels
+// run with `cargo bench --bench kernels` (add `--features parallel` for the threaded loops)
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use ndarray::Array2;
+use num_complex::Complex64;
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::gates::{hadamard, pauli_x};
+use qucom_rs::kernels;
+use qucom_rs::states::zero_state;
+use qucom_rs::utils::kron;
+
+/// the pre-kernel `apply_gate`: build the full 2^n x 2^n operator and multiply
+fn kron_apply_gate(
+    state: &Array2<Complex64>,
+    gate: &Array2<Complex64>,
+    qubit_index: usize,
+    n_qubits: usize,
+) -> Array2<Complex64> {
+    let mut op = Array2::<Complex64>::from_shape_vec((1, 1), vec![Complex64::new(1.0, 0.0)]).unwrap();
+    let eye = Array2::<Complex64>::eye(2);
+
+    for j in (0..n_qubits).rev() {
+        let g = if j == qubit_index { gate } else { &eye };
+        op = kron(g, &op);
+    }
+
+    op.dot(state)
+}
+
+/// average time per call over `iters` runs
+fn time<F: FnMut()>(iters: u32, mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iters {
+        f();
+    }
+    start.elapsed() / iters
+}
+
+fn main() {
+    let h = hadamard();
+    let x = pauli_x();
+
+    println!("single-qubit hadamard on the middle qubit");
+    println!("{:>8} {:>16} {:>16}", "qubits", "kronecker", "kernel");
+    for n in [4, 6, 8, 10] {
+        let state = zero_state(n);
+        let old = time(5, || {
+            black_box(kron_apply_gate(&state, &h, n / 2, n));
+        });
+        let mut new_state = state.clone();
+        let new = time(100, || {
+            kernels::apply_single_qubit(&mut new_state, &h, n / 2, n);
+            black_box(&new_state);
+        });
+        println!("{:>8} {:>16?} {:>16?}", n, old, new);
+    }
+
+    println!();
+    println!("kernel only (kronecker operator no longer fits in memory)");
+    println!("{:>8} {:>16} {:>16}", "qubits", "single-qubit", "controlled");
+    for n in [14, 16, 18, 20] {
+        let mut state = zero_state(n);
+        let single = time(10, || {
+            kernels::apply_single_qubit(&mut state, &h, n / 2, n);
+            black_box(&state);
+        });
+        let controlled = time(10, || {
+            kernels::apply_controlled(&mut state, &x, &[0], &[n - 1], n);
+            black_box(&state);
+        });
+        println!("{:>8} {:>16?} {:>16?}", n, single, controlled);
+    }
+
+    println!();
+    println!("full executor: hadamard layer + cx ladder + measure");
+    println!("{:>8} {:>16}", "qubits", "execute");
+    for n in [10, 14, 16] {
+        let elapsed = time(3, || {
+            let mut circuit = QuantumCircuit::new(n);
+            circuit.h_multi(0..n).unwrap();
+            for q in 0..n - 1 {
+                circuit.cx(q, q + 1).unwrap();
+            }
+            circuit.measure().unwrap();
+            black_box(circuit.execute().unwrap());
+        });
+        println!("{:>8} {:>16?}", n, elapsed);
+    }
+}
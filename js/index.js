@@ -59,8 +59,18 @@ export class QuantumCircuit {
   resetAllQubits() { this._circuit.reset_all_qubits(); return this; }
   barrier(qs) { this._circuit.barrier(qs); return this; }
   barrierAll() { this._circuit.barrier_all(); return this; }
+  setBackend(name) { this._circuit.set_backend(name); return this; }
+  setSeed(seed) { this._circuit.set_seed(BigInt(seed)); return this; }
   execute() { return this._circuit.execute(); }
+  executeShots(shots) { return JSON.parse(this._circuit.execute_shots(shots)); }
+  executeShotsMemory(shots) { return this._circuit.execute_shots_memory(shots); }
   reset() { this._circuit.reset(); return this; }
+  probabilities() { return this._circuit.probabilities(); }
+  marginalProbabilities(qs) { return this._circuit.marginal_probabilities(qs); }
+  amplitude(bits) { return this._circuit.amplitude(bits); }
+  blochVector(q) { return this._circuit.bloch_vector(q); }
+  expectation(labels, coefficients) { return this._circuit.expectation(labels, coefficients); }
+  estimateExpectation(labels, coefficients, shots) { return this._circuit.estimate_expectation(labels, coefficients, shots); }
   isExecuted() { return this._circuit.is_executed(); }
   toQASM() { return this._circuit.to_qasm(); }
   static fromQASM(qasmString) { return wasm.QuantumCircuit.from_qasm(qasmString); }
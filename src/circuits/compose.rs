@@ -0,0 +1,136 @@
+use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
+use crate::qasm::generator::first_duplicate;
+use crate::qasm::generator::QasmInstruction;
+use crate::qasm::{ClassicalExpr, QasmGenerator};
+
+/// building circuits from other circuits
+///
+/// each operation returns a new circuit with this circuit's backend, noise model, seed stream
+/// and custom gates; `inverse`, `power` and `compose` keep its starting state as well
+impl QuantumCircuit {
+    /// U†: the gates in reverse order, each inverted (see `QasmInstruction::inverse`)
+    /// errors if the circuit measures, resets or branches
+    pub fn inverse(&self) -> Result<QuantumCircuit, QucomError> {
+        let instructions = self
+            .instructions()
+            .iter()
+            .rev()
+            .map(|instr| instr.inverse())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.with_instructions(instructions))
+    }
+
+    /// U^k: the instructions repeated `k` times, the inverse's for negative `k`,
+    /// nothing for `k = 0`
+    pub fn power(&self, k: i32) -> Result<QuantumCircuit, QucomError> {
+        let inverse;
+        let instructions = match k < 0 {
+            true => {
+                inverse = self.inverse()?;
+                inverse.instructions()
+            }
+            false => self.instructions(),
+        };
+        let count = instructions.len() * k.unsigned_abs() as usize;
+        Ok(self.with_instructions(instructions.iter().cycle().take(count).cloned().collect()))
+    }
+
+    /// this circuit followed by `other`, whose qubit `i` is mapped to `qubit_map[i]`
+    /// classical bits are shared, and `other`'s whole-register measure, reset and barrier
+    /// only touch the mapped qubits
+    pub fn compose(&self, other: &QuantumCircuit, qubit_map: &[usize]) -> Result<QuantumCircuit, QucomError> {
+        if qubit_map.len() != other.n {
+            return Err(QucomError::InvalidArgument(format!(
+                "qubit map has {} entries for a {}-qubit circuit", qubit_map.len(), other.n
+            )));
+        }
+        if let Some(&qubit) = qubit_map.iter().find(|&&q| q >= self.n) {
+            return Err(QucomError::InvalidQubit { qubit, n_qubits: self.n });
+        }
+        if let Some(q) = first_duplicate(qubit_map) {
+            return Err(QucomError::DuplicateQubit(q));
+        }
+
+        let embedded = embed_all(other.instructions(), qubit_map, 0);
+        for instr in &embedded {
+            instr.validate(self.n, self.n_bits())?;
+        }
+        let mut instructions = self.instructions().to_vec();
+        instructions.extend(embedded);
+        let mut composed = self.with_instructions(instructions);
+        for definition in other.gates.values() {
+            composed.define_gate(definition.as_ref().clone())?;
+        }
+        Ok(composed)
+    }
+
+    /// this circuit on qubits `0..n` alongside `other` on qubits `n..n + m`, starting from |0…0⟩;
+    /// `other`'s classical bits follow this circuit's. both circuits' registers carry over
+    /// when their names don't clash, otherwise the result has plain `q` and `c` registers
+    pub fn tensor(&self, other: &QuantumCircuit) -> Result<QuantumCircuit, QucomError> {
+        let n = self.n + other.n;
+        let other_qubits: Vec<usize> = (self.n..n).collect();
+        let mut instructions = embed_all(self.instructions(), &(0..self.n).collect::<Vec<_>>(), 0);
+        instructions.extend(embed_all(other.instructions(), &other_qubits, self.n_bits()));
+
+        let quantum: Vec<(&str, usize)> = self
+            .quantum_registers()
+            .iter()
+            .chain(other.quantum_registers())
+            .map(|r| (r.name(), r.size()))
+            .collect();
+        let classical: Vec<(&str, usize)> = self
+            .classical_registers()
+            .iter()
+            .chain(other.classical_registers())
+            .map(|r| (r.name(), r.size()))
+            .collect();
+        let mut tensor = QuantumCircuit::new(n);
+        tensor.qasm_generator = QasmGenerator::with_registers(&quantum, &classical)
+            .or_else(|_| QasmGenerator::with_registers(&[("q", n)], &[("c", self.n_bits() + other.n_bits())]))?;
+        tensor.backend = self.backend;
+        tensor.noise = self.noise.clone();
+        tensor.rng = self.rng.clone();
+        tensor.mps_config = self.mps_config;
+        tensor.gates = self.gates.clone();
+        for definition in other.gates.values() {
+            tensor.define_gate(definition.as_ref().clone())?;
+        }
+        for instr in instructions {
+            tensor.add_instruction(instr)?;
+        }
+        Ok(tensor)
+    }
+}
+
+/// `instructions` with qubit `i` moved to `qubits[i]` and classical bit `c` to `c + clbit_offset`
+fn embed_all(instructions: &[QasmInstruction], qubits: &[usize], clbit_offset: usize) -> Vec<QasmInstruction> {
+    instructions.iter().flat_map(|instr| embed(instr, qubits, clbit_offset)).collect()
+}
+
+fn embed(instr: &QasmInstruction, qubits: &[usize], clbit_offset: usize) -> Vec<QasmInstruction> {
+    let body = |block: &[QasmInstruction]| embed_all(block, qubits, clbit_offset);
+    let shift = |expr: &ClassicalExpr| expr.map_bits(&|c| c + clbit_offset);
+    match instr {
+        QasmInstruction::MeasureAll => {
+            qubits.iter().enumerate().map(|(i, &q)| QasmInstruction::Measure(q, i + clbit_offset)).collect()
+        }
+        QasmInstruction::ResetAll => qubits.iter().map(|&q| QasmInstruction::Reset(q)).collect(),
+        QasmInstruction::BarrierAll => vec![QasmInstruction::Barrier(qubits.to_vec())],
+        QasmInstruction::Measure(q, c) => vec![QasmInstruction::Measure(qubits[*q], c + clbit_offset)],
+        QasmInstruction::If(condition, block) => vec![QasmInstruction::If(shift(condition), body(block))],
+        QasmInstruction::IfElse(condition, if_block, else_block) => {
+            vec![QasmInstruction::IfElse(shift(condition), body(if_block), body(else_block))]
+        }
+        QasmInstruction::While(condition, block) => vec![QasmInstruction::While(shift(condition), body(block))],
+        QasmInstruction::Assign(name, value) => vec![QasmInstruction::Assign(name.clone(), shift(value))],
+        QasmInstruction::For(var, values, block) => vec![QasmInstruction::For(var.clone(), values.clone(), body(block))],
+        QasmInstruction::Switch(value, cases, default) => vec![QasmInstruction::Switch(
+            shift(value),
+            cases.iter().map(|(values, block)| (values.clone(), body(block))).collect(),
+            body(default),
+        )],
+        gate => vec![gate.map_qubits(&|q| qubits[q])],
+    }
+}
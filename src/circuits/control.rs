@@ -1,60 +1,95 @@
 use crate::circuits::QuantumCircuit;
-use crate::qasm::generator::QasmInstruction;
+use crate::error::QucomError;
+use crate::qasm::generator::{LoopRange, QasmInstruction};
+use crate::qasm::ClassicalExpr;
 
 impl QuantumCircuit {
 
     // reset the qubit at qubit_index to |0>
-    pub fn reset_qubit(&mut self, qubit_index: usize) -> &mut Self {
-        self.add_instruction(QasmInstruction::Reset(qubit_index));
-        self
+    pub fn reset_qubit(&mut self, qubit_index: usize) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::Reset(qubit_index))
     }
     
     /// reset all qubits to |0>
-    pub fn reset_all_qubits(&mut self) -> &mut Self {
-        self.add_instruction(QasmInstruction::ResetAll);
-        self
+    pub fn reset_all_qubits(&mut self) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::ResetAll)
     }
     
     /// apply a barrier to the specified qubits
-    pub fn barrier(&mut self, qubits: &[usize]) -> &mut Self {
-        self.add_instruction(QasmInstruction::Barrier(qubits.to_vec()));
-        self
+    pub fn barrier(&mut self, qubits: &[usize]) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::Barrier(qubits.to_vec()))
     }
     
     /// apply a barrier to all qubits
-    pub fn barrier_all(&mut self) -> &mut Self {
-        self.add_instruction(QasmInstruction::BarrierAll);
-        self
+    pub fn barrier_all(&mut self) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::BarrierAll)
     }
     
     /// apply a delay to a qubit for a specified duration and unit
-    pub fn delay(&mut self, duration: f64, unit: &str, qubit_index: usize) -> &mut Self {
-        self.add_instruction(QasmInstruction::Delay(duration, unit.to_string(), qubit_index));
-        self
+    pub fn delay(&mut self, duration: f64, unit: &str, qubit_index: usize) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::Delay(duration, unit.to_string(), qubit_index))
     }
     
     /// if statement
-    pub fn if_eq(&mut self, classical_bit: usize, value: usize, instructions: Vec<QasmInstruction>) -> &mut Self {
-        self.add_instruction(QasmInstruction::If(classical_bit, value, instructions));
-        self
+    pub fn if_eq(&mut self, classical_bit: usize, value: usize, instructions: Vec<QasmInstruction>) -> Result<&mut Self, QucomError> {
+        self.if_condition(ClassicalExpr::bit_eq(classical_bit, value), instructions)
     }
     
     /// if-else statement
-    pub fn if_else(&mut self, classical_bit: usize, value: usize, if_block: Vec<QasmInstruction>, else_block: Vec<QasmInstruction>) -> &mut Self {
-        self.add_instruction(QasmInstruction::IfElse(classical_bit, value, if_block, else_block));
-        self
+    pub fn if_else(&mut self, classical_bit: usize, value: usize, if_block: Vec<QasmInstruction>, else_block: Vec<QasmInstruction>) -> Result<&mut Self, QucomError> {
+        self.if_else_condition(ClassicalExpr::bit_eq(classical_bit, value), if_block, else_block)
     }
     
     /// while loop
-    pub fn while_eq(&mut self, classical_bit: usize, value: usize, body: Vec<QasmInstruction>) -> &mut Self {
-        self.add_instruction(QasmInstruction::While(classical_bit, value, body));
-        self
+    pub fn while_eq(&mut self, classical_bit: usize, value: usize, body: Vec<QasmInstruction>) -> Result<&mut Self, QucomError> {
+        self.while_condition(ClassicalExpr::bit_eq(classical_bit, value), body)
+    }
+
+    /// if statement on any classical condition, e.g. a whole register compared to a value
+    pub fn if_condition(&mut self, condition: ClassicalExpr, instructions: Vec<QasmInstruction>) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::If(condition, instructions))
+    }
+
+    /// if-else statement on any classical condition
+    pub fn if_else_condition(&mut self, condition: ClassicalExpr, if_block: Vec<QasmInstruction>, else_block: Vec<QasmInstruction>) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::IfElse(condition, if_block, else_block))
+    }
+
+    /// while loop on any classical condition
+    pub fn while_condition(&mut self, condition: ClassicalExpr, body: Vec<QasmInstruction>) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::While(condition, body))
+    }
+
+    /// set a classical variable, e.g. a counter for repeat-until-success
+    pub fn assign(&mut self, name: &str, value: ClassicalExpr) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::Assign(name.to_string(), value))
     }
     
-    /// for loop
-    pub fn for_loop(&mut self, var_name: &str, start: usize, end: usize, body: Vec<QasmInstruction>) -> &mut Self {
-        self.add_instruction(QasmInstruction::For(var_name.to_string(), start, end, body));
-        self
+    /// for loop, the variable going from start to end (exclusive)
+    pub fn for_loop(&mut self, var_name: &str, start: usize, end: usize, body: Vec<QasmInstruction>) -> Result<&mut Self, QucomError> {
+        let range = LoopRange::Range { start: start as i64, step: 1, end: end as i64 - 1 };
+        self.add_instruction(QasmInstruction::For(var_name.to_string(), range, body))
+    }
+
+    /// for loop over any values, e.g. a stepped range `(0..10).step_by(3)` or a set `[1, 3, 5]`
+    pub fn for_values(&mut self, var_name: &str, values: Vec<i64>, body: Vec<QasmInstruction>) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::For(var_name.to_string(), LoopRange::Set(values), body))
+    }
+
+    /// run the body of the first case listing the value of `value`, or else `default`;
+    /// `QasmInstruction::Break` and `Continue` in a body act on the enclosing loop
+    pub fn switch(
+        &mut self,
+        value: ClassicalExpr,
+        cases: Vec<(Vec<i64>, Vec<QasmInstruction>)>,
+        default: Vec<QasmInstruction>,
+    ) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::Switch(value, cases, default))
+    }
+
+    /// stop the program here
+    pub fn end(&mut self) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::End)
     }
 }
 
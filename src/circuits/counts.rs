@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// histogram of measurement outcomes over many shots, returned by `QuantumCircuit::execute_shots`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Counts {
+    counts: BTreeMap<String, usize>,
+    memory: Option<Vec<String>>,
+    shots: usize,
+}
+
+impl Counts {
+    /// empty histogram; with `memory` every shot's outcome is also kept in order
+    pub fn new(memory: bool) -> Self {
+        Self { counts: BTreeMap::new(), memory: memory.then(Vec::new), shots: 0 }
+    }
+
+    /// add one shot
+    pub fn record(&mut self, outcome: String) {
+        *self.counts.entry(outcome.clone()).or_insert(0) += 1;
+        if let Some(memory) = &mut self.memory {
+            memory.push(outcome);
+        }
+        self.shots += 1;
+    }
+
+    /// how many shots produced `outcome`
+    pub fn get(&self, outcome: &str) -> usize {
+        self.counts.get(outcome).copied().unwrap_or(0)
+    }
+
+    pub fn shots(&self) -> usize {
+        self.shots
+    }
+
+    /// per-shot outcomes in execution order, if memory was requested
+    pub fn memory(&self) -> Option<&[String]> {
+        self.memory.as_deref()
+    }
+
+    /// distinct outcomes and their counts, sorted by outcome
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.counts.iter().map(|(outcome, count)| (outcome.as_str(), *count))
+    }
+
+    /// number of distinct outcomes
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// the outcome seen most often (ties go to the smallest bitstring)
+    pub fn most_frequent(&self) -> Option<&str> {
+        self.iter()
+            .fold(None, |best: Option<(&str, usize)>, (outcome, count)| match best {
+                Some((_, c)) if c >= count => best,
+                _ => Some((outcome, count)),
+            })
+            .map(|(outcome, _)| outcome)
+    }
+
+    /// relative frequency of every outcome
+    pub fn probabilities(&self) -> BTreeMap<String, f64> {
+        self.counts
+            .iter()
+            .map(|(outcome, count)| (outcome.clone(), *count as f64 / self.shots as f64))
+            .collect()
+    }
+}
+
+/// `{"00": 503, "11": 497}`
+impl fmt::Display for Counts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, (outcome, count)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "\"{}\": {}", outcome, count)?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,93 @@
+use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
+use crate::kernels;
+use crate::observables::{Pauli, SparsePauliOp};
+use crate::qasm::generator::QasmInstruction;
+use crate::simulators::Backend;
+
+impl QuantumCircuit {
+    /// exact ⟨ψ|O|ψ⟩ on the state left by `execute` (Tr(ρO) on the density matrix backend)
+    /// returns `None` before `execute` and on the stabilizer backend, and an error when `op`
+    /// acts on qubits outside the circuit
+    pub fn expectation(&self, op: &SparsePauliOp) -> Result<Option<f64>, QucomError> {
+        if op.min_qubits() > self.n {
+            return Err(QucomError::InvalidQubit { qubit: op.min_qubits() - 1, n_qubits: self.n });
+        }
+        let value = |pauli_expectation: &dyn Fn(usize, usize, usize) -> f64| {
+            op.terms()
+                .iter()
+                .map(|(c, pauli)| {
+                    let (x, z, ny) = pauli.masks(self.n);
+                    c * pauli_expectation(x, z, ny)
+                })
+                .sum()
+        };
+
+        Ok(match self.resolved_backend() {
+            Backend::DensityMatrix => self
+                .density
+                .as_ref()
+                .map(|sim| value(&|x, z, ny| sim.pauli_expectation(x, z, ny))),
+            Backend::MatrixProductState => self.mps.as_ref().map(|sim| {
+                let state = sim.to_statevector();
+                value(&|x, z, ny| kernels::pauli_expectation(&state, x, z, ny))
+            }),
+            _ => self
+                .state
+                .as_ref()
+                .map(|state| value(&|x, z, ny| kernels::pauli_expectation(state, x, z, ny))),
+        })
+    }
+
+    /// estimate ⟨O⟩ from `shots` samples per measurement basis, like hardware would
+    ///
+    /// terms are grouped into qubit-wise commuting sets; each set gets one copy of the circuit
+    /// with basis rotations (H for X, Sdg then H for Y) and measurements appended. a term's
+    /// value is the average parity of its measured qubits. the circuit's own state is untouched
+    pub fn estimate_expectation(&self, op: &SparsePauliOp, shots: usize) -> Result<f64, QucomError> {
+        if op.min_qubits() > self.n {
+            return Err(QucomError::InvalidQubit { qubit: op.min_qubits() - 1, n_qubits: self.n });
+        }
+        if shots == 0 {
+            return Err(QucomError::InvalidArgument("need at least one shot".to_string()));
+        }
+
+        let identity: f64 = op.terms().iter().filter(|(_, p)| p.is_identity()).map(|(c, _)| c).sum();
+        let mut total = identity;
+
+        for (basis, members) in op.measurement_groups() {
+            let mut program = self.qasm_generator.instructions().to_vec();
+            for &(q, p) in basis.paulis() {
+                match p {
+                    Pauli::X => program.push(QasmInstruction::H(q)),
+                    Pauli::Y => program.extend([QasmInstruction::S(q, true), QasmInstruction::H(q)]),
+                    Pauli::I | Pauli::Z => {}
+                }
+            }
+            let measured = basis.qubits();
+            program.extend(measured.iter().map(|&q| QasmInstruction::Measure(q, q)));
+
+            let counts = self.shots_of(&program, shots, false)?;
+            for k in members {
+                let (coefficient, pauli) = &op.terms()[k];
+                let positions: Vec<usize> = pauli
+                    .qubits()
+                    .iter()
+                    .map(|q| measured.iter().position(|m| m == q).unwrap())
+                    .collect();
+                let parity_sum: f64 = counts
+                    .iter()
+                    .map(|(outcome, count)| {
+                        // the basis measurements are the last bits of every outcome
+                        let bits = &outcome.as_bytes()[outcome.len() - measured.len()..];
+                        let ones = positions.iter().filter(|&&i| bits[i] == b'1').count();
+                        if ones.is_multiple_of(2) { count as f64 } else { -(count as f64) }
+                    })
+                    .sum();
+                total += coefficient * parity_sum / shots as f64;
+            }
+        }
+
+        Ok(total)
+    }
+}
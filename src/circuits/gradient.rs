@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_2, PI};
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
+use crate::gates::{phase, rx, ry, rz, u_gate, GateOperation};
+use crate::kernels;
+use crate::observables::SparsePauliOp;
+use crate::parameters::{Parameter, ParameterExpression};
+use crate::qasm::generator::QasmInstruction;
+use crate::simulators::Backend;
+use crate::states::multi_qubit::zero_state;
+
+/// gradients of ⟨O⟩ with respect to the circuit's parameters
+///
+/// both methods take `values` in the order of `parameters()` and return ∂⟨O⟩/∂p in that order.
+/// a parameter's derivative sums the chain rule over every angle expression it appears in.
+/// parameters inside control-flow bodies are rejected, their gates may run any number of times
+impl QuantumCircuit {
+    /// parameter-shift rule: every symbolic angle θ contributes (⟨O⟩(θ + π/2) - ⟨O⟩(θ - π/2)) / 2,
+    /// which is exact for rotation, phase and U gates but not their controlled versions. with `shots` each shifted circuit is
+    /// estimated from samples like `estimate_expectation`, otherwise it is executed exactly
+    pub fn parameter_shift_gradient(
+        &self,
+        op: &SparsePauliOp,
+        values: &[f64],
+        shots: Option<usize>,
+    ) -> Result<Vec<f64>, QucomError> {
+        let (parameters, bound) = self.differentiable_program(values)?;
+        let instructions = self.qasm_generator.instructions();
+        if let Some(instr) = instructions.iter().find(|instr| {
+            matches!(instr, QasmInstruction::Parameterized(gate, _) if matches!(gate.as_ref(), QasmInstruction::Controlled(..)))
+        }) {
+            // the generator of a controlled rotation has three eigenvalues, two shifts are not enough
+            return Err(QucomError::Unsupported(format!(
+                "the parameter-shift rule does not hold for `{}`, use adjoint_gradient", instr
+            )));
+        }
+        let mut gradient = vec![0.0; parameters.len()];
+
+        for (k, instr) in instructions.iter().enumerate() {
+            let QasmInstruction::Parameterized(_, slots) = instr else { continue };
+            for (slot, expr) in slots {
+                let shifted = |shift: f64| {
+                    let mut program = bound.clone();
+                    let mut angles = program[k].angles();
+                    angles[*slot] += shift;
+                    program[k] = program[k].with_angles(&angles);
+                    self.expectation_of(program, op, shots)
+                };
+                let derivative = (shifted(FRAC_PI_2)? - shifted(-FRAC_PI_2)?) / 2.0;
+                accumulate(&mut gradient, &parameters, expr, derivative);
+            }
+        }
+
+        Ok(gradient)
+    }
+
+    /// adjoint differentiation: one forward pass to the final state, then one backward pass
+    /// un-applying each gate from both |ψ⟩ and O|ψ⟩, so the cost is a few circuit executions
+    /// whatever the number of parameters. statevector only, and the circuit must be unitary
+    /// (no measurement, reset, control flow or noise). starts from the circuit's starting state
+    pub fn adjoint_gradient(&self, op: &SparsePauliOp, values: &[f64]) -> Result<Vec<f64>, QucomError> {
+        if op.min_qubits() > self.n {
+            return Err(QucomError::InvalidQubit { qubit: op.min_qubits() - 1, n_qubits: self.n });
+        }
+        // an automatic choice is free to simulate the statevector; a chosen backend is not
+        if !matches!(self.backend, Backend::Statevector | Backend::Automatic) {
+            return Err(QucomError::Unsupported(format!(
+                "adjoint differentiation needs the statevector backend, not {:?}", self.backend
+            )));
+        }
+        if self.noise.as_ref().is_some_and(|noise| !noise.is_ideal()) {
+            return Err(QucomError::Unsupported("adjoint differentiation needs a noiseless circuit".to_string()));
+        }
+
+        let (parameters, bound) = self.differentiable_program(values)?;
+        let instructions = self.qasm_generator.instructions();
+        // each operation remembers the program index of the symbolic gate it came from
+        let mut operations = Vec::new();
+        for (k, bound_instr) in bound.iter().enumerate() {
+            lower_unitary(bound_instr, Some(k), &mut operations)?;
+        }
+
+        let mut state = self.state.clone().unwrap_or_else(|| zero_state(self.n));
+        for (operation, _) in &operations {
+            operation.apply(&mut state, self.n);
+        }
+
+        // λ = O|ψ⟩
+        let mut lambda = Array2::<Complex64>::zeros(state.raw_dim());
+        for (c, pauli) in op.terms() {
+            let (x, z, ny) = pauli.masks(self.n);
+            lambda.scaled_add(Complex64::new(*c, 0.0), &kernels::apply_pauli(&state, x, z, ny));
+        }
+
+        let mut gradient = vec![0.0; parameters.len()];
+        for (operation, k) in operations.iter().rev() {
+            let inverse = operation.adjoint();
+            inverse.apply(&mut state, self.n);
+            if let Some(k) = *k
+                && let QasmInstruction::Parameterized(_, slots) = &instructions[k]
+            {
+                for (slot, expr) in slots {
+                    // ∂⟨O⟩/∂θ = 2 Re ⟨λ|∂U/∂θ|ψ⟩ with |ψ⟩ the state just before the gate
+                    let derivative = GateOperation { matrix: angle_derivative(uncontrolled(&bound[k]), *slot), ..operation.clone() };
+                    let mut derivative_state = state.clone();
+                    derivative.apply(&mut derivative_state, self.n);
+                    if !operation.controls.is_empty() {
+                        // the kernel leaves amplitudes whose controls don't match as they are,
+                        // where the derivative of a controlled gate is zero
+                        let mut unmatched = state.clone();
+                        GateOperation { matrix: Array2::zeros(derivative.matrix.raw_dim()), ..operation.clone() }
+                            .apply(&mut unmatched, self.n);
+                        derivative_state -= &unmatched;
+                    }
+                    let overlap: Complex64 = lambda.iter().zip(derivative_state.iter()).map(|(l, d)| l.conj() * d).sum();
+                    accumulate(&mut gradient, &parameters, expr, 2.0 * overlap.re);
+                }
+            }
+            inverse.apply(&mut lambda, self.n);
+        }
+
+        Ok(gradient)
+    }
+
+    /// the parameters and the program bound to `values`, checking every parameter is differentiable
+    fn differentiable_program(&self, values: &[f64]) -> Result<(Vec<Parameter>, Vec<QasmInstruction>), QucomError> {
+        let parameters = self.parameters();
+        if parameters.len() != values.len() {
+            return Err(QucomError::InvalidArgument(format!(
+                "expected {} parameter values, got {}", parameters.len(), values.len()
+            )));
+        }
+        let instructions = self.qasm_generator.instructions();
+        for instr in instructions {
+            match instr {
+                // controlled or not, the angles of a custom gate's body are only known numerically
+                QasmInstruction::Parameterized(gate, _) if matches!(uncontrolled(gate), QasmInstruction::GateCall(..)) => {
+                    return Err(QucomError::Unsupported(format!(
+                        "cannot differentiate through the arguments of custom gate '{}'", gate.name()
+                    )));
+                }
+                QasmInstruction::Parameterized(..) => {}
+                _ if !instr.parameters().is_empty() => {
+                    return Err(QucomError::Unsupported(format!(
+                        "cannot differentiate parameters inside control flow: {}", instr.name()
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        let values: HashMap<String, f64> =
+            parameters.iter().map(|p| p.name().to_string()).zip(values.iter().copied()).collect();
+        let bound = instructions.iter().map(|instr| instr.bind(&values)).collect::<Result<Vec<_>, _>>()?;
+        Ok((parameters, bound))
+    }
+
+    /// ⟨O⟩ after running `program` from the circuit's starting state
+    fn expectation_of(&self, program: Vec<QasmInstruction>, op: &SparsePauliOp, shots: Option<usize>) -> Result<f64, QucomError> {
+        let mut circuit = self.with_instructions(program);
+        match shots {
+            Some(shots) => circuit.estimate_expectation(op, shots),
+            None => {
+                circuit.execute()?;
+                circuit
+                    .expectation(op)?
+                    .ok_or_else(|| QucomError::Unsupported("the stabilizer backend keeps no amplitudes".to_string()))
+            }
+        }
+    }
+}
+
+/// gate operations of a bound instruction, custom gates expanded and barriers and delays dropped
+fn lower_unitary(instr: &QasmInstruction, index: Option<usize>, operations: &mut Vec<(GateOperation, Option<usize>)>) -> Result<(), QucomError> {
+    match instr {
+        QasmInstruction::Barrier(_) | QasmInstruction::BarrierAll | QasmInstruction::Delay(..) => {}
+        other if let Some(body) = other.expand() => {
+            for inner in &body {
+                lower_unitary(inner, None, operations)?;
+            }
+        }
+        other => {
+            let operation = GateOperation::from_instruction(other)
+                .ok_or_else(|| QucomError::NonUnitary(format!(
+                    "adjoint differentiation needs a unitary circuit, found `{}`", other
+                )))?;
+            operations.push((operation, index));
+        }
+    }
+    Ok(())
+}
+
+/// the gate inside any controls
+fn uncontrolled(gate: &QasmInstruction) -> &QasmInstruction {
+    match gate {
+        QasmInstruction::Controlled(gate, _, _) => uncontrolled(gate),
+        gate => gate,
+    }
+}
+
+/// add `derivative` (with respect to the angle `expr`) to every parameter of `expr`
+fn accumulate(gradient: &mut [f64], parameters: &[Parameter], expr: &ParameterExpression, derivative: f64) {
+    for (g, p) in gradient.iter_mut().zip(parameters) {
+        *g += expr.coefficient(p) * derivative;
+    }
+}
+
+/// ∂U/∂θ for angle `slot` of a bound rotation gate
+fn angle_derivative(gate: &QasmInstruction, slot: usize) -> Array2<Complex64> {
+    let i = Complex64::i();
+    match (gate, slot) {
+        // exp(-iθG/2) with G² = I: the derivative -iG/2·U(θ) equals U(θ + π)/2
+        (QasmInstruction::Rx(a, _), 0) => rx(a + PI) * 0.5,
+        (QasmInstruction::Ry(a, _), 0) => ry(a + PI) * 0.5,
+        (QasmInstruction::Rz(a, _), 0) => rz(a + PI) * 0.5,
+        (QasmInstruction::U(theta, phi, lambda, _), 0) => u_gate(theta + PI, *phi, *lambda) * 0.5,
+        // φ scales the bottom row by e^{iφ}, λ the right column by e^{iλ}
+        (QasmInstruction::U(theta, phi, lambda, _), 1) => {
+            let mut d = u_gate(*theta, *phi, *lambda);
+            d.row_mut(0).fill(Complex64::new(0.0, 0.0));
+            d.row_mut(1).mapv_inplace(|c| c * i);
+            d
+        }
+        (QasmInstruction::U(theta, phi, lambda, _), 2) => {
+            let mut d = u_gate(*theta, *phi, *lambda);
+            d.column_mut(0).fill(Complex64::new(0.0, 0.0));
+            d.column_mut(1).mapv_inplace(|c| c * i);
+            d
+        }
+        (QasmInstruction::Phase(a, _), 0) => {
+            let mut d = phase(*a);
+            d[[0, 0]] = Complex64::new(0.0, 0.0);
+            d[[1, 1]] *= i;
+            d
+        }
+        _ => panic!("{} has no angle {}", gate.name(), slot),
+    }
+}
@@ -2,18 +2,49 @@ pub use crate::gates::apply_controlled_gate;
 pub use crate::gates::apply_gate;
 pub mod circuit;
 pub mod control;
+pub mod counts;
+pub mod queries;
+pub mod expectation;
+pub mod gradient;
+pub mod compose;
+pub mod register;
+
+pub use counts::Counts;
+pub use register::{ClassicalRegister, QuantumRegister};
 
 use ndarray::{Array2};
 use num_complex::Complex64;
 
 use crate::states::multi_qubit::zero_state;
-use crate::qasm::{QasmGenerator, QasmExecutor, QasmParser};
-use crate::qasm::generator::QasmInstruction;
+use crate::qasm::{IncludeResolver, QasmGenerator, QasmExecutor, QasmParser, DEFAULT_ITERATION_LIMIT};
+use crate::qasm::lower;
+use crate::qasm::generator::{classical_variables, QasmInstruction};
+use crate::simulators::{
+    Backend, DensityMatrixSimulator, MpsConfig, MpsSimulator, NoisySimulator, Simulator, SimulatorRng,
+    StabilizerSimulator, StatevectorSimulator,
+};
+use rand::RngCore;
+use crate::noise::NoiseModel;
+use crate::error::QucomError;
+use crate::parameters::Parameter;
+use crate::qasm::GateDefinition;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 pub struct QuantumCircuit {
     pub n: usize,
     qasm_generator: QasmGenerator,
+    pub(crate) gates: HashMap<String, Arc<GateDefinition>>,
     state: Option<Array2<Complex64>>,
+    density: Option<DensityMatrixSimulator>,
+    stabilizer: Option<StabilizerSimulator>,
+    mps: Option<MpsSimulator>,
+    mps_config: MpsConfig,
+    backend: Backend,
+    noise: Option<NoiseModel>,
+    rng: SimulatorRng,
+    /// most `while` iterations one run or shot may take
+    iteration_limit: usize,
     executed: bool,
 }
 
@@ -22,11 +53,118 @@ impl QuantumCircuit {
         Self {
             n: n_qubits,
             qasm_generator: QasmGenerator::new(n_qubits),
+            gates: HashMap::new(),
             state: None,
+            density: None,
+            stabilizer: None,
+            mps: None,
+            mps_config: MpsConfig::default(),
+            backend: Backend::default(),
+            noise: None,
+            rng: SimulatorRng::default(),
+            iteration_limit: DEFAULT_ITERATION_LIMIT,
             executed: false,
         }
     }
 
+    /// choose the simulation backend used by `execute`
+    pub fn set_backend(&mut self, backend: Backend) -> &mut Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// the backend `execute` will actually run, resolving `Backend::Automatic`
+    pub fn resolved_backend(&self) -> Backend {
+        match self.backend {
+            Backend::Automatic => {
+                if self.noise.is_none() && StabilizerSimulator::supports(self.qasm_generator.instructions()) {
+                    Backend::Stabilizer
+                } else {
+                    Backend::Statevector
+                }
+            }
+            backend => backend,
+        }
+    }
+
+    /// stabilizer generators after `execute` on the stabilizer backend
+    pub fn stabilizers(&self) -> Option<Vec<String>> {
+        self.stabilizer.as_ref().map(|sim| sim.stabilizers())
+    }
+
+    /// draw measurement outcomes from a stream seeded with `seed`, so every run of the same
+    /// circuit with the same seed (native or wasm) gives the same results
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = SimulatorRng::seed_from_u64(seed);
+        self
+    }
+
+    /// draw measurement outcomes from a user-supplied generator
+    pub fn set_rng<R: RngCore + Send + 'static>(&mut self, rng: R) -> &mut Self {
+        self.rng = SimulatorRng::from_rng(rng);
+        self
+    }
+
+    /// bond dimension and truncation threshold for the matrix product state backend
+    pub fn set_mps_config(&mut self, config: MpsConfig) -> &mut Self {
+        self.mps_config = config;
+        self.mps = None;
+        self
+    }
+
+    /// stop a run or shot with `IterationLimit` once its `while` loops take more than `limit` iterations
+    pub fn set_iteration_limit(&mut self, limit: usize) -> &mut Self {
+        self.iteration_limit = limit;
+        self
+    }
+
+    pub fn iteration_limit(&self) -> usize {
+        self.iteration_limit
+    }
+
+    /// accumulated truncation error after `execute` on the matrix product state backend
+    pub fn truncation_error(&self) -> Option<f64> {
+        self.mps.as_ref().map(|sim| sim.truncation_error())
+    }
+
+    /// simulate with noise; the statevector backend samples one trajectory per `execute`,
+    /// the density matrix backend applies the channels exactly
+    pub fn set_noise_model(&mut self, noise: NoiseModel) -> &mut Self {
+        self.noise = Some(noise);
+        self
+    }
+
+    pub fn noise_model(&self) -> Option<&NoiseModel> {
+        self.noise.as_ref()
+    }
+
+    pub fn clear_noise_model(&mut self) -> &mut Self {
+        self.noise = None;
+        self
+    }
+
+    /// start from a (possibly mixed) density matrix; switches to the density matrix backend
+    pub fn initialize_density_matrix(&mut self, rho: &Array2<Complex64>) -> Result<&mut Self, QucomError> {
+        let dim = 1 << self.n;
+        if rho.dim() != (dim, dim) {
+            return Err(QucomError::InvalidArgument(format!(
+                "a {}-qubit density matrix is {}x{}, not {}x{}", self.n, dim, dim, rho.nrows(), rho.ncols()
+            )));
+        }
+        self.density = Some(DensityMatrixSimulator::from_density_matrix(rho));
+        self.backend = Backend::DensityMatrix;
+        Ok(self)
+    }
+
+    /// density matrix after `execute` on the density matrix backend
+    pub fn density_matrix(&self) -> Option<Array2<Complex64>> {
+        self.density.as_ref().map(|sim| sim.density_matrix())
+    }
+
     pub fn state(&self) -> Option<&Array2<Complex64>> {
         self.state.as_ref()
     }
@@ -35,28 +173,197 @@ impl QuantumCircuit {
         self.state.as_mut()
     }
 
+    /// the instructions added so far
+    pub fn instructions(&self) -> &[QasmInstruction] {
+        self.qasm_generator.instructions()
+    }
+
+    /// export as OpenQASM 3.0; see `QasmGenerator::to_qasm` for how unitaries are written
     pub fn to_qasm(&self) -> String {
         self.qasm_generator.to_qasm()
     }
 
-    pub fn execute(&mut self) -> Vec<String> {
-        if self.state.is_none() {
-            self.state = Some(zero_state(self.n));
+    /// export as OpenQASM `version` (2 or 3)
+    pub fn to_qasm_version(&self, version: u8) -> Result<String, QucomError> {
+        self.qasm_generator.to_qasm_version(version)
+    }
+
+    /// unbound parameters in order of first use
+    pub fn parameters(&self) -> Vec<Parameter> {
+        self.qasm_generator.parameters()
+    }
+
+    /// concrete copy of the circuit with a value for every parameter, keyed by name
+    /// backend, noise model, seed stream and starting state carry over
+    pub fn bind_parameters(&self, values: &HashMap<String, f64>) -> Result<QuantumCircuit, QucomError> {
+        let instructions = self.qasm_generator.instructions();
+        // classical variables stay symbolic, the executor binds them as it runs
+        let variables = classical_variables(instructions);
+        let instructions = instructions
+            .iter()
+            .map(|instr| instr.bind_except(values, &variables))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.with_instructions(instructions))
+    }
+
+    /// like `bind_parameters`, with values in the order of `parameters()`
+    pub fn bind_parameter_values(&self, values: &[f64]) -> Result<QuantumCircuit, QucomError> {
+        let parameters = self.parameters();
+        if parameters.len() != values.len() {
+            return Err(QucomError::InvalidArgument(format!(
+                "expected {} parameter values, got {}", parameters.len(), values.len()
+            )));
         }
-        
-        let measurements = QasmExecutor::execute(
-            self.qasm_generator.instructions(),
-            self.state.as_mut().unwrap(),
-            self.n,
-        );
+        let values = parameters.iter().map(|p| p.name().to_string()).zip(values.iter().copied()).collect();
+        self.bind_parameters(&values)
+    }
+
+    /// a new circuit with the same settings and starting state running `instructions`
+    pub(crate) fn with_instructions(&self, instructions: Vec<QasmInstruction>) -> QuantumCircuit {
+        let mut qasm_generator = self.qasm_generator.cleared();
+        for instr in instructions {
+            qasm_generator.add_instruction(instr);
+        }
+        QuantumCircuit {
+            n: self.n,
+            qasm_generator,
+            gates: self.gates.clone(),
+            state: self.state.clone(),
+            density: self.density.clone(),
+            stabilizer: self.stabilizer.clone(),
+            mps: self.mps.clone(),
+            mps_config: self.mps_config,
+            backend: self.backend,
+            noise: self.noise.clone(),
+            rng: self.rng.clone(),
+            iteration_limit: self.iteration_limit,
+            executed: false,
+        }
+    }
+
+    /// run the program on the chosen backend and return the measurement results
+    /// errors (leaving the state as it was) on unbound parameters or gates the backend can't run
+    pub fn execute(&mut self) -> Result<Vec<String>, QucomError> {
+        let (instructions, limit) = (self.qasm_generator.instructions(), self.iteration_limit);
+        let measurements = match self.resolved_backend() {
+            Backend::Statevector => {
+                let state = self.state.take().unwrap_or_else(|| zero_state(self.n));
+                let mut sim = StatevectorSimulator::from_state(state, self.n);
+                sim.set_rng(self.rng.clone());
+                let measurements = match &self.noise {
+                    Some(noise) => QasmExecutor::run_with_limit(instructions, &mut NoisySimulator::new(&mut sim, noise), limit),
+                    None => QasmExecutor::run_with_limit(instructions, &mut sim, limit),
+                };
+                self.state = Some(sim.into_state());
+                measurements?
+            }
+            Backend::DensityMatrix => {
+                let n = self.n;
+                let sim = self.density.get_or_insert_with(|| DensityMatrixSimulator::new(n));
+                sim.set_rng(self.rng.clone());
+                match &self.noise {
+                    Some(noise) => QasmExecutor::run_with_limit(instructions, &mut NoisySimulator::new(sim, noise), limit)?,
+                    None => QasmExecutor::run_with_limit(instructions, sim, limit)?,
+                }
+            }
+            Backend::Stabilizer => {
+                self.check_noiseless()?;
+                let n = self.n;
+                let sim = self.stabilizer.get_or_insert_with(|| StabilizerSimulator::new(n));
+                sim.set_rng(self.rng.clone());
+                QasmExecutor::run_with_limit(instructions, sim, limit)?
+            }
+            Backend::MatrixProductState => {
+                self.check_noiseless()?;
+                let (n, config) = (self.n, self.mps_config);
+                let sim = self.mps.get_or_insert_with(|| MpsSimulator::with_config(n, config));
+                sim.set_rng(self.rng.clone());
+                QasmExecutor::run_with_limit(instructions, sim, limit)?
+            }
+            Backend::Automatic => unreachable!("resolved_backend never returns Automatic"),
+        };
         
         self.executed = true;
-        measurements
+        Ok(measurements)
+    }
+
+    /// the stabilizer and MPS backends have no noise channels
+    fn check_noiseless(&self) -> Result<(), QucomError> {
+        match self.noise {
+            Some(_) => Err(QucomError::Unsupported(
+                "noise models need the statevector or density matrix backend".to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// run the circuit `shots` times and histogram the outcomes
+    /// a shot's outcome is its measurement results joined in order, e.g. "01" for two single-qubit
+    /// measurements. the stored state is left as it was before the call
+    pub fn execute_shots(&mut self, shots: usize) -> Result<Counts, QucomError> {
+        self.run_shots(shots, false)
+    }
+
+    /// like `execute_shots`, also keeping every shot's outcome in `Counts::memory`
+    pub fn execute_shots_with_memory(&mut self, shots: usize) -> Result<Counts, QucomError> {
+        self.run_shots(shots, true)
+    }
+
+    fn run_shots(&mut self, shots: usize, memory: bool) -> Result<Counts, QucomError> {
+        let counts = self.shots_of(self.qasm_generator.instructions(), shots, memory)?;
+        self.executed = true;
+        Ok(counts)
+    }
+
+    /// run `instructions` `shots` times on the circuit's backend, noise and starting state
+    pub(crate) fn shots_of(&self, instructions: &[QasmInstruction], shots: usize, memory: bool) -> Result<Counts, QucomError> {
+        let n = self.n;
+        match self.resolved_backend() {
+            Backend::Statevector => {
+                let state = self.state.clone().unwrap_or_else(|| zero_state(n));
+                self.shots_on(instructions, StatevectorSimulator::from_state(state, n), shots, memory)
+            }
+            Backend::DensityMatrix => {
+                let sim = self.density.clone().unwrap_or_else(|| DensityMatrixSimulator::new(n));
+                self.shots_on(instructions, sim, shots, memory)
+            }
+            Backend::Stabilizer => {
+                self.check_noiseless()?;
+                let sim = self.stabilizer.clone().unwrap_or_else(|| StabilizerSimulator::new(n));
+                self.shots_on(instructions, sim, shots, memory)
+            }
+            Backend::MatrixProductState => {
+                self.check_noiseless()?;
+                let sim = self.mps.clone().unwrap_or_else(|| MpsSimulator::with_config(n, self.mps_config));
+                self.shots_on(instructions, sim, shots, memory)
+            }
+            Backend::Automatic => unreachable!("resolved_backend never returns Automatic"),
+        }
+    }
+
+    fn shots_on<S: Simulator + Clone>(
+        &self,
+        instructions: &[QasmInstruction],
+        mut initial: S,
+        shots: usize,
+        memory: bool,
+    ) -> Result<Counts, QucomError> {
+        initial.set_rng(self.rng.clone());
+        match &self.noise {
+            Some(noise) => {
+                let sim = NoisySimulator::new(initial, noise);
+                QasmExecutor::run_shots_with_limit(instructions, &sim, shots, memory, self.iteration_limit)
+            }
+            None => QasmExecutor::run_shots_with_limit(instructions, &initial, shots, memory, self.iteration_limit),
+        }
     }
 
     pub fn reset(&mut self) {
         self.qasm_generator.clear();
         self.state = None;
+        self.density = None;
+        self.stabilizer = None;
+        self.mps = None;
         self.executed = false;
     }
 
@@ -65,19 +372,43 @@ impl QuantumCircuit {
     }
     
     /// parse and execute string
-    pub fn from_qasm(qasm_string: &str) -> Result<Vec<String>, String> {
-        let instructions = QasmParser::parse(qasm_string)?;
-        
-        // infer number of qubits from instructions
-        let n_qubits = Self::infer_qubit_count(&instructions);
-        
-        let mut circuit = Self::new(n_qubits);
-        
-        for instr in instructions {
-            circuit.add_instruction(instr);
+    pub fn from_qasm(qasm_string: &str) -> Result<Vec<String>, QucomError> {
+        let mut circuit = Self::parse_qasm(qasm_string)?;
+        circuit.set_backend(Backend::Automatic);
+        circuit.execute()
+    }
+
+    /// build a circuit from a QASM program, keeping its declared registers
+    /// a program that declares no qubits gets a `q` register sized by the highest qubit it uses,
+    /// and one that declares no classical bits gets a `c` register of one bit per qubit
+    pub fn parse_qasm(qasm_string: &str) -> Result<QuantumCircuit, QucomError> {
+        Self::lower_qasm(qasm_string, None)
+    }
+
+    /// like `parse_qasm`, reading `include`s other than the bundled `stdgates.inc` and `qelib1.inc` through `resolver`
+    pub fn parse_qasm_with_includes(qasm_string: &str, resolver: &dyn IncludeResolver) -> Result<QuantumCircuit, QucomError> {
+        Self::lower_qasm(qasm_string, Some(resolver))
+    }
+
+    fn lower_qasm(qasm_string: &str, resolver: Option<&dyn IncludeResolver>) -> Result<QuantumCircuit, QucomError> {
+        let lowered = lower::lower_with_registers(&QasmParser::parse_program(qasm_string)?, resolver)?;
+        let mut circuit = Self::new(0);
+        if lowered.quantum.is_empty() {
+            circuit.add_quantum_register("q", Self::infer_qubit_count(&lowered.instructions))?;
         }
-        
-        Ok(circuit.execute())
+        for (name, size) in &lowered.quantum {
+            circuit.add_quantum_register(name, *size)?;
+        }
+        if lowered.classical.is_empty() {
+            circuit.add_classical_register("c", circuit.n)?;
+        }
+        for (name, size) in &lowered.classical {
+            circuit.add_classical_register(name, *size)?;
+        }
+        for instr in lowered.instructions {
+            circuit.add_instruction(instr)?;
+        }
+        Ok(circuit)
     }
     
     fn infer_qubit_count(instructions: &[QasmInstruction]) -> usize {
@@ -95,22 +426,35 @@ impl QuantumCircuit {
                 QasmInstruction::CX(c, t) | QasmInstruction::CZ(c, t) | QasmInstruction::Swap(c, t) => {
                     max_qubit = max_qubit.max(*c).max(*t);
                 }
-                QasmInstruction::CCX(qs) | QasmInstruction::Barrier(qs) => {
+                QasmInstruction::CCX(qs) | QasmInstruction::Barrier(qs)
+                | QasmInstruction::GateCall(_, _, qs) | QasmInstruction::Unitary(_, qs) => {
                     for q in qs {
                         max_qubit = max_qubit.max(*q);
                     }
                 }
-                QasmInstruction::If(_, _, body) | QasmInstruction::While(_, _, body) 
-                | QasmInstruction::For(_, _, _, body) => {
+                QasmInstruction::If(_, body) | QasmInstruction::While(_, body)
+                | QasmInstruction::For(_, _, body) => {
                     let inner_count = Self::infer_qubit_count(body);
                     max_qubit = max_qubit.max(inner_count.saturating_sub(1));
                 }
-                QasmInstruction::IfElse(_, _, if_body, else_body) => {
+                QasmInstruction::Switch(_, cases, default) => {
+                    for body in cases.iter().map(|(_, body)| body).chain([default]) {
+                        max_qubit = max_qubit.max(Self::infer_qubit_count(body).saturating_sub(1));
+                    }
+                }
+                QasmInstruction::IfElse(_, if_body, else_body) => {
                     let if_count = Self::infer_qubit_count(if_body);
                     let else_count = Self::infer_qubit_count(else_body);
                     max_qubit = max_qubit.max(if_count.saturating_sub(1)).max(else_count.saturating_sub(1));
                 }
-                QasmInstruction::MeasureAll | QasmInstruction::ResetAll | QasmInstruction::BarrierAll => {}
+                QasmInstruction::Parameterized(_, _) | QasmInstruction::Controlled(_, _, _) => {
+                    for q in instr.qubits() {
+                        max_qubit = max_qubit.max(q);
+                    }
+                }
+                QasmInstruction::MeasureAll | QasmInstruction::ResetAll | QasmInstruction::BarrierAll
+                | QasmInstruction::Assign(_, _) | QasmInstruction::Break | QasmInstruction::Continue
+                | QasmInstruction::End => {}
             }
         }
         
@@ -120,7 +464,19 @@ impl QuantumCircuit {
     // internal helper to add instructions
     // for DRY principle lovers, i'm not repeating myself. i'm VERY MUCH repeating myself
     // (i believe this is an exception to the rule)
-    pub(crate) fn add_instruction(&mut self, instr: QasmInstruction) {
+    // every builder goes through here, so a bad qubit or classical bit fails at the call site
+    pub(crate) fn add_instruction(&mut self, instr: QasmInstruction) -> Result<&mut Self, QucomError> {
+        instr.validate(self.n, self.n_bits())?;
+        // the export declares parameters next to the registers, so they cannot share a name
+        if let Some(p) = instr.parameters().into_iter().find(|p| self.is_register(p.name())) {
+            return Err(QucomError::InvalidArgument(format!("parameter '{}' has the name of a register", p)));
+        }
         self.qasm_generator.add_instruction(instr);
+        Ok(self)
+    }
+
+    /// drop the instructions after the first `len`, undoing builders that failed part way
+    pub(crate) fn truncate_instructions(&mut self, len: usize) {
+        self.qasm_generator.truncate(len);
     }
 }
@@ -0,0 +1,89 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
+use crate::kernels;
+use crate::qasm::generator::first_duplicate;
+use crate::simulators::Backend;
+
+/// exact, non-destructive queries on the state left by `execute`
+///
+/// these read the statevector (or the density matrix on that backend) and never sample or
+/// collapse it; a matrix product state is contracted to a dense vector, except for `amplitude`.
+/// they return `None` before `execute` and on the stabilizer backend, which keeps no amplitudes,
+/// and an error for qubits or bitstrings that do not fit the circuit.
+impl QuantumCircuit {
+    /// probability of every basis state, indexed with qubit 0 as the most significant bit
+    pub fn probabilities(&self) -> Option<Vec<f64>> {
+        match self.resolved_backend() {
+            Backend::DensityMatrix => self.density.as_ref().map(|sim| sim.probabilities()),
+            Backend::MatrixProductState => self.mps.as_ref().map(|sim| kernels::probabilities(&sim.to_statevector())),
+            _ => self.state.as_ref().map(kernels::probabilities),
+        }
+    }
+
+    /// marginal distribution over `qubits`, indexed with `qubits[0]` as the most significant bit
+    pub fn marginal_probabilities(&self, qubits: &[usize]) -> Result<Option<Vec<f64>>, QucomError> {
+        for &qubit in qubits {
+            self.check_qubit(qubit)?;
+        }
+        if let Some(q) = first_duplicate(qubits) {
+            return Err(QucomError::DuplicateQubit(q));
+        }
+        Ok(self.probabilities().map(|probs| kernels::marginal_probabilities(&probs, qubits, self.n)))
+    }
+
+    /// probability of the bitstring `bits` (qubit 0 first)
+    pub fn probability(&self, bits: &str) -> Result<Option<f64>, QucomError> {
+        let index = self.basis_index(bits)?;
+        Ok(self.probabilities().map(|probs| probs[index]))
+    }
+
+    /// amplitude ⟨bits|ψ⟩ (qubit 0 first); a mixed density matrix has no amplitudes
+    pub fn amplitude(&self, bits: &str) -> Result<Option<Complex64>, QucomError> {
+        let index = self.basis_index(bits)?;
+        Ok(match self.resolved_backend() {
+            Backend::MatrixProductState => self.mps.as_ref().map(|sim| sim.amplitude(bits)),
+            Backend::DensityMatrix | Backend::Stabilizer => None,
+            _ => self.state.as_ref().map(|state| state[[index, 0]]),
+        })
+    }
+
+    /// 2x2 reduced density matrix of `qubit`
+    pub fn reduced_density_matrix(&self, qubit: usize) -> Result<Option<Array2<Complex64>>, QucomError> {
+        self.check_qubit(qubit)?;
+        Ok(match self.resolved_backend() {
+            Backend::DensityMatrix => self.density.as_ref().map(|sim| sim.reduced_density_matrix(qubit)),
+            Backend::MatrixProductState => self
+                .mps
+                .as_ref()
+                .map(|sim| kernels::reduced_density_matrix(&sim.to_statevector(), qubit, self.n)),
+            _ => self.state.as_ref().map(|state| kernels::reduced_density_matrix(state, qubit, self.n)),
+        })
+    }
+
+    /// Bloch vector (⟨X⟩, ⟨Y⟩, ⟨Z⟩) of `qubit`; shorter than 1 when it is entangled or mixed
+    pub fn bloch_vector(&self, qubit: usize) -> Result<Option<[f64; 3]>, QucomError> {
+        Ok(self.reduced_density_matrix(qubit)?.map(|rho| {
+            // ρ = (I + xX + yY + zZ) / 2, so ρ01 = (x - iy) / 2
+            let off_diagonal = rho[[0, 1]];
+            [2.0 * off_diagonal.re, -2.0 * off_diagonal.im, (rho[[0, 0]] - rho[[1, 1]]).re]
+        }))
+    }
+
+    fn check_qubit(&self, qubit: usize) -> Result<(), QucomError> {
+        match qubit < self.n {
+            true => Ok(()),
+            false => Err(QucomError::InvalidQubit { qubit, n_qubits: self.n }),
+        }
+    }
+
+    fn basis_index(&self, bits: &str) -> Result<usize, QucomError> {
+        if bits.len() != self.n || !bits.chars().all(|c| c == '0' || c == '1') {
+            return Err(QucomError::InvalidArgument(format!(
+                "'{}' is not a bitstring of one 0 or 1 per qubit ({} qubits)", bits, self.n
+            )));
+        }
+        Ok(usize::from_str_radix(bits, 2).expect("checked to be binary"))
+    }
+}
@@ -0,0 +1,170 @@
+use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
+
+/// named block of consecutive qubits in a circuit, e.g. `data` for qubits 0..4 and `ancilla`
+/// for qubit 4; get one from `QuantumCircuit::add_quantum_register`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuantumRegister {
+    name: String,
+    offset: usize,
+    size: usize,
+}
+
+impl QuantumRegister {
+    pub(crate) fn new(name: &str, offset: usize, size: usize) -> Self {
+        Self { name: name.to_string(), offset, size }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// circuit index of the register's first qubit
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// circuit index of qubit `index` of the register, for the gate builders
+    pub fn qubit(&self, index: usize) -> Result<usize, QucomError> {
+        match index < self.size {
+            true => Ok(self.offset + index),
+            false => Err(QucomError::InvalidArgument(format!(
+                "qubit {} is outside register '{}' of size {}", index, self.name, self.size
+            ))),
+        }
+    }
+
+    /// circuit indices of every qubit in the register
+    pub fn qubits(&self) -> Vec<usize> {
+        (self.offset..self.offset + self.size).collect()
+    }
+}
+
+/// named block of consecutive classical bits in a circuit;
+/// get one from `QuantumCircuit::add_classical_register`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassicalRegister {
+    name: String,
+    offset: usize,
+    size: usize,
+}
+
+impl ClassicalRegister {
+    pub(crate) fn new(name: &str, offset: usize, size: usize) -> Self {
+        Self { name: name.to_string(), offset, size }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// circuit index of the register's first bit
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// circuit index of bit `index` of the register, for `measure_qubit` and conditions
+    pub fn bit(&self, index: usize) -> Result<usize, QucomError> {
+        match index < self.size {
+            true => Ok(self.offset + index),
+            false => Err(QucomError::InvalidArgument(format!(
+                "bit {} is outside register '{}' of size {}", index, self.name, self.size
+            ))),
+        }
+    }
+
+    /// circuit indices of every bit in the register
+    pub fn bits(&self) -> Vec<usize> {
+        (self.offset..self.offset + self.size).collect()
+    }
+}
+
+/// error unless `name` can name a new register next to `taken`
+pub(crate) fn check_register_name<'a>(name: &str, size: usize, mut taken: impl Iterator<Item = &'a str>) -> Result<(), QucomError> {
+    let mut chars = name.chars();
+    let is_identifier = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if !is_identifier {
+        return Err(QucomError::InvalidArgument(format!("'{}' is not a valid register name", name)));
+    }
+    if size == 0 {
+        return Err(QucomError::InvalidArgument(format!("register '{}' must hold at least one bit", name)));
+    }
+    if taken.any(|other| other == name) {
+        return Err(QucomError::InvalidArgument(format!("a register named '{}' already exists", name)));
+    }
+    Ok(())
+}
+
+/// registers of a circuit
+///
+/// `QuantumCircuit::new(n)` starts with `n` qubits in register `q` and `n` classical bits in `c`;
+/// `QuantumCircuit::new(0)` starts with none, so a circuit can be laid out register by register.
+/// QASM output names every operand by its register, e.g. `cx data[1], ancilla[0];`
+impl QuantumCircuit {
+    /// append a register of `size` qubits after the existing ones, e.g. an ancilla register;
+    /// errors on a duplicate or invalid name, and once the circuit holds a simulated state
+    pub fn add_quantum_register(&mut self, name: &str, size: usize) -> Result<QuantumRegister, QucomError> {
+        self.check_unsimulated()?;
+        self.check_not_parameter(name)?;
+        let register = self.qasm_generator.add_quantum_register(name, size)?;
+        self.n += size;
+        Ok(register)
+    }
+
+    /// append a register of `size` classical bits after the existing ones
+    pub fn add_classical_register(&mut self, name: &str, size: usize) -> Result<ClassicalRegister, QucomError> {
+        self.check_not_parameter(name)?;
+        self.qasm_generator.add_classical_register(name, size)
+    }
+
+    pub fn quantum_registers(&self) -> &[QuantumRegister] {
+        self.qasm_generator.quantum_registers()
+    }
+
+    pub fn classical_registers(&self) -> &[ClassicalRegister] {
+        self.qasm_generator.classical_registers()
+    }
+
+    /// the quantum register called `name`
+    pub fn quantum_register(&self, name: &str) -> Option<&QuantumRegister> {
+        self.quantum_registers().iter().find(|r| r.name() == name)
+    }
+
+    /// the classical register called `name`
+    pub fn classical_register(&self, name: &str) -> Option<&ClassicalRegister> {
+        self.classical_registers().iter().find(|r| r.name() == name)
+    }
+
+    /// whether `name` is a quantum or a classical register
+    pub(crate) fn is_register(&self, name: &str) -> bool {
+        self.quantum_register(name).is_some() || self.classical_register(name).is_some()
+    }
+
+    fn check_not_parameter(&self, name: &str) -> Result<(), QucomError> {
+        if self.qasm_generator.parameters().iter().any(|p| p.name() == name) {
+            return Err(QucomError::InvalidArgument(format!("'{}' already names a parameter of the circuit", name)));
+        }
+        Ok(())
+    }
+
+    /// number of classical bits, across every classical register
+    pub fn n_bits(&self) -> usize {
+        self.qasm_generator.n_bits()
+    }
+
+    fn check_unsimulated(&self) -> Result<(), QucomError> {
+        match self.state.is_some() || self.density.is_some() || self.stabilizer.is_some() || self.mps.is_some() {
+            true => Err(QucomError::InvalidArgument("qubits can only be added before the circuit is simulated".to_string())),
+            false => Ok(()),
+        }
+    }
+}
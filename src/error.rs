@@ -0,0 +1,51 @@
+use std::fmt;
+
+/// errors reported while building, parsing or executing a circuit
+#[derive(Debug, Clone, PartialEq)]
+pub enum QucomError {
+    /// qubit index outside the circuit
+    InvalidQubit { qubit: usize, n_qubits: usize },
+    /// classical bit outside the circuit's register
+    InvalidClassicalBit { bit: usize, n_bits: usize },
+    /// one instruction uses the same qubit twice
+    DuplicateQubit(usize),
+    /// QASM source that could not be parsed, at a 1-based line and column
+    ParseError { line: usize, column: usize, message: String },
+    /// a matrix that is not unitary, or an instruction that has no inverse
+    NonUnitary(String),
+    /// a symbolic parameter without a value
+    UnboundParameter(String),
+    /// call of a custom gate that was never defined
+    UndefinedGate(String),
+    /// something the chosen backend or method cannot do
+    Unsupported(String),
+    /// any other bad argument: wrong arity, invalid name, mismatched lengths
+    InvalidArgument(String),
+    /// `while` loops ran more iterations than the executor's limit
+    IterationLimit(usize),
+}
+
+impl fmt::Display for QucomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QucomError::InvalidQubit { qubit, n_qubits } => {
+                write!(f, "qubit index {} out of bounds (circuit has {} qubits)", qubit, n_qubits)
+            }
+            QucomError::InvalidClassicalBit { bit, n_bits } => {
+                write!(f, "classical bit {} out of bounds (circuit has {} bits)", bit, n_bits)
+            }
+            QucomError::DuplicateQubit(qubit) => write!(f, "qubit {} is used more than once", qubit),
+            QucomError::ParseError { line, column, message } => {
+                write!(f, "parse error at line {}, column {}: {}", line, column, message)
+            }
+            QucomError::NonUnitary(message) => write!(f, "not unitary: {}", message),
+            QucomError::UnboundParameter(name) => write!(f, "no value for parameter '{}'", name),
+            QucomError::UndefinedGate(name) => write!(f, "gate '{}' is not defined", name),
+            QucomError::Unsupported(message) => write!(f, "unsupported: {}", message),
+            QucomError::InvalidArgument(message) => write!(f, "{}", message),
+            QucomError::IterationLimit(limit) => write!(f, "while loops ran more than {} iterations", limit),
+        }
+    }
+}
+
+impl std::error::Error for QucomError {}
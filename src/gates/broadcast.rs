@@ -0,0 +1,58 @@
+use std::borrow::Borrow;
+use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
+
+impl QuantumCircuit {
+    /// add `gate` on each of `qubits`, e.g. `circuit.on_each(&data.qubits(), |c, q| c.rx(0.3, q))`
+    ///
+    /// adds nothing if any of the gates fails
+    pub fn on_each<Q: Borrow<usize>>(
+        &mut self,
+        qubits: impl IntoIterator<Item = Q>,
+        mut gate: impl FnMut(&mut Self, usize) -> Result<&mut Self, QucomError>,
+    ) -> Result<&mut Self, QucomError> {
+        self.all_or_nothing(|circuit| {
+            for qubit in qubits {
+                gate(circuit, *qubit.borrow())?;
+            }
+            Ok(())
+        })
+    }
+
+    /// add `gate` on each pair of `first[i]` and `second[i]`, e.g. `cx` from one register onto another
+    /// or `measure_qubit` from a quantum onto a classical register
+    ///
+    /// both sides must be the same length; adds nothing if any of the gates fails
+    pub fn on_pairs<Q: Borrow<usize>, R: Borrow<usize>>(
+        &mut self,
+        first: impl IntoIterator<Item = Q>,
+        second: impl IntoIterator<Item = R>,
+        mut gate: impl FnMut(&mut Self, usize, usize) -> Result<&mut Self, QucomError>,
+    ) -> Result<&mut Self, QucomError> {
+        let first: Vec<usize> = first.into_iter().map(|q| *q.borrow()).collect();
+        let second: Vec<usize> = second.into_iter().map(|q| *q.borrow()).collect();
+        if first.len() != second.len() {
+            return Err(QucomError::InvalidArgument(format!(
+                "cannot pair {} qubits with {}", first.len(), second.len()
+            )));
+        }
+        self.all_or_nothing(|circuit| {
+            for (a, b) in first.into_iter().zip(second) {
+                gate(circuit, a, b)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// run `add`, taking back the instructions it added if it fails
+    fn all_or_nothing(&mut self, add: impl FnOnce(&mut Self) -> Result<(), QucomError>) -> Result<&mut Self, QucomError> {
+        let len = self.instructions().len();
+        match add(self) {
+            Ok(()) => Ok(self),
+            Err(error) => {
+                self.truncate_instructions(len);
+                Err(error)
+            }
+        }
+    }
+}
@@ -1,16 +1,15 @@
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
 
 impl QuantumCircuit {
 	/// add CNOT gate to circuit
-	pub fn cx(&mut self, control_qubit: usize, target_qubit: usize) -> &mut Self {
-		self.add_instruction(QasmInstruction::CX(control_qubit, target_qubit));
-		self
+	pub fn cx(&mut self, control_qubit: usize, target_qubit: usize) -> Result<&mut Self, QucomError> {
+		self.add_instruction(QasmInstruction::CX(control_qubit, target_qubit))
 	}
 	
 	/// add CZ gate to circuit
-	pub fn cz(&mut self, control_qubit: usize, target_qubit: usize) -> &mut Self {
-		self.add_instruction(QasmInstruction::CZ(control_qubit, target_qubit));
-		self
+	pub fn cz(&mut self, control_qubit: usize, target_qubit: usize) -> Result<&mut Self, QucomError> {
+		self.add_instruction(QasmInstruction::CZ(control_qubit, target_qubit))
 	}
 }
\ No newline at end of file
@@ -0,0 +1,66 @@
+use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
+use crate::parameters::ParameterExpression;
+use crate::qasm::generator::QasmInstruction;
+
+impl QuantumCircuit {
+    /// add `gate` controlled on `controls`: it acts where control `i` reads `ctrl_state[i]`
+    /// (`true` for a closed control, `false` for an open one) and leaves other amplitudes alone
+    /// works for any built-in, custom or unitary gate and keeps its symbolic angles
+    pub fn controlled(&mut self, gate: QasmInstruction, controls: &[usize], ctrl_state: &[bool]) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::controlled(gate, controls, ctrl_state)?)
+    }
+
+    /// add CY gate to circuit
+    pub fn cy(&mut self, control_qubit: usize, target_qubit: usize) -> Result<&mut Self, QucomError> {
+        self.controlled(QasmInstruction::Y(target_qubit), &[control_qubit], &[true])
+    }
+
+    /// add controlled Hadamard gate to circuit
+    pub fn ch(&mut self, control_qubit: usize, target_qubit: usize) -> Result<&mut Self, QucomError> {
+        self.controlled(QasmInstruction::H(target_qubit), &[control_qubit], &[true])
+    }
+
+    /// add controlled RX gate to circuit
+    pub fn crx(&mut self, angle: impl Into<ParameterExpression>, control_qubit: usize, target_qubit: usize) -> Result<&mut Self, QucomError> {
+        let gate = QasmInstruction::parameterized(QasmInstruction::Rx(0.0, target_qubit), vec![angle.into()]);
+        self.controlled(gate, &[control_qubit], &[true])
+    }
+
+    /// add controlled RY gate to circuit
+    pub fn cry(&mut self, angle: impl Into<ParameterExpression>, control_qubit: usize, target_qubit: usize) -> Result<&mut Self, QucomError> {
+        let gate = QasmInstruction::parameterized(QasmInstruction::Ry(0.0, target_qubit), vec![angle.into()]);
+        self.controlled(gate, &[control_qubit], &[true])
+    }
+
+    /// add controlled RZ gate to circuit
+    pub fn crz(&mut self, angle: impl Into<ParameterExpression>, control_qubit: usize, target_qubit: usize) -> Result<&mut Self, QucomError> {
+        let gate = QasmInstruction::parameterized(QasmInstruction::Rz(0.0, target_qubit), vec![angle.into()]);
+        self.controlled(gate, &[control_qubit], &[true])
+    }
+
+    /// add controlled phase gate to circuit
+    pub fn cp(&mut self, angle: impl Into<ParameterExpression>, control_qubit: usize, target_qubit: usize) -> Result<&mut Self, QucomError> {
+        let gate = QasmInstruction::parameterized(QasmInstruction::Phase(0.0, target_qubit), vec![angle.into()]);
+        self.controlled(gate, &[control_qubit], &[true])
+    }
+
+    /// add controlled U gate to circuit
+    pub fn cu(
+        &mut self,
+        theta: impl Into<ParameterExpression>,
+        phi: impl Into<ParameterExpression>,
+        lambda: impl Into<ParameterExpression>,
+        control_qubit: usize,
+        target_qubit: usize,
+    ) -> Result<&mut Self, QucomError> {
+        let angles = vec![theta.into(), phi.into(), lambda.into()];
+        let gate = QasmInstruction::parameterized(QasmInstruction::U(0.0, 0.0, 0.0, target_qubit), angles);
+        self.controlled(gate, &[control_qubit], &[true])
+    }
+
+    /// add controlled SWAP (Fredkin) gate to circuit
+    pub fn cswap(&mut self, control_qubit: usize, q1: usize, q2: usize) -> Result<&mut Self, QucomError> {
+        self.controlled(QasmInstruction::Swap(q1, q2), &[control_qubit], &[true])
+    }
+}
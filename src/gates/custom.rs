@@ -0,0 +1,46 @@
+use std::sync::Arc;
+use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
+use crate::parameters::ParameterExpression;
+use crate::qasm::generator::QasmInstruction;
+use crate::qasm::GateDefinition;
+
+impl QuantumCircuit {
+    /// register a custom gate so `gate` can call it by name
+    /// redefining a name with a different definition is an error
+    pub fn define_gate(&mut self, definition: GateDefinition) -> Result<&mut Self, QucomError> {
+        if let Some(existing) = self.gates.get(definition.name()) {
+            if existing.to_string() != definition.to_string() {
+                return Err(QucomError::InvalidArgument(format!(
+                    "gate '{}' is already defined differently", definition.name()
+                )));
+            }
+            return Ok(self);
+        }
+        self.gates.insert(definition.name().to_string(), Arc::new(definition));
+        Ok(self)
+    }
+
+    /// the registered definition of `name`
+    pub fn gate_definition(&self, name: &str) -> Option<&GateDefinition> {
+        self.gates.get(name).map(|definition| definition.as_ref())
+    }
+
+    /// add a call of the registered gate `name`; arguments may be parameter expressions
+    pub fn gate(&mut self, name: &str, args: &[ParameterExpression], qubits: &[usize]) -> Result<&mut Self, QucomError> {
+        let definition = self.gates.get(name).ok_or_else(|| QucomError::UndefinedGate(name.to_string()))?;
+        if args.len() != definition.parameters().len() {
+            return Err(QucomError::InvalidArgument(format!(
+                "gate '{}' takes {} arguments, got {}", name, definition.parameters().len(), args.len()
+            )));
+        }
+        let call = QasmInstruction::GateCall(definition.clone(), vec![0.0; args.len()], qubits.to_vec());
+        self.add_instruction(QasmInstruction::parameterized(call, args.to_vec()))
+    }
+
+    /// this circuit as a custom gate over all its qubits, its parameters (in `parameters()`
+    /// order) becoming the gate's arguments
+    pub fn to_gate(&self, name: &str) -> Result<GateDefinition, QucomError> {
+        GateDefinition::new(name, self.parameters(), self.n, self.instructions().to_vec())
+    }
+}
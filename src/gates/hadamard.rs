@@ -1,7 +1,9 @@
 use ndarray::Array2;
 use num_complex::Complex64;
+use std::borrow::Borrow;
 use std::f64;
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
 
 /// Hadamard gate
@@ -18,15 +20,11 @@ pub fn hadamard() -> Array2<Complex64> {
 
 impl QuantumCircuit {
     /// add Hadamard gate to circuit
-    pub fn h(&mut self, qubit_index: usize) -> &mut Self {
-        self.add_instruction(QasmInstruction::H(qubit_index));
-        self
+    pub fn h(&mut self, qubit_index: usize) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::H(qubit_index))
     }
-    /// add Hadamard gate to multiple qubits
-    pub fn h_multi(&mut self, range: std::ops::Range<usize>) -> &mut Self {
-        for qubit in range {
-            self.h(qubit);
-        }
-        self
+    /// add Hadamard gate to multiple qubits: a range, a slice or a register's `qubits()`
+    pub fn h_multi<Q: Borrow<usize>>(&mut self, qubits: impl IntoIterator<Item = Q>) -> Result<&mut Self, QucomError> {
+        self.on_each(qubits, Self::h)
     }
 }
\ No newline at end of file
@@ -1,5 +1,4 @@
 use crate::circuits::QuantumCircuit;
-use crate::qasm::generator::QasmInstruction;
 
 impl QuantumCircuit {
     /// Multi-Controlled Z gate on all qubits
@@ -11,8 +10,7 @@ impl QuantumCircuit {
 
         if n == 2 {
             // just cz qubits 0 and 1
-            self.add_instruction(QasmInstruction::CZ(0, 1));
-            return self;
+            return self.cz(0, 1).expect("mcz only uses the circuit's own qubits");
         }
 
         // MCZ = H(target) * MCX(all controls, target) * H(target)
@@ -24,10 +22,9 @@ impl QuantumCircuit {
         // do not try this at home
         let mut ancillas = vec![];
 
-        self.add_instruction(QasmInstruction::H(target));
-        self.mcx(&controls, target, &mut ancillas);
-        self.add_instruction(QasmInstruction::H(target));
-
-        self
+        self.h(target)
+            .and_then(|circuit| circuit.mcx(&controls, target, &mut ancillas))
+            .and_then(|circuit| circuit.h(target))
+            .expect("mcz only uses the circuit's own qubits")
     }
 }
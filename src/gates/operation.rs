@@ -0,0 +1,120 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::gates::*;
+use crate::kernels;
+use crate::qasm::generator::QasmInstruction;
+
+/// a gate instruction lowered to a matrix acting on `targets`, conditioned on `controls`
+/// used by backends that need the matrix itself rather than a dedicated kernel
+#[derive(Debug, Clone)]
+pub struct GateOperation {
+    pub matrix: Array2<Complex64>,
+    pub controls: Vec<usize>,
+    /// required value of each control, `false` for an open (|0⟩) control
+    pub ctrl_state: Vec<bool>,
+    pub targets: Vec<usize>,
+}
+
+impl GateOperation {
+    /// operation with closed controls only
+    pub fn new(matrix: Array2<Complex64>, controls: Vec<usize>, targets: Vec<usize>) -> Self {
+        let ctrl_state = vec![true; controls.len()];
+        Self { matrix, controls, ctrl_state, targets }
+    }
+
+    /// operation with control `i` required to be |1⟩ if `ctrl_state[i]` and |0⟩ otherwise
+    pub fn with_ctrl_state(matrix: Array2<Complex64>, controls: Vec<usize>, ctrl_state: Vec<bool>, targets: Vec<usize>) -> Self {
+        assert_eq!(controls.len(), ctrl_state.len(), "one control state per control qubit");
+        Self { matrix, controls, ctrl_state, targets }
+    }
+
+    /// lower a gate instruction, `None` for measurement, reset, control flow and custom gate calls
+    pub fn from_instruction(instr: &QasmInstruction) -> Option<Self> {
+        let single = |matrix: Array2<Complex64>, q: usize| Some(Self::new(matrix, vec![], vec![q]));
+
+        match instr {
+            QasmInstruction::H(q) => single(hadamard(), *q),
+            QasmInstruction::X(q) => single(pauli_x(), *q),
+            QasmInstruction::Y(q) => single(pauli_y(), *q),
+            QasmInstruction::Z(q) => single(pauli_z(), *q),
+            QasmInstruction::S(q, false) => single(s(), *q),
+            QasmInstruction::S(q, true) => single(sdg(), *q),
+            QasmInstruction::T(q, false) => single(t(), *q),
+            QasmInstruction::T(q, true) => single(tdg(), *q),
+            QasmInstruction::Rx(angle, q) => single(rx(*angle), *q),
+            QasmInstruction::Ry(angle, q) => single(ry(*angle), *q),
+            QasmInstruction::Rz(angle, q) => single(rz(*angle), *q),
+            QasmInstruction::Phase(angle, q) => single(phase(*angle), *q),
+            QasmInstruction::U(theta, phi, lambda, q) => single(u_gate(*theta, *phi, *lambda), *q),
+            QasmInstruction::CX(c, t) => Some(Self::new(pauli_x(), vec![*c], vec![*t])),
+            QasmInstruction::CZ(c, t) => Some(Self::new(pauli_z(), vec![*c], vec![*t])),
+            QasmInstruction::CCX(qs) => {
+                if qs.len() != 3 {
+                    panic!("CCX instruction requires exactly 3 qubits");
+                }
+                Some(Self::new(pauli_x(), qs[..2].to_vec(), vec![qs[2]]))
+            }
+            QasmInstruction::Swap(q1, q2) => Some(Self::new(swap(), vec![], vec![*q1, *q2])),
+            QasmInstruction::Unitary(matrix, qs) => Some(Self::new(matrix.clone(), vec![], qs.clone())),
+            QasmInstruction::Controlled(gate, controls, ctrl_state) => {
+                let inner = Self::from_instruction(gate)?;
+                Some(Self::with_ctrl_state(
+                    inner.matrix,
+                    controls.iter().chain(&inner.controls).copied().collect(),
+                    ctrl_state.iter().chain(&inner.ctrl_state).copied().collect(),
+                    inner.targets,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// all qubits the operation touches, controls first
+    pub fn qubits(&self) -> Vec<usize> {
+        self.controls.iter().chain(self.targets.iter()).copied().collect()
+    }
+
+    /// full matrix over `qubits()` (controls first), identity outside the block where the
+    /// controls match `ctrl_state`
+    pub fn full_matrix(&self) -> Array2<Complex64> {
+        let target_dim = self.matrix.nrows();
+        let dim = target_dim << self.controls.len();
+        let mut full = Array2::<Complex64>::eye(dim);
+        let block = self.ctrl_state.iter().fold(0, |acc, &closed| (acc << 1) | usize::from(closed));
+        let offset = block * target_dim;
+        full.slice_mut(ndarray::s![offset..offset + target_dim, offset..offset + target_dim]).assign(&self.matrix);
+        full
+    }
+
+    /// element-wise complex conjugate of the matrix
+    pub fn conjugate(&self) -> Self {
+        Self { matrix: self.matrix.mapv(|c| c.conj()), ..self.clone() }
+    }
+
+    /// inverse operation (conjugate transpose of the matrix)
+    pub fn adjoint(&self) -> Self {
+        Self { matrix: self.matrix.t().mapv(|c| c.conj()), ..self.clone() }
+    }
+
+    /// the same operation with every qubit index moved up by `offset`
+    pub fn shifted(&self, offset: usize) -> Self {
+        Self {
+            controls: self.controls.iter().map(|q| q + offset).collect(),
+            targets: self.targets.iter().map(|q| q + offset).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// apply the operation to a statevector in place
+    pub fn apply(&self, state: &mut Array2<Complex64>, n_qubits: usize) {
+        if !self.controls.is_empty() {
+            kernels::apply_controlled_on(state, &self.matrix, &self.controls, &self.ctrl_state, &self.targets, n_qubits);
+            return;
+        }
+        match self.targets.as_slice() {
+            [q] => kernels::apply_single_qubit(state, &self.matrix, *q, n_qubits),
+            [q0, q1] => kernels::apply_two_qubit(state, &self.matrix, *q0, *q1, n_qubits),
+            qubits => kernels::apply_multi_qubit(state, &self.matrix, qubits, n_qubits),
+        }
+    }
+}
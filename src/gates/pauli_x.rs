@@ -1,6 +1,7 @@
 use ndarray::Array2;
 use num_complex::Complex64;
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
 
 /// Pauli X gate
@@ -15,9 +16,8 @@ pub fn pauli_x() -> Array2<Complex64> {
 }
 
 impl QuantumCircuit {
-    pub fn x(&mut self, qubit_index: usize) -> &mut Self {
-        self.add_instruction(QasmInstruction::X(qubit_index));
-        self
+    pub fn x(&mut self, qubit_index: usize) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::X(qubit_index))
     }
 }
 
@@ -1,6 +1,7 @@
 use ndarray::Array2;
 use num_complex::Complex64;
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
 
 /// Pauli-Z gate
@@ -16,9 +17,8 @@ pub fn pauli_z() -> Array2<Complex64> {
 
 impl QuantumCircuit {
     /// add Pauli-Z gate to circuit
-    pub fn z(&mut self, qubit_index: usize) -> &mut Self {
-        self.add_instruction(QasmInstruction::Z(qubit_index));
-        self
+    pub fn z(&mut self, qubit_index: usize) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::Z(qubit_index))
     }
 }
 
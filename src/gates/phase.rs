@@ -1,7 +1,9 @@
 use ndarray::Array2;
 use num_complex::Complex64;
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
+use crate::parameters::ParameterExpression;
 
 /// phase gate
 pub fn phase(angle: f64) -> Array2<Complex64> {
@@ -16,8 +18,7 @@ pub fn phase(angle: f64) -> Array2<Complex64> {
 
 impl QuantumCircuit {
     /// add phase gate to circuit
-    pub fn phase(&mut self, angle: f64, qubit_index: usize) -> &mut Self {
-        self.add_instruction(QasmInstruction::Phase(angle, qubit_index));
-        self
+    pub fn phase(&mut self, angle: impl Into<ParameterExpression>, qubit_index: usize) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::parameterized(QasmInstruction::Phase(0.0, qubit_index), vec![angle.into()]))
     }
 }
@@ -1,7 +1,9 @@
 use ndarray::Array2;
 use num_complex::Complex64;
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
+use crate::parameters::ParameterExpression;
 
 /// rotation around Y-axis gate
 pub fn ry(angle: f64) -> Array2<Complex64> {
@@ -20,8 +22,7 @@ pub fn ry(angle: f64) -> Array2<Complex64> {
 
 impl QuantumCircuit {
     /// add Ry gate to circuit
-    pub fn ry(&mut self, angle: f64, qubit_index: usize) -> &mut Self {
-        self.add_instruction(QasmInstruction::Ry(angle, qubit_index));
-        self
+    pub fn ry(&mut self, angle: impl Into<ParameterExpression>, qubit_index: usize) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::parameterized(QasmInstruction::Ry(0.0, qubit_index), vec![angle.into()]))
     }
 }
@@ -1,7 +1,9 @@
 use ndarray::Array2;
 use num_complex::Complex64;
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
+use crate::parameters::ParameterExpression;
 
 /// rotation around Z-axis gate
 pub fn rz(angle: f64) -> Array2<Complex64> {
@@ -18,8 +20,7 @@ pub fn rz(angle: f64) -> Array2<Complex64> {
 
 impl QuantumCircuit {
     /// add Rz gate to circuit
-    pub fn rz(&mut self, angle: f64, qubit_index: usize) -> &mut Self {
-        self.add_instruction(QasmInstruction::Rz(angle, qubit_index));
-        self
+    pub fn rz(&mut self, angle: impl Into<ParameterExpression>, qubit_index: usize) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::parameterized(QasmInstruction::Rz(0.0, qubit_index), vec![angle.into()]))
     }
 }
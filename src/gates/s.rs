@@ -1,6 +1,7 @@
 use ndarray::Array2;
 use num_complex::Complex64;
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
 
 /// S gate
@@ -27,14 +28,12 @@ pub fn sdg() -> Array2<Complex64> {
 
 impl QuantumCircuit {
 	/// add S gate to circuit
-	pub fn s(&mut self, qubit_index: usize) -> &mut Self {
-		self.add_instruction(QasmInstruction::S(qubit_index, false));
-		self
+	pub fn s(&mut self, qubit_index: usize) -> Result<&mut Self, QucomError> {
+		self.add_instruction(QasmInstruction::S(qubit_index, false))
 	}
 	
 	/// add S dagger gate to circuit
-	pub fn sdg(&mut self, qubit_index: usize) -> &mut Self {
-		self.add_instruction(QasmInstruction::S(qubit_index, true));
-		self
+	pub fn sdg(&mut self, qubit_index: usize) -> Result<&mut Self, QucomError> {
+		self.add_instruction(QasmInstruction::S(qubit_index, true))
 	}
 }
\ No newline at end of file
@@ -2,6 +2,7 @@ use ndarray::Array2;
 use num_complex::Complex64;
 use std::f64::consts::PI;
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
 
 /// T gate
@@ -28,14 +29,12 @@ pub fn tdg() -> Array2<Complex64> {
 
 impl QuantumCircuit {
 	/// add T gate to circuit
-	pub fn t(&mut self, qubit_index: usize) -> &mut Self {
-		self.add_instruction(QasmInstruction::T(qubit_index, false));
-		self
+	pub fn t(&mut self, qubit_index: usize) -> Result<&mut Self, QucomError> {
+		self.add_instruction(QasmInstruction::T(qubit_index, false))
 	}
 	
 	/// add T dagger gate to circuit
-	pub fn tdg(&mut self, qubit_index: usize) -> &mut Self {
-		self.add_instruction(QasmInstruction::T(qubit_index, true));
-		self
+	pub fn tdg(&mut self, qubit_index: usize) -> Result<&mut Self, QucomError> {
+		self.add_instruction(QasmInstruction::T(qubit_index, true))
 	}
 }
\ No newline at end of file
@@ -1,7 +1,9 @@
 use ndarray::Array2;
 use num_complex::Complex64;
 use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
 use crate::qasm::generator::QasmInstruction;
+use crate::parameters::ParameterExpression;
 
 /// universal single-qubit gate U(θ, φ, λ)
 pub fn u_gate(theta: f64, phi: f64, lambda: f64) -> Array2<Complex64> {
@@ -20,10 +22,33 @@ pub fn u_gate(theta: f64, phi: f64, lambda: f64) -> Array2<Complex64> {
     ).unwrap()
 }
 
+/// (θ, φ, λ, γ) with `matrix` = e^{iγ} U(θ, φ, λ), for a 2x2 unitary
+pub(crate) fn u_angles(matrix: &Array2<Complex64>) -> (f64, f64, f64, f64) {
+    const EPS: f64 = 1e-12;
+    let (m00, m01, m10, m11) = (matrix[[0, 0]], matrix[[0, 1]], matrix[[1, 0]], matrix[[1, 1]]);
+    let theta = 2.0 * m10.norm().atan2(m00.norm());
+    // when cos(θ/2) or sin(θ/2) vanishes only φ - λ or φ + λ is fixed, the other angle is taken as 0
+    let (phi, lambda, gamma) = if m00.norm() < EPS {
+        let gamma = (-m01).arg();
+        (m10.arg() - gamma, 0.0, gamma)
+    } else if m10.norm() < EPS {
+        (0.0, m11.arg() - m00.arg(), m00.arg())
+    } else {
+        (m10.arg() - m00.arg(), (-m01).arg() - m00.arg(), m00.arg())
+    };
+    (theta, phi, lambda, gamma)
+}
+
 impl QuantumCircuit {
     /// add U gate to circuit
-    pub fn u(&mut self, theta: f64, phi: f64, lambda: f64, qubit_index: usize) -> &mut Self {
-        self.add_instruction(QasmInstruction::U(theta, phi, lambda, qubit_index));
-        self
+    pub fn u(
+        &mut self,
+        theta: impl Into<ParameterExpression>,
+        phi: impl Into<ParameterExpression>,
+        lambda: impl Into<ParameterExpression>,
+        qubit_index: usize,
+    ) -> Result<&mut Self, QucomError> {
+        let angles = vec![theta.into(), phi.into(), lambda.into()];
+        self.add_instruction(QasmInstruction::parameterized(QasmInstruction::U(0.0, 0.0, 0.0, qubit_index), angles))
     }
 }
@@ -0,0 +1,27 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::circuits::QuantumCircuit;
+use crate::error::QucomError;
+use crate::qasm::generator::QasmInstruction;
+
+/// a square root of a 2x2 unitary: (M + sI) / t with s² = det M and t² = tr M + 2s, taking
+/// the sign of s that keeps t away from 0
+pub(crate) fn square_root(matrix: &Array2<Complex64>) -> Array2<Complex64> {
+    let det = matrix[[0, 0]] * matrix[[1, 1]] - matrix[[0, 1]] * matrix[[1, 0]];
+    let trace = matrix[[0, 0]] + matrix[[1, 1]];
+    let s = det.sqrt();
+    let s = if (trace + 2.0 * s).norm() >= (trace - 2.0 * s).norm() { s } else { -s };
+    let t = (trace + 2.0 * s).sqrt();
+    (matrix + &(Array2::<Complex64>::eye(2) * s)) / t
+}
+
+impl QuantumCircuit {
+    /// add an arbitrary unitary on `qubits` (the first one is the most significant bit of the
+    /// matrix index); errors if the matrix has the wrong size or is not unitary
+    ///
+    /// `to_qasm` writes it as a `#pragma` only this crate reads, `to_qasm_version(2)` as `u3`
+    /// gates if it acts on one qubit
+    pub fn unitary(&mut self, matrix: Array2<Complex64>, qubits: &[usize]) -> Result<&mut Self, QucomError> {
+        self.add_instruction(QasmInstruction::unitary(matrix, qubits)?)
+    }
+}
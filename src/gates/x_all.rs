@@ -4,7 +4,7 @@ impl QuantumCircuit {
     /// add Pauli-X gate to all qubits in the circuit
     pub fn x_all(&mut self) -> &mut Self {
         for qubit in 0..self.n {
-            self.x(qubit);
+            self.x(qubit).expect("x_all only uses the circuit's own qubits");
         }
         self
     }
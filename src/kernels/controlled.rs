@@ -0,0 +1,65 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::kernels::{amplitudes_mut, bit_position, for_each_subspace, insert_zero_bits, subspace_offsets, SharedAmplitudes};
+
+/// apply `gate` to `targets` in place, only on basis states where every control qubit is |1⟩
+/// covers CX, CZ and CCX, as well as any controlled k-qubit gate
+pub fn apply_controlled(
+    state: &mut Array2<Complex64>,
+    gate: &Array2<Complex64>,
+    controls: &[usize],
+    targets: &[usize],
+    n_qubits: usize,
+) {
+    apply_controlled_on(state, gate, controls, &vec![true; controls.len()], targets, n_qubits);
+}
+
+/// like `apply_controlled`, with control `i` required to be |1⟩ if `ctrl_state[i]` and |0⟩ otherwise
+pub fn apply_controlled_on(
+    state: &mut Array2<Complex64>,
+    gate: &Array2<Complex64>,
+    controls: &[usize],
+    ctrl_state: &[bool],
+    targets: &[usize],
+    n_qubits: usize,
+) {
+    assert_eq!(controls.len(), ctrl_state.len(), "one control state per control qubit");
+    let k = targets.len();
+    let sub_dim = 1 << k;
+    assert_eq!(gate.dim(), (sub_dim, sub_dim), "gate size does not match number of targets");
+
+    let offsets = subspace_offsets(targets, n_qubits);
+    let control_mask = controls
+        .iter()
+        .zip(ctrl_state)
+        .filter(|(_, closed)| **closed)
+        .fold(0usize, |acc, (&c, _)| acc | (1 << bit_position(c, n_qubits)));
+
+    let mut positions: Vec<usize> = controls
+        .iter()
+        .chain(targets.iter())
+        .map(|&q| bit_position(q, n_qubits))
+        .collect();
+    positions.sort_unstable();
+    positions.dedup();
+    assert_eq!(positions.len(), controls.len() + k, "control and target qubits must be distinct");
+
+    let amps = amplitudes_mut(state);
+    let len = amps.len();
+    let shared = SharedAmplitudes::new(amps);
+
+    // only the 2^(n - controls - targets) subspaces matching the control state are touched
+    for_each_subspace(len, len >> positions.len(), || vec![Complex64::new(0.0, 0.0); sub_dim], |buffer, base| {
+        let i = insert_zero_bits(base, &positions) | control_mask;
+        // SAFETY: each base owns the 2^k indices i + offsets
+        unsafe {
+            for (b, &off) in buffer.iter_mut().zip(offsets.iter()) {
+                *b = shared.get(i + off);
+            }
+            for (r, &off) in offsets.iter().enumerate() {
+                let value = gate.row(r).iter().zip(buffer.iter()).map(|(g, v)| g * v).sum();
+                shared.set(i + off, value);
+            }
+        }
+    });
+}
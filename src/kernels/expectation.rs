@@ -0,0 +1,48 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+
+#[cfg(feature = "parallel")]
+use crate::kernels::use_parallel;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// ⟨ψ|P|ψ⟩ for a Pauli string given by its masks (see `PauliString::masks`), without building P
+/// P|i⟩ = i^ny (-1)^popcount(i & z_mask) |i ^ x_mask⟩, so every amplitude pairs with one partner
+pub fn pauli_expectation(state: &Array2<Complex64>, x_mask: usize, z_mask: usize, n_y: usize) -> f64 {
+    let amps = state.as_slice().expect("statevector must be a contiguous column");
+    let term = |(i, amp): (usize, &Complex64)| {
+        let sign = if (i & z_mask).count_ones().is_multiple_of(2) { 1.0 } else { -1.0 };
+        amps[i ^ x_mask].conj() * amp * sign
+    };
+
+    #[cfg(feature = "parallel")]
+    let sum: Complex64 = if use_parallel(amps.len()) {
+        amps.par_iter().enumerate().map(term).sum()
+    } else {
+        amps.iter().enumerate().map(term).sum()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let sum: Complex64 = amps.iter().enumerate().map(term).sum();
+
+    (sum * Complex64::i().powu(n_y as u32)).re
+}
+
+/// P|ψ⟩ for a Pauli string given by its masks, as a new statevector
+pub fn apply_pauli(state: &Array2<Complex64>, x_mask: usize, z_mask: usize, n_y: usize) -> Array2<Complex64> {
+    let amps = state.as_slice().expect("statevector must be a contiguous column");
+    let phase = Complex64::i().powu(n_y as u32);
+    // amplitude j of P|ψ⟩ comes from amplitude j ^ x_mask of |ψ⟩
+    let term = |j: usize| {
+        let i = j ^ x_mask;
+        let sign = if (i & z_mask).count_ones().is_multiple_of(2) { 1.0 } else { -1.0 };
+        amps[i] * phase * sign
+    };
+
+    #[cfg(feature = "parallel")]
+    if use_parallel(amps.len()) {
+        let out: Vec<Complex64> = (0..amps.len()).into_par_iter().map(term).collect();
+        return Array2::from_shape_vec((amps.len(), 1), out).unwrap();
+    }
+
+    Array2::from_shape_vec((amps.len(), 1), (0..amps.len()).map(term).collect()).unwrap()
+}
@@ -0,0 +1,87 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::kernels::{amplitudes_mut, bit_position};
+
+#[cfg(feature = "parallel")]
+use crate::kernels::use_parallel;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// probabilities of measuring |0⟩ and |1⟩ on `qubit`
+pub fn qubit_probabilities(state: &Array2<Complex64>, qubit: usize, n_qubits: usize) -> (f64, f64) {
+    let mask = 1 << bit_position(qubit, n_qubits);
+    let amps = state.as_slice().expect("statevector must be a contiguous column");
+    let split = |(i, amp): (usize, &Complex64)| {
+        if i & mask == 0 { (amp.norm_sqr(), 0.0) } else { (0.0, amp.norm_sqr()) }
+    };
+    let add = |a: (f64, f64), b: (f64, f64)| (a.0 + b.0, a.1 + b.1);
+
+    #[cfg(feature = "parallel")]
+    if use_parallel(amps.len()) {
+        return amps.par_iter().enumerate().map(split).reduce(|| (0.0, 0.0), add);
+    }
+
+    amps.iter().enumerate().map(split).fold((0.0, 0.0), add)
+}
+
+/// probability of every basis state
+pub fn probabilities(state: &Array2<Complex64>) -> Vec<f64> {
+    let amps = state.as_slice().expect("statevector must be a contiguous column");
+
+    #[cfg(feature = "parallel")]
+    if use_parallel(amps.len()) {
+        return amps.par_iter().map(|c| c.norm_sqr()).collect();
+    }
+
+    amps.iter().map(|c| c.norm_sqr()).collect()
+}
+
+/// marginal distribution over `qubits`, indexed with `qubits[0]` as the most significant bit
+pub fn marginal_probabilities(probabilities: &[f64], qubits: &[usize], n_qubits: usize) -> Vec<f64> {
+    let positions: Vec<usize> = qubits.iter().map(|&q| bit_position(q, n_qubits)).collect();
+    let mut marginal = vec![0.0; 1 << qubits.len()];
+    for (i, p) in probabilities.iter().enumerate() {
+        let outcome = positions.iter().fold(0, |acc, &pos| (acc << 1) | ((i >> pos) & 1));
+        marginal[outcome] += p;
+    }
+    marginal
+}
+
+/// 2x2 reduced density matrix of `qubit`, tracing out every other qubit
+pub fn reduced_density_matrix(state: &Array2<Complex64>, qubit: usize, n_qubits: usize) -> Array2<Complex64> {
+    let mask = 1 << bit_position(qubit, n_qubits);
+    let amps = state.as_slice().expect("statevector must be a contiguous column");
+    let mut rho = Array2::<Complex64>::zeros((2, 2));
+    for i in (0..amps.len()).filter(|i| i & mask == 0) {
+        let (a0, a1) = (amps[i], amps[i | mask]);
+        rho[[0, 0]] += a0 * a0.conj();
+        rho[[0, 1]] += a0 * a1.conj();
+        rho[[1, 1]] += a1 * a1.conj();
+    }
+    rho[[1, 0]] = rho[[0, 1]].conj();
+    rho
+}
+
+/// project `qubit` onto `outcome` and renormalize
+/// `probability` is the probability of that outcome before collapse
+pub fn collapse(state: &mut Array2<Complex64>, qubit: usize, outcome: usize, probability: f64, n_qubits: usize) {
+    let mask = 1 << bit_position(qubit, n_qubits);
+    let keep = if outcome == 0 { 0 } else { mask };
+    let scale = if probability > 1e-20 { 1.0 / probability.sqrt() } else { 1.0 };
+    let amps = amplitudes_mut(state);
+    let project = |(i, amp): (usize, &mut Complex64)| {
+        if i & mask == keep {
+            *amp *= scale;
+        } else {
+            *amp = Complex64::new(0.0, 0.0);
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    if use_parallel(amps.len()) {
+        amps.par_iter_mut().enumerate().for_each(project);
+        return;
+    }
+
+    amps.iter_mut().enumerate().for_each(project);
+}
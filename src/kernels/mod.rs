@@ -0,0 +1,126 @@
+pub mod single_qubit;
+pub mod two_qubit;
+pub mod multi_qubit;
+pub mod controlled;
+pub mod permutation;
+pub mod measurement;
+pub mod expectation;
+
+pub use single_qubit::apply_single_qubit;
+pub use two_qubit::apply_two_qubit;
+pub use multi_qubit::apply_multi_qubit;
+pub use controlled::{apply_controlled, apply_controlled_on};
+pub use permutation::apply_swap;
+pub use expectation::{apply_pauli, pauli_expectation};
+pub use measurement::{probabilities, qubit_probabilities, marginal_probabilities, reduced_density_matrix, collapse};
+
+use ndarray::Array2;
+use num_complex::Complex64;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// statevectors with fewer amplitudes than this stay on the calling thread
+#[cfg(feature = "parallel")]
+pub const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+/// smallest number of loop iterations handed to a single worker
+#[cfg(feature = "parallel")]
+const MIN_CHUNK: usize = 1 << 10;
+
+/// bit position of a qubit inside a basis-state index (qubit 0 is the most significant bit)
+#[inline]
+pub(crate) fn bit_position(qubit: usize, n_qubits: usize) -> usize {
+    n_qubits - 1 - qubit
+}
+
+/// insert a zero bit at every position in `sorted_positions` (ascending)
+#[inline]
+pub(crate) fn insert_zero_bits(mut index: usize, sorted_positions: &[usize]) -> usize {
+    for &p in sorted_positions {
+        let low = index & ((1 << p) - 1);
+        index = ((index >> p) << (p + 1)) | low;
+    }
+    index
+}
+
+/// offsets of the 2^k basis states spanned by `qubits`, in matrix order
+/// (the first qubit in the list is the most significant bit of the matrix index)
+pub(crate) fn subspace_offsets(qubits: &[usize], n_qubits: usize) -> Vec<usize> {
+    let k = qubits.len();
+    (0..1usize << k)
+        .map(|m| {
+            qubits.iter().enumerate().fold(0, |acc, (j, &q)| {
+                if (m >> (k - 1 - j)) & 1 == 1 {
+                    acc | (1 << bit_position(q, n_qubits))
+                } else {
+                    acc
+                }
+            })
+        })
+        .collect()
+}
+
+/// contiguous amplitude slice of a column statevector
+#[inline]
+pub(crate) fn amplitudes_mut(state: &mut Array2<Complex64>) -> &mut [Complex64] {
+    state.as_slice_mut().expect("statevector must be a contiguous column")
+}
+
+/// whether a loop over `len` amplitudes should be split across threads
+#[cfg(feature = "parallel")]
+#[inline]
+pub(crate) fn use_parallel(len: usize) -> bool {
+    len >= PARALLEL_THRESHOLD
+}
+
+/// raw amplitude pointer shared between workers
+/// every kernel hands each worker a disjoint set of indices, so writes never alias
+#[derive(Clone, Copy)]
+pub(crate) struct SharedAmplitudes(*mut Complex64);
+
+unsafe impl Send for SharedAmplitudes {}
+unsafe impl Sync for SharedAmplitudes {}
+
+impl SharedAmplitudes {
+    pub(crate) fn new(amps: &mut [Complex64]) -> Self {
+        Self(amps.as_mut_ptr())
+    }
+
+    /// # Safety
+    /// `i` must be in bounds and not written by another worker
+    #[inline]
+    pub(crate) unsafe fn get(&self, i: usize) -> Complex64 {
+        unsafe { *self.0.add(i) }
+    }
+
+    /// # Safety
+    /// `i` must be in bounds and owned by the calling worker
+    #[inline]
+    pub(crate) unsafe fn set(&self, i: usize, value: Complex64) {
+        unsafe { *self.0.add(i) = value }
+    }
+}
+
+/// run `f` for every subspace index in `0..count`, on worker threads when `amplitudes` is large
+/// `init` builds per-worker scratch space (e.g. a gather buffer)
+#[allow(unused_variables)]
+pub(crate) fn for_each_subspace<T, I, F>(amplitudes: usize, count: usize, init: I, f: F)
+where
+    I: Fn() -> T + Sync + Send,
+    F: Fn(&mut T, usize) + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    if use_parallel(amplitudes) {
+        (0..count)
+            .into_par_iter()
+            .with_min_len(MIN_CHUNK)
+            .for_each_init(&init, |scratch, base| f(scratch, base));
+        return;
+    }
+
+    let mut scratch = init();
+    for base in 0..count {
+        f(&mut scratch, base);
+    }
+}
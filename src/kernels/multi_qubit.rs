@@ -0,0 +1,40 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::kernels::{amplitudes_mut, bit_position, for_each_subspace, insert_zero_bits, subspace_offsets, SharedAmplitudes};
+
+/// apply a 2^k x 2^k gate to `qubits` in place
+/// the first qubit in `qubits` is the most significant bit of the matrix index
+pub fn apply_multi_qubit(
+    state: &mut Array2<Complex64>,
+    gate: &Array2<Complex64>,
+    qubits: &[usize],
+    n_qubits: usize,
+) {
+    let k = qubits.len();
+    let sub_dim = 1 << k;
+    assert_eq!(gate.dim(), (sub_dim, sub_dim), "gate size does not match number of qubits");
+
+    let offsets = subspace_offsets(qubits, n_qubits);
+    let mut positions: Vec<usize> = qubits.iter().map(|&q| bit_position(q, n_qubits)).collect();
+    positions.sort_unstable();
+    positions.dedup();
+    assert_eq!(positions.len(), k, "gate qubits must be distinct");
+
+    let amps = amplitudes_mut(state);
+    let len = amps.len();
+    let shared = SharedAmplitudes::new(amps);
+
+    for_each_subspace(len, len >> k, || vec![Complex64::new(0.0, 0.0); sub_dim], |buffer, base| {
+        let i = insert_zero_bits(base, &positions);
+        // SAFETY: each base owns the 2^k indices i + offsets
+        unsafe {
+            for (b, &off) in buffer.iter_mut().zip(offsets.iter()) {
+                *b = shared.get(i + off);
+            }
+            for (r, &off) in offsets.iter().enumerate() {
+                let value = gate.row(r).iter().zip(buffer.iter()).map(|(g, v)| g * v).sum();
+                shared.set(i + off, value);
+            }
+        }
+    });
+}
@@ -0,0 +1,29 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::kernels::{amplitudes_mut, bit_position, for_each_subspace, insert_zero_bits, SharedAmplitudes};
+
+/// swap two qubits in place by exchanging the |..0..1..⟩ and |..1..0..⟩ amplitudes
+pub fn apply_swap(state: &mut Array2<Complex64>, q1: usize, q2: usize, n_qubits: usize) {
+    if q1 == q2 {
+        return;
+    }
+
+    let mask1 = 1 << bit_position(q1, n_qubits);
+    let mask2 = 1 << bit_position(q2, n_qubits);
+    let mut positions = [bit_position(q1, n_qubits), bit_position(q2, n_qubits)];
+    positions.sort_unstable();
+
+    let amps = amplitudes_mut(state);
+    let len = amps.len();
+    let shared = SharedAmplitudes::new(amps);
+
+    for_each_subspace(len, len >> 2, || (), |_, base| {
+        let i = insert_zero_bits(base, &positions);
+        // SAFETY: each base owns the pair (i | mask1, i | mask2)
+        unsafe {
+            let a = shared.get(i | mask1);
+            shared.set(i | mask1, shared.get(i | mask2));
+            shared.set(i | mask2, a);
+        }
+    });
+}
@@ -0,0 +1,30 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::kernels::{amplitudes_mut, bit_position, for_each_subspace, insert_zero_bits, SharedAmplitudes};
+
+/// apply a 2x2 gate to one qubit of the statevector in place
+/// walks every (|..0..⟩, |..1..⟩) amplitude pair once, so no 2^n x 2^n operator is built
+pub fn apply_single_qubit(
+    state: &mut Array2<Complex64>,
+    gate: &Array2<Complex64>,
+    qubit: usize,
+    n_qubits: usize,
+) {
+    let (g00, g01, g10, g11) = (gate[[0, 0]], gate[[0, 1]], gate[[1, 0]], gate[[1, 1]]);
+    let position = [bit_position(qubit, n_qubits)];
+    let stride = 1 << position[0];
+
+    let amps = amplitudes_mut(state);
+    let len = amps.len();
+    let shared = SharedAmplitudes::new(amps);
+
+    for_each_subspace(len, len >> 1, || (), |_, base| {
+        let i = insert_zero_bits(base, &position);
+        // SAFETY: each base owns the pair (i, i + stride)
+        unsafe {
+            let (v0, v1) = (shared.get(i), shared.get(i + stride));
+            shared.set(i, g00 * v0 + g01 * v1);
+            shared.set(i + stride, g10 * v0 + g11 * v1);
+        }
+    });
+}
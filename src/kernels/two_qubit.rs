@@ -0,0 +1,43 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::kernels::{amplitudes_mut, bit_position, for_each_subspace, insert_zero_bits, SharedAmplitudes};
+
+/// apply a 4x4 gate to two qubits of the statevector in place
+/// the matrix is indexed as |q0 q1⟩, matching `swap()` and the kronecker ordering
+pub fn apply_two_qubit(
+    state: &mut Array2<Complex64>,
+    gate: &Array2<Complex64>,
+    q0: usize,
+    q1: usize,
+    n_qubits: usize,
+) {
+    assert_ne!(q0, q1, "two-qubit gate needs two distinct qubits");
+
+    let mut g = [[Complex64::new(0.0, 0.0); 4]; 4];
+    for (r, row) in g.iter_mut().enumerate() {
+        for (c, v) in row.iter_mut().enumerate() {
+            *v = gate[[r, c]];
+        }
+    }
+
+    let m0 = 1 << bit_position(q0, n_qubits);
+    let m1 = 1 << bit_position(q1, n_qubits);
+    let offsets = [0, m1, m0, m0 | m1];
+    let mut positions = [bit_position(q0, n_qubits), bit_position(q1, n_qubits)];
+    positions.sort_unstable();
+
+    let amps = amplitudes_mut(state);
+    let len = amps.len();
+    let shared = SharedAmplitudes::new(amps);
+
+    for_each_subspace(len, len >> 2, || (), |_, base| {
+        let i = insert_zero_bits(base, &positions);
+        // SAFETY: each base owns the four indices i + offsets
+        unsafe {
+            let v = offsets.map(|off| shared.get(i + off));
+            for (row, &off) in g.iter().zip(offsets.iter()) {
+                shared.set(i + off, row[0] * v[0] + row[1] * v[1] + row[2] * v[2] + row[3] * v[3]);
+            }
+        }
+    });
+}
@@ -1,8 +1,16 @@
 pub mod circuits;
 pub mod states;
 pub mod gates;
+pub mod kernels;
 pub mod utils;
 pub mod qasm;
+pub mod simulators;
+pub mod noise;
+pub mod observables;
+pub mod parameters;
+pub mod error;
+
+pub use error::QucomError;
 
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
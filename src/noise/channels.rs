@@ -0,0 +1,153 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::error::QucomError;
+use crate::gates::{pauli_x, pauli_y, pauli_z};
+use crate::utils::kron;
+
+/// a completely positive, trace preserving map given by its Kraus operators, ρ → Σ KρK†
+#[derive(Debug, Clone)]
+pub struct QuantumChannel {
+    kraus: Vec<Array2<Complex64>>,
+    n_qubits: usize,
+}
+
+impl QuantumChannel {
+    /// channel from an arbitrary list of 2^k x 2^k Kraus operators
+    /// errors unless Σ K†K = I
+    pub fn from_kraus(kraus: Vec<Array2<Complex64>>) -> Result<Self, QucomError> {
+        let invalid = |message: &str| Err(QucomError::InvalidArgument(message.to_string()));
+        let Some(first) = kraus.first() else {
+            return invalid("a channel needs at least one Kraus operator");
+        };
+        let (dim, cols) = first.dim();
+        if dim != cols || !dim.is_power_of_two() {
+            return invalid("Kraus operators must be 2^k x 2^k");
+        }
+
+        let mut sum = Array2::<Complex64>::zeros((dim, dim));
+        for k in &kraus {
+            if k.dim() != (dim, dim) {
+                return invalid("Kraus operators must all have the same size");
+            }
+            sum = sum + k.t().mapv(|c| c.conj()).dot(k);
+        }
+        let identity = Array2::<Complex64>::eye(dim);
+        let deviation = (&sum - &identity).iter().map(|c| c.norm()).fold(0.0, f64::max);
+        if deviation >= 1e-8 {
+            return invalid("Kraus operators are not trace preserving (Σ K†K ≠ I)");
+        }
+
+        Ok(Self { kraus, n_qubits: dim.trailing_zeros() as usize })
+    }
+
+    /// Pauli channel: X, Y, Z applied with probabilities px, py, pz
+    pub fn pauli(px: f64, py: f64, pz: f64) -> Result<Self, QucomError> {
+        let pi = 1.0 - px - py - pz;
+        if !(px >= 0.0 && py >= 0.0 && pz >= 0.0 && pi >= -1e-12) {
+            return Err(QucomError::InvalidArgument(format!(
+                "Pauli probabilities must be non-negative and sum to at most 1, got {}, {}, {}", px, py, pz
+            )));
+        }
+
+        let scaled = |m: Array2<Complex64>, p: f64| m * Complex64::new(p.max(0.0).sqrt(), 0.0);
+        Self::from_kraus(vec![
+            scaled(Array2::eye(2), pi),
+            scaled(pauli_x(), px),
+            scaled(pauli_y(), py),
+            scaled(pauli_z(), pz),
+        ])
+    }
+
+    /// depolarizing channel on `n_qubits`: ρ → (1 - p)ρ + p I / 2^n
+    pub fn depolarizing(p: f64, n_qubits: usize) -> Result<Self, QucomError> {
+        check_probability("depolarizing probability", p)?;
+        let paulis = [Array2::<Complex64>::eye(2), pauli_x(), pauli_y(), pauli_z()];
+        let n_paulis = 1usize << (2 * n_qubits);
+
+        let kraus = (0..n_paulis)
+            .map(|index| {
+                // base-4 digits of index pick the Pauli on each qubit
+                let mut op = Array2::<Complex64>::eye(1);
+                for q in (0..n_qubits).rev() {
+                    op = kron(&op, &paulis[(index >> (2 * q)) & 3]);
+                }
+                let weight = if index == 0 {
+                    1.0 - p + p / n_paulis as f64
+                } else {
+                    p / n_paulis as f64
+                };
+                op * Complex64::new(weight.sqrt(), 0.0)
+            })
+            .collect();
+
+        Self::from_kraus(kraus)
+    }
+
+    /// amplitude damping (energy relaxation |1⟩ → |0⟩) with probability gamma
+    pub fn amplitude_damping(gamma: f64) -> Result<Self, QucomError> {
+        check_probability("gamma", gamma)?;
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        Self::from_kraus(vec![
+            Array2::from_shape_vec((2, 2), vec![one, zero, zero, Complex64::new((1.0 - gamma).sqrt(), 0.0)]).unwrap(),
+            Array2::from_shape_vec((2, 2), vec![zero, Complex64::new(gamma.sqrt(), 0.0), zero, zero]).unwrap(),
+        ])
+    }
+
+    /// phase damping (pure dephasing) with probability lambda
+    pub fn phase_damping(lambda: f64) -> Result<Self, QucomError> {
+        check_probability("lambda", lambda)?;
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        Self::from_kraus(vec![
+            Array2::from_shape_vec((2, 2), vec![one, zero, zero, Complex64::new((1.0 - lambda).sqrt(), 0.0)]).unwrap(),
+            Array2::from_shape_vec((2, 2), vec![zero, zero, zero, Complex64::new(lambda.sqrt(), 0.0)]).unwrap(),
+        ])
+    }
+
+    /// thermal relaxation towards |0⟩ over `time`, given T1 and T2 (same units, T2 ≤ 2·T1)
+    pub fn thermal_relaxation(t1: f64, t2: f64, time: f64) -> Result<Self, QucomError> {
+        if !(t1 > 0.0 && t2 > 0.0 && time >= 0.0) {
+            return Err(QucomError::InvalidArgument("T1, T2 and time must be positive".to_string()));
+        }
+        if t2 > 2.0 * t1 {
+            return Err(QucomError::InvalidArgument("T2 cannot exceed 2·T1".to_string()));
+        }
+
+        // amplitude damping gives e^(-t/2T1) coherence decay, phase damping makes up the rest to e^(-t/T2)
+        let gamma = 1.0 - (-time / t1).exp();
+        let lambda = 1.0 - (-2.0 * time / t2 + time / t1).exp();
+        Self::amplitude_damping(gamma)?.compose(&Self::phase_damping(lambda.max(0.0))?)
+    }
+
+    /// the channel that applies `self` and then `other`
+    pub fn compose(&self, other: &QuantumChannel) -> Result<Self, QucomError> {
+        if self.n_qubits != other.n_qubits {
+            return Err(QucomError::InvalidArgument(format!(
+                "cannot compose a {}-qubit channel with a {}-qubit one", self.n_qubits, other.n_qubits
+            )));
+        }
+        let kraus = other
+            .kraus
+            .iter()
+            .flat_map(|b| self.kraus.iter().map(move |a| b.dot(a)))
+            .collect();
+        Self::from_kraus(kraus)
+    }
+
+    pub fn kraus(&self) -> &[Array2<Complex64>] {
+        &self.kraus
+    }
+
+    pub fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+}
+
+/// error unless `p` lies in [0, 1]
+fn check_probability(name: &str, p: f64) -> Result<(), QucomError> {
+    match (0.0..=1.0).contains(&p) {
+        true => Ok(()),
+        false => Err(QucomError::InvalidArgument(format!("{} must be in [0, 1], got {}", name, p))),
+    }
+}
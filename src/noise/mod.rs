@@ -0,0 +1,102 @@
+pub mod channels;
+pub mod readout;
+
+pub use channels::QuantumChannel;
+pub use readout::ReadoutError;
+
+/// a channel attached to a set of instruction names, optionally only on specific qubits
+#[derive(Debug, Clone)]
+struct QuantumError {
+    channel: QuantumChannel,
+    instructions: Vec<String>,
+    qubits: Option<Vec<usize>>,
+}
+
+/// noise applied during simulation
+///
+/// quantum errors are attached to instruction names as returned by `QasmInstruction::name`
+/// ("h", "cx", "measure", "reset", "delay", ...). a channel on as many qubits as the instruction
+/// acts on all of them; a single-qubit channel on a wider instruction acts on each qubit in turn.
+/// "measure" errors are applied just before the measurement, everything else just after.
+#[derive(Debug, Clone, Default)]
+pub struct NoiseModel {
+    quantum_errors: Vec<QuantumError>,
+    readout_errors: Vec<(Option<usize>, ReadoutError)>,
+}
+
+impl NoiseModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// attach `channel` to `instructions` on every qubit
+    pub fn add_all_qubit_quantum_error(&mut self, channel: QuantumChannel, instructions: &[&str]) -> &mut Self {
+        self.quantum_errors.push(QuantumError {
+            channel,
+            instructions: instructions.iter().map(|s| s.to_string()).collect(),
+            qubits: None,
+        });
+        self
+    }
+
+    /// attach `channel` to `instructions` acting on exactly `qubits` (in operand order)
+    pub fn add_quantum_error(&mut self, channel: QuantumChannel, instructions: &[&str], qubits: &[usize]) -> &mut Self {
+        self.quantum_errors.push(QuantumError {
+            channel,
+            instructions: instructions.iter().map(|s| s.to_string()).collect(),
+            qubits: Some(qubits.to_vec()),
+        });
+        self
+    }
+
+    /// readout error for every measured qubit
+    pub fn add_all_qubit_readout_error(&mut self, error: ReadoutError) -> &mut Self {
+        self.readout_errors.push((None, error));
+        self
+    }
+
+    /// readout error for one qubit, takes precedence over the all-qubit error
+    pub fn add_readout_error(&mut self, error: ReadoutError, qubit: usize) -> &mut Self {
+        self.readout_errors.push((Some(qubit), error));
+        self
+    }
+
+    /// true when no errors have been added
+    pub fn is_ideal(&self) -> bool {
+        self.quantum_errors.is_empty() && self.readout_errors.is_empty()
+    }
+
+    /// channels to apply for instruction `name` on `qubits`, with the qubits each one acts on
+    pub fn channels_for(&self, name: &str, qubits: &[usize]) -> Vec<(&QuantumChannel, Vec<usize>)> {
+        let mut channels = Vec::new();
+
+        for error in &self.quantum_errors {
+            if !error.instructions.iter().any(|i| i == name) {
+                continue;
+            }
+            if let Some(targets) = &error.qubits
+                && targets.as_slice() != qubits
+            {
+                continue;
+            }
+
+            let arity = error.channel.n_qubits();
+            if arity == qubits.len() {
+                channels.push((&error.channel, qubits.to_vec()));
+            } else if arity == 1 {
+                channels.extend(qubits.iter().map(|&q| (&error.channel, vec![q])));
+            }
+        }
+
+        channels
+    }
+
+    /// readout error for `qubit`, if any
+    pub fn readout_error_for(&self, qubit: usize) -> Option<&ReadoutError> {
+        self.readout_errors
+            .iter()
+            .find(|(q, _)| *q == Some(qubit))
+            .or_else(|| self.readout_errors.iter().find(|(q, _)| q.is_none()))
+            .map(|(_, error)| error)
+    }
+}
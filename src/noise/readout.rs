@@ -0,0 +1,42 @@
+use rand::Rng;
+use crate::error::QucomError;
+
+/// classical readout error: `probabilities[actual][recorded]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadoutError {
+    probabilities: [[f64; 2]; 2],
+}
+
+impl ReadoutError {
+    /// from an assignment matrix whose rows are the actual outcome and columns the recorded one
+    pub fn new(probabilities: [[f64; 2]; 2]) -> Result<Self, QucomError> {
+        for row in &probabilities {
+            if !row.iter().all(|p| *p >= 0.0) {
+                return Err(QucomError::InvalidArgument("readout probabilities must be non-negative".to_string()));
+            }
+            if (row[0] + row[1] - 1.0).abs() >= 1e-9 {
+                return Err(QucomError::InvalidArgument("readout matrix rows must sum to 1".to_string()));
+            }
+        }
+        Ok(Self { probabilities })
+    }
+
+    /// probability `p0_given_1` of reading 0 for |1⟩ and `p1_given_0` of reading 1 for |0⟩
+    pub fn asymmetric(p1_given_0: f64, p0_given_1: f64) -> Result<Self, QucomError> {
+        Self::new([[1.0 - p1_given_0, p1_given_0], [p0_given_1, 1.0 - p0_given_1]])
+    }
+
+    /// both outcomes flip with probability `p`
+    pub fn symmetric(p: f64) -> Result<Self, QucomError> {
+        Self::asymmetric(p, p)
+    }
+
+    pub fn probabilities(&self) -> [[f64; 2]; 2] {
+        self.probabilities
+    }
+
+    /// sample the recorded outcome for an actual outcome
+    pub fn apply<R: Rng + ?Sized>(&self, outcome: usize, rng: &mut R) -> usize {
+        if rng.random::<f64>() < self.probabilities[outcome][0] { 0 } else { 1 }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod pauli_string;
+pub mod sparse_pauli_op;
+
+pub use pauli_string::{Pauli, PauliString};
+pub use sparse_pauli_op::SparsePauliOp;
@@ -0,0 +1,128 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::error::QucomError;
+use crate::kernels::bit_position;
+
+/// single-qubit Pauli operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pauli {
+    I,
+    X,
+    Y,
+    Z,
+}
+
+impl Pauli {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'I' => Some(Pauli::I),
+            'X' => Some(Pauli::X),
+            'Y' => Some(Pauli::Y),
+            'Z' => Some(Pauli::Z),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Pauli::I => 'I',
+            Pauli::X => 'X',
+            Pauli::Y => 'Y',
+            Pauli::Z => 'Z',
+        }
+    }
+}
+
+/// tensor product of Paulis on specific qubits, identity everywhere else
+///
+/// stored sparsely as (qubit, Pauli) pairs sorted by qubit, so the same string can be applied
+/// to circuits of any width that contain its qubits
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PauliString {
+    paulis: Vec<(usize, Pauli)>,
+}
+
+impl PauliString {
+    /// identity on every qubit
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    /// from (qubit, Pauli) pairs, e.g. `[(0, Pauli::Z), (3, Pauli::Z)]`; errors when a qubit
+    /// appears twice
+    pub fn from_sparse(paulis: &[(usize, Pauli)]) -> Result<Self, QucomError> {
+        let mut sorted: Vec<(usize, Pauli)> = paulis.iter().copied().filter(|(_, p)| *p != Pauli::I).collect();
+        sorted.sort_by_key(|(q, _)| *q);
+        if let Some(w) = sorted.windows(2).find(|w| w[0].0 == w[1].0) {
+            return Err(QucomError::DuplicateQubit(w[0].0));
+        }
+        Ok(Self { paulis: sorted })
+    }
+
+    /// non-identity factors as (qubit, Pauli), sorted by qubit
+    pub fn paulis(&self) -> &[(usize, Pauli)] {
+        &self.paulis
+    }
+
+    /// qubits with a non-identity factor
+    pub fn qubits(&self) -> Vec<usize> {
+        self.paulis.iter().map(|(q, _)| *q).collect()
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.paulis.is_empty()
+    }
+
+    /// Pauli acting on `qubit`
+    pub fn get(&self, qubit: usize) -> Pauli {
+        self.paulis
+            .iter()
+            .find(|(q, _)| *q == qubit)
+            .map_or(Pauli::I, |(_, p)| *p)
+    }
+
+    /// highest qubit index acted on, plus one
+    pub fn min_qubits(&self) -> usize {
+        self.paulis.last().map_or(0, |(q, _)| q + 1)
+    }
+
+    /// (x mask, z mask, number of Y factors) over basis-state indices of an n-qubit register
+    /// P|i⟩ = i^ny (-1)^popcount(i & z) |i ^ x⟩, since Y = iXZ
+    pub fn masks(&self, n_qubits: usize) -> (usize, usize, usize) {
+        assert!(self.min_qubits() <= n_qubits, "Pauli string acts outside the register");
+        self.paulis.iter().fold((0, 0, 0), |(x, z, ny), &(q, p)| {
+            let bit = 1 << bit_position(q, n_qubits);
+            match p {
+                Pauli::I => (x, z, ny),
+                Pauli::X => (x | bit, z, ny),
+                Pauli::Y => (x | bit, z | bit, ny + 1),
+                Pauli::Z => (x, z | bit, ny),
+            }
+        })
+    }
+}
+
+/// dense labels list qubit 0 first, e.g. "XIZ" is X on qubit 0 and Z on qubit 2
+impl FromStr for PauliString {
+    type Err = String;
+
+    fn from_str(label: &str) -> Result<Self, Self::Err> {
+        let paulis = label
+            .chars()
+            .enumerate()
+            .map(|(q, c)| Pauli::from_char(c).map(|p| (q, p)).ok_or_else(|| format!("Invalid Pauli '{}' in '{}'", c, label)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_sparse(&paulis).expect("a dense label has one Pauli per qubit"))
+    }
+}
+
+/// sparse form, e.g. "X0 Z2", or "I" for the identity
+impl fmt::Display for PauliString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.paulis.is_empty() {
+            return write!(f, "I");
+        }
+        let factors: Vec<String> = self.paulis.iter().map(|(q, p)| format!("{}{}", p.to_char(), q)).collect();
+        write!(f, "{}", factors.join(" "))
+    }
+}
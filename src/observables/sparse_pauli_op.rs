@@ -0,0 +1,78 @@
+use std::fmt;
+use crate::error::QucomError;
+use crate::observables::{Pauli, PauliString};
+
+/// real linear combination of Pauli strings, Σ c_k P_k, e.g. a VQE Hamiltonian
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SparsePauliOp {
+    terms: Vec<(f64, PauliString)>,
+}
+
+impl SparsePauliOp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// from (coefficient, dense label) pairs, e.g. `[(0.5, "ZZ"), (-0.2, "XI")]`
+    pub fn from_list(terms: &[(f64, &str)]) -> Result<Self, QucomError> {
+        let mut op = Self::new();
+        for (coefficient, label) in terms {
+            op.add_term(*coefficient, label.parse().map_err(QucomError::InvalidArgument)?);
+        }
+        Ok(op)
+    }
+
+    /// add c · P, merging with an existing term on the same Pauli string
+    pub fn add_term(&mut self, coefficient: f64, pauli: PauliString) -> &mut Self {
+        match self.terms.iter_mut().find(|(_, p)| *p == pauli) {
+            Some((c, _)) => *c += coefficient,
+            None => self.terms.push((coefficient, pauli)),
+        }
+        self
+    }
+
+    pub fn terms(&self) -> &[(f64, PauliString)] {
+        &self.terms
+    }
+
+    /// highest qubit index acted on by any term, plus one
+    pub fn min_qubits(&self) -> usize {
+        self.terms.iter().map(|(_, p)| p.min_qubits()).max().unwrap_or(0)
+    }
+
+    /// split the terms into groups measurable in one basis: within a group every qubit is
+    /// acted on by at most one kind of Pauli (qubit-wise commuting). greedy, first fit.
+    /// returns each group's shared basis and its term indices; identity terms are left out
+    pub fn measurement_groups(&self) -> Vec<(PauliString, Vec<usize>)> {
+        let mut groups: Vec<(PauliString, Vec<usize>)> = Vec::new();
+        for (k, (_, pauli)) in self.terms.iter().enumerate().filter(|(_, (_, p))| !p.is_identity()) {
+            let fits = |basis: &PauliString| {
+                pauli.paulis().iter().all(|&(q, p)| matches!(basis.get(q), Pauli::I) || basis.get(q) == p)
+            };
+            match groups.iter_mut().find(|(basis, _)| fits(basis)) {
+                Some((basis, members)) => {
+                    let mut merged = basis.paulis().to_vec();
+                    merged.extend(pauli.paulis().iter().filter(|(q, _)| basis.get(*q) == Pauli::I));
+                    *basis = PauliString::from_sparse(&merged).expect("only qubits the basis leaves free are added");
+                    members.push(k);
+                }
+                None => groups.push((pauli.clone(), vec![k])),
+            }
+        }
+        groups
+    }
+}
+
+impl From<PauliString> for SparsePauliOp {
+    fn from(pauli: PauliString) -> Self {
+        Self { terms: vec![(1.0, pauli)] }
+    }
+}
+
+/// e.g. "0.5 * Z0 Z1 + -0.2 * X0"
+impl fmt::Display for SparsePauliOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let terms: Vec<String> = self.terms.iter().map(|(c, p)| format!("{} * {}", c, p)).collect();
+        write!(f, "{}", terms.join(" + "))
+    }
+}
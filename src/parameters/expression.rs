@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use crate::error::QucomError;
+use crate::parameters::Parameter;
+
+/// linear expression in parameters, c0 + Σ c_k θ_k
+///
+/// linear is enough for rotation angles in variational circuits, and keeps ∂angle/∂θ a
+/// constant (`coefficient`) for gradients
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParameterExpression {
+    terms: Vec<(Parameter, f64)>,
+    constant: f64,
+}
+
+impl ParameterExpression {
+    pub fn constant(value: f64) -> Self {
+        Self { terms: Vec::new(), constant: value }
+    }
+
+    /// parameters with a non-zero coefficient, in order of first use
+    pub fn parameters(&self) -> impl Iterator<Item = &Parameter> {
+        self.terms.iter().map(|(p, _)| p)
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// the value if no parameters are left
+    pub fn value(&self) -> Option<f64> {
+        self.is_constant().then_some(self.constant)
+    }
+
+    /// ∂expression/∂parameter
+    pub fn coefficient(&self, parameter: &Parameter) -> f64 {
+        self.terms.iter().find(|(p, _)| p == parameter).map_or(0.0, |(_, c)| *c)
+    }
+
+    /// evaluate with a value for every parameter, keyed by name
+    pub fn bind(&self, values: &HashMap<String, f64>) -> Result<f64, QucomError> {
+        self.terms.iter().try_fold(self.constant, |acc, (p, c)| match values.get(p.name()) {
+            Some(v) => Ok(acc + c * v),
+            None => Err(QucomError::UnboundParameter(p.name().to_string())),
+        })
+    }
+
+    /// the expression with the parameters named in `values` replaced by their values,
+    /// the others kept
+    pub fn substitute(&self, values: &HashMap<String, f64>) -> ParameterExpression {
+        let mut result = ParameterExpression::constant(self.constant);
+        for (p, c) in &self.terms {
+            match values.get(p.name()) {
+                Some(v) => result.constant += c * v,
+                None => result.add_term(p.clone(), *c),
+            }
+        }
+        result
+    }
+
+    fn add_term(&mut self, parameter: Parameter, coefficient: f64) {
+        match self.terms.iter_mut().find(|(p, _)| *p == parameter) {
+            Some((_, c)) => *c += coefficient,
+            None => self.terms.push((parameter, coefficient)),
+        }
+        self.terms.retain(|(_, c)| *c != 0.0);
+    }
+
+    fn scale(mut self, factor: f64) -> Self {
+        self.constant *= factor;
+        self.terms.iter_mut().for_each(|(_, c)| *c *= factor);
+        self.terms.retain(|(_, c)| *c != 0.0);
+        self
+    }
+}
+
+impl From<f64> for ParameterExpression {
+    fn from(value: f64) -> Self {
+        Self::constant(value)
+    }
+}
+
+impl From<&Parameter> for ParameterExpression {
+    fn from(parameter: &Parameter) -> Self {
+        Self { terms: vec![(parameter.clone(), 1.0)], constant: 0.0 }
+    }
+}
+
+impl From<Parameter> for ParameterExpression {
+    fn from(parameter: Parameter) -> Self {
+        Self { terms: vec![(parameter, 1.0)], constant: 0.0 }
+    }
+}
+
+impl<T: Into<ParameterExpression>> Add<T> for ParameterExpression {
+    type Output = Self;
+    fn add(mut self, rhs: T) -> Self {
+        let rhs = rhs.into();
+        self.constant += rhs.constant;
+        for (p, c) in rhs.terms {
+            self.add_term(p, c);
+        }
+        self
+    }
+}
+
+impl<T: Into<ParameterExpression>> Sub<T> for ParameterExpression {
+    type Output = Self;
+    fn sub(self, rhs: T) -> Self {
+        self + rhs.into().scale(-1.0)
+    }
+}
+
+impl Mul<f64> for ParameterExpression {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self {
+        self.scale(rhs)
+    }
+}
+
+impl Mul<ParameterExpression> for f64 {
+    type Output = ParameterExpression;
+    fn mul(self, rhs: ParameterExpression) -> ParameterExpression {
+        rhs.scale(self)
+    }
+}
+
+impl Div<f64> for ParameterExpression {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self {
+        self.scale(1.0 / rhs)
+    }
+}
+
+impl Neg for ParameterExpression {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.scale(-1.0)
+    }
+}
+
+/// QASM 3 form, e.g. `2*theta - phi + 0.5`
+impl fmt::Display for ParameterExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "{}", self.constant);
+        }
+        for (i, (p, c)) in self.terms.iter().enumerate() {
+            let sign = if *c < 0.0 { "-" } else { "+" };
+            match i {
+                0 if *c < 0.0 => write!(f, "-")?,
+                0 => {}
+                _ => write!(f, " {} ", sign)?,
+            }
+            if c.abs() == 1.0 {
+                write!(f, "{}", p)?;
+            } else {
+                write!(f, "{}*{}", c.abs(), p)?;
+            }
+        }
+        if self.constant != 0.0 {
+            let sign = if self.constant < 0.0 { "-" } else { "+" };
+            write!(f, " {} {}", sign, self.constant.abs())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod parameter;
+pub mod expression;
+
+pub use parameter::Parameter;
+pub use expression::ParameterExpression;
@@ -0,0 +1,90 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use crate::error::QucomError;
+use crate::parameters::ParameterExpression;
+use crate::qasm::lower;
+use crate::qasm::parser::KEYWORDS;
+
+/// named placeholder for an angle, bound to a value after the circuit is built
+/// emitted as `input float[64] <name>;` in QASM 3
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Parameter {
+    name: String,
+}
+
+impl Parameter {
+    /// the name must be an identifier the QASM export can declare and read back, so not a
+    /// keyword or a constant such as `pi`
+    pub fn new(name: &str) -> Result<Self, QucomError> {
+        let mut chars = name.chars();
+        let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_');
+        if !valid {
+            return Err(QucomError::InvalidArgument(format!("parameter name '{}' is not a valid identifier", name)));
+        }
+        if KEYWORDS.contains(&name) || lower::constant(name).is_some() {
+            return Err(QucomError::InvalidArgument(format!(
+                "'{}' already means something in QASM, so it cannot name a parameter", name
+            )));
+        }
+        Ok(Self::named(name))
+    }
+
+    /// a parameter for a name a QASM program already declared
+    pub(crate) fn named(name: &str) -> Self {
+        Self { name: name.to_string() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+// arithmetic on parameters builds expressions, e.g. `2.0 * &theta + 0.5`
+impl<T: Into<ParameterExpression>> Add<T> for &Parameter {
+    type Output = ParameterExpression;
+    fn add(self, rhs: T) -> ParameterExpression {
+        ParameterExpression::from(self) + rhs
+    }
+}
+
+impl<T: Into<ParameterExpression>> Sub<T> for &Parameter {
+    type Output = ParameterExpression;
+    fn sub(self, rhs: T) -> ParameterExpression {
+        ParameterExpression::from(self) - rhs
+    }
+}
+
+impl Mul<f64> for &Parameter {
+    type Output = ParameterExpression;
+    fn mul(self, rhs: f64) -> ParameterExpression {
+        ParameterExpression::from(self) * rhs
+    }
+}
+
+impl Mul<&Parameter> for f64 {
+    type Output = ParameterExpression;
+    fn mul(self, rhs: &Parameter) -> ParameterExpression {
+        ParameterExpression::from(rhs) * self
+    }
+}
+
+impl Div<f64> for &Parameter {
+    type Output = ParameterExpression;
+    fn div(self, rhs: f64) -> ParameterExpression {
+        ParameterExpression::from(self) / rhs
+    }
+}
+
+impl Neg for &Parameter {
+    type Output = ParameterExpression;
+    fn neg(self) -> ParameterExpression {
+        -ParameterExpression::from(self)
+    }
+}
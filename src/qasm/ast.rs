@@ -0,0 +1,162 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::error::QucomError;
+
+/// 1-based line and column of a token or node in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    /// parse error pointing at this position
+    pub fn error(self, message: impl Into<String>) -> QucomError {
+        QucomError::ParseError { line: self.line, column: self.column, message: message.into() }
+    }
+}
+
+/// a parsed OpenQASM program, before names are resolved
+#[derive(Debug, Clone)]
+pub struct Program {
+    /// the `OPENQASM x.y;` header, if present
+    pub version: Option<String>,
+    pub statements: Vec<Statement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+/// the values a `for` loop runs over
+#[derive(Debug, Clone)]
+pub enum LoopValues {
+    /// `[start:end]` or `[start:step:end]`, the end included
+    Range { start: Expr, step: Option<Expr>, end: Expr },
+    /// `{1, 3, 5}`
+    Set(Vec<Expr>),
+}
+
+#[derive(Debug, Clone)]
+pub enum StatementKind {
+    /// `include "file";`
+    Include(String),
+    /// `qubit[n] name;`, `qubit name;` or `qreg name[n];`; `size` is `None` for a lone qubit
+    QubitDeclaration { name: String, size: Option<usize> },
+    /// `bit[n] name;`, `bit name;` or `creg name[n];`
+    BitDeclaration { name: String, size: Option<usize> },
+    /// `input float[64] name;`
+    Input { ty: String, name: String },
+    /// `const float[64] name = value;`
+    Const { ty: String, name: String, value: Expr },
+    /// `int[32] name;`, `uint name = value;` or `bool name = value;`
+    ClassicalDeclaration { ty: String, name: String, value: Option<Expr> },
+    /// `name = value;`, `name += value;` with `op` `Some("+")`, or `c[0] = f(q[0]);`
+    Assignment { target: Operand, op: Option<&'static str>, value: Expr },
+    /// `gate name(params) a, b { body }`
+    GateDefinition { name: String, params: Vec<String>, qubits: Vec<String>, body: Vec<Statement> },
+    /// `opaque name(params) a, b;`, a gate with no definition
+    Opaque { name: String, params: Vec<String>, qubits: Vec<String> },
+    /// `def name(qubit a, float[64] theta) -> bit { body }`
+    Subroutine { name: String, arguments: Vec<Argument>, returns: Option<String>, body: Vec<Statement> },
+    /// `return;` or `return value;`
+    Return(Option<Expr>),
+    /// `return measure q;`
+    ReturnMeasure(Operand),
+    /// `ctrl @ inv @ name(args) a, b;`
+    GateCall { modifiers: Vec<Modifier>, name: String, name_span: Span, args: Vec<Expr>, operands: Vec<Operand> },
+    /// `measure q -> c;` or `c = measure q;`; `target` is `None` for a bare `measure q;`
+    Measure { qubit: Operand, target: Option<Operand> },
+    Reset(Operand),
+    /// `barrier;` (every qubit) or `barrier a, b;`
+    Barrier(Vec<Operand>),
+    /// `delay[100ns] q[0];`
+    Delay { duration: f64, unit: String, operands: Vec<Operand> },
+    If { condition: Expr, then_body: Vec<Statement>, else_body: Option<Vec<Statement>> },
+    While { condition: Expr, body: Vec<Statement> },
+    /// `for i in [start:end] { body }`, `for i in [start:step:end]` or `for i in {1, 3, 5}`
+    For { variable: String, values: LoopValues, body: Vec<Statement> },
+    /// `switch (value) { case 1, 2 { ... } default { ... } }`
+    Switch { value: Expr, cases: Vec<(Vec<Expr>, Vec<Statement>)>, default: Option<Vec<Statement>> },
+    Break,
+    Continue,
+    /// `end;`, stopping the program
+    End,
+    /// `#pragma qucom unitary [[...]] q[0];`
+    Unitary { matrix: Array2<Complex64>, operands: Vec<Operand> },
+    /// any other `#pragma`, kept as text
+    Pragma(String),
+}
+
+/// typed argument of a `def`
+#[derive(Debug, Clone)]
+pub struct Argument {
+    pub ty: ArgumentType,
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ArgumentType {
+    /// `qubit a` or `qubit[n] a`, passed a qubit or a whole register
+    Qubit(Option<usize>),
+    /// `bit c` or `bit[n] c`
+    Bit(Option<usize>),
+    /// `int[32] n`, `float[64] theta`, `bool flag`, ... passed by value
+    Value(String),
+}
+
+/// gate modifier in front of a call
+#[derive(Debug, Clone)]
+pub enum Modifier {
+    /// `ctrl(count) @`, or `negctrl(count) @` when `negated`
+    Ctrl { count: usize, negated: bool },
+    /// `inv @`
+    Inv,
+    /// `pow(k) @`
+    Pow(Expr),
+}
+
+/// qubit or classical bit reference
+#[derive(Debug, Clone)]
+pub struct Operand {
+    pub target: OperandTarget,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum OperandTarget {
+    /// a whole register or a lone qubit/bit, or a gate's qubit argument
+    Register(String),
+    /// `name[index]`, the index an integer expression such as `i + 1`
+    Indexed(String, Expr),
+    /// `$index`
+    Physical(usize),
+}
+
+/// angle, index or classical expression
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
+    Number(f64),
+    Identifier(String),
+    /// `-x`, `+x`, `!x` or `~x`
+    Unary(&'static str, Box<Expr>),
+    /// `a + b`, `a ** b`, `a << b`, `a == b`, `a && b`, ...
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    /// `c[0]`, a bit of a register
+    Index(String, Box<Expr>),
+    /// `sin(x)`, `pow(a, b)`, ...
+    Call(String, Vec<Expr>),
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Mul, Sub};
+use crate::circuits::ClassicalRegister;
+
+/// operator of a classical expression, with its QASM symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Shl,
+    Shr,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Pow => "**",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        }
+    }
+
+    /// the operator written `symbol`, if any
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        const ALL: [BinaryOp; 19] = [
+            BinaryOp::Add, BinaryOp::Sub, BinaryOp::Mul, BinaryOp::Div, BinaryOp::Mod, BinaryOp::Pow,
+            BinaryOp::Shl, BinaryOp::Shr, BinaryOp::BitAnd, BinaryOp::BitOr, BinaryOp::BitXor, BinaryOp::Eq,
+            BinaryOp::Ne, BinaryOp::Lt, BinaryOp::Le, BinaryOp::Gt, BinaryOp::Ge, BinaryOp::And, BinaryOp::Or,
+        ];
+        ALL.into_iter().find(|op| op.symbol() == symbol)
+    }
+
+    /// `lhs op rhs` with wrapping arithmetic; comparisons and logic give 0 or 1, division and
+    /// modulo by zero give 0 and shifts past 63 bits give 0
+    pub fn apply(self, lhs: i64, rhs: i64) -> i64 {
+        let shift = |x: i64, by: i64, f: fn(i64, u32) -> Option<i64>| {
+            u32::try_from(by).ok().and_then(|by| f(x, by)).unwrap_or(0)
+        };
+        match self {
+            BinaryOp::Add => lhs.wrapping_add(rhs),
+            BinaryOp::Sub => lhs.wrapping_sub(rhs),
+            BinaryOp::Mul => lhs.wrapping_mul(rhs),
+            BinaryOp::Div => lhs.checked_div(rhs).unwrap_or(0),
+            BinaryOp::Mod => lhs.checked_rem(rhs).unwrap_or(0),
+            BinaryOp::Pow => u32::try_from(rhs).map_or(0, |exp| lhs.wrapping_pow(exp)),
+            BinaryOp::Shl => shift(lhs, rhs, i64::checked_shl),
+            BinaryOp::Shr => shift(lhs, rhs, i64::checked_shr),
+            BinaryOp::BitAnd => lhs & rhs,
+            BinaryOp::BitOr => lhs | rhs,
+            BinaryOp::BitXor => lhs ^ rhs,
+            BinaryOp::Eq => i64::from(lhs == rhs),
+            BinaryOp::Ne => i64::from(lhs != rhs),
+            BinaryOp::Lt => i64::from(lhs < rhs),
+            BinaryOp::Le => i64::from(lhs <= rhs),
+            BinaryOp::Gt => i64::from(lhs > rhs),
+            BinaryOp::Ge => i64::from(lhs >= rhs),
+            BinaryOp::And => i64::from(lhs != 0 && rhs != 0),
+            BinaryOp::Or => i64::from(lhs != 0 || rhs != 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    /// `-x`
+    Neg,
+    /// `!x`, 1 if x is 0 and 0 otherwise
+    Not,
+    /// `~x`
+    BitNot,
+}
+
+impl UnaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            UnaryOp::Neg => "-",
+            UnaryOp::Not => "!",
+            UnaryOp::BitNot => "~",
+        }
+    }
+
+    pub fn apply(self, x: i64) -> i64 {
+        match self {
+            UnaryOp::Neg => x.wrapping_neg(),
+            UnaryOp::Not => i64::from(x == 0),
+            UnaryOp::BitNot => !x,
+        }
+    }
+}
+
+/// integer expression over classical bits, registers and variables, evaluated while the
+/// circuit runs; used as the condition of `if` and `while` (non-zero is true) and the value of
+/// `QasmInstruction::Assign`
+///
+/// `ClassicalExpr::bit(0).equals(1)` is `c[0] == 1`; comparisons, `and`, `or` and `logical_not`
+/// give 0 or 1
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassicalExpr {
+    /// integer literal; `true` and `false` are 1 and 0
+    Int(i64),
+    /// one classical bit, by circuit index
+    Bit(usize),
+    /// `size` bits from `offset` read as an unsigned integer, bit `offset` the least significant
+    Register(usize, usize),
+    /// classical variable or loop variable, 0 until assigned
+    Var(String),
+    Unary(UnaryOp, Box<ClassicalExpr>),
+    Binary(BinaryOp, Box<ClassicalExpr>, Box<ClassicalExpr>),
+}
+
+impl ClassicalExpr {
+    pub fn bit(bit: usize) -> Self {
+        ClassicalExpr::Bit(bit)
+    }
+
+    /// the value of a whole classical register
+    pub fn register(register: &ClassicalRegister) -> Self {
+        ClassicalExpr::Register(register.offset(), register.size())
+    }
+
+    pub fn var(name: &str) -> Self {
+        ClassicalExpr::Var(name.to_string())
+    }
+
+    /// `bit == value`, the condition of `QuantumCircuit::if_eq`
+    pub fn bit_eq(bit: usize, value: usize) -> Self {
+        ClassicalExpr::Bit(bit).equals(value as i64)
+    }
+
+    pub fn binary(self, op: BinaryOp, rhs: impl Into<ClassicalExpr>) -> Self {
+        ClassicalExpr::Binary(op, Box::new(self), Box::new(rhs.into()))
+    }
+
+    pub fn equals(self, rhs: impl Into<ClassicalExpr>) -> Self {
+        self.binary(BinaryOp::Eq, rhs)
+    }
+
+    pub fn not_equals(self, rhs: impl Into<ClassicalExpr>) -> Self {
+        self.binary(BinaryOp::Ne, rhs)
+    }
+
+    pub fn less_than(self, rhs: impl Into<ClassicalExpr>) -> Self {
+        self.binary(BinaryOp::Lt, rhs)
+    }
+
+    pub fn less_equal(self, rhs: impl Into<ClassicalExpr>) -> Self {
+        self.binary(BinaryOp::Le, rhs)
+    }
+
+    pub fn greater_than(self, rhs: impl Into<ClassicalExpr>) -> Self {
+        self.binary(BinaryOp::Gt, rhs)
+    }
+
+    pub fn greater_equal(self, rhs: impl Into<ClassicalExpr>) -> Self {
+        self.binary(BinaryOp::Ge, rhs)
+    }
+
+    pub fn and(self, rhs: impl Into<ClassicalExpr>) -> Self {
+        self.binary(BinaryOp::And, rhs)
+    }
+
+    pub fn or(self, rhs: impl Into<ClassicalExpr>) -> Self {
+        self.binary(BinaryOp::Or, rhs)
+    }
+
+    pub fn logical_not(self) -> Self {
+        ClassicalExpr::Unary(UnaryOp::Not, Box::new(self))
+    }
+
+    /// value given the classical bits and variables; bits past the end read as 0
+    pub fn eval(&self, bits: &[usize], vars: &HashMap<String, i64>) -> i64 {
+        let bit = |b: usize| bits.get(b).map_or(0, |&v| (v & 1) as i64);
+        match self {
+            ClassicalExpr::Int(value) => *value,
+            ClassicalExpr::Bit(b) => bit(*b),
+            ClassicalExpr::Register(offset, size) => {
+                (0..*size).filter(|i| *i < 64).fold(0, |acc, i| acc | bit(offset + i) << i)
+            }
+            ClassicalExpr::Var(name) => vars.get(name).copied().unwrap_or(0),
+            ClassicalExpr::Unary(op, x) => op.apply(x.eval(bits, vars)),
+            ClassicalExpr::Binary(op, lhs, rhs) => op.apply(lhs.eval(bits, vars), rhs.eval(bits, vars)),
+        }
+    }
+
+    /// every classical bit the expression reads
+    pub fn bits(&self) -> Vec<usize> {
+        match self {
+            ClassicalExpr::Bit(b) => vec![*b],
+            ClassicalExpr::Register(offset, size) => (*offset..offset + size).collect(),
+            ClassicalExpr::Unary(_, x) => x.bits(),
+            ClassicalExpr::Binary(_, lhs, rhs) => lhs.bits().into_iter().chain(rhs.bits()).collect(),
+            ClassicalExpr::Int(_) | ClassicalExpr::Var(_) => Vec::new(),
+        }
+    }
+
+    /// the same expression with every classical bit `b` replaced by `map(b)`; a register
+    /// keeps its size and moves with its first bit
+    pub fn map_bits(&self, map: &dyn Fn(usize) -> usize) -> ClassicalExpr {
+        match self {
+            ClassicalExpr::Bit(b) => ClassicalExpr::Bit(map(*b)),
+            ClassicalExpr::Register(offset, size) => ClassicalExpr::Register(map(*offset), *size),
+            ClassicalExpr::Unary(op, x) => ClassicalExpr::Unary(*op, Box::new(x.map_bits(map))),
+            ClassicalExpr::Binary(op, lhs, rhs) => {
+                ClassicalExpr::Binary(*op, Box::new(lhs.map_bits(map)), Box::new(rhs.map_bits(map)))
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// QASM text, naming bits with `bit` and whole registers (offset, size) with `register`;
+    /// nested operations are parenthesised so the text parses back to the same tree
+    pub(crate) fn to_qasm(&self, bit: &dyn Fn(usize) -> String, register: &dyn Fn(usize, usize) -> String) -> String {
+        let nested = |x: &ClassicalExpr| match x {
+            ClassicalExpr::Binary(..) => format!("({})", x.to_qasm(bit, register)),
+            _ => x.to_qasm(bit, register),
+        };
+        match self {
+            ClassicalExpr::Int(value) => value.to_string(),
+            ClassicalExpr::Bit(b) => bit(*b),
+            ClassicalExpr::Register(offset, size) => register(*offset, *size),
+            ClassicalExpr::Var(name) => name.clone(),
+            ClassicalExpr::Unary(op, x) => format!("{}{}", op.symbol(), nested(x)),
+            ClassicalExpr::Binary(op, lhs, rhs) => format!("{} {} {}", nested(lhs), op.symbol(), nested(rhs)),
+        }
+    }
+}
+
+/// a register with no name of its own, written bit by bit: `(c[0] | c[1] << 1 | c[2] << 2)`
+pub(crate) fn register_from_bits(offset: usize, size: usize, bit: &dyn Fn(usize) -> String) -> String {
+    let terms: Vec<String> = (0..size)
+        .map(|i| match i {
+            0 => bit(offset),
+            i => format!("{} << {}", bit(offset + i), i),
+        })
+        .collect();
+    format!("({})", terms.join(" | "))
+}
+
+impl fmt::Display for ClassicalExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bit = |b: usize| format!("c[{}]", b);
+        write!(f, "{}", self.to_qasm(&bit, &|offset, size| register_from_bits(offset, size, &bit)))
+    }
+}
+
+impl From<i64> for ClassicalExpr {
+    fn from(value: i64) -> Self {
+        ClassicalExpr::Int(value)
+    }
+}
+
+impl From<bool> for ClassicalExpr {
+    fn from(value: bool) -> Self {
+        ClassicalExpr::Int(i64::from(value))
+    }
+}
+
+impl From<&ClassicalRegister> for ClassicalExpr {
+    fn from(register: &ClassicalRegister) -> Self {
+        ClassicalExpr::register(register)
+    }
+}
+
+impl<T: Into<ClassicalExpr>> Add<T> for ClassicalExpr {
+    type Output = ClassicalExpr;
+    fn add(self, rhs: T) -> ClassicalExpr {
+        self.binary(BinaryOp::Add, rhs)
+    }
+}
+
+impl<T: Into<ClassicalExpr>> Sub<T> for ClassicalExpr {
+    type Output = ClassicalExpr;
+    fn sub(self, rhs: T) -> ClassicalExpr {
+        self.binary(BinaryOp::Sub, rhs)
+    }
+}
+
+impl<T: Into<ClassicalExpr>> Mul<T> for ClassicalExpr {
+    type Output = ClassicalExpr;
+    fn mul(self, rhs: T) -> ClassicalExpr {
+        self.binary(BinaryOp::Mul, rhs)
+    }
+}
+
+impl<T: Into<ClassicalExpr>> BitAnd<T> for ClassicalExpr {
+    type Output = ClassicalExpr;
+    fn bitand(self, rhs: T) -> ClassicalExpr {
+        self.binary(BinaryOp::BitAnd, rhs)
+    }
+}
+
+impl<T: Into<ClassicalExpr>> BitOr<T> for ClassicalExpr {
+    type Output = ClassicalExpr;
+    fn bitor(self, rhs: T) -> ClassicalExpr {
+        self.binary(BinaryOp::BitOr, rhs)
+    }
+}
+
+impl<T: Into<ClassicalExpr>> BitXor<T> for ClassicalExpr {
+    type Output = ClassicalExpr;
+    fn bitxor(self, rhs: T) -> ClassicalExpr {
+        self.binary(BinaryOp::BitXor, rhs)
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use crate::error::QucomError;
+use crate::parameters::Parameter;
+use crate::qasm::generator::{separator, Layout, QasmInstruction, WithLayout};
+use crate::qasm::lower;
+use crate::qasm::parser::KEYWORDS;
+
+/// whether the parser already gives `name` a meaning: a built-in gate under any alias, a
+/// keyword or a constant, so a definition taking it would read back as something else
+fn reserved(name: &str) -> bool {
+    lower::is_builtin(name) || KEYWORDS.contains(&name) || lower::constant(name).is_some()
+}
+
+/// composite gate: a named sub-circuit over `n_qubits` local qubits and a list of angle parameters
+///
+/// the body acts on qubits `0..n_qubits` and may use the parameters in its angle expressions.
+/// a call (`QasmInstruction::GateCall`) substitutes the arguments and maps local qubit `i` to
+/// the call's `i`-th operand. emitted as `gate name(theta) q0, q1 { ... }` in QASM
+#[derive(Debug, Clone, PartialEq)]
+pub struct GateDefinition {
+    name: String,
+    parameters: Vec<Parameter>,
+    n_qubits: usize,
+    body: Vec<QasmInstruction>,
+    /// the definition this one inverts, written as `inv @ name` in QASM
+    inverse_of: Option<Arc<GateDefinition>>,
+    /// defined by a bundled gate library such as `qelib1.inc`
+    library: bool,
+}
+
+impl GateDefinition {
+    /// checks that the body is unitary (gates, barriers and other custom gates only), stays on
+    /// its qubits and uses no parameters beyond `parameters`
+    pub fn new(name: &str, parameters: Vec<Parameter>, n_qubits: usize, body: Vec<QasmInstruction>) -> Result<Self, QucomError> {
+        let mut chars = name.chars();
+        let identifier = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_');
+        if !identifier || reserved(name) {
+            return Err(QucomError::InvalidArgument(format!("'{}' cannot be used as a gate name", name)));
+        }
+        if n_qubits == 0 {
+            return Err(QucomError::InvalidArgument(format!("gate '{}' must act on at least one qubit", name)));
+        }
+
+        for instr in &body {
+            if matches!(
+                instr,
+                QasmInstruction::Measure(_, _) | QasmInstruction::MeasureAll
+                | QasmInstruction::Reset(_) | QasmInstruction::ResetAll
+                | QasmInstruction::BarrierAll | QasmInstruction::Delay(_, _, _)
+                | QasmInstruction::If(_, _) | QasmInstruction::IfElse(_, _, _)
+                | QasmInstruction::While(_, _) | QasmInstruction::For(_, _, _) | QasmInstruction::Assign(_, _)
+                | QasmInstruction::Switch(_, _, _) | QasmInstruction::Break | QasmInstruction::Continue
+                | QasmInstruction::End
+            ) {
+                return Err(QucomError::InvalidArgument(format!(
+                    "gate '{}' body may only contain gates, found `{}`", name, instr
+                )));
+            }
+            instr.validate(n_qubits, 0)?;
+            if let Some(p) = instr.parameters().into_iter().find(|p| !parameters.contains(p)) {
+                return Err(QucomError::UnboundParameter(p.name().to_string()));
+            }
+        }
+
+        Ok(Self { name: name.to_string(), parameters, n_qubits, body, inverse_of: None, library: false })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+
+    pub fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    pub fn body(&self) -> &[QasmInstruction] {
+        &self.body
+    }
+
+    /// the definition this one is the inverse of, if it came from `inverse`
+    pub fn inverse_of(&self) -> Option<&GateDefinition> {
+        self.inverse_of.as_deref()
+    }
+
+    /// `name_dg`: the same parameters with the body reversed and every gate inverted;
+    /// the inverse of an inverse is the original definition
+    pub fn inverse(&self) -> Result<GateDefinition, QucomError> {
+        if let Some(original) = &self.inverse_of {
+            return Ok(original.as_ref().clone());
+        }
+        let body = self.body.iter().rev().map(|instr| instr.inverse()).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            name: format!("{}_dg", self.name),
+            parameters: self.parameters.clone(),
+            n_qubits: self.n_qubits,
+            body,
+            inverse_of: Some(Arc::new(self.clone())),
+            library: false,
+        })
+    }
+
+    /// whether a bundled gate library such as `stdgates.inc` defines this gate
+    pub fn is_library(&self) -> bool {
+        self.library
+    }
+
+    pub(crate) fn in_library(self) -> Self {
+        Self { library: true, ..self }
+    }
+
+    /// the body with `args` substituted for the parameters, acting on `qubits`
+    pub fn instantiate(&self, args: &[f64], qubits: &[usize]) -> Vec<QasmInstruction> {
+        assert_eq!(args.len(), self.parameters.len(), "gate '{}' takes {} arguments", self.name, self.parameters.len());
+        assert_eq!(qubits.len(), self.n_qubits, "gate '{}' acts on {} qubits", self.name, self.n_qubits);
+        let values: HashMap<String, f64> =
+            self.parameters.iter().map(|p| p.name().to_string()).zip(args.iter().copied()).collect();
+        self.body
+            .iter()
+            .map(|instr| {
+                instr
+                    .bind(&values)
+                    .expect("body parameters are checked at definition")
+                    .map_qubits(&|q| qubits[q])
+            })
+            .collect()
+    }
+}
+
+/// `gate name(theta, phi) q0, q1 { h q0; cx q0, q1; }`
+impl fmt::Display for GateDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_qasm(&Layout::default()))
+    }
+}
+
+impl GateDefinition {
+    /// the definition with itself and the gates it calls named by `layout`
+    pub(crate) fn to_qasm(&self, layout: &Layout) -> String {
+        let mut qasm = format!("gate {}", layout.gate_name(self));
+        if !self.parameters.is_empty() {
+            let names: Vec<&str> = self.parameters.iter().map(|p| p.name()).collect();
+            qasm.push_str(&format!("({})", names.join(", ")));
+        }
+        let qubits: Vec<String> = (0..self.n_qubits).map(|q| format!("q{}", q)).collect();
+        qasm.push_str(&format!(" {} {{ ", qubits.join(", ")));
+        for instr in &self.body {
+            let statement = local_operands(&WithLayout(instr, layout).to_string());
+            qasm.push_str(&format!("{}{}", statement, separator(&statement)));
+        }
+        qasm.push('}');
+        qasm
+    }
+}
+
+/// `name`, or the first of `name_1`, `name_2`, ... that is not `taken`
+pub(crate) fn free_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    (0..)
+        .map(|i| match i {
+            0 => name.to_string(),
+            i => format!("{}_{}", name, i),
+        })
+        .find(|name| !taken(name))
+        .expect("some suffix is free")
+}
+
+/// rewrite every `q[i]` operand as the local qubit name `qi`
+pub(crate) fn local_operands(statement: &str) -> String {
+    let mut out = String::with_capacity(statement.len());
+    let mut rest = statement;
+    while let Some(start) = rest.find("q[") {
+        let (before, after) = rest.split_at(start);
+        out.push_str(before);
+        match after[2..].find(']') {
+            Some(end) if after[2..2 + end].chars().all(|c| c.is_ascii_digit()) => {
+                out.push('q');
+                out.push_str(&after[2..2 + end]);
+                rest = &after[3 + end..];
+            }
+            _ => {
+                out.push_str("q[");
+                rest = &after[2..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
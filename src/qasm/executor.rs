@@ -1,10 +1,15 @@
+use std::collections::HashMap;
 use ndarray::Array2;
 use num_complex::Complex64;
-use crate::qasm::generator::QasmInstruction;
-use crate::gates::*;
-use rand::rng;
-use rand_distr::weighted::WeightedIndex;
-use rand_distr::Distribution;
+use crate::circuits::Counts;
+use crate::error::QucomError;
+use crate::qasm::classical::ClassicalExpr;
+use crate::qasm::generator::{free_parameters, QasmInstruction};
+use crate::noise::NoiseModel;
+use crate::simulators::{NoisySimulator, Simulator, SimulatorRng, StatevectorSimulator};
+
+/// `while` iterations a run may take before it is stopped with `QucomError::IterationLimit`
+pub const DEFAULT_ITERATION_LIMIT: usize = 1_000_000;
 
 pub struct QasmExecutor;
 
@@ -14,190 +19,368 @@ impl QasmExecutor {
         instructions: &[QasmInstruction],
         state: &mut Array2<Complex64>,
         n_qubits: usize,
-    ) -> Vec<String> {
-        Self::execute_instruction(instructions, state, n_qubits, &mut vec![0; n_qubits])
+    ) -> Result<Vec<String>, QucomError> {
+        Self::execute_with_rng(instructions, state, n_qubits, SimulatorRng::default())
     }
-    
-    fn execute_instruction(
+
+    /// execute QASM instructions drawing measurement outcomes from `rng`,
+    /// e.g. `SimulatorRng::seed_from_u64(7)` for reproducible results
+    pub fn execute_with_rng(
         instructions: &[QasmInstruction],
         state: &mut Array2<Complex64>,
         n_qubits: usize,
-        classical_bits: &mut Vec<usize>,
-    ) -> Vec<String> {
+        rng: SimulatorRng,
+    ) -> Result<Vec<String>, QucomError> {
+        let mut simulator = StatevectorSimulator::from_state(std::mem::take(state), n_qubits);
+        simulator.set_rng(rng);
+        let measurements = Self::run(instructions, &mut simulator);
+        *state = simulator.into_state();
+        measurements
+    }
+
+    /// execute QASM instructions as a single noisy trajectory (Monte Carlo wavefunction)
+    pub fn execute_with_noise(
+        instructions: &[QasmInstruction],
+        state: &mut Array2<Complex64>,
+        n_qubits: usize,
+        noise: &NoiseModel,
+    ) -> Result<Vec<String>, QucomError> {
+        let mut simulator = NoisySimulator::new(StatevectorSimulator::from_state(std::mem::take(state), n_qubits), noise);
+        let measurements = Self::run(instructions, &mut simulator);
+        *state = simulator.into_inner().into_state();
+        measurements
+    }
+
+    /// execute QASM instructions on any simulation backend
+    /// the whole program is checked first (see `check`), so an error leaves the state untouched
+    pub fn run<S: Simulator>(instructions: &[QasmInstruction], simulator: &mut S) -> Result<Vec<String>, QucomError> {
+        Self::run_with_limit(instructions, simulator, DEFAULT_ITERATION_LIMIT)
+    }
+
+    /// `run`, stopping with `QucomError::IterationLimit` once `while` loops have run `limit`
+    /// iterations in total; the state is then left where the program stopped
+    pub fn run_with_limit<S: Simulator>(
+        instructions: &[QasmInstruction],
+        simulator: &mut S,
+        limit: usize,
+    ) -> Result<Vec<String>, QucomError> {
+        Self::check(instructions, simulator)?;
+        let n_bits = Self::classical_width(instructions, simulator.n_qubits());
         let mut measurements = Vec::new();
+        Self::execute_instruction(instructions, simulator, &mut ClassicalState::new(n_bits, limit), &mut measurements)?;
+        Ok(measurements)
+    }
 
+    /// error unless every instruction is valid on the simulator's qubits, has its parameters
+    /// bound and is supported by the backend
+    pub fn check<S: Simulator>(instructions: &[QasmInstruction], simulator: &S) -> Result<(), QucomError> {
+        let n_qubits = simulator.n_qubits();
+        let n_bits = Self::classical_width(instructions, n_qubits);
         for instr in instructions {
-            match instr {
-                QasmInstruction::H(q) => {
-                    *state = apply_gate(state, &hadamard(), *q, n_qubits);
-                }
-                QasmInstruction::X(q) => {
-                    *state = apply_gate(state, &pauli_x(), *q, n_qubits);
-                }
-                QasmInstruction::Y(q) => {
-                    *state = apply_gate(state, &pauli_y(), *q, n_qubits);
-                }
-                QasmInstruction::Z(q) => {
-                    *state = apply_gate(state, &pauli_z(), *q, n_qubits);
-                }
-                QasmInstruction::S(q, false) => {
-                    *state = apply_gate(state, &s(), *q, n_qubits);
-                }
-                QasmInstruction::S(q, true) => {
-                    *state = apply_gate(state, &sdg(), *q, n_qubits);
-                }
-                QasmInstruction::T(q, false) => {
-                    *state = apply_gate(state, &t(), *q, n_qubits);
+            instr.validate(n_qubits, n_bits)?;
+        }
+        // classical variables are bound as the program runs
+        if let Some(parameter) = free_parameters(instructions).first() {
+            return Err(QucomError::UnboundParameter(parameter.name().to_string()));
+        }
+        simulator.check_support(instructions)
+    }
+
+    /// run the program `shots` times from `initial` and histogram the outcomes
+    /// a shot's outcome is its measurement results joined in program order
+    ///
+    /// when every measurement is terminal and the backend is deterministic, the pre-measurement
+    /// state is prepared once and sampled; otherwise every shot re-simulates from `initial`
+    pub fn run_shots<S: Simulator + Clone>(
+        instructions: &[QasmInstruction],
+        initial: &S,
+        shots: usize,
+        memory: bool,
+    ) -> Result<Counts, QucomError> {
+        Self::run_shots_with_limit(instructions, initial, shots, memory, DEFAULT_ITERATION_LIMIT)
+    }
+
+    /// `run_shots` with the `while` iteration limit of `run_with_limit`, counted per shot
+    pub fn run_shots_with_limit<S: Simulator + Clone>(
+        instructions: &[QasmInstruction],
+        initial: &S,
+        shots: usize,
+        memory: bool,
+        limit: usize,
+    ) -> Result<Counts, QucomError> {
+        Self::check(instructions, initial)?;
+        let n_bits = Self::classical_width(instructions, initial.n_qubits());
+        let mut counts = Counts::new(memory);
+        match Self::terminal_measurements(instructions) {
+            Some(split) if initial.is_deterministic() => {
+                let mut simulator = initial.clone();
+                let mut classical = ClassicalState::new(n_bits, limit);
+                Self::execute_instruction(&instructions[..split], &mut simulator, &mut classical, &mut Vec::new())?;
+                for sample in simulator.sample(shots) {
+                    let outcome: String = instructions[split..]
+                        .iter()
+                        .filter_map(|instr| match instr {
+                            QasmInstruction::Measure(q, _) => sample.get(*q..*q + 1),
+                            QasmInstruction::MeasureAll => Some(sample.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    counts.record(outcome);
                 }
-                QasmInstruction::T(q, true) => {
-                    *state = apply_gate(state, &tdg(), *q, n_qubits);
+            }
+            _ => {
+                for _ in 0..shots {
+                    let mut simulator = initial.clone();
+                    let mut measurements = Vec::new();
+                    let mut classical = ClassicalState::new(n_bits, limit);
+                    Self::execute_instruction(instructions, &mut simulator, &mut classical, &mut measurements)?;
+                    counts.record(measurements.concat());
                 }
-                QasmInstruction::CX(control, target) => {
-                    *state = apply_controlled_gate(state, &pauli_x(), *control, *target, n_qubits);
+            }
+        }
+        Ok(counts)
+    }
+
+    /// size of the classical register: one bit per qubit, or more if the program measures into
+    /// or branches on a higher bit (circuits check their bits against their own registers)
+    pub fn classical_width(instructions: &[QasmInstruction], n_qubits: usize) -> usize {
+        instructions
+            .iter()
+            .map(|instr| match instr {
+                QasmInstruction::Measure(_, c) => c + 1,
+                QasmInstruction::If(condition, body) | QasmInstruction::While(condition, body) => {
+                    Self::bits_width(condition).max(Self::classical_width(body, 0))
                 }
-                QasmInstruction::CCX(qs) => {
-                    if qs.len() != 3 {
-                        panic!("CCX instruction requires exactly 3 qubits");
+                QasmInstruction::IfElse(condition, if_block, else_block) => Self::bits_width(condition)
+                    .max(Self::classical_width(if_block, 0))
+                    .max(Self::classical_width(else_block, 0)),
+                QasmInstruction::Assign(_, value) => Self::bits_width(value),
+                QasmInstruction::For(_, _, body) => Self::classical_width(body, 0),
+                QasmInstruction::Switch(value, cases, default) => cases
+                    .iter()
+                    .map(|(_, body)| Self::classical_width(body, 0))
+                    .fold(Self::bits_width(value).max(Self::classical_width(default, 0)), usize::max),
+                _ => 0,
+            })
+            .fold(n_qubits, usize::max)
+    }
+
+    fn bits_width(expr: &ClassicalExpr) -> usize {
+        expr.bits().into_iter().max().map_or(0, |b| b + 1)
+    }
+
+    /// index where the trailing measurements start, if the program is gates followed only by
+    /// measurements and barriers that one sampled bitstring per shot can answer:
+    /// no mid-circuit measurement, reset or control flow, each qubit measured at most once
+    /// and `MeasureAll` (which samples without collapsing) only as the final measurement
+    pub fn terminal_measurements(instructions: &[QasmInstruction]) -> Option<usize> {
+        let is_tail = |instr: &QasmInstruction| matches!(
+            instr,
+            QasmInstruction::Measure(_, _) | QasmInstruction::MeasureAll
+            | QasmInstruction::Barrier(_) | QasmInstruction::BarrierAll
+        );
+        let split = instructions.iter().rposition(|instr| !is_tail(instr)).map_or(0, |i| i + 1);
+
+        let prefix_is_unitary = instructions[..split].iter().all(|instr| !matches!(
+            instr,
+            QasmInstruction::Measure(_, _) | QasmInstruction::MeasureAll
+            | QasmInstruction::Reset(_) | QasmInstruction::ResetAll
+            | QasmInstruction::If(_, _) | QasmInstruction::IfElse(_, _, _)
+            | QasmInstruction::While(_, _) | QasmInstruction::For(_, _, _) | QasmInstruction::Switch(_, _, _)
+            | QasmInstruction::Break | QasmInstruction::Continue | QasmInstruction::End
+        ));
+        if !prefix_is_unitary {
+            return None;
+        }
+
+        let mut measured = Vec::new();
+        let mut sampled_all = false;
+        for instr in &instructions[split..] {
+            match instr {
+                QasmInstruction::Measure(q, _) => {
+                    if sampled_all || measured.contains(q) {
+                        return None;
                     }
-                    let c1 = qs[0];
-                    let c2 = qs[1];
-                    let target = qs[2];
-                    toffoli(state, c1, c2, target, n_qubits);
-                }
-                QasmInstruction::CZ(control, target) => {
-                    *state = apply_controlled_gate(state, &pauli_z(), *control, *target, n_qubits);
-                }
-                QasmInstruction::Swap(q1, q2) => {
-                    apply_swap(state, *q1, *q2, n_qubits);
-                }
-                QasmInstruction::Rx(angle, q) => {
-                    *state = apply_gate(state, &rx(*angle), *q, n_qubits);
-                }
-                QasmInstruction::Ry(angle, q) => {
-                    *state = apply_gate(state, &ry(*angle), *q, n_qubits);
-                }
-                QasmInstruction::Rz(angle, q) => {
-                    *state = apply_gate(state, &rz(*angle), *q, n_qubits);
+                    measured.push(*q);
                 }
-                QasmInstruction::Phase(angle, q) => {
-                    *state = apply_gate(state, &phase(*angle), *q, n_qubits);
-                }
-                QasmInstruction::U(theta, phi, lambda, q) => {
-                    *state = apply_gate(state, &u_gate(*theta, *phi, *lambda), *q, n_qubits);
+                QasmInstruction::MeasureAll => {
+                    if sampled_all {
+                        return None;
+                    }
+                    sampled_all = true;
                 }
+                _ => {}
+            }
+        }
+        Some(split)
+    }
+    
+    /// run `instructions`, adding their measurement results to `measurements`; stops early at
+    /// `break`, `continue` or `end`, telling the enclosing block which
+    fn execute_instruction<S: Simulator>(
+        instructions: &[QasmInstruction],
+        simulator: &mut S,
+        classical: &mut ClassicalState,
+        measurements: &mut Vec<String>,
+    ) -> Result<Flow, QucomError> {
+        for instr in instructions {
+            match instr {
                 QasmInstruction::Reset(q) => {
-                    Self::reset_qubit(state, *q, n_qubits);
+                    simulator.reset(*q);
                 }
                 QasmInstruction::ResetAll => {
-                    *state = crate::states::multi_qubit::zero_state(n_qubits);
+                    simulator.reset_all();
                 }
                 QasmInstruction::Barrier(_) | QasmInstruction::BarrierAll => {
                     // barrier has no effect on simulation
                 }
-                QasmInstruction::Delay(_, _, _) => {
-                    // delay has no effect on ideal simulation
+                QasmInstruction::Delay(duration, unit, q) => {
+                    // no effect on ideal simulation, noisy backends apply idle errors
+                    simulator.delay(*duration, unit, *q);
                 }
-                QasmInstruction::If(bit, value, instrs) => {
-                    if *bit < classical_bits.len() && classical_bits[*bit] == *value {
-                        Self::execute_instruction(instrs, state, n_qubits, classical_bits);
+                QasmInstruction::If(condition, body) => {
+                    if classical.eval(condition) != 0 {
+                        let flow = Self::execute_instruction(body, simulator, classical, measurements)?;
+                        if flow != Flow::Next {
+                            return Ok(flow);
+                        }
                     }
                 }
-                QasmInstruction::IfElse(bit, value, if_block, else_block) => {
-                    if *bit < classical_bits.len() && classical_bits[*bit] == *value {
-                        Self::execute_instruction(if_block, state, n_qubits, classical_bits);
-                    } else {
-                        Self::execute_instruction(else_block, state, n_qubits, classical_bits);
+                QasmInstruction::IfElse(condition, if_block, else_block) => {
+                    let block = if classical.eval(condition) != 0 { if_block } else { else_block };
+                    let flow = Self::execute_instruction(block, simulator, classical, measurements)?;
+                    if flow != Flow::Next {
+                        return Ok(flow);
                     }
                 }
-                QasmInstruction::While(bit, value, body) => {
-                    while *bit < classical_bits.len() && classical_bits[*bit] == *value {
-                        Self::execute_instruction(body, state, n_qubits, classical_bits);
+                QasmInstruction::Switch(value, cases, default) => {
+                    let value = classical.eval(value);
+                    let block = cases.iter().find(|(values, _)| values.contains(&value)).map_or(default, |(_, body)| body);
+                    let flow = Self::execute_instruction(block, simulator, classical, measurements)?;
+                    if flow != Flow::Next {
+                        return Ok(flow);
                     }
                 }
-                QasmInstruction::For(_, start, end, body) => {
-                    for _ in *start..*end {
-                        Self::execute_instruction(body, state, n_qubits, classical_bits);
+                QasmInstruction::While(condition, body) => {
+                    while classical.eval(condition) != 0 {
+                        classical.iterate()?;
+                        match Self::execute_instruction(body, simulator, classical, measurements)? {
+                            Flow::Break => break,
+                            Flow::End => return Ok(Flow::End),
+                            Flow::Next | Flow::Continue => {}
+                        }
+                    }
+                }
+                QasmInstruction::For(var, values, body) => {
+                    let outer = classical.vars.get(var).copied();
+                    let mut flow = Flow::Next;
+                    for value in values.iter() {
+                        classical.vars.insert(var.clone(), value);
+                        match Self::execute_instruction(body, simulator, classical, measurements)? {
+                            Flow::Break => break,
+                            Flow::End => {
+                                flow = Flow::End;
+                                break;
+                            }
+                            Flow::Next | Flow::Continue => {}
+                        }
                     }
+                    match outer {
+                        Some(value) => classical.vars.insert(var.clone(), value),
+                        None => classical.vars.remove(var),
+                    };
+                    if flow == Flow::End {
+                        return Ok(Flow::End);
+                    }
+                }
+                QasmInstruction::Break => return Ok(Flow::Break),
+                QasmInstruction::Continue => return Ok(Flow::Continue),
+                QasmInstruction::End => return Ok(Flow::End),
+                QasmInstruction::Assign(name, value) => {
+                    let value = classical.eval(value);
+                    classical.vars.insert(name.clone(), value);
                 }
                 QasmInstruction::Measure(q, c) => {
-                    let result = Self::measure_qubit(state, *q, n_qubits);
-                    let bit_value = if result == "1" { 1 } else { 0 };
-                    if *c < classical_bits.len() {
-                        classical_bits[*c] = bit_value;
+                    let outcome = simulator.measure(*q);
+                    if *c < classical.bits.len() {
+                        classical.bits[*c] = outcome;
                     }
-                    measurements.push(result);
+                    measurements.push(outcome.to_string());
                 }
                 QasmInstruction::MeasureAll => {
-                    let result = Self::measure_all(state);
+                    let result = simulator.measure_all();
                     // update classical bits
                     for (i, ch) in result.chars().enumerate() {
-                        if i < classical_bits.len() {
-                            classical_bits[i] = if ch == '1' { 1 } else { 0 };
+                        if i < classical.bits.len() {
+                            classical.bits[i] = if ch == '1' { 1 } else { 0 };
                         }
                     }
                     measurements.push(result);
                 }
+                QasmInstruction::Parameterized(_, _) => {
+                    Self::apply_gate(&classical.bind(instr), simulator, classical)?;
+                }
+                gate => Self::apply_gate(gate, simulator, classical)?,
             }
         }
 
-        measurements
+        Ok(Flow::Next)
     }
 
-    /// measure a single qubit
-    fn measure_qubit(state: &mut Array2<Complex64>, qubit_index: usize, n_qubits: usize) -> String {
-        let dim = 1 << n_qubits;
-
-        let mut p0 = 0.0;
-        let mut p1 = 0.0;
-
-        for i in 0..dim {
-            let bit = (i >> (n_qubits - 1 - qubit_index)) & 1;
-            let amp = state[[i, 0]];
-            if bit == 0 { p0 += amp.norm_sqr(); }
-            else { p1 += amp.norm_sqr(); }
+    fn apply_gate<S: Simulator>(gate: &QasmInstruction, simulator: &mut S, classical: &mut ClassicalState) -> Result<(), QucomError> {
+        match gate.expand() {
+            Some(body) => {
+                Self::execute_instruction(&body, simulator, classical, &mut Vec::new())?;
+            }
+            None => simulator.apply_gate(gate),
         }
+        Ok(())
+    }
+}
 
-        let dist = WeightedIndex::new(&[p0, p1]).unwrap();
-        let mut rng = rng();
-        let outcome = dist.sample(&mut rng);
+/// how a block of instructions finished
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    End,
+}
 
-        // collapse state
-        for i in 0..dim {
-            let bit = (i >> (n_qubits - 1 - qubit_index)) & 1;
-            if bit != outcome {
-                state[[i, 0]] = Complex64::new(0.0, 0.0);
-            }
-        }
+/// classical bits and integer variables of a running program
+struct ClassicalState {
+    bits: Vec<usize>,
+    vars: HashMap<String, i64>,
+    /// `while` iterations run so far, and how many are allowed
+    iterations: usize,
+    limit: usize,
+}
 
-        // normalize
-        let norm: f64 = state.iter().map(|c| c.norm_sqr()).sum::<f64>().sqrt();
-        if norm > 1e-10 {
-            for i in 0..dim {
-                state[[i, 0]] /= norm;
-            }
-        }
+impl ClassicalState {
+    fn new(n_bits: usize, limit: usize) -> Self {
+        Self { bits: vec![0; n_bits], vars: HashMap::new(), iterations: 0, limit }
+    }
 
-        if outcome == 0 { "0".to_string() } else { "1".to_string() }
+    /// count one `while` iteration, erroring past the limit
+    fn iterate(&mut self) -> Result<(), QucomError> {
+        self.iterations += 1;
+        if self.iterations > self.limit {
+            return Err(QucomError::IterationLimit(self.limit));
+        }
+        Ok(())
     }
 
-    /// measure all qubits
-    fn measure_all(state: &Array2<Complex64>) -> String {
-        let probs: Vec<f64> = state.iter().map(|c| c.norm_sqr()).collect();
-        let dist = WeightedIndex::new(&probs).unwrap();
-        let mut rng = rng();
-        let idx = dist.sample(&mut rng);
-        let n_qubits = (probs.len() as f64).log2() as usize;
-        format!("{:0width$b}", idx, width = n_qubits)
+    fn eval(&self, expr: &ClassicalExpr) -> i64 {
+        expr.eval(&self.bits, &self.vars)
     }
-    
-    /// reset qubit to |0⟩ state
-    fn reset_qubit(state: &mut Array2<Complex64>, qubit_index: usize, n_qubits: usize) {
-        // measure the qubit
-        let measurement = Self::measure_qubit(state, qubit_index, n_qubits);
-        
-        // if measured |1⟩, apply X to flip it to |0⟩
-        if measurement == "1" {
-            *state = apply_gate(state, &pauli_x(), qubit_index, n_qubits);
-        }
+
+    /// the gate with its angles taken from the current variables; unassigned ones read as 0
+    fn bind(&self, gate: &QasmInstruction) -> QasmInstruction {
+        let values: HashMap<String, f64> = gate
+            .parameters()
+            .iter()
+            .map(|p| (p.name().to_string(), self.vars.get(p.name()).copied().unwrap_or(0) as f64))
+            .collect();
+        gate.bind(&values).expect("every parameter has a value")
     }
 }
@@ -1,6 +1,23 @@
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
 use std::fmt;
+use std::sync::Arc;
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::parameters::{Parameter, ParameterExpression};
+use crate::gates::GateOperation;
+use crate::qasm::definition::free_name;
+use crate::qasm::GateDefinition;
+use crate::error::QucomError;
+use crate::circuits::{ClassicalRegister, QuantumRegister};
+use crate::circuits::register::check_register_name;
+use crate::qasm::classical::{register_from_bits, ClassicalExpr};
+use crate::qasm::qasm2;
 
-#[derive(Debug, Clone)]
+/// most copies `QasmInstruction::power` makes of a gate whose power has no closed form
+pub const MAX_POWER_COPIES: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum QasmInstruction {
     H(usize),
     X(usize),
@@ -24,95 +41,1104 @@ pub enum QasmInstruction {
     Delay(f64, String, usize),
     Measure(usize, usize),
     MeasureAll,
-    If(usize, usize, Vec<QasmInstruction>),
-    IfElse(usize, usize, Vec<QasmInstruction>, Vec<QasmInstruction>),
-    While(usize, usize, Vec<QasmInstruction>),
-    For(String, usize, usize, Vec<QasmInstruction>),
+    /// body run when the condition is non-zero
+    If(ClassicalExpr, Vec<QasmInstruction>),
+    IfElse(ClassicalExpr, Vec<QasmInstruction>, Vec<QasmInstruction>),
+    While(ClassicalExpr, Vec<QasmInstruction>),
+    /// body run for the variable taking each value in turn; angles in the body may use the
+    /// variable as a parameter, and conditions as `ClassicalExpr::Var`
+    For(String, LoopRange, Vec<QasmInstruction>),
+    /// the body of the first case listing the value, or else the default body
+    Switch(ClassicalExpr, Vec<(Vec<i64>, Vec<QasmInstruction>)>, Vec<QasmInstruction>),
+    /// leave the innermost loop
+    Break,
+    /// go on with the next iteration of the innermost loop
+    Continue,
+    /// stop the program
+    End,
+    /// `name = value;` on a classical variable, which angles can then use as a parameter
+    Assign(String, ClassicalExpr),
+    /// rotation gate whose angles (by slot, see `angles`) are parameter expressions;
+    /// the wrapped gate holds the remaining constant angles
+    Parameterized(Box<QasmInstruction>, Vec<(usize, ParameterExpression)>),
+    /// call of a custom gate with its arguments and operands
+    GateCall(Arc<GateDefinition>, Vec<f64>, Vec<usize>),
+    /// arbitrary unitary on `qubits`, the first qubit being the most significant bit of the matrix
+    Unitary(Array2<Complex64>, Vec<usize>),
+    /// gate applied when every control qubit matches its state (`true` for |1⟩, `false` for |0⟩);
+    /// built with `QasmInstruction::controlled`, which keeps angle parameters outermost
+    Controlled(Box<QasmInstruction>, Vec<usize>, Vec<bool>),
 }
 
-impl fmt::Display for QasmInstruction {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// the values a `for` loop runs over, a range stored by its ends so that a long one costs nothing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoopRange {
+    /// `start`, `start + step`, ... up to and including `end`, as QASM's `[start:step:end]`;
+    /// empty when `end` is on the other side of `start` from the step, or the step is 0
+    Range { start: i64, step: i64, end: i64 },
+    /// the values in order, as QASM's `{1, 4, 2}`
+    Set(Vec<i64>),
+}
+
+impl LoopRange {
+    pub fn iter(&self) -> Box<dyn Iterator<Item = i64> + '_> {
+        match *self {
+            LoopRange::Range { start, step, end } => {
+                let within = move |value: &i64| (step > 0 && *value <= end) || (step < 0 && *value >= end);
+                Box::new(std::iter::successors(Some(start).filter(within), move |value| {
+                    value.checked_add(step).filter(within)
+                }))
+            }
+            LoopRange::Set(ref values) => Box::new(values.iter().copied()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+impl QasmInstruction {
+    /// QASM name of the instruction, e.g. "h", "sdg", "cx" or "measure"
+    pub fn name(&self) -> &str {
         match self {
-            QasmInstruction::H(q) => write!(f, "h q[{}];", q),
-            QasmInstruction::X(q) => write!(f, "x q[{}];", q),
-            QasmInstruction::Y(q) => write!(f, "y q[{}];", q),
-            QasmInstruction::Z(q) => write!(f, "z q[{}];", q),
-            QasmInstruction::S(q, false) => write!(f, "s q[{}];", q),
-            QasmInstruction::S(q, true) => write!(f, "sdg q[{}];", q),
-            QasmInstruction::T(q, false) => write!(f, "t q[{}];", q),
-            QasmInstruction::T(q, true) => write!(f, "tdg q[{}];", q),
-            QasmInstruction::CX(c, t) => write!(f, "cx q[{}], q[{}];", c, t),
-            QasmInstruction::CCX(qs) => write!(f, "ccx q[{}], q[{}], q[{}];", qs[0], qs[1], qs[2]),
-            QasmInstruction::CZ(c, t) => write!(f, "cz q[{}], q[{}];", c, t),
-            QasmInstruction::Swap(q1, q2) => write!(f, "swap q[{}], q[{}];", q1, q2),
-            QasmInstruction::Rx(angle, q) => write!(f, "rx({}) q[{}];", angle, q),
-            QasmInstruction::Ry(angle, q) => write!(f, "ry({}) q[{}];", angle, q),
-            QasmInstruction::Rz(angle, q) => write!(f, "rz({}) q[{}];", angle, q),
-            QasmInstruction::Phase(angle, q) => write!(f, "p({}) q[{}];", angle, q),
-            QasmInstruction::U(theta, phi, lambda, q) => write!(f, "u({}, {}, {}) q[{}];", theta, phi, lambda, q),
-            QasmInstruction::Reset(q) => write!(f, "reset q[{}];", q),
-            QasmInstruction::ResetAll => write!(f, "reset q;"),
-            QasmInstruction::Barrier(qs) if qs.is_empty() => write!(f, "barrier;"),
-            QasmInstruction::Barrier(qs) => {
-                write!(f, "barrier ")?;
-                for (i, q) in qs.iter().enumerate() {
-                    if i > 0 { write!(f, ", ")?; }
-                    write!(f, "q[{}]", q)?;
+            QasmInstruction::H(_) => "h",
+            QasmInstruction::X(_) => "x",
+            QasmInstruction::Y(_) => "y",
+            QasmInstruction::Z(_) => "z",
+            QasmInstruction::S(_, false) => "s",
+            QasmInstruction::S(_, true) => "sdg",
+            QasmInstruction::T(_, false) => "t",
+            QasmInstruction::T(_, true) => "tdg",
+            QasmInstruction::CX(_, _) => "cx",
+            QasmInstruction::CZ(_, _) => "cz",
+            QasmInstruction::CCX(_) => "ccx",
+            QasmInstruction::Swap(_, _) => "swap",
+            QasmInstruction::Rx(_, _) => "rx",
+            QasmInstruction::Ry(_, _) => "ry",
+            QasmInstruction::Rz(_, _) => "rz",
+            QasmInstruction::Phase(_, _) => "p",
+            QasmInstruction::U(_, _, _, _) => "u",
+            QasmInstruction::Reset(_) | QasmInstruction::ResetAll => "reset",
+            QasmInstruction::Barrier(_) | QasmInstruction::BarrierAll => "barrier",
+            QasmInstruction::Delay(_, _, _) => "delay",
+            QasmInstruction::Measure(_, _) | QasmInstruction::MeasureAll => "measure",
+            QasmInstruction::If(_, _) | QasmInstruction::IfElse(_, _, _) => "if",
+            QasmInstruction::While(_, _) => "while",
+            QasmInstruction::For(_, _, _) => "for",
+            QasmInstruction::Switch(_, _, _) => "switch",
+            QasmInstruction::Break => "break",
+            QasmInstruction::Continue => "continue",
+            QasmInstruction::End => "end",
+            QasmInstruction::Assign(_, _) => "assign",
+            QasmInstruction::Parameterized(gate, _) => gate.name(),
+            QasmInstruction::GateCall(definition, _, _) => definition.name(),
+            QasmInstruction::Unitary(_, _) => "unitary",
+            QasmInstruction::Controlled(gate, controls, ctrl_state) => {
+                match (gate.as_ref(), controls.len(), ctrl_state[0]) {
+                    (QasmInstruction::H(_), 1, true) => "ch",
+                    (QasmInstruction::Y(_), 1, true) => "cy",
+                    (QasmInstruction::Rx(_, _), 1, true) => "crx",
+                    (QasmInstruction::Ry(_, _), 1, true) => "cry",
+                    (QasmInstruction::Rz(_, _), 1, true) => "crz",
+                    (QasmInstruction::Phase(_, _), 1, true) => "cp",
+                    (QasmInstruction::U(_, _, _, _), 1, true) => "cu",
+                    (QasmInstruction::Swap(_, _), 1, true) => "cswap",
+                    _ => "ctrl",
+                }
+            }
+        }
+    }
+
+    /// qubits the instruction acts on directly, in operand order
+    /// empty for whole-register instructions and control flow
+    pub fn qubits(&self) -> Vec<usize> {
+        match self {
+            QasmInstruction::H(q) | QasmInstruction::X(q) | QasmInstruction::Y(q)
+            | QasmInstruction::Z(q) | QasmInstruction::S(q, _) | QasmInstruction::T(q, _)
+            | QasmInstruction::Rx(_, q) | QasmInstruction::Ry(_, q) | QasmInstruction::Rz(_, q)
+            | QasmInstruction::Phase(_, q) | QasmInstruction::U(_, _, _, q)
+            | QasmInstruction::Measure(q, _) | QasmInstruction::Reset(q) | QasmInstruction::Delay(_, _, q) => vec![*q],
+            QasmInstruction::CX(c, t) | QasmInstruction::CZ(c, t) | QasmInstruction::Swap(c, t) => vec![*c, *t],
+            QasmInstruction::CCX(qs) | QasmInstruction::Barrier(qs)
+            | QasmInstruction::GateCall(_, _, qs) | QasmInstruction::Unitary(_, qs) => qs.clone(),
+            QasmInstruction::Parameterized(gate, _) => gate.qubits(),
+            QasmInstruction::Controlled(gate, controls, _) => controls.iter().copied().chain(gate.qubits()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// unitary instruction, checking the matrix is 2^k x 2^k for k distinct qubits and U†U = I
+    pub fn unitary(matrix: Array2<Complex64>, qubits: &[usize]) -> Result<QasmInstruction, QucomError> {
+        let dim = 1usize << qubits.len();
+        if qubits.is_empty() || matrix.dim() != (dim, dim) {
+            return Err(QucomError::InvalidArgument(format!(
+                "a unitary on {} qubits must be {}x{}, got {:?}", qubits.len(), dim, dim, matrix.dim()
+            )));
+        }
+        if let Some(q) = first_duplicate(qubits) {
+            return Err(QucomError::DuplicateQubit(q));
+        }
+        let product = matrix.t().mapv(|c| c.conj()).dot(&matrix);
+        let deviation = product
+            .indexed_iter()
+            .map(|((i, j), c)| (c - if i == j { Complex64::new(1.0, 0.0) } else { Complex64::new(0.0, 0.0) }).norm())
+            .fold(0.0, f64::max);
+        if deviation > 1e-10 {
+            return Err(QucomError::NonUnitary(format!("U†U deviates from I by {:e}", deviation)));
+        }
+        Ok(QasmInstruction::Unitary(matrix, qubits.to_vec()))
+    }
+
+    /// `gate` applied only when each control matches `ctrl_state` (`true` for |1⟩, `false` for |0⟩)
+    ///
+    /// controls of an already controlled gate (including CX, CZ and CCX) are merged, closed
+    /// controls on X and Z come back as CX, CCX and CZ, and the angle expressions of a
+    /// parameterized gate stay outermost
+    pub fn controlled(gate: QasmInstruction, controls: &[usize], ctrl_state: &[bool]) -> Result<QasmInstruction, QucomError> {
+        if controls.len() != ctrl_state.len() {
+            return Err(QucomError::InvalidArgument(format!(
+                "{} controls but {} control states", controls.len(), ctrl_state.len()
+            )));
+        }
+        if controls.is_empty() {
+            return Ok(gate);
+        }
+
+        let merge = |extra: &[usize]| {
+            let controls: Vec<usize> = controls.iter().chain(extra).copied().collect();
+            let ctrl_state: Vec<bool> = ctrl_state.iter().copied().chain(extra.iter().map(|_| true)).collect();
+            (controls, ctrl_state)
+        };
+        let (gate, controls, ctrl_state) = match gate {
+            QasmInstruction::Parameterized(gate, slots) => {
+                let gate = Self::controlled(*gate, controls, ctrl_state)?;
+                return Ok(QasmInstruction::Parameterized(Box::new(gate), slots));
+            }
+            QasmInstruction::CX(c, t) => {
+                let (controls, ctrl_state) = merge(&[c]);
+                (QasmInstruction::X(t), controls, ctrl_state)
+            }
+            QasmInstruction::CZ(c, t) => {
+                let (controls, ctrl_state) = merge(&[c]);
+                (QasmInstruction::Z(t), controls, ctrl_state)
+            }
+            QasmInstruction::CCX(qs) if qs.len() == 3 => {
+                let (controls, ctrl_state) = merge(&qs[..2]);
+                (QasmInstruction::X(qs[2]), controls, ctrl_state)
+            }
+            QasmInstruction::Controlled(gate, inner, inner_state) => (
+                *gate,
+                controls.iter().chain(&inner).copied().collect(),
+                ctrl_state.iter().chain(&inner_state).copied().collect(),
+            ),
+            gate => (gate, controls.to_vec(), ctrl_state.to_vec()),
+        };
+
+        if matches!(
+            gate,
+            QasmInstruction::Measure(_, _) | QasmInstruction::MeasureAll
+            | QasmInstruction::Reset(_) | QasmInstruction::ResetAll
+            | QasmInstruction::Barrier(_) | QasmInstruction::BarrierAll | QasmInstruction::Delay(_, _, _)
+            | QasmInstruction::If(_, _) | QasmInstruction::IfElse(_, _, _)
+            | QasmInstruction::While(_, _) | QasmInstruction::For(_, _, _) | QasmInstruction::Assign(_, _)
+            | QasmInstruction::Switch(_, _, _) | QasmInstruction::Break | QasmInstruction::Continue | QasmInstruction::End
+        ) {
+            return Err(QucomError::InvalidArgument(format!("only gates can be controlled, not `{}`", gate)));
+        }
+        let qubits: Vec<usize> = controls.iter().copied().chain(gate.qubits()).collect();
+        if let Some(q) = first_duplicate(&qubits) {
+            return Err(QucomError::DuplicateQubit(q));
+        }
+
+        let closed = ctrl_state.iter().all(|&c| c);
+        Ok(match (gate, controls.as_slice()) {
+            (QasmInstruction::X(t), [c]) if closed => QasmInstruction::CX(*c, t),
+            (QasmInstruction::X(t), [a, b]) if closed => QasmInstruction::CCX(vec![*a, *b, t]),
+            (QasmInstruction::Z(t), [c]) if closed => QasmInstruction::CZ(*c, t),
+            (gate, _) => QasmInstruction::Controlled(Box::new(gate), controls, ctrl_state),
+        })
+    }
+
+    /// rotation gate with angle expressions; stays a plain gate when they are all constant
+    pub fn parameterized(gate: QasmInstruction, angles: Vec<ParameterExpression>) -> QasmInstruction {
+        assert_eq!(gate.angles().len(), angles.len(), "wrong number of angles for {}", gate.name());
+        let constants: Vec<f64> = angles.iter().map(|a| a.value().unwrap_or(0.0)).collect();
+        let symbolic: Vec<(usize, ParameterExpression)> = angles
+            .into_iter()
+            .enumerate()
+            .filter(|(_, a)| !a.is_constant())
+            .collect();
+        let gate = gate.with_angles(&constants);
+        if symbolic.is_empty() {
+            gate
+        } else {
+            QasmInstruction::Parameterized(Box::new(gate), symbolic)
+        }
+    }
+
+    /// angle arguments of rotation gates (theta, phi, lambda for U) and custom gate calls,
+    /// empty for everything else
+    pub fn angles(&self) -> Vec<f64> {
+        match self {
+            QasmInstruction::GateCall(_, args, _) => args.clone(),
+            QasmInstruction::Controlled(gate, _, _) => gate.angles(),
+            QasmInstruction::Rx(a, _) | QasmInstruction::Ry(a, _) | QasmInstruction::Rz(a, _)
+            | QasmInstruction::Phase(a, _) => vec![*a],
+            QasmInstruction::U(theta, phi, lambda, _) => vec![*theta, *phi, *lambda],
+            _ => Vec::new(),
+        }
+    }
+
+    /// the same rotation gate with new angles
+    pub fn with_angles(&self, angles: &[f64]) -> QasmInstruction {
+        match self {
+            QasmInstruction::Rx(_, q) => QasmInstruction::Rx(angles[0], *q),
+            QasmInstruction::Ry(_, q) => QasmInstruction::Ry(angles[0], *q),
+            QasmInstruction::Rz(_, q) => QasmInstruction::Rz(angles[0], *q),
+            QasmInstruction::Phase(_, q) => QasmInstruction::Phase(angles[0], *q),
+            QasmInstruction::U(_, _, _, q) => QasmInstruction::U(angles[0], angles[1], angles[2], *q),
+            QasmInstruction::GateCall(definition, _, qs) => {
+                QasmInstruction::GateCall(definition.clone(), angles.to_vec(), qs.clone())
+            }
+            QasmInstruction::Controlled(gate, controls, ctrl_state) => {
+                QasmInstruction::Controlled(Box::new(gate.with_angles(angles)), controls.clone(), ctrl_state.clone())
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// body of a custom gate call, with the call's controls (if any) on every gate;
+    /// `None` for every other instruction
+    pub fn expand(&self) -> Option<Vec<QasmInstruction>> {
+        match self {
+            QasmInstruction::GateCall(definition, args, qubits) => Some(definition.instantiate(args, qubits)),
+            QasmInstruction::Controlled(gate, controls, ctrl_state) => gate.expand().map(|body| {
+                body.into_iter()
+                    .filter(|instr| !matches!(instr, QasmInstruction::Barrier(_)))
+                    .map(|instr| {
+                        QasmInstruction::controlled(instr, controls, ctrl_state)
+                            .expect("custom gate bodies only contain gates on the call's qubits")
+                    })
+                    .collect()
+            }),
+            _ => None,
+        }
+    }
+
+    /// the inverse gate: S/T toggle their dagger, rotations negate their angles, U(θ, φ, λ)
+    /// becomes U(-θ, -λ, -φ) and custom gates call their `GateDefinition::inverse`
+    /// errors for measurement, reset and control flow, which have no inverse
+    pub fn inverse(&self) -> Result<QasmInstruction, QucomError> {
+        Ok(match self {
+            QasmInstruction::S(q, dagger) => QasmInstruction::S(*q, !dagger),
+            QasmInstruction::T(q, dagger) => QasmInstruction::T(*q, !dagger),
+            QasmInstruction::Rx(a, q) => QasmInstruction::Rx(-a, *q),
+            QasmInstruction::Ry(a, q) => QasmInstruction::Ry(-a, *q),
+            QasmInstruction::Rz(a, q) => QasmInstruction::Rz(-a, *q),
+            QasmInstruction::Phase(a, q) => QasmInstruction::Phase(-a, *q),
+            QasmInstruction::U(theta, phi, lambda, q) => QasmInstruction::U(-theta, -lambda, -phi, *q),
+            QasmInstruction::H(_) | QasmInstruction::X(_) | QasmInstruction::Y(_) | QasmInstruction::Z(_)
+            | QasmInstruction::CX(_, _) | QasmInstruction::CZ(_, _) | QasmInstruction::CCX(_)
+            | QasmInstruction::Swap(_, _) | QasmInstruction::Barrier(_) | QasmInstruction::BarrierAll
+            | QasmInstruction::Delay(_, _, _) => self.clone(),
+            QasmInstruction::Unitary(matrix, qubits) => {
+                QasmInstruction::Unitary(matrix.t().mapv(|c| c.conj()), qubits.clone())
+            }
+            QasmInstruction::GateCall(definition, args, qubits) => {
+                QasmInstruction::GateCall(Arc::new(definition.inverse()?), args.clone(), qubits.clone())
+            }
+            QasmInstruction::Controlled(gate, controls, ctrl_state) => {
+                QasmInstruction::Controlled(Box::new(gate.inverse()?), controls.clone(), ctrl_state.clone())
+            }
+            QasmInstruction::Parameterized(gate, slots) => {
+                let base = match gate.as_ref() {
+                    QasmInstruction::Controlled(inner, _, _) => inner.as_ref(),
+                    gate => gate,
+                };
+                let slots = slots
+                    .iter()
+                    .map(|(slot, expr)| match (base, slot) {
+                        // the inverse definition takes the same arguments
+                        (QasmInstruction::GateCall(..), _) => (*slot, expr.clone()),
+                        (QasmInstruction::U(..), 1) => (2, -expr.clone()),
+                        (QasmInstruction::U(..), 2) => (1, -expr.clone()),
+                        _ => (*slot, -expr.clone()),
+                    })
+                    .collect();
+                QasmInstruction::Parameterized(Box::new(gate.inverse()?), slots)
+            }
+            QasmInstruction::Reset(_) | QasmInstruction::ResetAll | QasmInstruction::Measure(_, _)
+            | QasmInstruction::MeasureAll | QasmInstruction::If(_, _) | QasmInstruction::IfElse(_, _, _)
+            | QasmInstruction::While(_, _) | QasmInstruction::For(_, _, _) | QasmInstruction::Assign(_, _)
+            | QasmInstruction::Switch(_, _, _) | QasmInstruction::Break | QasmInstruction::Continue | QasmInstruction::End => {
+                return Err(QucomError::NonUnitary(format!("`{}` has no inverse", self)));
+            }
+        })
+    }
+
+    /// the gate raised to the power `k`, e.g. `S^0.5 = T`. a whole `k` is taken modulo the order
+    /// of Pauli and Clifford gates (`X^4` is nothing, `S^3` is `sdg`), scales the angle of a
+    /// rotation or phase gate and raises a unitary's matrix; any other gate is repeated, at most
+    /// `MAX_POWER_COPIES` times. a fractional `k` needs a rotation or phase gate
+    pub fn power(&self, k: f64) -> Result<Vec<QasmInstruction>, QucomError> {
+        let inverse = self.inverse()?;
+        if k.fract() != 0.0 {
+            return Ok(vec![self.fractional_power(k)?]);
+        }
+        if let Some(order) = self.order() {
+            let r = k.rem_euclid(order as f64) as usize;
+            return Ok(match r <= order / 2 {
+                true => vec![self.clone(); r],
+                false => vec![inverse; order - r],
+            });
+        }
+        if let Some(gate) = self.scaled(k) {
+            return Ok(vec![gate]);
+        }
+        if let QasmInstruction::Unitary(matrix, qubits) = self {
+            let base = if k < 0.0 { matrix.t().mapv(|c| c.conj()) } else { matrix.clone() };
+            return Ok(vec![QasmInstruction::Unitary(matrix_power(base, k.abs() as u64), qubits.clone())]);
+        }
+        if k.abs() > MAX_POWER_COPIES as f64 {
+            return Err(QucomError::Unsupported(format!(
+                "`{}` has no closed-form power, raising it to {} would repeat it more than {} times",
+                self, k, MAX_POWER_COPIES
+            )));
+        }
+        let gate = if k < 0.0 { inverse } else { self.clone() };
+        Ok(vec![gate; k.abs() as usize])
+    }
+
+    /// smallest `n` with the gate to the `n` the identity, for Pauli and Clifford gates and `T`
+    fn order(&self) -> Option<usize> {
+        match self {
+            QasmInstruction::H(_) | QasmInstruction::X(_) | QasmInstruction::Y(_) | QasmInstruction::Z(_)
+            | QasmInstruction::CX(_, _) | QasmInstruction::CZ(_, _) | QasmInstruction::CCX(_)
+            | QasmInstruction::Swap(_, _) => Some(2),
+            QasmInstruction::S(_, _) => Some(4),
+            QasmInstruction::T(_, _) => Some(8),
+            QasmInstruction::Controlled(gate, _, _) => gate.order(),
+            _ => None,
+        }
+    }
+
+    fn fractional_power(&self, k: f64) -> Result<QasmInstruction, QucomError> {
+        self.scaled(k).ok_or_else(|| QucomError::Unsupported(format!(
+            "`{}` can only be raised to whole powers; fractional powers need a rotation or phase gate", self
+        )))
+    }
+
+    /// a rotation or phase gate with its angle scaled by `k`
+    pub(crate) fn scaled(&self, k: f64) -> Option<QasmInstruction> {
+        let sign = |dagger: bool| if dagger { -1.0 } else { 1.0 };
+        Some(match self {
+            QasmInstruction::Rx(a, q) => QasmInstruction::Rx(a * k, *q),
+            QasmInstruction::Ry(a, q) => QasmInstruction::Ry(a * k, *q),
+            QasmInstruction::Rz(a, q) => QasmInstruction::Rz(a * k, *q),
+            QasmInstruction::Phase(a, q) => QasmInstruction::Phase(a * k, *q),
+            QasmInstruction::Z(q) => QasmInstruction::Phase(PI * k, *q),
+            QasmInstruction::S(q, dagger) => QasmInstruction::Phase(sign(*dagger) * FRAC_PI_2 * k, *q),
+            QasmInstruction::T(q, dagger) => QasmInstruction::Phase(sign(*dagger) * FRAC_PI_4 * k, *q),
+            QasmInstruction::CZ(c, t) => QasmInstruction::Controlled(Box::new(QasmInstruction::Phase(PI * k, *t)), vec![*c], vec![true]),
+            QasmInstruction::Controlled(gate, controls, ctrl_state) => {
+                QasmInstruction::Controlled(Box::new(gate.scaled(k)?), controls.clone(), ctrl_state.clone())
+            }
+            QasmInstruction::Parameterized(gate, slots) => {
+                let slots = slots.iter().map(|(slot, expr)| (*slot, expr.clone() * k)).collect();
+                QasmInstruction::Parameterized(Box::new(gate.scaled(k)?), slots)
+            }
+            _ => return None,
+        })
+    }
+
+    /// check qubits are below `n_qubits` and distinct, classical bits below `n_bits`, the
+    /// operand counts of CCX, custom gate calls and unitaries, and that `Break` and `Continue`
+    /// sit in a loop body, control-flow bodies included
+    pub fn validate(&self, n_qubits: usize, n_bits: usize) -> Result<(), QucomError> {
+        self.validate_in(n_qubits, n_bits, false)
+    }
+
+    fn validate_in(&self, n_qubits: usize, n_bits: usize, in_loop: bool) -> Result<(), QucomError> {
+        let check_bit = |bit: usize| match bit < n_bits {
+            true => Ok(()),
+            false => Err(QucomError::InvalidClassicalBit { bit, n_bits }),
+        };
+        let check_body = |body: &[QasmInstruction], in_loop: bool| {
+            body.iter().try_for_each(|instr| instr.validate_in(n_qubits, n_bits, in_loop))
+        };
+        let check_all = |body: &[QasmInstruction]| check_body(body, in_loop);
+        let arity = |expected: usize, found: usize, what: &str| match expected == found {
+            true => Ok(()),
+            false => Err(QucomError::InvalidArgument(format!("{} takes {} {}, got {}", self.name(), expected, what, found))),
+        };
+        match self {
+            QasmInstruction::CCX(qs) => arity(3, qs.len(), "qubits")?,
+            QasmInstruction::GateCall(definition, args, qs) => {
+                arity(definition.parameters().len(), args.len(), "arguments")?;
+                arity(definition.n_qubits(), qs.len(), "qubits")?;
+            }
+            QasmInstruction::Unitary(matrix, qs) => {
+                let dim = 1usize << qs.len();
+                if qs.is_empty() || matrix.dim() != (dim, dim) {
+                    return Err(QucomError::InvalidArgument(format!("unitary on {} qubits must be {}x{}", qs.len(), dim, dim)));
                 }
-                write!(f, ";")
-            }
-            QasmInstruction::BarrierAll => write!(f, "barrier q;"),
-            QasmInstruction::Delay(duration, unit, q) => write!(f, "delay[{}{}] q[{}];", duration, unit, q),
-            QasmInstruction::Measure(q, c) => write!(f, "measure q[{}] -> c[{}];", q, c),
-            QasmInstruction::MeasureAll => write!(f, "measure q -> c;"),
-            QasmInstruction::If(bit, val, instrs) => {
-                write!(f, "if (c[{}] == {}) {{ ", bit, val)?;
-                for instr in instrs {
-                    write!(f, "{} ", instr)?;
+            }
+            QasmInstruction::Controlled(gate, controls, ctrl_state) => {
+                arity(controls.len(), ctrl_state.len(), "control states")?;
+                gate.validate(n_qubits, n_bits)?;
+            }
+            QasmInstruction::Parameterized(gate, slots) => {
+                gate.validate(n_qubits, n_bits)?;
+                if let Some((slot, _)) = slots.iter().find(|(slot, _)| *slot >= gate.angles().len()) {
+                    return Err(QucomError::InvalidArgument(format!("{} has no angle {}", gate.name(), slot)));
                 }
-                write!(f, "}}")
             }
-            QasmInstruction::IfElse(bit, val, if_block, else_block) => {
-                write!(f, "if (c[{}] == {}) {{ ", bit, val)?;
-                for instr in if_block {
-                    write!(f, "{} ", instr)?;
+            QasmInstruction::Measure(_, c) => check_bit(*c)?,
+            QasmInstruction::If(condition, body) => {
+                condition.bits().into_iter().try_for_each(check_bit)?;
+                check_all(body)?;
+            }
+            QasmInstruction::While(condition, body) => {
+                condition.bits().into_iter().try_for_each(check_bit)?;
+                check_body(body, true)?;
+            }
+            QasmInstruction::Assign(_, value) => value.bits().into_iter().try_for_each(check_bit)?,
+            QasmInstruction::IfElse(condition, if_block, else_block) => {
+                condition.bits().into_iter().try_for_each(check_bit)?;
+                check_all(if_block)?;
+                check_all(else_block)?;
+            }
+            QasmInstruction::For(_, _, body) => check_body(body, true)?,
+            QasmInstruction::Switch(value, cases, default) => {
+                value.bits().into_iter().try_for_each(check_bit)?;
+                cases.iter().try_for_each(|(_, body)| check_all(body))?;
+                check_all(default)?;
+            }
+            QasmInstruction::Break | QasmInstruction::Continue if !in_loop => {
+                return Err(QucomError::InvalidArgument(format!("{} must be inside a loop", self.name())));
+            }
+            _ => {}
+        }
+
+        let qubits = self.qubits();
+        if let Some(&qubit) = qubits.iter().find(|&&q| q >= n_qubits) {
+            return Err(QucomError::InvalidQubit { qubit, n_qubits });
+        }
+        match first_duplicate(&qubits) {
+            Some(q) => Err(QucomError::DuplicateQubit(q)),
+            None => Ok(()),
+        }
+    }
+
+    /// the same instruction with every qubit index `q` replaced by `map(q)`, control-flow bodies
+    /// included; classical bits are unchanged
+    pub fn map_qubits(&self, map: &dyn Fn(usize) -> usize) -> QasmInstruction {
+        let map_all = |body: &[QasmInstruction]| body.iter().map(|i| i.map_qubits(map)).collect::<Vec<_>>();
+        match self {
+            QasmInstruction::H(q) => QasmInstruction::H(map(*q)),
+            QasmInstruction::X(q) => QasmInstruction::X(map(*q)),
+            QasmInstruction::Y(q) => QasmInstruction::Y(map(*q)),
+            QasmInstruction::Z(q) => QasmInstruction::Z(map(*q)),
+            QasmInstruction::S(q, dagger) => QasmInstruction::S(map(*q), *dagger),
+            QasmInstruction::T(q, dagger) => QasmInstruction::T(map(*q), *dagger),
+            QasmInstruction::CX(c, t) => QasmInstruction::CX(map(*c), map(*t)),
+            QasmInstruction::CZ(c, t) => QasmInstruction::CZ(map(*c), map(*t)),
+            QasmInstruction::CCX(qs) => QasmInstruction::CCX(qs.iter().map(|q| map(*q)).collect()),
+            QasmInstruction::Swap(a, b) => QasmInstruction::Swap(map(*a), map(*b)),
+            QasmInstruction::Rx(a, q) => QasmInstruction::Rx(*a, map(*q)),
+            QasmInstruction::Ry(a, q) => QasmInstruction::Ry(*a, map(*q)),
+            QasmInstruction::Rz(a, q) => QasmInstruction::Rz(*a, map(*q)),
+            QasmInstruction::Phase(a, q) => QasmInstruction::Phase(*a, map(*q)),
+            QasmInstruction::U(theta, phi, lambda, q) => QasmInstruction::U(*theta, *phi, *lambda, map(*q)),
+            QasmInstruction::Reset(q) => QasmInstruction::Reset(map(*q)),
+            QasmInstruction::Barrier(qs) => QasmInstruction::Barrier(qs.iter().map(|q| map(*q)).collect()),
+            QasmInstruction::Delay(duration, unit, q) => QasmInstruction::Delay(*duration, unit.clone(), map(*q)),
+            QasmInstruction::Measure(q, c) => QasmInstruction::Measure(map(*q), *c),
+            QasmInstruction::If(condition, body) => QasmInstruction::If(condition.clone(), map_all(body)),
+            QasmInstruction::IfElse(condition, if_block, else_block) => {
+                QasmInstruction::IfElse(condition.clone(), map_all(if_block), map_all(else_block))
+            }
+            QasmInstruction::While(condition, body) => QasmInstruction::While(condition.clone(), map_all(body)),
+            QasmInstruction::For(var, values, body) => QasmInstruction::For(var.clone(), values.clone(), map_all(body)),
+            QasmInstruction::Switch(value, cases, default) => QasmInstruction::Switch(
+                value.clone(),
+                cases.iter().map(|(values, body)| (values.clone(), map_all(body))).collect(),
+                map_all(default),
+            ),
+            QasmInstruction::Parameterized(gate, slots) => {
+                QasmInstruction::Parameterized(Box::new(gate.map_qubits(map)), slots.clone())
+            }
+            QasmInstruction::GateCall(definition, args, qs) => {
+                QasmInstruction::GateCall(definition.clone(), args.clone(), qs.iter().map(|q| map(*q)).collect())
+            }
+            QasmInstruction::Unitary(matrix, qs) => {
+                QasmInstruction::Unitary(matrix.clone(), qs.iter().map(|q| map(*q)).collect())
+            }
+            QasmInstruction::Controlled(gate, controls, ctrl_state) => QasmInstruction::Controlled(
+                Box::new(gate.map_qubits(map)),
+                controls.iter().map(|q| map(*q)).collect(),
+                ctrl_state.clone(),
+            ),
+            QasmInstruction::ResetAll | QasmInstruction::BarrierAll | QasmInstruction::MeasureAll
+            | QasmInstruction::Assign(_, _) | QasmInstruction::Break | QasmInstruction::Continue
+            | QasmInstruction::End => self.clone(),
+        }
+    }
+
+    /// custom gates called anywhere in the instruction, their own dependencies first
+    pub(crate) fn collect_definitions<'a>(&'a self, found: &mut Vec<&'a GateDefinition>) {
+        let bodies: Vec<&Vec<QasmInstruction>> = match self {
+            QasmInstruction::Parameterized(gate, _) | QasmInstruction::Controlled(gate, _, _) => {
+                return gate.collect_definitions(found);
+            }
+            QasmInstruction::GateCall(definition, _, _) => {
+                // an inverted gate is written as `inv @` on the original
+                let definition: &GateDefinition = definition.inverse_of().unwrap_or(definition);
+                // equal definitions are written once; different ones of the same name are renamed
+                // when written, see `GateNames`
+                if !found.contains(&definition) {
+                    for instr in definition.body() {
+                        instr.collect_definitions(found);
+                    }
+                    found.push(definition);
                 }
-                write!(f, "}} else {{ ")?;
-                for instr in else_block {
-                    write!(f, "{} ", instr)?;
+                Vec::new()
+            }
+            QasmInstruction::If(_, body) | QasmInstruction::While(_, body) | QasmInstruction::For(_, _, body) => vec![body],
+            QasmInstruction::IfElse(_, if_block, else_block) => vec![if_block, else_block],
+            QasmInstruction::Switch(_, cases, default) => cases.iter().map(|(_, body)| body).chain([default]).collect(),
+            _ => Vec::new(),
+        };
+        for instr in bodies.into_iter().flatten() {
+            instr.collect_definitions(found);
+        }
+    }
+
+    /// parameters used anywhere in the instruction, including control-flow bodies
+    pub fn parameters(&self) -> Vec<Parameter> {
+        let mut found = Vec::new();
+        self.collect_parameters(&mut found);
+        found
+    }
+
+    fn collect_parameters(&self, found: &mut Vec<Parameter>) {
+        let bodies: Vec<&Vec<QasmInstruction>> = match self {
+            // the loop variable is bound by the loop itself
+            QasmInstruction::For(var, _, body) => {
+                for p in body.iter().flat_map(|instr| instr.parameters()) {
+                    if p.name() != var && !found.contains(&p) {
+                        found.push(p);
+                    }
                 }
-                write!(f, "}}")
+                Vec::new()
             }
-            QasmInstruction::While(bit, val, body) => {
-                write!(f, "while (c[{}] == {}) {{ ", bit, val)?;
-                for instr in body {
-                    write!(f, "{} ", instr)?;
+            QasmInstruction::Parameterized(_, slots) => {
+                for p in slots.iter().flat_map(|(_, expr)| expr.parameters()) {
+                    if !found.contains(p) {
+                        found.push(p.clone());
+                    }
                 }
-                write!(f, "}}")
+                Vec::new()
+            }
+            QasmInstruction::If(_, body) | QasmInstruction::While(_, body) => vec![body],
+            QasmInstruction::IfElse(_, if_block, else_block) => vec![if_block, else_block],
+            QasmInstruction::Switch(_, cases, default) => cases.iter().map(|(_, body)| body).chain([default]).collect(),
+            _ => Vec::new(),
+        };
+        for instr in bodies.into_iter().flatten() {
+            instr.collect_parameters(found);
+        }
+    }
+
+    /// substitute values for every parameter, keyed by name
+    pub fn bind(&self, values: &HashMap<String, f64>) -> Result<QasmInstruction, QucomError> {
+        self.bind_except(values, &[])
+    }
+
+    /// `bind`, leaving the classical variables `locals` (and loop variables) in place for the
+    /// executor to fill in
+    pub(crate) fn bind_except(&self, values: &HashMap<String, f64>, locals: &[String]) -> Result<QasmInstruction, QucomError> {
+        let bind_all = |body: &[QasmInstruction]| {
+            body.iter().map(|i| i.bind_except(values, locals)).collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match self {
+            QasmInstruction::Parameterized(gate, slots) => {
+                let mut angles = gate.angles();
+                let mut symbolic = Vec::new();
+                for (slot, expr) in slots {
+                    let expr = expr.substitute(values);
+                    if let Some(p) = expr.parameters().find(|p| !locals.iter().any(|l| l == p.name())) {
+                        return Err(QucomError::UnboundParameter(p.name().to_string()));
+                    }
+                    match expr.value() {
+                        Some(angle) => angles[*slot] = angle,
+                        None => symbolic.push((*slot, expr)),
+                    }
+                }
+                QasmInstruction::parameterized(gate.with_angles(&angles), (0..angles.len())
+                    .map(|slot| match symbolic.iter().find(|(s, _)| *s == slot) {
+                        Some((_, expr)) => expr.clone(),
+                        None => ParameterExpression::constant(angles[slot]),
+                    })
+                    .collect())
+            }
+            QasmInstruction::If(condition, body) => QasmInstruction::If(condition.clone(), bind_all(body)?),
+            QasmInstruction::IfElse(condition, if_block, else_block) => {
+                QasmInstruction::IfElse(condition.clone(), bind_all(if_block)?, bind_all(else_block)?)
+            }
+            QasmInstruction::While(condition, body) => QasmInstruction::While(condition.clone(), bind_all(body)?),
+            QasmInstruction::For(var, range, body) => {
+                let locals: Vec<String> = locals.iter().cloned().chain([var.clone()]).collect();
+                let body = body.iter().map(|i| i.bind_except(values, &locals)).collect::<Result<Vec<_>, _>>()?;
+                QasmInstruction::For(var.clone(), range.clone(), body)
+            }
+            QasmInstruction::Switch(value, cases, default) => QasmInstruction::Switch(
+                value.clone(),
+                cases.iter().map(|(values, body)| Ok((values.clone(), bind_all(body)?))).collect::<Result<_, QucomError>>()?,
+                bind_all(default)?,
+            ),
+            other => other.clone(),
+        })
+    }
+}
+
+/// names of the classical variables the program assigns, loop variables included
+pub fn classical_variables(instructions: &[QasmInstruction]) -> Vec<String> {
+    variables(instructions, true)
+}
+
+/// names the program assigns, with or without the loop variables
+fn variables(instructions: &[QasmInstruction], loops: bool) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    let mut add = |name: &String| {
+        if !found.contains(name) {
+            found.push(name.clone());
+        }
+    };
+    for instr in instructions {
+        match instr {
+            QasmInstruction::Assign(name, _) => add(name),
+            QasmInstruction::For(var, _, body) => {
+                if loops {
+                    add(var);
+                }
+                variables(body, loops).iter().for_each(&mut add);
             }
-            QasmInstruction::For(var, start, end, body) => {
-                write!(f, "for {} in [{}:{}] {{ ", var, start, end)?;
-                for instr in body {
-                    write!(f, "{} ", instr)?;
+            QasmInstruction::If(_, body) | QasmInstruction::While(_, body) => {
+                variables(body, loops).iter().for_each(&mut add);
+            }
+            QasmInstruction::IfElse(_, if_block, else_block) => {
+                variables(if_block, loops).iter().chain(&variables(else_block, loops)).for_each(&mut add);
+            }
+            QasmInstruction::Switch(_, cases, default) => {
+                for body in cases.iter().map(|(_, body)| body).chain([default]) {
+                    variables(body, loops).iter().for_each(&mut add);
+                }
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// parameters used by the program, in order of first use, leaving out its classical variables
+pub fn free_parameters(instructions: &[QasmInstruction]) -> Vec<Parameter> {
+    let variables = classical_variables(instructions);
+    let mut found: Vec<Parameter> = Vec::new();
+    for p in instructions.iter().flat_map(|i| i.parameters()) {
+        if !found.contains(&p) && !variables.iter().any(|v| v == p.name()) {
+            found.push(p);
+        }
+    }
+    found
+}
+
+impl fmt::Display for QasmInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", WithLayout(self, &Layout::default()))
+    }
+}
+
+/// how QASM output names operands: the register, and index in it, of each qubit and classical
+/// bit. the default layout calls them `q[i]` and `c[i]`
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Layout<'a> {
+    qubits: &'a [QuantumRegister],
+    bits: &'a [ClassicalRegister],
+    /// names custom gates are written under, their own names if not given
+    gates: Option<&'a GateNames<'a>>,
+}
+
+impl<'a> Layout<'a> {
+    pub(crate) fn new(qubits: &'a [QuantumRegister], bits: &'a [ClassicalRegister]) -> Self {
+        Self { qubits, bits, gates: None }
+    }
+
+    pub(crate) fn with_gates(self, gates: &'a GateNames<'a>) -> Self {
+        Self { gates: Some(gates), ..self }
+    }
+
+    pub(crate) fn gate_name(&self, definition: &GateDefinition) -> String {
+        match self.gates {
+            Some(gates) => gates.name(definition),
+            None => definition.name().to_string(),
+        }
+    }
+
+    fn qubit(&self, q: usize) -> String {
+        match self.qubits.iter().find(|r| (r.offset()..r.offset() + r.size()).contains(&q)) {
+            Some(register) => format!("{}[{}]", register.name(), q - register.offset()),
+            None => format!("q[{}]", q),
+        }
+    }
+
+    fn bit(&self, c: usize) -> String {
+        match self.bits.iter().find(|r| (r.offset()..r.offset() + r.size()).contains(&c)) {
+            Some(register) => format!("{}[{}]", register.name(), c - register.offset()),
+            None => format!("c[{}]", c),
+        }
+    }
+
+    /// the classical register of exactly these bits
+    pub(crate) fn register(&self, offset: usize, size: usize) -> Option<&str> {
+        self.bits.iter().find(|r| r.offset() == offset && r.size() == size).map(|r| r.name())
+    }
+
+    /// a classical expression, naming a whole register where one matches
+    pub(crate) fn classical(&self, expr: &ClassicalExpr) -> String {
+        let bit = |c: usize| self.bit(c);
+        let register = |offset: usize, size: usize| match self.register(offset, size) {
+            Some(name) => name.to_string(),
+            None => register_from_bits(offset, size, &bit),
+        };
+        expr.to_qasm(&bit, &register)
+    }
+
+    /// `q[0], q[2]`
+    pub(crate) fn operands(&self, qubits: &[usize]) -> String {
+        qubits.iter().map(|q| self.qubit(*q)).collect::<Vec<_>>().join(", ")
+    }
+
+    pub(crate) fn qubit_registers(&self) -> Vec<&str> {
+        match self.qubits.is_empty() {
+            true => vec!["q"],
+            false => self.qubits.iter().map(|r| r.name()).collect(),
+        }
+    }
+
+    /// `measure q -> c;`, or one measurement per qubit (into the bit of the same index) when the
+    /// registers don't pair up
+    pub(crate) fn measure_all(&self) -> String {
+        match (self.qubits, self.bits) {
+            ([], []) => "measure q -> c;".to_string(),
+            ([q], [c]) if q.size() == c.size() => format!("measure {} -> {};", q.name(), c.name()),
+            _ => {
+                let n_qubits: usize = self.qubits.iter().map(|r| r.size()).sum();
+                let n_bits: usize = self.bits.iter().map(|r| r.size()).sum();
+                (0..n_qubits.min(n_bits))
+                    .map(|i| format!("measure {} -> {};", self.qubit(i), self.bit(i)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+        }
+    }
+}
+
+/// loop values as a range `[start:end]` or `[start:step:end]` (end included), a set of evenly
+/// spaced values as a range too, otherwise a set `{1, 4, 2}`; no values at all is the empty
+/// range `[0:-1]`, as QASM has no empty set
+fn loop_values(values: &LoopRange) -> String {
+    let range = |start: i64, step: i64, end: i64| match step {
+        1 => format!("[{}:{}]", start, end),
+        _ => format!("[{}:{}:{}]", start, step, end),
+    };
+    match values {
+        LoopRange::Range { step: 0, .. } => range(0, 1, -1),
+        LoopRange::Range { start, step, end } => range(*start, *step, *end),
+        LoopRange::Set(values) => {
+            let step = if values.len() > 1 { values[1] - values[0] } else { 1 };
+            let spaced = step != 0 && values.windows(2).all(|pair| pair[1] - pair[0] == step);
+            match (values.first(), values.last()) {
+                (Some(first), Some(last)) if spaced => range(*first, step, *last),
+                (None, _) => range(0, 1, -1),
+                _ => {
+                    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                    format!("{{{}}}", values.join(", "))
+                }
+            }
+        }
+    }
+}
+
+/// names the custom gates of a program are written under: a definition keeps its own name
+/// unless a different definition took it first, then it gets the first free `name_1`, `name_2`, ...
+#[derive(Debug, Default)]
+pub(crate) struct GateNames<'a>(Vec<(&'a GateDefinition, String)>);
+
+impl<'a> GateNames<'a> {
+    pub(crate) fn new(definitions: &[&'a GateDefinition]) -> Self {
+        let mut names = Self::default();
+        for definition in definitions {
+            let name = free_name(definition.name(), |name| names.0.iter().any(|(_, taken)| taken == name));
+            names.0.push((definition, name));
+        }
+        names
+    }
+
+    fn name(&self, definition: &GateDefinition) -> String {
+        match self.0.iter().find(|(d, _)| *d == definition) {
+            Some((_, name)) => name.clone(),
+            None => definition.name().to_string(),
+        }
+    }
+}
+
+/// what follows `statement` inside a block: a pragma runs to the end of its line, so it takes a newline
+pub(crate) fn separator(statement: &str) -> &'static str {
+    if statement.starts_with("#pragma") {
+        "\n"
+    } else {
+        " "
+    }
+}
+
+/// an instruction written with a layout's register names
+pub(crate) struct WithLayout<'a>(pub &'a QasmInstruction, pub &'a Layout<'a>);
+
+impl fmt::Display for WithLayout<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let WithLayout(instr, layout) = *self;
+        let q = |q: &usize| layout.qubit(*q);
+        let block = |f: &mut fmt::Formatter<'_>, body: &[QasmInstruction]| -> fmt::Result {
+            write!(f, "{{ ")?;
+            for instr in body {
+                let statement = WithLayout(instr, layout).to_string();
+                write!(f, "{}{}", statement, separator(&statement))?;
+            }
+            write!(f, "}}")
+        };
+        match instr {
+            QasmInstruction::H(a) => write!(f, "h {};", q(a)),
+            QasmInstruction::X(a) => write!(f, "x {};", q(a)),
+            QasmInstruction::Y(a) => write!(f, "y {};", q(a)),
+            QasmInstruction::Z(a) => write!(f, "z {};", q(a)),
+            QasmInstruction::S(a, false) => write!(f, "s {};", q(a)),
+            QasmInstruction::S(a, true) => write!(f, "sdg {};", q(a)),
+            QasmInstruction::T(a, false) => write!(f, "t {};", q(a)),
+            QasmInstruction::T(a, true) => write!(f, "tdg {};", q(a)),
+            QasmInstruction::CX(c, t) => write!(f, "cx {}, {};", q(c), q(t)),
+            QasmInstruction::CCX(qs) => write!(f, "ccx {};", layout.operands(qs)),
+            QasmInstruction::CZ(c, t) => write!(f, "cz {}, {};", q(c), q(t)),
+            QasmInstruction::Swap(a, b) => write!(f, "swap {}, {};", q(a), q(b)),
+            QasmInstruction::Rx(angle, a) => write!(f, "rx({}) {};", angle, q(a)),
+            QasmInstruction::Ry(angle, a) => write!(f, "ry({}) {};", angle, q(a)),
+            QasmInstruction::Rz(angle, a) => write!(f, "rz({}) {};", angle, q(a)),
+            QasmInstruction::Phase(angle, a) => write!(f, "p({}) {};", angle, q(a)),
+            QasmInstruction::U(theta, phi, lambda, a) => write!(f, "u({}, {}, {}) {};", theta, phi, lambda, q(a)),
+            QasmInstruction::Reset(a) => write!(f, "reset {};", q(a)),
+            QasmInstruction::ResetAll => {
+                let resets: Vec<String> = layout.qubit_registers().iter().map(|r| format!("reset {};", r)).collect();
+                write!(f, "{}", resets.join(" "))
+            }
+            QasmInstruction::Barrier(qs) if qs.is_empty() => write!(f, "barrier;"),
+            QasmInstruction::Barrier(qs) => write!(f, "barrier {};", layout.operands(qs)),
+            QasmInstruction::BarrierAll => write!(f, "barrier {};", layout.qubit_registers().join(", ")),
+            QasmInstruction::Delay(duration, unit, a) => write!(f, "delay[{}{}] {};", duration, unit, q(a)),
+            QasmInstruction::Measure(a, c) => write!(f, "measure {} -> {};", q(a), layout.bit(*c)),
+            QasmInstruction::MeasureAll => write!(f, "{}", layout.measure_all()),
+            QasmInstruction::If(condition, body) => {
+                write!(f, "if ({}) ", layout.classical(condition))?;
+                block(f, body)
+            }
+            QasmInstruction::IfElse(condition, if_block, else_block) => {
+                write!(f, "if ({}) ", layout.classical(condition))?;
+                block(f, if_block)?;
+                write!(f, " else ")?;
+                block(f, else_block)
+            }
+            QasmInstruction::While(condition, body) => {
+                write!(f, "while ({}) ", layout.classical(condition))?;
+                block(f, body)
+            }
+            QasmInstruction::Assign(name, value) => write!(f, "{} = {};", name, layout.classical(value)),
+            QasmInstruction::For(var, values, body) => {
+                write!(f, "for {} in {} ", var, loop_values(values))?;
+                block(f, body)
+            }
+            QasmInstruction::Switch(value, cases, default) => {
+                write!(f, "switch ({}) {{ ", layout.classical(value))?;
+                for (values, body) in cases {
+                    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                    write!(f, "case {} ", values.join(", "))?;
+                    block(f, body)?;
+                    write!(f, " ")?;
+                }
+                if !default.is_empty() {
+                    write!(f, "default ")?;
+                    block(f, default)?;
+                    write!(f, " ")?;
                 }
                 write!(f, "}}")
             }
+            QasmInstruction::Break => write!(f, "break;"),
+            QasmInstruction::Continue => write!(f, "continue;"),
+            QasmInstruction::End => write!(f, "end;"),
+            QasmInstruction::Parameterized(gate, slots) => {
+                let angles: Vec<String> = gate
+                    .angles()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, a)| match slots.iter().find(|(slot, _)| *slot == i) {
+                        Some((_, expr)) => expr.to_string(),
+                        None => a.to_string(),
+                    })
+                    .collect();
+                write_gate(f, gate, &angles, layout)
+            }
+            QasmInstruction::GateCall(_, args, _) => {
+                let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+                write_gate(f, instr, &args, layout)
+            }
+            QasmInstruction::Unitary(matrix, qs) => {
+                // QASM has no matrix literal, so the matrix travels in a pragma QasmParser reads back
+                let rows: Vec<String> = matrix
+                    .rows()
+                    .into_iter()
+                    .map(|row| format!("[{}]", row.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", ")))
+                    .collect();
+                write!(f, "#pragma qucom unitary [{}] {};", rows.join(", "), layout.operands(qs))
+            }
+            QasmInstruction::Controlled(gate, controls, ctrl_state) if matches!(gate.as_ref(), QasmInstruction::Unitary(..)) => {
+                // no modifier syntax for the pragma, so write the controlled matrix itself
+                let QasmInstruction::Unitary(matrix, qs) = gate.as_ref() else { unreachable!() };
+                let operation = GateOperation::with_ctrl_state(matrix.clone(), controls.clone(), ctrl_state.clone(), qs.clone());
+                let unitary = QasmInstruction::Unitary(operation.full_matrix(), operation.qubits());
+                write!(f, "{}", WithLayout(&unitary, layout))
+            }
+            QasmInstruction::Controlled(_, _, _) => {
+                let angles: Vec<String> = instr.angles().iter().map(|a| a.to_string()).collect();
+                write_gate(f, instr, &angles, layout)
+            }
+        }
+    }
+}
+
+/// `matrix` to the `k`, by repeated squaring
+fn matrix_power(mut matrix: Array2<Complex64>, mut k: u64) -> Array2<Complex64> {
+    let mut result = Array2::eye(matrix.nrows());
+    while k > 0 {
+        if k & 1 == 1 {
+            result = result.dot(&matrix);
+        }
+        matrix = matrix.dot(&matrix);
+        k >>= 1;
+    }
+    result
+}
+
+/// the first qubit that appears twice
+pub(crate) fn first_duplicate(qubits: &[usize]) -> Option<usize> {
+    qubits.iter().enumerate().find(|(i, q)| qubits[..*i].contains(q)).map(|(_, q)| *q)
+}
+
+/// `name(angles) operands;` with the angles given as text, preceded by a `ctrl @` or
+/// `negctrl @` modifier per control of a controlled gate and `inv @` for an inverted custom gate
+fn write_gate(f: &mut fmt::Formatter, gate: &QasmInstruction, angles: &[String], layout: &Layout) -> fmt::Result {
+    let (gate, controls) = match gate {
+        QasmInstruction::Controlled(inner, controls, ctrl_state) => {
+            for &closed in ctrl_state {
+                write!(f, "{}", if closed { "ctrl @ " } else { "negctrl @ " })?;
+            }
+            (inner.as_ref(), controls.as_slice())
         }
+        gate => (gate, &[][..]),
+    };
+    let name = match gate {
+        QasmInstruction::GateCall(definition, _, _) => match definition.inverse_of() {
+            Some(original) => {
+                write!(f, "inv @ ")?;
+                layout.gate_name(original)
+            }
+            None => layout.gate_name(definition),
+        },
+        gate => gate.name().to_string(),
+    };
+    write!(f, "{}", name)?;
+    if !angles.is_empty() {
+        write!(f, "({})", angles.join(", "))?;
     }
+    let qubits: Vec<usize> = controls.iter().copied().chain(gate.qubits()).collect();
+    write!(f, " {};", layout.operands(&qubits))
 }
 
 /// QASM generator
 pub struct QasmGenerator {
     n_qubits: usize,
+    n_bits: usize,
+    quantum_registers: Vec<QuantumRegister>,
+    classical_registers: Vec<ClassicalRegister>,
     instructions: Vec<QasmInstruction>,
 }
 
 impl QasmGenerator {
+    /// `n_qubits` qubits in register `q` and as many classical bits in `c`
     pub fn new(n_qubits: usize) -> Self {
-        Self {
-            n_qubits,
-            instructions: Vec::new(),
+        let (quantum_registers, classical_registers) = match n_qubits {
+            0 => (Vec::new(), Vec::new()),
+            n => (vec![QuantumRegister::new("q", 0, n)], vec![ClassicalRegister::new("c", 0, n)]),
+        };
+        Self { n_qubits, n_bits: n_qubits, quantum_registers, classical_registers, instructions: Vec::new() }
+    }
+
+    /// qubits and classical bits split into the given registers, laid out in order
+    pub fn with_registers(quantum: &[(&str, usize)], classical: &[(&str, usize)]) -> Result<Self, QucomError> {
+        let mut generator = Self::new(0);
+        for &(name, size) in quantum {
+            generator.add_quantum_register(name, size)?;
+        }
+        for &(name, size) in classical {
+            generator.add_classical_register(name, size)?;
         }
+        Ok(generator)
+    }
+
+    /// append a register of `size` qubits after the existing ones
+    pub fn add_quantum_register(&mut self, name: &str, size: usize) -> Result<QuantumRegister, QucomError> {
+        check_register_name(name, size, self.register_names())?;
+        let register = QuantumRegister::new(name, self.n_qubits, size);
+        self.n_qubits += size;
+        self.quantum_registers.push(register.clone());
+        Ok(register)
+    }
+
+    /// append a register of `size` classical bits after the existing ones
+    pub fn add_classical_register(&mut self, name: &str, size: usize) -> Result<ClassicalRegister, QucomError> {
+        check_register_name(name, size, self.register_names())?;
+        let register = ClassicalRegister::new(name, self.n_bits, size);
+        self.n_bits += size;
+        self.classical_registers.push(register.clone());
+        Ok(register)
+    }
+
+    fn register_names(&self) -> impl Iterator<Item = &str> {
+        self.quantum_registers.iter().map(|r| r.name()).chain(self.classical_registers.iter().map(|r| r.name()))
+    }
+
+    pub fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    pub fn n_bits(&self) -> usize {
+        self.n_bits
+    }
+
+    pub fn quantum_registers(&self) -> &[QuantumRegister] {
+        &self.quantum_registers
+    }
+
+    pub fn classical_registers(&self) -> &[ClassicalRegister] {
+        &self.classical_registers
     }
 
     pub fn add_instruction(&mut self, instruction: QasmInstruction) {
@@ -123,32 +1149,86 @@ impl QasmGenerator {
         &self.instructions
     }
 
-    pub fn to_qasm(&self) -> String {
-        self.to_qasm_version(3)
+    /// keep the first `len` instructions
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.instructions.truncate(len);
     }
 
-    /// export to QASM string with specified version
-    pub fn to_qasm_version(&self, version: u8) -> String {
-        let mut qasm = String::new();
-        
-        if version == 3 {
-            qasm.push_str("OPENQASM 3.0;\n");
-            qasm.push_str(&format!("qubit[{}] q;\n", self.n_qubits));
-            qasm.push_str(&format!("bit[{}] c;\n\n", self.n_qubits));
-        } else {
-            qasm.push_str("OPENQASM 2.0;\n");
-            qasm.push_str("include \"qelib1.inc\";\n\n");
-            qasm.push_str(&format!("qreg q[{}];\n", self.n_qubits));
-            qasm.push_str(&format!("creg c[{}];\n\n", self.n_qubits));
+    /// parameters in order of first use, leaving out classical variables
+    pub fn parameters(&self) -> Vec<Parameter> {
+        free_parameters(&self.instructions)
+    }
+
+    /// custom gates the program calls, each defined after the gates its body uses
+    pub fn definitions(&self) -> Vec<&GateDefinition> {
+        let mut found = Vec::new();
+        for instr in &self.instructions {
+            instr.collect_definitions(&mut found);
+        }
+        found
+    }
+
+    /// export as OpenQASM 3.0
+    ///
+    /// OpenQASM has no statement for a matrix, so unitaries (controlled ones as their full
+    /// matrix) are written as `#pragma qucom unitary [[..], ..] q[0];`, which only this crate
+    /// reads back; other tools skip or reject the pragma. for a program they can run, use
+    /// `to_qasm_version(2)`, which writes single-qubit unitaries as `u3` and fails on larger ones
+    pub fn to_qasm(&self) -> String {
+        let mut qasm = String::from("OPENQASM 3.0;\n");
+        for register in &self.quantum_registers {
+            qasm.push_str(&format!("qubit[{}] {};\n", register.size(), register.name()));
         }
-        
+        for register in &self.classical_registers {
+            qasm.push_str(&format!("bit[{}] {};\n", register.size(), register.name()));
+        }
+        for p in self.parameters() {
+            qasm.push_str(&format!("input float[64] {};\n", p));
+        }
+        for name in variables(&self.instructions, false) {
+            qasm.push_str(&format!("int[64] {};\n", name));
+        }
+        qasm.push('\n');
+
+        let definitions = self.definitions();
+        let names = GateNames::new(&definitions);
+        for definition in &definitions {
+            qasm.push_str(&format!("{}\n", definition.to_qasm(&Layout::default().with_gates(&names))));
+        }
+        if !definitions.is_empty() {
+            qasm.push('\n');
+        }
+
+        let layout = Layout::new(&self.quantum_registers, &self.classical_registers).with_gates(&names);
         for instr in &self.instructions {
-            qasm.push_str(&format!("{}\n", instr));
+            qasm.push_str(&format!("{}\n", WithLayout(instr, &layout)));
         }
-        
+
         qasm
     }
 
+    /// export as OpenQASM 3.0 (`to_qasm`) or 2.0; version 2 fails on what it cannot express,
+    /// such as loops, classical variables, conditions other than `register == value` and
+    /// unbound parameters
+    pub fn to_qasm_version(&self, version: u8) -> Result<String, QucomError> {
+        match version {
+            2 => qasm2::program(self),
+            3 => Ok(self.to_qasm()),
+            _ => Err(QucomError::InvalidArgument(format!("no OpenQASM version {}, only 2 and 3", version))),
+        }
+    }
+
+    /// a generator with the same registers and no instructions
+    pub(crate) fn cleared(&self) -> Self {
+        Self {
+            n_qubits: self.n_qubits,
+            n_bits: self.n_bits,
+            quantum_registers: self.quantum_registers.clone(),
+            classical_registers: self.classical_registers.clone(),
+            instructions: Vec::new(),
+        }
+    }
+
     pub fn clear(&mut self) {
         self.instructions.clear();
     }
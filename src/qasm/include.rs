@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// supplies the source of the files a program includes
+///
+/// `stdgates.inc` and `qelib1.inc` are bundled with the crate and never reach the resolver
+pub trait IncludeResolver {
+    /// the source of `path`, or `None` if there is no such file
+    fn resolve(&self, path: &str) -> Option<String>;
+}
+
+/// in-memory files keyed by path, e.g. for tests or the browser
+impl IncludeResolver for HashMap<String, String> {
+    fn resolve(&self, path: &str) -> Option<String> {
+        self.get(path).cloned()
+    }
+}
+
+/// files read from disk, relative paths taken from `root`
+///
+/// a program only reaches files under the root: absolute paths, `..` and links leading out of
+/// it are not found
+#[derive(Debug, Clone)]
+pub struct FileResolver {
+    root: PathBuf,
+}
+
+impl FileResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl IncludeResolver for FileResolver {
+    fn resolve(&self, path: &str) -> Option<String> {
+        if !Path::new(path).components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+            return None;
+        }
+        let root = self.root.canonicalize().ok()?;
+        let file = root.join(path).canonicalize().ok()?;
+        match file.starts_with(&root) {
+            true => std::fs::read_to_string(file).ok(),
+            false => None,
+        }
+    }
+}
+
+/// source of a bundled gate library
+pub(crate) fn bundled(path: &str) -> Option<&'static str> {
+    match path {
+        "stdgates.inc" => Some(include_str!("include/stdgates.inc")),
+        "qelib1.inc" => Some(include_str!("include/qelib1.inc")),
+        _ => None,
+    }
+}
+
+/// whether the bundled library `path` defines `gate`
+pub(crate) fn defines(path: &str, gate: &str) -> bool {
+    bundled(path).is_some_and(|source| {
+        source.lines().filter_map(|line| line.strip_prefix("gate ")).any(|line| {
+            line.strip_prefix(gate).is_some_and(|rest| rest.starts_with([' ', '(']))
+        })
+    })
+}
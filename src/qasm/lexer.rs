@@ -0,0 +1,221 @@
+use crate::error::QucomError;
+use crate::qasm::ast::Span;
+
+/// token of OpenQASM source
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Identifier(String),
+    Integer(usize),
+    Float(f64),
+    /// number with a time unit, e.g. `100ns` or `1.5us`
+    Duration(f64, String),
+    /// `"..."`, without the quotes
+    String(String),
+    /// `$3`
+    PhysicalQubit(usize),
+    /// the rest of a `#pragma` line
+    Pragma(String),
+    /// punctuation or operator, e.g. `;`, `->` or `==`
+    Symbol(&'static str),
+    Eof,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// operators and punctuation, longest first so `->` wins over `-`
+const SYMBOLS: &[&str] = &[
+    "<<=", ">>=", "->", "==", "!=", "<=", ">=", "&&", "||", "**", "<<", ">>", "+=", "-=", "*=", "/=", "%=", "&=",
+    "|=", "^=",
+    "{", "}", "(", ")", "[", "]", ";", ",", ":", "@", "=", "+", "-", "*", "/", "%", "<", ">", "!", "~", "&", "|",
+    "^", ".",
+];
+
+const TIME_UNITS: &[&str] = &["dt", "ns", "us", "µs", "ms", "s"];
+
+/// splits source into tokens, skipping whitespace, `//` and `/* */` comments
+pub struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer {
+    pub fn new(source: &str) -> Self {
+        Self::starting_at(source, Span::new(1, 1))
+    }
+
+    /// lexer for a piece of a larger source that starts at `start`, so spans point into the whole
+    pub fn starting_at(source: &str, start: Span) -> Self {
+        Self { chars: source.chars().collect(), pos: 0, line: start.line, column: start.column }
+    }
+
+    /// every token, ending with `Eof`
+    pub fn tokenize(mut self) -> Result<Vec<Token>, QucomError> {
+        let mut tokens = Vec::new();
+        loop {
+            let token = self.next_token()?;
+            let done = token.kind == TokenKind::Eof;
+            tokens.push(token);
+            if done {
+                return Ok(tokens);
+            }
+        }
+    }
+
+    fn peek(&self, ahead: usize) -> Option<char> {
+        self.chars.get(self.pos + ahead).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn span(&self) -> Span {
+        Span::new(self.line, self.column)
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), QucomError> {
+        loop {
+            match (self.peek(0), self.peek(1)) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.bump();
+                }
+                (Some('/'), Some('/')) => {
+                    while self.peek(0).is_some_and(|c| c != '\n') {
+                        self.bump();
+                    }
+                }
+                (Some('/'), Some('*')) => {
+                    let start = self.span();
+                    self.bump();
+                    self.bump();
+                    loop {
+                        match (self.peek(0), self.peek(1)) {
+                            (Some('*'), Some('/')) => {
+                                self.bump();
+                                self.bump();
+                                break;
+                            }
+                            (Some(_), _) => {
+                                self.bump();
+                            }
+                            (None, _) => return Err(start.error("unterminated block comment")),
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Token, QucomError> {
+        self.skip_trivia()?;
+        let span = self.span();
+        let Some(c) = self.peek(0) else {
+            return Ok(Token { kind: TokenKind::Eof, span });
+        };
+
+        let kind = if c.is_ascii_digit() || (c == '.' && self.peek(1).is_some_and(|d| d.is_ascii_digit())) {
+            self.number(span)?
+        } else if c.is_alphabetic() || c == '_' {
+            TokenKind::Identifier(self.identifier())
+        } else if c == '"' || c == '\'' {
+            self.bump();
+            let mut text = String::new();
+            loop {
+                match self.bump() {
+                    Some(end) if end == c => break,
+                    Some('\n') | None => return Err(span.error("unterminated string")),
+                    Some(other) => text.push(other),
+                }
+            }
+            TokenKind::String(text)
+        } else if c == '$' {
+            self.bump();
+            let digits = self.take_while(|d| d.is_ascii_digit());
+            let index = digits.parse().map_err(|_| span.error("expected a qubit number after '$'"))?;
+            TokenKind::PhysicalQubit(index)
+        } else if c == '#' {
+            self.bump();
+            let directive = self.identifier();
+            if directive != "pragma" {
+                return Err(span.error(format!("unknown directive '#{}'", directive)));
+            }
+            TokenKind::Pragma(self.take_while(|c| c != '\n'))
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| s.chars().enumerate().all(|(i, sc)| self.peek(i) == Some(sc)))
+                .ok_or_else(|| span.error(format!("unexpected character '{}'", c)))?;
+            for _ in 0..symbol.chars().count() {
+                self.bump();
+            }
+            TokenKind::Symbol(symbol)
+        };
+        Ok(Token { kind, span })
+    }
+
+    fn take_while(&mut self, keep: impl Fn(char) -> bool) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek(0).filter(|c| keep(*c)) {
+            text.push(c);
+            self.bump();
+        }
+        text
+    }
+
+    fn identifier(&mut self) -> String {
+        self.take_while(|c| c.is_alphanumeric() || c == '_')
+    }
+
+    /// integer, float (with optional exponent) or duration
+    fn number(&mut self, span: Span) -> Result<TokenKind, QucomError> {
+        let mut text = self.take_while(|c| c.is_ascii_digit() || c == '.' || c == '_');
+        // exponent, e.g. 1.5e-3 (but not the start of an identifier like "euler")
+        if matches!(self.peek(0), Some('e' | 'E')) {
+            let sign = usize::from(matches!(self.peek(1), Some('+' | '-')));
+            if self.peek(1 + sign).is_some_and(|d| d.is_ascii_digit()) {
+                for _ in 0..1 + sign {
+                    text.push(self.bump().unwrap_or('e'));
+                }
+                text.push_str(&self.take_while(|c| c.is_ascii_digit()));
+            }
+        }
+        let text = text.replace('_', "");
+        let invalid = || span.error(format!("invalid number '{}'", text));
+
+        let unit = TIME_UNITS
+            .iter()
+            .find(|unit| {
+                let n = unit.chars().count();
+                unit.chars().enumerate().all(|(i, uc)| self.peek(i) == Some(uc))
+                    && !self.peek(n).is_some_and(|c| c.is_alphanumeric() || c == '_')
+            })
+            .map(|unit| unit.to_string());
+        if let Some(unit) = unit {
+            for _ in 0..unit.chars().count() {
+                self.bump();
+            }
+            return Ok(TokenKind::Duration(text.parse().map_err(|_| invalid())?, unit));
+        }
+
+        if text.chars().all(|c| c.is_ascii_digit()) {
+            Ok(TokenKind::Integer(text.parse().map_err(|_| invalid())?))
+        } else {
+            Ok(TokenKind::Float(text.parse().map_err(|_| invalid())?))
+        }
+    }
+}
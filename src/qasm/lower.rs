@@ -0,0 +1,1045 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::error::QucomError;
+use crate::parameters::{Parameter, ParameterExpression};
+use crate::qasm::ast::{
+    Argument, ArgumentType, Expr, ExprKind, LoopValues, Modifier, Operand, OperandTarget, Program, Span, Statement, StatementKind,
+};
+use crate::qasm::classical::{BinaryOp, ClassicalExpr, UnaryOp};
+use crate::qasm::generator::{LoopRange, QasmInstruction, MAX_POWER_COPIES};
+use crate::qasm::include::{self, IncludeResolver};
+use crate::qasm::{GateDefinition, QasmParser};
+
+/// subroutine calls nested deeper than this are taken to be recursion, which cannot be expanded
+const MAX_CALL_DEPTH: usize = 64;
+
+/// resolve the names of a parsed program and turn it into instructions
+///
+/// registers are laid out one after another in declaration order, so with `qubit[2] a; qubit b;`
+/// `b` is qubit 2. a program that declares no qubit (or no bit) registers addresses them by
+/// index under any name, as in `h q[0];`
+///
+/// `stdgates.inc` and `qelib1.inc` are bundled; other includes are read through `resolver`
+pub(crate) fn lower(program: &Program, resolver: Option<&dyn IncludeResolver>) -> Result<Vec<QasmInstruction>, QucomError> {
+    Ok(lower_with_registers(program, resolver)?.instructions)
+}
+
+/// a lowered program with its registers as (name, size), in declaration order
+pub(crate) struct Lowered {
+    pub instructions: Vec<QasmInstruction>,
+    pub quantum: Vec<(String, usize)>,
+    pub classical: Vec<(String, usize)>,
+}
+
+/// `lower`, also returning the declared registers; a lone `qubit a;` is a register of one
+pub(crate) fn lower_with_registers(program: &Program, resolver: Option<&dyn IncludeResolver>) -> Result<Lowered, QucomError> {
+    let qasm2 = program.version.as_deref().is_some_and(|version| version.starts_with('2'));
+    let mut lowering = Lowering { resolver, qasm2, ..Lowering::default() };
+    let mut instructions = Vec::new();
+    lowering.block(&program.statements, &mut instructions)?;
+    Ok(Lowered { instructions, quantum: lowering.qubits.sizes(), classical: lowering.bits.sizes() })
+}
+
+/// an operand with its index evaluated
+enum Target<'a> {
+    Register(&'a str),
+    Indexed(&'a str, usize),
+    Physical(usize),
+}
+
+/// declared registers of one kind, laid out in order
+#[derive(Debug, Default)]
+struct Registers {
+    /// name, offset and size (`None` for a lone `qubit a;`)
+    entries: Vec<(String, usize, Option<usize>)>,
+    /// the entries are a subroutine's arguments, naming some of the program's qubits or bits
+    aliased: bool,
+}
+
+impl Registers {
+    fn total(&self) -> usize {
+        self.entries.iter().map(|(_, _, size)| size.unwrap_or(1)).sum()
+    }
+
+    fn declare(&mut self, name: &str, size: Option<usize>) {
+        let offset = self.total();
+        self.entries.push((name.to_string(), offset, size));
+    }
+
+    /// a subroutine argument for the caller's qubits or bits from `offset`
+    fn alias(&mut self, name: &str, offset: usize, size: Option<usize>) {
+        self.aliased = true;
+        self.entries.push((name.to_string(), offset, size));
+    }
+
+    fn sizes(&self) -> Vec<(String, usize)> {
+        self.entries.iter().map(|(name, _, size)| (name.clone(), size.unwrap_or(1))).collect()
+    }
+
+    fn get(&self, name: &str) -> Option<(usize, Option<usize>)> {
+        self.entries.iter().find(|(n, _, _)| n == name).map(|(_, offset, size)| (*offset, *size))
+    }
+
+    /// the single qubit or bit `target` names
+    fn index(&self, target: &Target, span: Span, kind: &str) -> Result<usize, QucomError> {
+        match *target {
+            Target::Physical(index) => Ok(index),
+            Target::Indexed(_, index) if self.entries.is_empty() && !self.aliased => Ok(index),
+            Target::Indexed(name, index) => match self.get(name) {
+                Some((offset, Some(size))) if index < size => Ok(offset + index),
+                Some((_, Some(size))) => Err(span.error(format!(
+                    "index {} is out of range for {} register '{}' of size {}", index, kind, name, size
+                ))),
+                Some((_, None)) => Err(span.error(format!("'{}' is a single {}, not a register", name, kind))),
+                None => Err(span.error(format!("unknown {} register '{}'", kind, name))),
+            },
+            Target::Register(name) => match self.get(name) {
+                Some((offset, None)) => Ok(offset),
+                Some((_, Some(size))) => Err(span.error(format!(
+                    "expected a single {}, '{}' is a register of {}", kind, name, size
+                ))),
+                None => Err(span.error(format!("unknown {} '{}'", kind, name))),
+            },
+        }
+    }
+
+    /// every index `target` names, `None` for a bare name when no registers are declared
+    /// (meaning all of them)
+    fn indices(&self, target: &Target, span: Span, kind: &str) -> Result<Option<Vec<usize>>, QucomError> {
+        match *target {
+            Target::Register(_) if self.entries.is_empty() && !self.aliased => Ok(None),
+            Target::Register(name) => match self.get(name) {
+                Some((offset, size)) => Ok(Some((offset..offset + size.unwrap_or(1)).collect())),
+                None => Err(span.error(format!("unknown {} register '{}'", kind, name))),
+            },
+            _ => Ok(Some(vec![self.index(target, span, kind)?])),
+        }
+    }
+
+    /// whether `indices` is every declared index, in order
+    fn is_all(&self, indices: &[usize]) -> bool {
+        !self.aliased && indices.len() == self.total() && indices.iter().enumerate().all(|(i, q)| i == *q)
+    }
+}
+
+/// names in scope while lowering a gate call: the program's registers and inputs, or a gate
+/// definition's qubit arguments and parameters
+enum Scope<'a> {
+    Program,
+    Gate { qubits: &'a [String], params: &'a [String] },
+}
+
+/// a `def`, expanded inline at every call
+struct Subroutine {
+    arguments: Vec<Argument>,
+    returns: Option<String>,
+    body: Vec<Statement>,
+}
+
+/// a value argument of a subroutine, as an angle and, when it is an integer, as a classical value
+struct Value {
+    angle: Option<ParameterExpression>,
+    classical: Option<ClassicalExpr>,
+}
+
+/// where a subroutine call's `return` goes
+enum ReturnTarget {
+    Bit(usize),
+    Variable(String),
+}
+
+#[derive(Default)]
+struct Lowering<'r> {
+    qubits: Registers,
+    bits: Registers,
+    inputs: Vec<String>,
+    constants: HashMap<String, f64>,
+    /// declared `int`, `uint` and `bool` variables
+    variables: Vec<String>,
+    /// variables of the `for` loops being lowered as loops, read at run time
+    loop_variables: Vec<String>,
+    /// variables of the `for` loops being unrolled, with the current iteration's value
+    loop_values: HashMap<String, i64>,
+    gates: HashMap<String, Arc<GateDefinition>>,
+    /// whether the statements being lowered come from a bundled library, whose gates the program may redefine
+    in_library: bool,
+    subroutines: HashMap<String, Arc<Subroutine>>,
+    /// value arguments of the subroutine being expanded
+    arguments: HashMap<String, Value>,
+    /// where the subroutine being expanded sends its `return`, `None` for a bare call
+    returns: Option<ReturnTarget>,
+    /// number of subroutine calls being expanded
+    depth: usize,
+    /// number of `while` and `for` loops being lowered as loops, which `break` and `continue` need
+    loops: usize,
+    /// files already included, each read once
+    included: Vec<String>,
+    resolver: Option<&'r dyn IncludeResolver>,
+    /// `opaque` gates, declared without a definition
+    opaque: Vec<String>,
+    /// the program follows OpenQASM 2.0
+    qasm2: bool,
+}
+
+impl Lowering<'_> {
+    fn block(&mut self, statements: &[Statement], out: &mut Vec<QasmInstruction>) -> Result<(), QucomError> {
+        for statement in statements {
+            self.statement(statement, out)?;
+        }
+        Ok(())
+    }
+
+    fn body(&mut self, statements: &[Statement]) -> Result<Vec<QasmInstruction>, QucomError> {
+        let mut out = Vec::new();
+        self.block(statements, &mut out)?;
+        Ok(out)
+    }
+
+    fn statement(&mut self, statement: &Statement, out: &mut Vec<QasmInstruction>) -> Result<(), QucomError> {
+        let span = statement.span;
+        if self.depth > 0
+            && matches!(
+                statement.kind,
+                StatementKind::Include(_) | StatementKind::QubitDeclaration { .. } | StatementKind::BitDeclaration { .. }
+                | StatementKind::Input { .. } | StatementKind::GateDefinition { .. } | StatementKind::Subroutine { .. }
+            )
+        {
+            return Err(span.error("a subroutine cannot include files or declare qubits, bits, inputs, gates or subroutines"));
+        }
+        match &statement.kind {
+            StatementKind::Include(path) => self.include(path, span, out)?,
+            // pragmas other than unitaries are hints
+            StatementKind::Pragma(_) => {}
+            StatementKind::QubitDeclaration { name, size } => {
+                self.check_new_name(name, span)?;
+                self.qubits.declare(name, *size);
+            }
+            StatementKind::BitDeclaration { name, size } => {
+                self.check_new_name(name, span)?;
+                self.bits.declare(name, *size);
+            }
+            StatementKind::Input { name, .. } => {
+                self.check_new_name(name, span)?;
+                self.inputs.push(name.clone());
+            }
+            StatementKind::Const { name, value, .. } => {
+                self.check_new_name(name, span)?;
+                let value = self
+                    .expression(value, &Scope::Program)?
+                    .value()
+                    .ok_or_else(|| value.span.error(format!("const '{}' must not depend on inputs", name)))?;
+                self.constants.insert(name.clone(), value);
+            }
+            // OpenQASM 2.0 programs often define qelib1 gates themselves; the built-in gate is the same
+            StatementKind::GateDefinition { name, .. } if self.qasm2 && builtin(name, &[]).is_some() => {}
+            StatementKind::GateDefinition { name, params, qubits, body } => {
+                if self.gates.get(name).is_some_and(|gate| gate.is_library()) {
+                    if self.in_library {
+                        // both libraries define it
+                        return Ok(());
+                    }
+                } else if self.gates.contains_key(name) || self.subroutines.contains_key(name) || self.opaque.contains(name) {
+                    return Err(span.error(format!("gate '{}' is already defined", name)));
+                }
+                let scope = Scope::Gate { qubits, params };
+                let mut instructions = Vec::new();
+                for statement in body {
+                    instructions.extend(self.gate_body_statement(statement, &scope)?);
+                }
+                let parameters = params.iter().map(|p| Parameter::named(p)).collect();
+                let mut definition = GateDefinition::new(name, parameters, qubits.len(), instructions)
+                    .map_err(|e| span.error(e.to_string()))?;
+                if self.in_library {
+                    definition = definition.in_library();
+                }
+                self.gates.insert(name.clone(), Arc::new(definition));
+            }
+            StatementKind::Opaque { name, .. } => {
+                if self.gates.contains_key(name) || self.opaque.contains(name) || builtin(name, &[]).is_some() {
+                    return Err(span.error(format!("gate '{}' is already defined", name)));
+                }
+                self.opaque.push(name.clone());
+            }
+            StatementKind::Subroutine { name, arguments, returns, body } => {
+                if self.gates.contains_key(name) || self.subroutines.contains_key(name) || builtin(name, &[]).is_some() {
+                    return Err(span.error(format!("'{}' is already defined", name)));
+                }
+                if let Some(span) = misplaced_return(body) {
+                    return Err(span.error("return must be the last statement of a subroutine"));
+                }
+                let subroutine = Subroutine { arguments: arguments.clone(), returns: returns.clone(), body: body.clone() };
+                self.subroutines.insert(name.clone(), Arc::new(subroutine));
+            }
+            StatementKind::GateCall { modifiers, name, args, operands, .. } if self.subroutines.contains_key(name) => {
+                if !modifiers.is_empty() || !operands.is_empty() {
+                    return Err(span.error(format!("'{}' is a subroutine; pass its qubits inside the parentheses", name)));
+                }
+                self.call(name, args, span, None, out)?;
+            }
+            StatementKind::GateCall { .. } => out.extend(self.gate_call(statement, &Scope::Program)?),
+            StatementKind::Return(value) => {
+                if self.depth == 0 {
+                    return Err(span.error("return outside a subroutine"));
+                }
+                if let Some(value) = value {
+                    let value = self.classical(value)?;
+                    match &self.returns {
+                        Some(ReturnTarget::Variable(name)) => out.push(QasmInstruction::Assign(name.clone(), value)),
+                        Some(ReturnTarget::Bit(_)) => return Err(span.error("only a measurement can be returned into a bit")),
+                        None => {}
+                    }
+                }
+            }
+            StatementKind::ReturnMeasure(operand) => {
+                if self.depth == 0 {
+                    return Err(span.error("return outside a subroutine"));
+                }
+                let qubit = self.qubit(operand, &Scope::Program)?;
+                match &self.returns {
+                    Some(ReturnTarget::Bit(bit)) => out.push(QasmInstruction::Measure(qubit, *bit)),
+                    _ => return Err(span.error("a returned measurement must be assigned to a bit, e.g. `c[0] = f(q[0]);`")),
+                }
+            }
+            StatementKind::Unitary { matrix, operands } => {
+                let qubits = operands.iter().map(|op| self.qubit(op, &Scope::Program)).collect::<Result<Vec<_>, _>>()?;
+                out.push(QasmInstruction::unitary(matrix.clone(), &qubits)?);
+            }
+            StatementKind::Measure { qubit, target } => {
+                let target = target
+                    .as_ref()
+                    .ok_or_else(|| span.error("measure needs a target, e.g. `c[0] = measure q[0];`"))?;
+                let qubits = self.qubits.indices(&self.target(qubit)?, qubit.span, "qubit")?;
+                let bits = self.bits.indices(&self.target(target)?, target.span, "bit")?;
+                match (qubits, bits) {
+                    (None, None) => out.push(QasmInstruction::MeasureAll),
+                    (Some(qubits), Some(bits)) if qubits.len() == bits.len() => {
+                        if self.qubits.is_all(&qubits) && qubits == bits {
+                            out.push(QasmInstruction::MeasureAll);
+                        } else {
+                            out.extend(qubits.into_iter().zip(bits).map(|(q, c)| QasmInstruction::Measure(q, c)));
+                        }
+                    }
+                    _ => return Err(span.error("measure needs as many bits as qubits")),
+                }
+            }
+            StatementKind::Reset(operand) => match self.qubits.indices(&self.target(operand)?, operand.span, "qubit")? {
+                None => out.push(QasmInstruction::ResetAll),
+                Some(qubits) if is_register(operand) && self.qubits.is_all(&qubits) => out.push(QasmInstruction::ResetAll),
+                Some(qubits) => out.extend(qubits.into_iter().map(QasmInstruction::Reset)),
+            },
+            StatementKind::Barrier(operands) => {
+                let mut qubits = Vec::new();
+                for operand in operands {
+                    match self.qubits.indices(&self.target(operand)?, operand.span, "qubit")? {
+                        None => {
+                            out.push(QasmInstruction::BarrierAll);
+                            return Ok(());
+                        }
+                        Some(indices) => qubits.extend(indices),
+                    }
+                }
+                // whole registers that together cover every qubit, e.g. `barrier data, ancilla;`
+                if qubits.is_empty() || (operands.iter().all(is_register) && self.qubits.is_all(&qubits)) {
+                    out.push(QasmInstruction::BarrierAll);
+                } else {
+                    out.push(QasmInstruction::Barrier(qubits));
+                }
+            }
+            StatementKind::Delay { duration, unit, operands } => {
+                if operands.is_empty() {
+                    return Err(span.error("delay needs qubit operands"));
+                }
+                for operand in operands {
+                    let qubits = self
+                        .qubits
+                        .indices(&self.target(operand)?, operand.span, "qubit")?
+                        .ok_or_else(|| operand.span.error("declare the register to delay all of its qubits"))?;
+                    out.extend(qubits.into_iter().map(|q| QasmInstruction::Delay(*duration, unit.clone(), q)));
+                }
+            }
+            StatementKind::ClassicalDeclaration { name, value, .. } => {
+                self.check_new_name(name, span)?;
+                self.variables.push(name.clone());
+                match value.as_ref().map(|value| (value, &value.kind)) {
+                    Some((_, ExprKind::Call(callee, args))) if self.subroutines.contains_key(callee) => {
+                        self.call(callee, args, span, Some(ReturnTarget::Variable(name.clone())), out)?;
+                    }
+                    Some((value, _)) => out.push(QasmInstruction::Assign(name.clone(), self.classical(value)?)),
+                    None => {}
+                }
+            }
+            StatementKind::Assignment { target, op, value } => {
+                if let (None, ExprKind::Call(callee, args)) = (op, &value.kind)
+                    && self.subroutines.contains_key(callee)
+                {
+                    let target = match &target.target {
+                        OperandTarget::Register(name) if self.variables.contains(name) => ReturnTarget::Variable(name.clone()),
+                        _ => ReturnTarget::Bit(self.bits.index(&self.target(target)?, target.span, "bit")?),
+                    };
+                    return self.call(callee, args, span, Some(target), out);
+                }
+                let OperandTarget::Register(name) = &target.target else {
+                    return Err(target.span.error("only classical variables can be assigned a value"));
+                };
+                if !self.variables.contains(name) {
+                    return Err(span.error(format!("'{}' is not a classical variable", name)));
+                }
+                let mut value = self.classical(value)?;
+                if let Some(op) = op {
+                    let op = BinaryOp::from_symbol(op).ok_or_else(|| span.error(format!("unknown operator '{}='", op)))?;
+                    value = ClassicalExpr::var(name).binary(op, value);
+                }
+                out.push(QasmInstruction::Assign(name.clone(), value));
+            }
+            StatementKind::If { condition, then_body, else_body } => {
+                let condition = self.classical(condition)?;
+                let then_body = self.body(then_body)?;
+                out.push(match else_body {
+                    Some(else_body) => QasmInstruction::IfElse(condition, then_body, self.body(else_body)?),
+                    None => QasmInstruction::If(condition, then_body),
+                });
+            }
+            StatementKind::While { condition, body } => {
+                let condition = self.classical(condition)?;
+                out.push(QasmInstruction::While(condition, self.loop_body(body)?));
+            }
+            StatementKind::For { variable, values, body } => {
+                let values = self.loop_values(values)?;
+                let outer = self.loop_values.remove(variable);
+                if indexes_with(body, variable) {
+                    // qubits and bits are fixed before the program runs, so a loop that picks them
+                    // by its variable is unrolled
+                    if let Some(span) = find_loop_control(body) {
+                        return Err(span.error("a loop that picks qubits or bits by its variable cannot break or continue"));
+                    }
+                    for value in values.iter() {
+                        self.loop_values.insert(variable.clone(), value);
+                        self.block(body, out)?;
+                    }
+                    self.loop_values.remove(variable);
+                } else {
+                    self.loop_variables.push(variable.clone());
+                    let body = self.loop_body(body);
+                    self.loop_variables.pop();
+                    out.push(QasmInstruction::For(variable.clone(), values, body?));
+                }
+                if let Some(value) = outer {
+                    self.loop_values.insert(variable.clone(), value);
+                }
+            }
+            StatementKind::Switch { value, cases, default } => {
+                let value = self.classical(value)?;
+                let mut seen = Vec::new();
+                let mut lowered = Vec::new();
+                for (values, body) in cases {
+                    let mut labels = Vec::new();
+                    for expr in values {
+                        let label = self.integer(expr)?;
+                        if seen.contains(&label) {
+                            return Err(expr.span.error(format!("case {} is already listed", label)));
+                        }
+                        seen.push(label);
+                        labels.push(label);
+                    }
+                    lowered.push((labels, self.body(body)?));
+                }
+                let default = match default {
+                    Some(body) => self.body(body)?,
+                    None => Vec::new(),
+                };
+                out.push(QasmInstruction::Switch(value, lowered, default));
+            }
+            StatementKind::Break | StatementKind::Continue if self.loops == 0 => {
+                return Err(span.error("break and continue must be inside a loop"));
+            }
+            StatementKind::Break => out.push(QasmInstruction::Break),
+            StatementKind::Continue => out.push(QasmInstruction::Continue),
+            StatementKind::End => out.push(QasmInstruction::End),
+        }
+        Ok(())
+    }
+
+    fn loop_body(&mut self, statements: &[Statement]) -> Result<Vec<QasmInstruction>, QucomError> {
+        self.loops += 1;
+        let body = self.body(statements);
+        self.loops -= 1;
+        body
+    }
+
+    /// the values of a `for` loop, a range including its end
+    fn loop_values(&self, values: &LoopValues) -> Result<LoopRange, QucomError> {
+        match values {
+            LoopValues::Range { start, step, end } => {
+                let (start, end) = (self.integer(start)?, self.integer(end)?);
+                let step = match step {
+                    Some(step) if self.integer(step)? == 0 => return Err(step.span.error("a range step cannot be 0")),
+                    Some(step) => self.integer(step)?,
+                    None => 1,
+                };
+                Ok(LoopRange::Range { start, step, end })
+            }
+            LoopValues::Set(values) => Ok(LoopRange::Set(values.iter().map(|value| self.integer(value)).collect::<Result<_, _>>()?)),
+        }
+    }
+
+    /// lower the statements of an included file in place, reading each file once
+    fn include(&mut self, path: &str, span: Span, out: &mut Vec<QasmInstruction>) -> Result<(), QucomError> {
+        if self.included.iter().any(|p| p == path) {
+            return Ok(());
+        }
+        self.included.push(path.to_string());
+        let (source, library) = match include::bundled(path) {
+            Some(source) => (source.to_string(), true),
+            None => {
+                let source = self
+                    .resolver
+                    .and_then(|resolver| resolver.resolve(path))
+                    .ok_or_else(|| span.error(format!("cannot find include file '{}'", path)))?;
+                (source, false)
+            }
+        };
+        // errors inside the file point at the include, naming the position in the file
+        let in_file = |e: QucomError| match e {
+            QucomError::ParseError { line, column, message } => {
+                span.error(format!("in '{}' at {}:{}: {}", path, line, column, message))
+            }
+            other => other,
+        };
+        let program = QasmParser::parse_program_as(&source, self.qasm2 && !library).map_err(in_file)?;
+        let outer = std::mem::replace(&mut self.in_library, library);
+        let lowered = self.block(&program.statements, out).map_err(in_file);
+        self.in_library = outer;
+        lowered
+    }
+
+    /// expand a call of subroutine `name` in place, its `return` going to `target`
+    fn call(
+        &mut self,
+        name: &str,
+        args: &[Expr],
+        span: Span,
+        target: Option<ReturnTarget>,
+        out: &mut Vec<QasmInstruction>,
+    ) -> Result<(), QucomError> {
+        let subroutine = self.subroutines[name].clone();
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(span.error(format!("calls of '{}' nest too deeply; subroutines cannot recurse", name)));
+        }
+        if args.len() != subroutine.arguments.len() {
+            return Err(span.error(format!("'{}' takes {} arguments, got {}", name, subroutine.arguments.len(), args.len())));
+        }
+        if target.is_some() && subroutine.returns.is_none() {
+            return Err(span.error(format!("'{}' does not return a value", name)));
+        }
+
+        // the body sees its arguments, the program's consts, gates and subroutines, and nothing else
+        let mut callee = Lowering {
+            constants: self.constants.clone(),
+            gates: self.gates.clone(),
+            subroutines: self.subroutines.clone(),
+            returns: target,
+            depth: self.depth + 1,
+            resolver: self.resolver,
+            ..Lowering::default()
+        };
+        callee.qubits.aliased = true;
+        callee.bits.aliased = true;
+        for (argument, arg) in subroutine.arguments.iter().zip(args) {
+            match &argument.ty {
+                ArgumentType::Qubit(size) => {
+                    callee.qubits.alias(&argument.name, self.pass(arg, &self.qubits, "qubit", *size)?, *size);
+                }
+                ArgumentType::Bit(size) => {
+                    callee.bits.alias(&argument.name, self.pass(arg, &self.bits, "bit", *size)?, *size);
+                }
+                ArgumentType::Value(ty) => {
+                    let angle = self.expression(arg, &Scope::Program);
+                    let classical = self.classical(arg);
+                    let value = match ty.as_str() {
+                        "float" | "angle" => Value { angle: Some(angle?), classical: classical.ok() },
+                        _ => Value { classical: Some(classical?), angle: angle.ok() },
+                    };
+                    callee.arguments.insert(argument.name.clone(), value);
+                }
+            }
+        }
+        callee.block(&subroutine.body, out)
+    }
+
+    /// the first qubit or bit of a subroutine argument: a single one, or a whole register of `size`
+    fn pass(&self, arg: &Expr, registers: &Registers, kind: &str, size: Option<usize>) -> Result<usize, QucomError> {
+        let target = match &arg.kind {
+            ExprKind::Identifier(name) => Target::Register(name),
+            ExprKind::Index(name, index) => Target::Indexed(name, self.index(index)?),
+            _ => return Err(arg.span.error(format!("expected a {}", kind))),
+        };
+        match (size, registers.indices(&target, arg.span, kind)?) {
+            (None, Some(indices)) if indices.len() == 1 => Ok(indices[0]),
+            (Some(size), Some(indices)) if indices.len() == size => Ok(indices[0]),
+            (None, _) => Err(arg.span.error(format!("expected a single {}", kind))),
+            (Some(size), _) => Err(arg.span.error(format!("expected a register of {} {}s", size, kind))),
+        }
+    }
+
+    fn check_new_name(&self, name: &str, span: Span) -> Result<(), QucomError> {
+        if self.qubits.get(name).is_some() || self.bits.get(name).is_some() || self.inputs.iter().any(|i| i == name)
+            || self.constants.contains_key(name) || self.variables.contains(&name.to_string())
+        {
+            return Err(span.error(format!("'{}' is already declared", name)));
+        }
+        Ok(())
+    }
+
+    /// `operand` with its index evaluated
+    fn target<'a>(&self, operand: &'a Operand) -> Result<Target<'a>, QucomError> {
+        Ok(match &operand.target {
+            OperandTarget::Register(name) => Target::Register(name),
+            OperandTarget::Indexed(name, index) => Target::Indexed(name, self.index(index)?),
+            OperandTarget::Physical(index) => Target::Physical(*index),
+        })
+    }
+
+    /// a register index: an integer known before the program runs, e.g. `n - 1` or an unrolled `i`
+    fn index(&self, expr: &Expr) -> Result<usize, QucomError> {
+        self.expression(expr, &Scope::Program)?
+            .value()
+            .filter(|value| *value >= 0.0 && value.fract() == 0.0)
+            .map(|value| value as usize)
+            .ok_or_else(|| expr.span.error("an index must be a non-negative integer known before the program runs"))
+    }
+
+    /// an integer known before the program runs, e.g. a loop bound or a case label
+    fn integer(&self, expr: &Expr) -> Result<i64, QucomError> {
+        self.expression(expr, &Scope::Program)?
+            .value()
+            .filter(|value| value.fract() == 0.0)
+            .map(|value| value as i64)
+            .ok_or_else(|| expr.span.error("expected an integer known before the program runs"))
+    }
+
+    /// a condition or a value for a classical variable
+    fn classical(&self, expr: &Expr) -> Result<ClassicalExpr, QucomError> {
+        match &expr.kind {
+            ExprKind::Number(value) if value.fract() == 0.0 => Ok(ClassicalExpr::Int(*value as i64)),
+            ExprKind::Number(_) => Err(expr.span.error("classical values must be integers")),
+            ExprKind::Identifier(name) => {
+                if let Some(argument) = self.arguments.get(name) {
+                    return argument
+                        .classical
+                        .clone()
+                        .ok_or_else(|| expr.span.error(format!("'{}' is not an integer", name)));
+                }
+                if let Some(value) = self.loop_values.get(name) {
+                    return Ok(ClassicalExpr::Int(*value));
+                }
+                if self.variables.contains(name) || self.loop_variables.contains(name) {
+                    return Ok(ClassicalExpr::var(name));
+                }
+                match (name.as_str(), self.constants.get(name), self.bits.get(name)) {
+                    ("true", _, _) => Ok(ClassicalExpr::Int(1)),
+                    ("false", _, _) => Ok(ClassicalExpr::Int(0)),
+                    (_, Some(value), _) if value.fract() == 0.0 => Ok(ClassicalExpr::Int(*value as i64)),
+                    (_, Some(_), _) => Err(expr.span.error(format!("const '{}' is not an integer", name))),
+                    (_, None, Some((offset, Some(size)))) => Ok(ClassicalExpr::Register(offset, size)),
+                    (_, None, Some((offset, None))) => Ok(ClassicalExpr::Bit(offset)),
+                    _ => Err(expr.span.error(format!("unknown identifier '{}'", name))),
+                }
+            }
+            ExprKind::Index(name, index) => {
+                let bit = self.bits.index(&Target::Indexed(name, self.index(index)?), expr.span, "bit")?;
+                Ok(ClassicalExpr::Bit(bit))
+            }
+            ExprKind::Unary(op, operand) => {
+                let operand = self.classical(operand)?;
+                Ok(match *op {
+                    "-" => ClassicalExpr::Unary(UnaryOp::Neg, Box::new(operand)),
+                    "!" => ClassicalExpr::Unary(UnaryOp::Not, Box::new(operand)),
+                    "~" => ClassicalExpr::Unary(UnaryOp::BitNot, Box::new(operand)),
+                    _ => operand,
+                })
+            }
+            ExprKind::Binary(op, lhs, rhs) => {
+                let op = BinaryOp::from_symbol(op)
+                    .ok_or_else(|| expr.span.error(format!("'{}' is not a classical operator", op)))?;
+                Ok(self.classical(lhs)?.binary(op, self.classical(rhs)?))
+            }
+            ExprKind::Call(name, _) => Err(expr.span.error(format!("'{}' cannot be used in a classical expression", name))),
+        }
+    }
+
+    /// gate calls, barriers and unitaries on the gate's own qubits
+    fn gate_body_statement(&self, statement: &Statement, scope: &Scope) -> Result<Vec<QasmInstruction>, QucomError> {
+        match &statement.kind {
+            StatementKind::GateCall { .. } => self.gate_call(statement, scope),
+            StatementKind::Barrier(operands) => {
+                let qubits = operands.iter().map(|op| self.qubit(op, scope)).collect::<Result<Vec<_>, _>>()?;
+                Ok(vec![QasmInstruction::Barrier(qubits)])
+            }
+            StatementKind::Unitary { matrix, operands } => {
+                let qubits = operands.iter().map(|op| self.qubit(op, scope)).collect::<Result<Vec<_>, _>>()?;
+                Ok(vec![QasmInstruction::unitary(matrix.clone(), &qubits)?])
+            }
+            _ => Err(statement.span.error("a gate body may only contain gate calls and barriers")),
+        }
+    }
+
+    fn qubit(&self, operand: &Operand, scope: &Scope) -> Result<usize, QucomError> {
+        match (scope, &operand.target) {
+            (Scope::Program, _) => self.qubits.index(&self.target(operand)?, operand.span, "qubit"),
+            (Scope::Gate { qubits, .. }, OperandTarget::Register(name)) => qubits
+                .iter()
+                .position(|q| q == name)
+                .ok_or_else(|| operand.span.error(format!("'{}' is not a qubit of this gate", name))),
+            (Scope::Gate { .. }, _) => Err(operand.span.error("a gate body can only use the gate's qubit arguments")),
+        }
+    }
+
+    /// the qubits of a gate operand: one, or every qubit of a register it names
+    fn operand_qubits(&self, operand: &Operand, scope: &Scope) -> Result<Vec<usize>, QucomError> {
+        if let (Scope::Program, OperandTarget::Register(_)) = (scope, &operand.target)
+            && let Some(qubits) = self.qubits.indices(&self.target(operand)?, operand.span, "qubit")?
+        {
+            return Ok(qubits);
+        }
+        Ok(vec![self.qubit(operand, scope)?])
+    }
+
+    /// a gate call, broadcast over register operands: `h q;` is `h q[0]; h q[1]; ...` and `cx a, b;`
+    /// pairs the registers index by index, repeating single-qubit operands
+    fn gate_call(&self, statement: &Statement, scope: &Scope) -> Result<Vec<QasmInstruction>, QucomError> {
+        let StatementKind::GateCall { operands, .. } = &statement.kind else {
+            unreachable!("gate_call() is only called on gate calls")
+        };
+        let lists = operands.iter().map(|op| self.operand_qubits(op, scope)).collect::<Result<Vec<_>, _>>()?;
+        let width = lists.iter().map(Vec::len).find(|&len| len != 1).unwrap_or(1);
+        if let Some((operand, _)) = operands.iter().zip(&lists).find(|(_, qubits)| qubits.len() != 1 && qubits.len() != width) {
+            return Err(operand.span.error(format!("registers of a broadcast gate must all have {} qubits", width)));
+        }
+        let mut gates = Vec::new();
+        for i in 0..width {
+            let qubits: Vec<usize> = lists.iter().map(|qubits| if qubits.len() == 1 { qubits[0] } else { qubits[i] }).collect();
+            gates.extend(self.gate(statement, &qubits, scope)?);
+        }
+        Ok(gates)
+    }
+
+    /// `ctrl @ inv @ name(args) operands;` on `qubits`, the modifiers taking their controls from the
+    /// front of the operands in order and applying from the innermost (rightmost) out
+    fn gate(&self, statement: &Statement, qubits: &[usize], scope: &Scope) -> Result<Vec<QasmInstruction>, QucomError> {
+        let StatementKind::GateCall { modifiers, name, name_span, args, operands } = &statement.kind else {
+            unreachable!("gate() is only called on gate calls")
+        };
+        let n_controls: usize = modifiers
+            .iter()
+            .map(|modifier| match modifier {
+                Modifier::Ctrl { count, .. } => *count,
+                Modifier::Inv | Modifier::Pow(_) => 0,
+            })
+            .sum();
+        if operands.len() < n_controls {
+            return Err(statement.span.error(format!(
+                "{} control qubits needed, only {} operands given", n_controls, operands.len()
+            )));
+        }
+
+        let (controls, targets) = qubits.split_at(n_controls);
+        let angles = args.iter().map(|arg| self.expression(arg, scope)).collect::<Result<Vec<_>, _>>()?;
+
+        let (gate, n_angles, n_qubits) = match builtin(name, targets) {
+            Some(builtin) => builtin,
+            None => {
+                let definition = self.gates.get(name).ok_or_else(|| {
+                    if self.opaque.contains(name) {
+                        name_span.error(format!("opaque gate '{}' has no definition to simulate", name))
+                    } else {
+                        name_span.error(format!("unknown gate '{}'", name))
+                    }
+                })?;
+                let n_angles = definition.parameters().len();
+                let call = QasmInstruction::GateCall(definition.clone(), vec![0.0; n_angles], targets.to_vec());
+                (call, n_angles, definition.n_qubits())
+            }
+        };
+        if angles.len() != n_angles {
+            return Err(name_span.error(format!("'{}' takes {} angles, got {}", name, n_angles, angles.len())));
+        }
+        if targets.len() != n_qubits {
+            return Err(name_span.error(format!("'{}' acts on {} qubits, got {}", name, n_qubits, targets.len())));
+        }
+
+        let mut gates = vec![QasmInstruction::parameterized(gate, angles)];
+        let mut controls = controls;
+        for modifier in modifiers.iter().rev() {
+            let modified: Result<Vec<QasmInstruction>, QucomError> = match modifier {
+                Modifier::Ctrl { count, negated } => {
+                    // the innermost modifier takes the last of the control operands
+                    let (outer, own) = controls.split_at(controls.len() - count);
+                    controls = outer;
+                    gates.into_iter().map(|gate| QasmInstruction::controlled(gate, own, &vec![!negated; *count])).collect()
+                }
+                Modifier::Inv => gates.iter().rev().map(QasmInstruction::inverse).collect(),
+                Modifier::Pow(exponent) => {
+                    let k = self
+                        .expression(exponent, scope)?
+                        .value()
+                        .ok_or_else(|| exponent.span.error("the exponent of 'pow' must not depend on parameters"))?;
+                    power(gates, k)
+                }
+            };
+            gates = modified.map_err(|e| statement.span.error(e.to_string()))?;
+        }
+        Ok(gates)
+    }
+
+    /// an angle: numbers, pi/tau/euler (or π/τ/ℇ), consts, parameters in scope, + - * / % **,
+    /// math functions and parentheses. the result must stay linear in the parameters
+    fn expression(&self, expr: &Expr, scope: &Scope) -> Result<ParameterExpression, QucomError> {
+        match &expr.kind {
+            ExprKind::Number(value) => Ok(ParameterExpression::constant(*value)),
+            ExprKind::Identifier(name) => match constant(name) {
+                Some(value) => Ok(ParameterExpression::constant(value)),
+                None if self.arguments.contains_key(name) => self.arguments[name]
+                    .angle
+                    .clone()
+                    .ok_or_else(|| expr.span.error(format!("'{}' cannot be used in an angle", name))),
+                None if self.constants.contains_key(name) => Ok(ParameterExpression::constant(self.constants[name])),
+                None => match scope {
+                    Scope::Program if self.loop_values.contains_key(name) => {
+                        Ok(ParameterExpression::constant(self.loop_values[name] as f64))
+                    }
+                    // inputs are bound before the run, classical variables as it reaches the gate
+                    Scope::Program if self.inputs.contains(name) || self.variables.contains(name)
+                        || self.loop_variables.contains(name) => Ok(Parameter::named(name).into()),
+                    Scope::Gate { params, .. } if params.contains(name) => Ok(Parameter::named(name).into()),
+                    _ => Err(expr.span.error(format!("unknown identifier '{}'", name))),
+                },
+            },
+            ExprKind::Index(..) => Err(expr.span.error("bits cannot be used in an angle")),
+            ExprKind::Unary("-", operand) => Ok(-self.expression(operand, scope)?),
+            ExprKind::Unary("+", operand) => self.expression(operand, scope),
+            ExprKind::Unary(op, _) => Err(expr.span.error(format!("'{}' cannot be used in an angle", op))),
+            ExprKind::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.expression(lhs, scope)?, self.expression(rhs, scope)?);
+                match (*op, lhs.value(), rhs.value()) {
+                    ("+", _, _) => Ok(lhs + rhs),
+                    ("-", _, _) => Ok(lhs - rhs),
+                    ("*", Some(c), _) => Ok(rhs * c),
+                    ("*", _, Some(c)) => Ok(lhs * c),
+                    ("/", _, Some(c)) if c != 0.0 => Ok(lhs / c),
+                    ("/", _, Some(_)) => Err(expr.span.error("division by zero")),
+                    ("%", Some(a), Some(b)) if b != 0.0 => Ok(ParameterExpression::constant(a % b)),
+                    ("%", Some(_), Some(_)) => Err(expr.span.error("modulo by zero")),
+                    ("**", Some(a), Some(b)) => finite(a.powf(b), expr.span, "**"),
+                    ("*" | "/" | "%" | "**", _, _) => {
+                        // parameter * parameter or division by a parameter is not linear
+                        Err(expr.span.error("angle expressions must be linear in the parameters"))
+                    }
+                    _ => Err(expr.span.error(format!("'{}' cannot be used in an angle", op))),
+                }
+            }
+            ExprKind::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| {
+                        self.expression(arg, scope)?
+                            .value()
+                            .ok_or_else(|| arg.span.error(format!("the argument of '{}' must not depend on parameters", name)))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let value = match (name.as_str(), args.as_slice()) {
+                    ("sin", [x]) => x.sin(),
+                    ("cos", [x]) => x.cos(),
+                    ("tan", [x]) => x.tan(),
+                    ("arcsin", [x]) => x.asin(),
+                    ("arccos", [x]) => x.acos(),
+                    ("arctan", [x]) => x.atan(),
+                    ("exp", [x]) => x.exp(),
+                    ("ln" | "log", [x]) => x.ln(),
+                    ("sqrt", [x]) => x.sqrt(),
+                    ("floor", [x]) => x.floor(),
+                    ("ceiling", [x]) => x.ceil(),
+                    ("pow", [x, y]) => x.powf(*y),
+                    ("mod", [x, y]) if *y != 0.0 => x % y,
+                    ("sin" | "cos" | "tan" | "arcsin" | "arccos" | "arctan" | "exp" | "ln" | "log" | "sqrt" | "floor"
+                    | "ceiling", _) => {
+                        return Err(expr.span.error(format!("'{}' takes 1 argument, got {}", name, args.len())));
+                    }
+                    ("mod", [_, _]) => return Err(expr.span.error("modulo by zero")),
+                    ("pow" | "mod", _) => {
+                        return Err(expr.span.error(format!("'{}' takes 2 arguments, got {}", name, args.len())));
+                    }
+                    _ => return Err(expr.span.error(format!("unknown function '{}'", name))),
+                };
+                finite(value, expr.span, name)
+            }
+        }
+    }
+}
+
+/// whether `statements` pick a qubit or bit by `variable`, as in `h q[i];` or `if (c[i] == 1)`,
+/// or need its value to lower at all, as in `for int j in [0:i]` or `case i`
+fn indexes_with(statements: &[Statement], variable: &str) -> bool {
+    let operand = |op: &Operand| matches!(&op.target, OperandTarget::Indexed(_, index) if mentions(index, variable));
+    let expr = |e: &Expr| indexes_in(e, variable);
+    statements.iter().any(|statement| match &statement.kind {
+        StatementKind::GateCall { args, operands, .. } => args.iter().any(expr) || operands.iter().any(operand),
+        StatementKind::Barrier(operands) | StatementKind::Delay { operands, .. } | StatementKind::Unitary { operands, .. } => operands.iter().any(operand),
+        StatementKind::Measure { qubit, target } => operand(qubit) || target.as_ref().is_some_and(operand),
+        StatementKind::Reset(op) => operand(op),
+        StatementKind::If { condition, then_body, else_body } => {
+            expr(condition) || indexes_with(then_body, variable)
+                || else_body.as_ref().is_some_and(|body| indexes_with(body, variable))
+        }
+        StatementKind::While { condition, body } => expr(condition) || indexes_with(body, variable),
+        // a nested range or case label must be known before the run too, so one that uses the
+        // variable needs it unrolled
+        StatementKind::For { variable: inner, values, body } => {
+            let bounds = match values {
+                LoopValues::Range { start, step, end } => {
+                    [Some(start), step.as_ref(), Some(end)].into_iter().flatten().any(|e| mentions(e, variable))
+                }
+                LoopValues::Set(values) => values.iter().any(|e| mentions(e, variable)),
+            };
+            bounds || (inner != variable && indexes_with(body, variable))
+        }
+        StatementKind::Switch { value, cases, default } => {
+            expr(value)
+                || cases.iter().any(|(labels, body)| labels.iter().any(|e| mentions(e, variable)) || indexes_with(body, variable))
+                || default.as_ref().is_some_and(|body| indexes_with(body, variable))
+        }
+        StatementKind::Assignment { target, value, .. } => operand(target) || expr(value),
+        StatementKind::ClassicalDeclaration { value: Some(value), .. } => expr(value),
+        _ => false,
+    })
+}
+
+/// whether `expr` indexes a register by `variable`
+fn indexes_in(expr: &Expr, variable: &str) -> bool {
+    match &expr.kind {
+        ExprKind::Index(_, index) => mentions(index, variable),
+        ExprKind::Unary(_, operand) => indexes_in(operand, variable),
+        ExprKind::Binary(_, lhs, rhs) => indexes_in(lhs, variable) || indexes_in(rhs, variable),
+        ExprKind::Call(_, args) => args.iter().any(|arg| indexes_in(arg, variable)),
+        ExprKind::Number(_) | ExprKind::Identifier(_) => false,
+    }
+}
+
+fn mentions(expr: &Expr, variable: &str) -> bool {
+    match &expr.kind {
+        ExprKind::Identifier(name) => name == variable,
+        ExprKind::Index(_, index) | ExprKind::Unary(_, index) => mentions(index, variable),
+        ExprKind::Binary(_, lhs, rhs) => mentions(lhs, variable) || mentions(rhs, variable),
+        ExprKind::Call(_, args) => args.iter().any(|arg| mentions(arg, variable)),
+        ExprKind::Number(_) => false,
+    }
+}
+
+/// a single gate raised to any power, or the gate sequence `gates` repeated `k` times
+fn power(gates: Vec<QasmInstruction>, k: f64) -> Result<Vec<QasmInstruction>, QucomError> {
+    match gates.as_slice() {
+        [gate] => gate.power(k),
+        _ if k.abs() > MAX_POWER_COPIES as f64 => Err(QucomError::Unsupported(format!(
+            "raising a sequence of gates to {} would repeat it more than {} times", k, MAX_POWER_COPIES
+        ))),
+        _ if k.fract() == 0.0 => {
+            let gates = match k < 0.0 {
+                true => gates.iter().rev().map(QasmInstruction::inverse).collect::<Result<Vec<_>, _>>()?,
+                false => gates,
+            };
+            Ok(gates.iter().cycle().take(gates.len() * k.abs() as usize).cloned().collect())
+        }
+        _ => Err(QucomError::Unsupported("a fractional power needs a single rotation or phase gate".to_string())),
+    }
+}
+
+/// a `return` that is not the last statement of a subroutine body
+fn misplaced_return(body: &[Statement]) -> Option<Span> {
+    body.iter().enumerate().find_map(|(i, statement)| match &statement.kind {
+        StatementKind::Return(_) | StatementKind::ReturnMeasure(_) => (i + 1 < body.len()).then_some(statement.span),
+        _ => find_return(std::slice::from_ref(statement)),
+    })
+}
+
+/// the first `return` anywhere in `statements`, nested blocks included
+fn find_return(statements: &[Statement]) -> Option<Span> {
+    statements.iter().find_map(|statement| match &statement.kind {
+        StatementKind::Return(_) | StatementKind::ReturnMeasure(_) => Some(statement.span),
+        StatementKind::If { then_body, else_body, .. } => {
+            find_return(then_body).or_else(|| else_body.as_deref().and_then(find_return))
+        }
+        StatementKind::While { body, .. } | StatementKind::For { body, .. } => find_return(body),
+        StatementKind::Switch { cases, default, .. } => cases
+            .iter()
+            .find_map(|(_, body)| find_return(body))
+            .or_else(|| default.as_deref().and_then(find_return)),
+        _ => None,
+    })
+}
+
+/// the first `break` or `continue` in `statements` that belongs to the enclosing loop, not to a nested one
+fn find_loop_control(statements: &[Statement]) -> Option<Span> {
+    statements.iter().find_map(|statement| match &statement.kind {
+        StatementKind::Break | StatementKind::Continue => Some(statement.span),
+        StatementKind::If { then_body, else_body, .. } => {
+            find_loop_control(then_body).or_else(|| else_body.as_deref().and_then(find_loop_control))
+        }
+        StatementKind::Switch { cases, default, .. } => cases
+            .iter()
+            .find_map(|(_, body)| find_loop_control(body))
+            .or_else(|| default.as_deref().and_then(find_loop_control)),
+        _ => None,
+    })
+}
+
+fn is_register(operand: &Operand) -> bool {
+    matches!(operand.target, OperandTarget::Register(_))
+}
+
+/// `value` as a constant, or an error if `operation` left the real numbers (e.g. `sqrt(-1)`)
+fn finite(value: f64, span: Span, operation: &str) -> Result<ParameterExpression, QucomError> {
+    if value.is_finite() {
+        Ok(ParameterExpression::constant(value))
+    } else {
+        Err(span.error(format!("'{}' is undefined for these arguments", operation)))
+    }
+}
+
+/// a built-in gate on `qubits` with zero angles, with its number of angles and qubits;
+/// `None` if `name` is not built in
+/// whether `name` calls a built-in gate, under any of its aliases
+pub(crate) fn is_builtin(name: &str) -> bool {
+    builtin(name, &[]).is_some()
+}
+
+/// the value of a built-in constant such as `pi`
+pub(crate) fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" | "π" => Some(std::f64::consts::PI),
+        "tau" | "τ" => Some(std::f64::consts::TAU),
+        "euler" | "ℇ" => Some(std::f64::consts::E),
+        _ => None,
+    }
+}
+
+fn builtin(name: &str, qubits: &[usize]) -> Option<(QasmInstruction, usize, usize)> {
+    let q = |i: usize| qubits.get(i).copied().unwrap_or(0);
+    let gate = match name {
+        "h" => (QasmInstruction::H(q(0)), 0, 1),
+        "x" => (QasmInstruction::X(q(0)), 0, 1),
+        "y" => (QasmInstruction::Y(q(0)), 0, 1),
+        "z" => (QasmInstruction::Z(q(0)), 0, 1),
+        "s" | "sdg" => (QasmInstruction::S(q(0), name == "sdg"), 0, 1),
+        "t" | "tdg" => (QasmInstruction::T(q(0), name == "tdg"), 0, 1),
+        "cx" | "CX" | "cnot" => (QasmInstruction::CX(q(0), q(1)), 0, 2),
+        "cz" => (QasmInstruction::CZ(q(0), q(1)), 0, 2),
+        "ccx" | "toffoli" => (QasmInstruction::CCX(vec![q(0), q(1), q(2)]), 0, 3),
+        "swap" => (QasmInstruction::Swap(q(0), q(1)), 0, 2),
+        "rx" => (QasmInstruction::Rx(0.0, q(0)), 1, 1),
+        "ry" => (QasmInstruction::Ry(0.0, q(0)), 1, 1),
+        "rz" => (QasmInstruction::Rz(0.0, q(0)), 1, 1),
+        "p" | "phase" => (QasmInstruction::Phase(0.0, q(0)), 1, 1),
+        "u" | "u3" | "U" => (QasmInstruction::U(0.0, 0.0, 0.0, q(0)), 3, 1),
+        _ => return None,
+    };
+    Some(gate)
+}
@@ -1,7 +1,17 @@
 pub mod generator;
 pub mod executor;
+pub mod lexer;
+pub mod ast;
 pub mod parser;
+pub(crate) mod lower;
+pub mod definition;
+pub mod classical;
+pub mod include;
+pub(crate) mod qasm2;
 
 pub use generator::QasmGenerator;
-pub use executor::QasmExecutor;
+pub use executor::{QasmExecutor, DEFAULT_ITERATION_LIMIT};
 pub use parser::QasmParser;
+pub use definition::GateDefinition;
+pub use classical::ClassicalExpr;
+pub use include::{FileResolver, IncludeResolver};
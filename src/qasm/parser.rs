@@ -1,550 +1,787 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::error::QucomError;
+use crate::qasm::ast::{Argument, ArgumentType, Expr, ExprKind, LoopValues, Modifier, Operand, OperandTarget, Program, Span, Statement, StatementKind};
 use crate::qasm::generator::QasmInstruction;
+use crate::qasm::lexer::{Lexer, Token, TokenKind};
+use crate::qasm::include::IncludeResolver;
+use crate::qasm::lower;
 
-/// parse QASM 3 string into executable instructions
+/// parse QASM 3, or QASM 2 under an `OPENQASM 2.0;` header, into executable instructions
 pub struct QasmParser;
 
 impl QasmParser {
     /// parse a QASM 3 program string and return instructions
-    pub fn parse(qasm_string: &str) -> Result<Vec<QasmInstruction>, String> {
-        let mut instructions = Vec::new();
-        let lines: Vec<&str> = qasm_string.lines().collect();
-        let mut i = 0;
-        
-        while i < lines.len() {
-            let trimmed = lines[i].trim();
-            
-            // skip empty lines and comments
-            if trimmed.is_empty() || trimmed.starts_with("//") {
-                i += 1;
-                continue;
-            }
-            
-            // skip version and include statements
-            if trimmed.starts_with("OPENQASM") || trimmed.starts_with("include") {
-                i += 1;
-                continue;
-            }
-            
-            // skip register declarations
-            if trimmed.starts_with("qubit") || trimmed.starts_with("bit") 
-                || trimmed.starts_with("qreg") || trimmed.starts_with("creg") {
-                i += 1;
-                continue;
-            }
-            
-            // handle control flow blocks
-            if trimmed.starts_with("if") {
-                let (instr, lines_consumed) = Self::parse_if_block(&lines[i..])?;
-                instructions.push(instr);
-                i += lines_consumed;
-                continue;
-            }
-            
-            if trimmed.starts_with("while") {
-                let (instr, lines_consumed) = Self::parse_while_block(&lines[i..])?;
-                instructions.push(instr);
-                i += lines_consumed;
-                continue;
-            }
-            
-            if trimmed.starts_with("for") {
-                let (instr, lines_consumed) = Self::parse_for_block(&lines[i..])?;
-                instructions.push(instr);
-                i += lines_consumed;
-                continue;
-            }
-            
-            // parse gate instruction
-            if let Some(instr) = Self::parse_instruction(trimmed) {
-                instructions.push(instr);
-            } else if !trimmed.is_empty() {
-                return Err(format!("failed to parse line {}: {}", i + 1, trimmed));
-            }
-            
-            i += 1;
+    ///
+    /// errors carry the 1-based line and column of the token that failed
+    pub fn parse(qasm_string: &str) -> Result<Vec<QasmInstruction>, QucomError> {
+        lower::lower(&Self::parse_program(qasm_string)?, None)
+    }
+
+    /// like `parse`, reading `include`s other than the bundled `stdgates.inc` and `qelib1.inc` through `resolver`
+    pub fn parse_with_includes(qasm_string: &str, resolver: &dyn IncludeResolver) -> Result<Vec<QasmInstruction>, QucomError> {
+        lower::lower(&Self::parse_program(qasm_string)?, Some(resolver))
+    }
+
+    /// the program's syntax tree, with register and gate names still unresolved
+    ///
+    /// an `OPENQASM 2.x;` header selects the OpenQASM 2.0 rules
+    pub fn parse_program(qasm_string: &str) -> Result<Program, QucomError> {
+        Parser::new(Lexer::new(qasm_string).tokenize()?, false).program()
+    }
+
+    /// like `parse_program`, with `qasm2` choosing the rules for a file without a version header
+    pub(crate) fn parse_program_as(qasm_string: &str, qasm2: bool) -> Result<Program, QucomError> {
+        Parser::new(Lexer::new(qasm_string).tokenize()?, qasm2).program()
+    }
+}
+
+/// binary operators from loosest to tightest binding; `**` binds tighter still, see `power`
+const PRECEDENCE: &[&[&str]] = &[
+    &["||"], &["&&"], &["|"], &["^"], &["&"], &["==", "!="], &["<", "<=", ">", ">="], &["<<", ">>"], &["+", "-"],
+    &["*", "/", "%"],
+];
+
+/// `name op= value;` assignments
+const COMPOUND_ASSIGNMENTS: &[&str] = &["+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>="];
+
+/// statements OpenQASM 2.0 does not have; its programs may still use these words as gate names
+const QASM3_ONLY: &[&str] = &[
+    "qubit", "bit", "input", "const", "int", "uint", "bool", "def", "return", "delay", "while", "for", "ctrl",
+    "negctrl", "inv", "pow", "switch", "break", "continue",
+];
+
+/// words that start a statement, modify a call or name a type, so a gate or parameter cannot
+/// take them and still read back the same
+pub(crate) const KEYWORDS: &[&str] = &[
+    "OPENQASM", "include", "qubit", "bit", "qreg", "creg", "input", "output", "const", "let", "int", "uint", "float",
+    "angle", "bool", "complex", "duration", "stretch", "array", "gate", "opaque", "def", "extern", "return",
+    "measure", "reset", "barrier", "delay", "if", "else", "while", "for", "in", "switch", "case", "default",
+    "break", "continue", "end", "ctrl", "negctrl", "inv", "pow",
+];
+
+/// deepest nesting of blocks, parentheses and prefix operators a program may use, so that the
+/// recursive descent fails with an error well before it runs out of stack
+const MAX_NESTING: usize = 64;
+
+/// recursive-descent parser over a token list ending in `Eof`
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// follow the OpenQASM 2.0 grammar
+    qasm2: bool,
+    /// blocks and expressions currently open
+    depth: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>, qasm2: bool) -> Self {
+        Self { tokens, pos: 0, qasm2, depth: 0 }
+    }
+
+    /// `parse` one level of nesting deeper
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, QucomError>) -> Result<T, QucomError> {
+        if self.depth == MAX_NESTING {
+            return Err(self.peek().span.error(format!("nesting is limited to {} levels", MAX_NESTING)));
         }
-        
-        Ok(instructions)
-    }
-    
-    fn parse_instruction(line: &str) -> Option<QasmInstruction> {
-        let line = line.trim_end_matches(';').trim();
-        
-        // split by whitespace but handle parentheses
-        let gate_end = line.find('(').or_else(|| line.find(' ')).unwrap_or(line.len());
-        let gate = &line[..gate_end];
-        let args = line[gate_end..].trim();
-        
-        match gate {
-            "h" => {
-                let qubit = Self::extract_qubit_index(args)?;
-                Some(QasmInstruction::H(qubit))
-            }
-            "x" => {
-                let qubit = Self::extract_qubit_index(args)?;
-                Some(QasmInstruction::X(qubit))
-            }
-            "y" => {
-                let qubit = Self::extract_qubit_index(args)?;
-                Some(QasmInstruction::Y(qubit))
-            }
-            "z" => {
-                let qubit = Self::extract_qubit_index(args)?;
-                Some(QasmInstruction::Z(qubit))
-            }
-            "s" | "sdg" => {
-                let qubit = Self::extract_qubit_index(args)?;
-                let is_dagger = gate == "sdg";
-                Some(QasmInstruction::S(qubit, is_dagger))
-            }
-            "t" | "tdg" => {
-                let qubit = Self::extract_qubit_index(args)?;
-                let is_dagger = gate == "tdg";
-                Some(QasmInstruction::T(qubit, is_dagger))
-            }
-            "cx" | "cnot" => {
-                let indices = Self::extract_two_qubit_indices(args)?;
-                Some(QasmInstruction::CX(indices.0, indices.1))
-            }
-            "cz" => {
-                let indices = Self::extract_two_qubit_indices(args)?;
-                Some(QasmInstruction::CZ(indices.0, indices.1))
-            }
-            "ccx" | "toffoli" => {
-                let indices = Self::extract_three_qubit_indices(args)?;
-                Some(QasmInstruction::CCX(vec![indices.0, indices.1, indices.2]))
-            }
-            "swap" => {
-                let indices = Self::extract_two_qubit_indices(args)?;
-                Some(QasmInstruction::Swap(indices.0, indices.1))
-            }
-            "rx" => {
-                let (angle, qubit) = Self::extract_parameterized_gate(args)?;
-                Some(QasmInstruction::Rx(angle, qubit))
-            }
-            "ry" => {
-                let (angle, qubit) = Self::extract_parameterized_gate(args)?;
-                Some(QasmInstruction::Ry(angle, qubit))
-            }
-            "rz" => {
-                let (angle, qubit) = Self::extract_parameterized_gate(args)?;
-                Some(QasmInstruction::Rz(angle, qubit))
-            }
-            "p" | "phase" => {
-                let (angle, qubit) = Self::extract_parameterized_gate(args)?;
-                Some(QasmInstruction::Phase(angle, qubit))
-            }
-            "u" | "u3" => {
-                let (params, qubit) = Self::extract_u_gate(args)?;
-                Some(QasmInstruction::U(params.0, params.1, params.2, qubit))
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn peek_at(&self, ahead: usize) -> &TokenKind {
+        &self.tokens[(self.pos + ahead).min(self.tokens.len() - 1)].kind
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn at_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek().kind, TokenKind::Symbol(s) if s == symbol)
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Identifier(name) if name == keyword)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.at_symbol(symbol);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    /// error at the next token, naming what was found there
+    fn unexpected(&self, expected: &str) -> QucomError {
+        let found = match &self.peek().kind {
+            TokenKind::Identifier(name) => format!("'{}'", name),
+            TokenKind::Integer(value) => value.to_string(),
+            TokenKind::Float(value) => value.to_string(),
+            TokenKind::Duration(value, unit) => format!("{}{}", value, unit),
+            TokenKind::String(text) => format!("\"{}\"", text),
+            TokenKind::PhysicalQubit(index) => format!("${}", index),
+            TokenKind::Pragma(_) => "#pragma".to_string(),
+            TokenKind::Symbol(symbol) => format!("'{}'", symbol),
+            TokenKind::Eof => "end of input".to_string(),
+        };
+        self.peek().span.error(format!("expected {}, found {}", expected, found))
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<Span, QucomError> {
+        if self.at_symbol(symbol) {
+            Ok(self.advance().span)
+        } else {
+            Err(self.unexpected(&format!("'{}'", symbol)))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<Span, QucomError> {
+        if self.at_keyword(keyword) {
+            Ok(self.advance().span)
+        } else {
+            Err(self.unexpected(&format!("'{}'", keyword)))
+        }
+    }
+
+    fn expect_identifier(&mut self) -> Result<(String, Span), QucomError> {
+        match self.peek().kind.clone() {
+            TokenKind::Identifier(name) => Ok((name, self.advance().span)),
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    fn expect_integer(&mut self) -> Result<usize, QucomError> {
+        match self.peek().kind {
+            TokenKind::Integer(value) => {
+                self.advance();
+                Ok(value)
             }
-            "reset" => {
-                let args = args.trim();
-                if args.is_empty() || args == "q" || args == "q;" {
-                    Some(QasmInstruction::ResetAll)
+            _ => Err(self.unexpected("an integer")),
+        }
+    }
+
+    fn program(mut self) -> Result<Program, QucomError> {
+        let mut version = None;
+        if self.at_keyword("OPENQASM") {
+            self.advance();
+            version = Some(match self.peek().kind {
+                TokenKind::Integer(value) => value.to_string(),
+                TokenKind::Float(value) => format!("{:?}", value),
+                _ => return Err(self.unexpected("a version number")),
+            });
+            self.advance();
+            self.expect_symbol(";")?;
+            self.qasm2 = version.as_deref().is_some_and(|v| v.starts_with('2'));
+        }
+
+        let mut statements = Vec::new();
+        while self.peek().kind != TokenKind::Eof {
+            statements.push(self.statement()?);
+        }
+        Ok(Program { version, statements })
+    }
+
+    fn statement(&mut self) -> Result<Statement, QucomError> {
+        let span = self.peek().span;
+        let keyword = match &self.peek().kind {
+            TokenKind::Identifier(name) => name.clone(),
+            TokenKind::Pragma(_) => return self.pragma(),
+            _ => return Err(self.unexpected("a statement")),
+        };
+
+        let kind = match keyword.as_str() {
+            _ if self.qasm2 && QASM3_ONLY.contains(&keyword.as_str()) => {
+                return Err(span.error(format!("'{}' is not part of OpenQASM 2.0", keyword)));
+            }
+            "include" => {
+                self.advance();
+                let TokenKind::String(path) = self.peek().kind.clone() else {
+                    return Err(self.unexpected("a file name in quotes"));
+                };
+                self.advance();
+                self.expect_symbol(";")?;
+                StatementKind::Include(path)
+            }
+            "qubit" | "bit" => {
+                self.advance();
+                let size = self.designator()?;
+                let (name, _) = self.expect_identifier()?;
+                self.expect_symbol(";")?;
+                if keyword == "qubit" {
+                    StatementKind::QubitDeclaration { name, size }
                 } else {
-                    let qubit = Self::extract_qubit_index(args)?;
-                    Some(QasmInstruction::Reset(qubit))
+                    StatementKind::BitDeclaration { name, size }
                 }
             }
-            "barrier" => {
-                let args = args.trim();
-                if args.is_empty() || args == "q" || args == "q;" {
-                    Some(QasmInstruction::BarrierAll)
+            "qreg" | "creg" => {
+                self.advance();
+                let (name, _) = self.expect_identifier()?;
+                let size = self.designator()?;
+                self.expect_symbol(";")?;
+                if keyword == "qreg" {
+                    StatementKind::QubitDeclaration { name, size }
                 } else {
-                    let qubits = Self::extract_qubit_list(args)?;
-                    Some(QasmInstruction::Barrier(qubits))
+                    StatementKind::BitDeclaration { name, size }
                 }
             }
-            "delay" => {
-                let (duration, unit, qubit) = Self::extract_delay(args)?;
-                Some(QasmInstruction::Delay(duration, unit, qubit))
+            "input" => {
+                self.advance();
+                let ty = self.type_name()?;
+                let (name, _) = self.expect_identifier()?;
+                self.expect_symbol(";")?;
+                StatementKind::Input { ty, name }
+            }
+            "const" => {
+                self.advance();
+                let ty = self.type_name()?;
+                let (name, _) = self.expect_identifier()?;
+                self.expect_symbol("=")?;
+                let value = self.expression()?;
+                self.expect_symbol(";")?;
+                StatementKind::Const { ty, name, value }
+            }
+            "int" | "uint" | "bool" => {
+                self.advance();
+                let ty = match self.designator()? {
+                    Some(width) => format!("{}[{}]", keyword, width),
+                    None => keyword.clone(),
+                };
+                let (name, _) = self.expect_identifier()?;
+                let value = if self.eat_symbol("=") { Some(self.expression()?) } else { None };
+                self.expect_symbol(";")?;
+                StatementKind::ClassicalDeclaration { ty, name, value }
+            }
+            "gate" => self.gate_definition()?,
+            "opaque" => {
+                self.advance();
+                let (name, params, qubits) = self.gate_signature()?;
+                self.expect_symbol(";")?;
+                StatementKind::Opaque { name, params, qubits }
+            }
+            "def" => self.subroutine()?,
+            "return" => {
+                self.advance();
+                let kind = if self.at_symbol(";") {
+                    StatementKind::Return(None)
+                } else if self.at_keyword("measure") {
+                    self.advance();
+                    StatementKind::ReturnMeasure(self.operand()?)
+                } else {
+                    StatementKind::Return(Some(self.expression()?))
+                };
+                self.expect_symbol(";")?;
+                kind
             }
             "measure" => {
-                Self::parse_measurement_from_args(line)
+                self.advance();
+                let qubit = self.operand()?;
+                let target = if self.eat_symbol("->") { Some(self.operand()?) } else { None };
+                self.expect_symbol(";")?;
+                StatementKind::Measure { qubit, target }
             }
-            _ => None,
-        }
+            "reset" => {
+                self.advance();
+                let operand = self.operand()?;
+                self.expect_symbol(";")?;
+                StatementKind::Reset(operand)
+            }
+            "barrier" => {
+                self.advance();
+                let operands = self.operands()?;
+                self.expect_symbol(";")?;
+                StatementKind::Barrier(operands)
+            }
+            "delay" => {
+                self.advance();
+                self.expect_symbol("[")?;
+                let TokenKind::Duration(duration, unit) = self.peek().kind.clone() else {
+                    return Err(self.unexpected("a duration such as 100ns"));
+                };
+                self.advance();
+                self.expect_symbol("]")?;
+                let operands = self.operands()?;
+                self.expect_symbol(";")?;
+                StatementKind::Delay { duration, unit, operands }
+            }
+            "if" if self.qasm2 => self.qasm2_if()?,
+            "if" => {
+                self.advance();
+                let condition = self.condition()?;
+                let then_body = self.block()?;
+                let else_body = if self.at_keyword("else") {
+                    self.advance();
+                    Some(self.block()?)
+                } else {
+                    None
+                };
+                StatementKind::If { condition, then_body, else_body }
+            }
+            "while" => {
+                self.advance();
+                let condition = self.condition()?;
+                StatementKind::While { condition, body: self.block()? }
+            }
+            "for" => self.for_loop()?,
+            "switch" => self.switch()?,
+            // only before `;`, so programs may still name gates `end`
+            "break" | "continue" | "end" if matches!(self.peek_at(1), TokenKind::Symbol(";")) => {
+                self.advance();
+                self.advance();
+                match keyword.as_str() {
+                    "break" => StatementKind::Break,
+                    "continue" => StatementKind::Continue,
+                    _ => StatementKind::End,
+                }
+            }
+            _ if !self.qasm2 && matches!(self.peek_at(1), TokenKind::Symbol(s) if ["=", "["].contains(s) || COMPOUND_ASSIGNMENTS.contains(s)) => {
+                self.assignment()?
+            }
+            _ => self.gate_call()?,
+        };
+        Ok(Statement { kind, span })
     }
-    
-    fn extract_qubit_index(s: &str) -> Option<usize> {
-        // handle q[0] or $0 format
-        if s.contains('[') {
-            let start = s.find('[')? + 1;
-            let end = s.find(']')?;
-            s[start..end].parse().ok()
-        } else if s.starts_with('$') {
-            s[1..].parse().ok()
+
+    /// optional `[n]` after a type or register name
+    fn designator(&mut self) -> Result<Option<usize>, QucomError> {
+        if self.eat_symbol("[") {
+            let size = self.expect_integer()?;
+            self.expect_symbol("]")?;
+            Ok(Some(size))
         } else {
-            s.parse().ok()
+            Ok(None)
         }
     }
-    
-    fn extract_two_qubit_indices(s: &str) -> Option<(usize, usize)> {
-        // handle "q[0],q[1]" or "q[0], q[1]" or "$0,$1" format
-        let s = s.trim();
-        let parts: Vec<&str> = s.split(',').map(|p| p.trim()).collect();
-        if parts.len() != 2 {
-            return None;
-        }
-        let q1 = Self::extract_qubit_index(parts[0])?;
-        let q2 = Self::extract_qubit_index(parts[1])?;
-        Some((q1, q2))
-    }
-    
-    fn extract_three_qubit_indices(s: &str) -> Option<(usize, usize, usize)> {
-        // handle "q[0],q[1],q[2]" or "q[0], q[1], q[2]" or "$0,$1,$2" format
-        let s = s.trim();
-        let parts: Vec<&str> = s.split(',').map(|p| p.trim()).collect();
-        if parts.len() != 3 {
-            return None;
-        }
-        let q1 = Self::extract_qubit_index(parts[0])?;
-        let q2 = Self::extract_qubit_index(parts[1])?;
-        let q3 = Self::extract_qubit_index(parts[2])?;
-        Some((q1, q2, q3))
-    }
-    
-    fn extract_parameterized_gate(s: &str) -> Option<(f64, usize)> {
-        // handle "rx(pi/2) q[0]" or "rx(1.57) $0" format
-        if !s.contains('(') {
-            return None;
-        }
-        
-        let param_start = s.find('(')? + 1;
-        let param_end = s.find(')')?;
-        let param_str = &s[param_start..param_end];
-        let angle = Self::parse_angle(param_str)?;
-        
-        let qubit_part = s[param_end + 1..].trim();
-        let qubit = Self::extract_qubit_index(qubit_part)?;
-        
-        Some((angle, qubit))
-    }
-    
-    fn extract_u_gate(s: &str) -> Option<((f64, f64, f64), usize)> {
-        // handle "u(theta,phi,lambda) q[0]" format
-        if !s.contains('(') {
-            return None;
-        }
-        
-        let param_start = s.find('(')? + 1;
-        let param_end = s.find(')')?;
-        let params_str = &s[param_start..param_end];
-        let params: Vec<&str> = params_str.split(',').collect();
-        
-        if params.len() != 3 {
-            return None;
-        }
-        
-        let theta = Self::parse_angle(params[0].trim())?;
-        let phi = Self::parse_angle(params[1].trim())?;
-        let lambda = Self::parse_angle(params[2].trim())?;
-        
-        let qubit_part = s[param_end + 1..].trim();
-        let qubit = Self::extract_qubit_index(qubit_part)?;
-        
-        Some(((theta, phi, lambda), qubit))
-    }
-    
-    fn parse_angle(s: &str) -> Option<f64> {
-        let s = s.trim();
-        
-        // handle pi, tau, euler constants
-        if s.contains("pi") || s.contains("π") {
-            let pi = std::f64::consts::PI;
-            let s = s.replace("π", "pi");
-            
-            if s == "pi" {
-                return Some(pi);
-            } else if s.starts_with("pi/") {
-                let denom: f64 = s[3..].parse().ok()?;
-                return Some(pi / denom);
-            } else if s.starts_with("pi*") {
-                let mult: f64 = s[3..].parse().ok()?;
-                return Some(pi * mult);
-            } else if s.ends_with("*pi") {
-                let mult: f64 = s[..s.len() - 3].parse().ok()?;
-                return Some(mult * pi);
-            } else if s.contains("*pi/") {
-                let parts: Vec<&str> = s.split("*pi/").collect();
-                if parts.len() == 2 {
-                    let mult: f64 = parts[0].parse().ok()?;
-                    let denom: f64 = parts[1].parse().ok()?;
-                    return Some(mult * pi / denom);
-                }
-            }
-        }
-        
-        if s.contains("tau") || s.contains("τ") {
-            let tau = 2.0 * std::f64::consts::PI;
-            let s = s.replace("τ", "tau");
-            
-            if s == "tau" {
-                return Some(tau);
-            } else if s.starts_with("tau/") {
-                let denom: f64 = s[4..].parse().ok()?;
-                return Some(tau / denom);
-            } else if s.starts_with("tau*") {
-                let mult: f64 = s[4..].parse().ok()?;
-                return Some(tau * mult);
+
+    /// `float`, `float[64]`, `angle[32]`, ...
+    fn type_name(&mut self) -> Result<String, QucomError> {
+        let (name, _) = self.expect_identifier()?;
+        Ok(match self.designator()? {
+            Some(width) => format!("{}[{}]", name, width),
+            None => name,
+        })
+    }
+
+    /// `gate name(a, b) q0, q1 { ... }`
+    fn gate_definition(&mut self) -> Result<StatementKind, QucomError> {
+        self.expect_keyword("gate")?;
+        let (name, params, qubits) = self.gate_signature()?;
+        self.expect_symbol("{")?;
+        let mut body = Vec::new();
+        while !self.eat_symbol("}") {
+            if self.peek().kind == TokenKind::Eof {
+                return Err(self.unexpected("'}'"));
             }
+            body.push(self.statement()?);
         }
-        
-        if s.contains("euler") || s.contains("ℇ") {
-            let euler = std::f64::consts::E;
-            let s = s.replace("ℇ", "euler");
-            
-            if s == "euler" {
-                return Some(euler);
+        Ok(StatementKind::GateDefinition { name, params, qubits, body })
+    }
+
+    /// `name(a, b) q0, q1` after `gate` or `opaque`
+    fn gate_signature(&mut self) -> Result<(String, Vec<String>, Vec<String>), QucomError> {
+        let (name, _) = self.expect_identifier()?;
+        let mut params = Vec::new();
+        if self.eat_symbol("(") {
+            while !self.at_symbol(")") {
+                params.push(self.expect_identifier()?.0);
+                if !self.eat_symbol(",") {
+                    break;
+                }
             }
+            self.expect_symbol(")")?;
         }
-        
-        // handle plain numbers
-        s.parse().ok()
-    }
-    
-    fn extract_qubit_list(s: &str) -> Option<Vec<usize>> {
-        let s = s.trim();
-        if s.is_empty() {
-            return Some(Vec::new());
+        let mut qubits = vec![self.expect_identifier()?.0];
+        while self.eat_symbol(",") {
+            qubits.push(self.expect_identifier()?.0);
         }
-        
-        let parts: Vec<&str> = s.split(',').map(|p| p.trim()).collect();
-        let mut qubits = Vec::new();
-        
-        for part in parts {
-            qubits.push(Self::extract_qubit_index(part)?);
-        }
-        
-        Some(qubits)
-    }
-    
-    fn extract_delay(s: &str) -> Option<(f64, String, usize)> {
-        // handle "delay[100ns] q[0]" or "delay[1.5us] q[0]"
-        if !s.contains('[') {
-            return None;
+        Ok((name, params, qubits))
+    }
+
+    /// OpenQASM 2.0 `if(c==3) op;`: a whole classical register against an integer, guarding one operation
+    fn qasm2_if(&mut self) -> Result<StatementKind, QucomError> {
+        self.expect_keyword("if")?;
+        self.expect_symbol("(")?;
+        let (register, register_span) = self.expect_identifier()?;
+        self.expect_symbol("==")?;
+        let value_span = self.peek().span;
+        let value = self.expect_integer()?;
+        self.expect_symbol(")")?;
+        let condition = binary(
+            "==",
+            Expr { kind: ExprKind::Identifier(register), span: register_span },
+            Expr { kind: ExprKind::Number(value as f64), span: value_span },
+        );
+        let statement = self.statement()?;
+        if !matches!(statement.kind, StatementKind::GateCall { .. } | StatementKind::Measure { .. } | StatementKind::Reset(_)) {
+            return Err(statement.span.error("an OpenQASM 2.0 if guards a single gate, measure or reset"));
         }
-        
-        let bracket_start = s.find('[')?;
-        let bracket_end = s.find(']')?;
-        let duration_str = &s[bracket_start + 1..bracket_end];
-        
-        // extract duration and unit
-        let (duration, unit) = Self::parse_duration(duration_str)?;
-        
-        let qubit_part = s[bracket_end + 1..].trim();
-        let qubit = Self::extract_qubit_index(qubit_part)?;
-        
-        Some((duration, unit, qubit))
-    }
-    
-    fn parse_duration(s: &str) -> Option<(f64, String)> {
-        let s = s.trim();
-        
-        // try to find where the number ends and unit begins
-        let mut num_end = 0;
-        for (i, c) in s.chars().enumerate() {
-            if c.is_ascii_digit() || c == '.' || c == '-' {
-                num_end = i + 1;
-            } else {
+        Ok(StatementKind::If { condition, then_body: vec![statement], else_body: None })
+    }
+
+    /// `def name(qubit a, qubit[2] b, float[64] theta) -> bit { ... }`
+    fn subroutine(&mut self) -> Result<StatementKind, QucomError> {
+        self.expect_keyword("def")?;
+        let (name, _) = self.expect_identifier()?;
+        self.expect_symbol("(")?;
+        let mut arguments = Vec::new();
+        while !self.at_symbol(")") {
+            let span = self.peek().span;
+            let (ty, _) = self.expect_identifier()?;
+            let size = self.designator()?;
+            let ty = match ty.as_str() {
+                "qubit" => ArgumentType::Qubit(size),
+                "bit" => ArgumentType::Bit(size),
+                "int" | "uint" | "float" | "angle" | "bool" => ArgumentType::Value(ty),
+                _ => return Err(span.error(format!("unknown argument type '{}'", ty))),
+            };
+            let (argument, _) = self.expect_identifier()?;
+            arguments.push(Argument { ty, name: argument, span });
+            if !self.eat_symbol(",") {
                 break;
             }
         }
-        
-        if num_end == 0 {
-            return None;
-        }
-        
-        let duration: f64 = s[..num_end].parse().ok()?;
-        let unit = s[num_end..].trim().to_string();
-        
-        Some((duration, unit))
-    }
-    
-    fn parse_if_block(lines: &[&str]) -> Result<(QasmInstruction, usize), String> {
-        let first_line = lines[0].trim();
-        
-        // extract condition
-        let if_start = first_line.find('(').ok_or("missing (")?;
-        let if_end = first_line.find(')').ok_or("missing )")?;
-        let condition = &first_line[if_start + 1..if_end];
-        
-        let parts: Vec<&str> = condition.split("==").map(|p| p.trim()).collect();
-        if parts.len() != 2 {
-            return Err("invalid condition".to_string());
+        self.expect_symbol(")")?;
+        let returns = if self.eat_symbol("->") { Some(self.type_name()?) } else { None };
+        if !self.at_symbol("{") {
+            return Err(self.unexpected("'{'"));
         }
-        
-        let bit = Self::extract_qubit_index(parts[0]).ok_or("invalid bit index")?;
-        let value: usize = parts[1].parse().map_err(|_| "invalid value")?;
-        
-        // check if single-line if statement
-        if !first_line.contains('{') {
-            let instr_str = first_line[if_end + 1..].trim();
-            if let Some(instr) = Self::parse_instruction(instr_str) {
-                return Ok((QasmInstruction::If(bit, value, vec![instr]), 1));
-            }
+        Ok(StatementKind::Subroutine { name, arguments, returns, body: self.block()? })
+    }
+
+    /// `for i in [0:4] { ... }`, `[0:2:8]` or `{1, 3, 5}`, the loop variable optionally typed (`for uint i in ...`)
+    fn for_loop(&mut self) -> Result<StatementKind, QucomError> {
+        self.expect_keyword("for")?;
+        let (mut variable, _) = self.expect_identifier()?;
+        if !self.at_keyword("in") {
+            // the first name was the type
+            self.designator()?;
+            variable = self.expect_identifier()?.0;
         }
-        
-        // multi-line if block
-        let mut if_body = Vec::new();
-        let mut else_body = Vec::new();
-        let mut i = 1;
-        let mut in_else = false;
-        let mut brace_count = 1;
-        
-        while i < lines.len() {
-            let line = lines[i].trim();
-            
-            if line.contains('{') {
-                brace_count += line.matches('{').count();
-            }
-            if line.contains('}') {
-                brace_count -= line.matches('}').count();
-                if brace_count == 0 {
-                    i += 1;
-                    break;
+        self.expect_keyword("in")?;
+        let values = if self.eat_symbol("[") {
+            let start = self.expression()?;
+            self.expect_symbol(":")?;
+            let second = self.expression()?;
+            let values = if self.eat_symbol(":") {
+                LoopValues::Range { start, step: Some(second), end: self.expression()? }
+            } else {
+                LoopValues::Range { start, step: None, end: second }
+            };
+            self.expect_symbol("]")?;
+            values
+        } else if self.eat_symbol("{") {
+            let mut values = vec![self.expression()?];
+            while self.eat_symbol(",") {
+                values.push(self.expression()?);
+            }
+            self.expect_symbol("}")?;
+            LoopValues::Set(values)
+        } else {
+            return Err(self.unexpected("a range or a set"));
+        };
+        Ok(StatementKind::For { variable, values, body: self.block()? })
+    }
+
+    /// `switch (value) { case 1, 2 { ... } default { ... } }`
+    fn switch(&mut self) -> Result<StatementKind, QucomError> {
+        self.expect_keyword("switch")?;
+        let value = self.condition()?;
+        self.expect_symbol("{")?;
+        let mut cases = Vec::new();
+        let mut default = None;
+        while !self.eat_symbol("}") {
+            let span = self.peek().span;
+            if self.at_keyword("case") {
+                self.advance();
+                let mut values = vec![self.expression()?];
+                while self.eat_symbol(",") {
+                    values.push(self.expression()?);
                 }
-            }
-            
-            if brace_count == 1 && line.starts_with("else") {
-                in_else = true;
-                i += 1;
-                continue;
-            }
-            
-            if !line.is_empty() && !line.starts_with("//") {
-                if let Some(instr) = Self::parse_instruction(line) {
-                    if in_else {
-                        else_body.push(instr);
-                    } else {
-                        if_body.push(instr);
-                    }
+                cases.push((values, self.case_block()?));
+            } else if self.at_keyword("default") {
+                self.advance();
+                if default.is_some() {
+                    return Err(span.error("switch has more than one default"));
                 }
+                default = Some(self.case_block()?);
+            } else {
+                return Err(self.unexpected("'case', 'default' or '}'"));
             }
-            
-            i += 1;
         }
-        
-        if else_body.is_empty() {
-            Ok((QasmInstruction::If(bit, value, if_body), i))
-        } else {
-            Ok((QasmInstruction::IfElse(bit, value, if_body, else_body), i))
+        Ok(StatementKind::Switch { value, cases, default })
+    }
+
+    /// the braced body of a `case` or `default`
+    fn case_block(&mut self) -> Result<Vec<Statement>, QucomError> {
+        if !self.at_symbol("{") {
+            return Err(self.unexpected("'{'"));
         }
+        self.block()
     }
-    
-    fn parse_while_block(lines: &[&str]) -> Result<(QasmInstruction, usize), String> {
-        let first_line = lines[0].trim();
-        
-        let while_start = first_line.find('(').ok_or("missing (")?;
-        let while_end = first_line.find(')').ok_or("missing )")?;
-        let condition = &first_line[while_start + 1..while_end];
-        
-        let parts: Vec<&str> = condition.split("==").map(|p| p.trim()).collect();
-        if parts.len() != 2 {
-            return Err("invalid condition".to_string());
+
+    /// `(c[0] == 1)`, `(c == 5 && n < 3)`, ...
+    fn condition(&mut self) -> Result<Expr, QucomError> {
+        self.expect_symbol("(")?;
+        let condition = self.expression()?;
+        self.expect_symbol(")")?;
+        Ok(condition)
+    }
+
+    /// `c[0] = measure q[0];`, `c = measure q;`, `n = n + 1;` or `n += 1;`
+    fn assignment(&mut self) -> Result<StatementKind, QucomError> {
+        let target = self.operand()?;
+        let op = match self.peek().kind {
+            TokenKind::Symbol("=") => None,
+            TokenKind::Symbol(s) if COMPOUND_ASSIGNMENTS.contains(&s) => Some(&s[..s.len() - 1]),
+            _ => return Err(self.unexpected("'='")),
+        };
+        self.advance();
+        if op.is_none() && self.at_keyword("measure") {
+            self.advance();
+            let qubit = self.operand()?;
+            self.expect_symbol(";")?;
+            return Ok(StatementKind::Measure { qubit, target: Some(target) });
         }
-        
-        let bit = Self::extract_qubit_index(parts[0]).ok_or("invalid bit index")?;
-        let value: usize = parts[1].parse().map_err(|_| "invalid value")?;
-        
-        let mut body = Vec::new();
-        let mut i = 1;
-        let mut brace_count = 1;
-        
-        while i < lines.len() {
-            let line = lines[i].trim();
-            
-            if line.contains('{') {
-                brace_count += line.matches('{').count();
-            }
-            if line.contains('}') {
-                brace_count -= line.matches('}').count();
-                if brace_count == 0 {
-                    i += 1;
-                    break;
+        let value = self.expression()?;
+        self.expect_symbol(";")?;
+        Ok(StatementKind::Assignment { target, op, value })
+    }
+
+    /// `{ statements }` or a single statement
+    fn block(&mut self) -> Result<Vec<Statement>, QucomError> {
+        self.nested(|parser| {
+            if !parser.eat_symbol("{") {
+                return Ok(vec![parser.statement()?]);
+            }
+            let mut statements = Vec::new();
+            while !parser.eat_symbol("}") {
+                if parser.peek().kind == TokenKind::Eof {
+                    return Err(parser.unexpected("'}'"));
                 }
+                statements.push(parser.statement()?);
             }
-            
-            if !line.is_empty() && !line.starts_with("//") {
-                if let Some(instr) = Self::parse_instruction(line) {
-                    body.push(instr);
+            Ok(statements)
+        })
+    }
+
+    /// `ctrl @ negctrl(2) @ inv @ pow(2) @ name(args) operands;`
+    fn gate_call(&mut self) -> Result<StatementKind, QucomError> {
+        let mut modifiers = Vec::new();
+        loop {
+            let modifier = if self.at_keyword("ctrl") || self.at_keyword("negctrl") {
+                let negated = self.at_keyword("negctrl");
+                let span = self.peek().span;
+                self.advance();
+                let count = if self.eat_symbol("(") {
+                    let count = self.expect_integer()?;
+                    self.expect_symbol(")")?;
+                    count
+                } else {
+                    1
+                };
+                if count == 0 {
+                    return Err(span.error("a control modifier needs at least one control"));
                 }
-            }
-            
-            i += 1;
-        }
-        
-        Ok((QasmInstruction::While(bit, value, body), i))
-    }
-    
-    fn parse_for_block(lines: &[&str]) -> Result<(QasmInstruction, usize), String> {
-        let first_line = lines[0].trim();
-        
-        // parse "for i in [0:10]" or similar
-        let in_pos = first_line.find(" in ").ok_or("missing 'in'")?;
-        let var_part = &first_line[3..in_pos].trim();
-        
-        let bracket_start = first_line.find('[').ok_or("missing [")?;
-        let bracket_end = first_line.find(']').ok_or("missing ]")?;
-        let range_part = &first_line[bracket_start + 1..bracket_end];
-        
-        let range_parts: Vec<&str> = range_part.split(':').collect();
-        if range_parts.len() != 2 {
-            return Err("invalid range".to_string());
+                Modifier::Ctrl { count, negated }
+            } else if self.at_keyword("inv") {
+                self.advance();
+                Modifier::Inv
+            } else if self.at_keyword("pow") {
+                self.advance();
+                self.expect_symbol("(")?;
+                let exponent = self.expression()?;
+                self.expect_symbol(")")?;
+                Modifier::Pow(exponent)
+            } else {
+                break;
+            };
+            self.expect_symbol("@")?;
+            modifiers.push(modifier);
         }
-        
-        let start: usize = range_parts[0].trim().parse().map_err(|_| "invalid start")?;
-        let end: usize = range_parts[1].trim().parse().map_err(|_| "invalid end")?;
-        
-        let mut body = Vec::new();
-        let mut i = 1;
-        let mut brace_count = 1;
-        
-        while i < lines.len() {
-            let line = lines[i].trim();
-            
-            if line.contains('{') {
-                brace_count += line.matches('{').count();
-            }
-            if line.contains('}') {
-                brace_count -= line.matches('}').count();
-                if brace_count == 0 {
-                    i += 1;
+
+        let (name, name_span) = self.expect_identifier()?;
+        let mut args = Vec::new();
+        if self.eat_symbol("(") {
+            while !self.at_symbol(")") {
+                args.push(self.expression()?);
+                if !self.eat_symbol(",") {
                     break;
                 }
             }
-            
-            if !line.is_empty() && !line.starts_with("//") {
-                if let Some(instr) = Self::parse_instruction(line) {
-                    body.push(instr);
+            self.expect_symbol(")")?;
+        }
+        let operands = self.operands()?;
+        self.expect_symbol(";")?;
+        Ok(StatementKind::GateCall { modifiers, name, name_span, args, operands })
+    }
+
+    /// comma-separated operands, possibly none
+    fn operands(&mut self) -> Result<Vec<Operand>, QucomError> {
+        let mut operands = Vec::new();
+        if matches!(self.peek().kind, TokenKind::Identifier(_) | TokenKind::PhysicalQubit(_)) {
+            operands.push(self.operand()?);
+            while self.eat_symbol(",") {
+                operands.push(self.operand()?);
+            }
+        }
+        Ok(operands)
+    }
+
+    /// `name`, `name[index]` or `$index`
+    fn operand(&mut self) -> Result<Operand, QucomError> {
+        let span = self.peek().span;
+        let target = match self.peek().kind.clone() {
+            TokenKind::PhysicalQubit(index) => {
+                self.advance();
+                OperandTarget::Physical(index)
+            }
+            TokenKind::Identifier(name) => {
+                self.advance();
+                if self.eat_symbol("[") {
+                    let index = self.expression()?;
+                    self.expect_symbol("]")?;
+                    OperandTarget::Indexed(name, index)
+                } else {
+                    OperandTarget::Register(name)
                 }
             }
-            
-            i += 1;
+            _ => return Err(self.unexpected("a qubit or bit")),
+        };
+        Ok(Operand { target, span })
+    }
+
+    fn expression(&mut self) -> Result<Expr, QucomError> {
+        self.nested(|parser| parser.infix(0))
+    }
+
+    /// left-associative operators of `PRECEDENCE[level]` and tighter
+    fn infix(&mut self, level: usize) -> Result<Expr, QucomError> {
+        let Some(ops) = PRECEDENCE.get(level) else {
+            return self.unary();
+        };
+        let mut expr = self.infix(level + 1)?;
+        while let TokenKind::Symbol(op) = self.peek().kind
+            && ops.contains(&op)
+        {
+            self.advance();
+            let rhs = self.infix(level + 1)?;
+            expr = binary(op, expr, rhs);
         }
-        
-        Ok((QasmInstruction::For(var_part.to_string(), start, end, body), i))
-    }
-    
-    fn parse_measurement_from_args(line: &str) -> Option<QasmInstruction> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 2 {
-            return None;
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, QucomError> {
+        let span = self.peek().span;
+        match self.peek().kind {
+            TokenKind::Symbol(op @ ("-" | "+" | "!" | "~")) => {
+                self.advance();
+                let operand = self.nested(Self::unary)?;
+                Ok(Expr { kind: ExprKind::Unary(op, Box::new(operand)), span })
+            }
+            _ => self.power(),
         }
-        
-        // check for "measure q -> c" format (measure all)
-        if parts.len() >= 4 && parts[2] == "->" {
-            return Some(QasmInstruction::MeasureAll);
+    }
+
+    /// `a ** b`, right-associative and binding tighter than unary minus, so `-2 ** 2` is -4
+    fn power(&mut self) -> Result<Expr, QucomError> {
+        let base = self.primary()?;
+        if self.eat_symbol("**") {
+            let exponent = self.nested(Self::unary)?;
+            return Ok(binary("**", base, exponent));
         }
-        
-        // handle "measure q[0] -> c[0]" format
-        let qubit_str = parts.get(1)?;
-        let qubit = Self::extract_qubit_index(qubit_str)?;
-        
-        let classical_str = parts.get(3)?;
-        let classical = Self::extract_qubit_index(classical_str)?;
-        
-        Some(QasmInstruction::Measure(qubit, classical))
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, QucomError> {
+        let span = self.peek().span;
+        let kind = match self.peek().kind.clone() {
+            TokenKind::Integer(value) => ExprKind::Number(value as f64),
+            TokenKind::Float(value) => ExprKind::Number(value),
+            TokenKind::Identifier(name) if *self.peek_at(1) == TokenKind::Symbol("(") => {
+                self.advance();
+                self.advance();
+                let mut args = Vec::new();
+                while !self.at_symbol(")") {
+                    args.push(self.expression()?);
+                    if !self.eat_symbol(",") {
+                        break;
+                    }
+                }
+                self.expect_symbol(")")?;
+                return Ok(Expr { kind: ExprKind::Call(name, args), span });
+            }
+            TokenKind::Identifier(name) if *self.peek_at(1) == TokenKind::Symbol("[") => {
+                self.advance();
+                self.advance();
+                let index = self.expression()?;
+                self.expect_symbol("]")?;
+                return Ok(Expr { kind: ExprKind::Index(name, Box::new(index)), span });
+            }
+            TokenKind::Identifier(name) => ExprKind::Identifier(name),
+            TokenKind::Symbol("(") => {
+                self.advance();
+                let expr = self.expression()?;
+                self.expect_symbol(")")?;
+                return Ok(expr);
+            }
+            _ => return Err(self.unexpected("an expression")),
+        };
+        self.advance();
+        Ok(Expr { kind, span })
     }
+
+    /// `#pragma qucom unitary [[a, b], [c, d]] q[0];` becomes a `Unitary`, any other pragma is kept as text
+    fn pragma(&mut self) -> Result<Statement, QucomError> {
+        let token = self.advance();
+        let TokenKind::Pragma(text) = token.kind else { unreachable!("pragma() is only called on a pragma") };
+        let span = token.span;
+        let Some(matrix_text) = text.trim_start().strip_prefix("qucom").map(str::trim_start).and_then(|t| t.strip_prefix("unitary"))
+        else {
+            return Ok(Statement { kind: StatementKind::Pragma(text.trim().to_string()), span });
+        };
+
+        let matrix_text = matrix_text.trim_start();
+        let end = matrix_text.rfind("]]").ok_or_else(|| span.error("expected a matrix after 'unitary'"))? + 2;
+        let matrix = parse_matrix(&matrix_text[..end]).ok_or_else(|| span.error("invalid unitary matrix"))?;
+
+        // operands after the matrix, lexed at their real position for error messages
+        let tail = &matrix_text[end..];
+        let tail_column = span.column + "#pragma".len() + text.chars().count() - tail.chars().count();
+        let mut operands_parser = Parser::new(Lexer::starting_at(tail, Span::new(span.line, tail_column)).tokenize()?, false);
+        let operands = operands_parser.operands()?;
+        operands_parser.eat_symbol(";");
+        if operands_parser.peek().kind != TokenKind::Eof {
+            return Err(operands_parser.unexpected("the end of the pragma"));
+        }
+        Ok(Statement { kind: StatementKind::Unitary { matrix, operands }, span })
+    }
+}
+
+fn binary(op: &'static str, lhs: Expr, rhs: Expr) -> Expr {
+    let span = lhs.span;
+    Expr { kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)), span }
+}
+
+/// `[[1+0i, 0+0i], [0+0i, 1+0i]]`, as `QasmGenerator` writes it
+fn parse_matrix(text: &str) -> Option<Array2<Complex64>> {
+    let rows: Vec<Vec<Complex64>> = text
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .split(']')
+        .map(|row| row.trim_start_matches([',', ' ', '[']))
+        .filter(|row| !row.is_empty())
+        .map(|row| row.split(',').map(|c| c.trim().parse().ok()).collect::<Option<Vec<_>>>())
+        .collect::<Option<Vec<_>>>()?;
+    let dim = rows.len();
+    Array2::from_shape_vec((dim, dim), rows.into_iter().flatten().collect()).ok()
 }
@@ -0,0 +1,334 @@
+use crate::error::QucomError;
+use crate::gates::unitary::square_root;
+use crate::gates::u::u_angles;
+use crate::gates::GateOperation;
+use crate::parameters::ParameterExpression;
+use crate::qasm::classical::{BinaryOp, ClassicalExpr};
+use crate::qasm::definition::{free_name, local_operands};
+use crate::qasm::generator::{Layout, QasmInstruction, WithLayout};
+use crate::qasm::{include, GateDefinition, QasmGenerator};
+
+/// the program as OpenQASM 2.0 with `qelib1.inc`: controlled gates under their qelib1 names,
+/// several controls split into singly controlled square roots, and an error for anything the
+/// older language cannot say (input parameters, loops, classical variables, general conditions)
+pub(crate) fn program(generator: &QasmGenerator) -> Result<String, QucomError> {
+    let layout = Layout::new(generator.quantum_registers(), generator.classical_registers());
+    let mut writer = Writer::default();
+    let mut statements = Vec::new();
+    for instr in generator.instructions() {
+        statements.extend(writer.statement(instr, &layout)?);
+    }
+    if let Some(parameter) = generator.parameters().first() {
+        return Err(QucomError::Unsupported(format!(
+            "OpenQASM 2.0 has no input parameters, bind '{}' before exporting", parameter
+        )));
+    }
+
+    let mut qasm = String::from("OPENQASM 2.0;\ninclude \"qelib1.inc\";\n\n");
+    for register in generator.quantum_registers() {
+        qasm.push_str(&format!("qreg {}[{}];\n", register.name(), register.size()));
+    }
+    for register in generator.classical_registers() {
+        qasm.push_str(&format!("creg {}[{}];\n", register.name(), register.size()));
+    }
+    qasm.push('\n');
+    for definition in &writer.definitions {
+        qasm.push_str(&format!("{}\n", definition));
+    }
+    if !writer.definitions.is_empty() {
+        qasm.push('\n');
+    }
+    for statement in statements {
+        qasm.push_str(&format!("{}\n", statement));
+    }
+    Ok(qasm)
+}
+
+#[derive(Default)]
+struct Writer {
+    /// `gate` definitions the program needs, each after the gates its body uses
+    definitions: Vec<String>,
+    /// custom gates already defined, in the output or by qelib1, and the names they have there
+    names: Vec<(GateDefinition, String)>,
+}
+
+impl Writer {
+    /// a top-level instruction, a condition repeated on every statement of its body
+    fn statement(&mut self, instr: &QasmInstruction, layout: &Layout) -> Result<Vec<String>, QucomError> {
+        let QasmInstruction::If(condition, body) = instr else {
+            return self.operation(instr, layout);
+        };
+        let (register, bits, value) = guard(condition, layout)?;
+        let mut statements = Vec::new();
+        for (i, instr) in body.iter().enumerate() {
+            match instr {
+                // later statements would see the new value
+                QasmInstruction::Measure(_, bit) if bits.contains(bit) && i + 1 < body.len() => {
+                    return Err(QucomError::Unsupported(format!(
+                        "OpenQASM 2.0 repeats the condition on '{}' for every statement, so `{}` must come last",
+                        register, instr
+                    )));
+                }
+                QasmInstruction::Measure(_, _) | QasmInstruction::Reset(_) | QasmInstruction::ResetAll => {}
+                _ if is_gate(instr) => {}
+                _ => {
+                    return Err(QucomError::Unsupported(format!(
+                        "an OpenQASM 2.0 condition guards gates, measurements and resets, not `{}`", instr
+                    )));
+                }
+            }
+            for statement in self.operation(instr, layout)? {
+                statements.push(format!("if({}=={}) {}", register, value, statement));
+            }
+        }
+        Ok(statements)
+    }
+
+    fn operation(&mut self, instr: &QasmInstruction, layout: &Layout) -> Result<Vec<String>, QucomError> {
+        Ok(match instr {
+            QasmInstruction::Measure(_, _) | QasmInstruction::Reset(_) => vec![WithLayout(instr, layout).to_string()],
+            QasmInstruction::Barrier(qubits) if !qubits.is_empty() => vec![WithLayout(instr, layout).to_string()],
+            QasmInstruction::Barrier(_) | QasmInstruction::BarrierAll => {
+                vec![format!("barrier {};", layout.qubit_registers().join(", "))]
+            }
+            QasmInstruction::ResetAll => layout.qubit_registers().iter().map(|r| format!("reset {};", r)).collect(),
+            QasmInstruction::MeasureAll => vec![layout.measure_all()],
+            _ if is_gate(instr) => {
+                let mut gates = Vec::new();
+                self.gates(instr, &mut gates)?;
+                gates.iter().map(|gate| self.write_gate(gate, layout)).collect()
+            }
+            _ => return Err(QucomError::Unsupported(format!("OpenQASM 2.0 cannot express `{}`", instr))),
+        })
+    }
+
+    /// `gate` as gates OpenQASM 2.0 has names for, defining custom gates on the way
+    fn gates(&mut self, gate: &QasmInstruction, out: &mut Vec<QasmInstruction>) -> Result<(), QucomError> {
+        let (base, angles) = expressions(gate);
+        match base {
+            QasmInstruction::Controlled(inner, controls, ctrl_state) => {
+                // an open control is a closed one between two x gates
+                let open: Vec<usize> = controls.iter().zip(ctrl_state).filter(|(_, closed)| !**closed).map(|(c, _)| *c).collect();
+                out.extend(open.iter().map(|c| QasmInstruction::X(*c)));
+                self.controlled(QasmInstruction::parameterized(inner.as_ref().clone(), angles), controls, out)?;
+                out.extend(open.iter().map(|c| QasmInstruction::X(*c)));
+            }
+            QasmInstruction::GateCall(definition, _, _) => {
+                self.define(definition)?;
+                out.push(gate.clone());
+            }
+            QasmInstruction::Unitary(matrix, qubits) if qubits.len() == 1 => {
+                let (theta, phi, lambda, _) = u_angles(matrix);
+                out.push(QasmInstruction::U(theta, phi, lambda, qubits[0]));
+            }
+            QasmInstruction::Unitary(_, qubits) => {
+                return Err(QucomError::Unsupported(format!(
+                    "OpenQASM 2.0 has no statement for a {}-qubit unitary, decompose it into gates first", qubits.len()
+                )));
+            }
+            _ => out.push(gate.clone()),
+        }
+        Ok(())
+    }
+
+    /// `gate` under the closed `controls`
+    fn controlled(&mut self, gate: QasmInstruction, controls: &[usize], out: &mut Vec<QasmInstruction>) -> Result<(), QucomError> {
+        let closed = vec![true; controls.len()];
+        let (base, _) = expressions(&gate);
+        match (base, controls) {
+            (_, []) => self.gates(&gate, out),
+            (QasmInstruction::GateCall(definition, args, qubits), _) if !matches!(gate, QasmInstruction::Parameterized(..)) => {
+                // C(AB) = C(A) C(B), so the body is controlled gate by gate
+                for inner in definition.instantiate(args, qubits) {
+                    if matches!(inner, QasmInstruction::Barrier(_)) {
+                        continue;
+                    }
+                    self.gates(&QasmInstruction::controlled(inner, controls, &closed)?, out)?;
+                }
+                Ok(())
+            }
+            (QasmInstruction::Swap(a, b), [_, _, ..]) => {
+                let (a, b) = (*a, *b);
+                out.push(QasmInstruction::CX(b, a));
+                let controls: Vec<usize> = controls.iter().copied().chain([a]).collect();
+                self.controlled(QasmInstruction::X(b), &controls, out)?;
+                out.push(QasmInstruction::CX(b, a));
+                Ok(())
+            }
+            // cx, ccx, c3x, c4x and cswap
+            (QasmInstruction::X(_), [_] | [_, _] | [_, _, _] | [_, _, _, _]) | (QasmInstruction::Swap(_, _), [_]) => {
+                out.push(QasmInstruction::controlled(gate, controls, &closed)?);
+                Ok(())
+            }
+            (QasmInstruction::Unitary(matrix, qubits), [c]) if qubits.len() == 1 => {
+                // the global phase of the matrix becomes a phase on the control
+                let (theta, phi, lambda, gamma) = u_angles(matrix);
+                out.push(QasmInstruction::Phase(gamma, *c));
+                out.push(QasmInstruction::controlled(QasmInstruction::U(theta, phi, lambda, qubits[0]), controls, &closed)?);
+                Ok(())
+            }
+            (QasmInstruction::S(_, _) | QasmInstruction::T(_, _), [_]) => {
+                let phase = gate.scaled(1.0).expect("s and t are phase gates");
+                out.push(QasmInstruction::controlled(phase, controls, &closed)?);
+                Ok(())
+            }
+            (_, [_]) if single_controlled(base) => {
+                out.push(QasmInstruction::controlled(gate, controls, &closed)?);
+                Ok(())
+            }
+            (_, [_, .., last]) if base.qubits().len() == 1 => {
+                // with V² = U: CV(last) CX(rest → last) CV†(last) CX(rest → last) C..CV(rest)
+                let root = root(&gate)?;
+                let rest = &controls[..controls.len() - 1];
+                self.controlled(root.clone(), &[*last], out)?;
+                self.controlled(QasmInstruction::X(*last), rest, out)?;
+                self.controlled(root.inverse()?, &[*last], out)?;
+                self.controlled(QasmInstruction::X(*last), rest, out)?;
+                self.controlled(root, rest, out)
+            }
+            _ => Err(QucomError::Unsupported(format!(
+                "OpenQASM 2.0 cannot express `{}` with {} controls", gate, controls.len()
+            ))),
+        }
+    }
+
+    /// write out `definition`, after the gates its body uses, unless qelib1 has it
+    fn define(&mut self, definition: &GateDefinition) -> Result<(), QucomError> {
+        if self.names.iter().any(|(defined, _)| defined == definition) {
+            return Ok(());
+        }
+        let mut name = definition.name().to_string();
+        if !(definition.is_library() && include::defines("qelib1.inc", definition.name())) {
+            let mut body = Vec::new();
+            for instr in definition.body() {
+                body.extend(self.operation(instr, &Layout::default())?);
+            }
+            let parameters: Vec<&str> = definition.parameters().iter().map(|p| p.name()).collect();
+            let parameters = match parameters.is_empty() {
+                true => String::new(),
+                false => format!("({})", parameters.join(", ")),
+            };
+            let qubits: Vec<String> = (0..definition.n_qubits()).map(|q| format!("q{}", q)).collect();
+            let body: Vec<String> = body.iter().map(|statement| local_operands(statement)).collect();
+            // a different gate of the same name, such as a user's `g_dg` next to the inverse of
+            // `g`, or a gate qelib1 defines, is written under a free name
+            name = free_name(definition.name(), |name| {
+                include::defines("qelib1.inc", name) || self.names.iter().any(|(_, taken)| taken == name)
+            });
+            self.definitions.push(format!("gate {}{} {} {{ {} }}", name, parameters, qubits.join(", "), body.join(" ")));
+        }
+        self.names.push((definition.clone(), name));
+        Ok(())
+    }
+
+    /// `name(angles) operands;` for a gate `Writer::gates` produced
+    fn write_gate(&self, gate: &QasmInstruction, layout: &Layout) -> String {
+        let (base, angles) = expressions(gate);
+        let (name, qubits) = match base {
+            QasmInstruction::Phase(_, q) => ("u1", vec![*q]),
+            QasmInstruction::U(_, _, _, q) => ("u3", vec![*q]),
+            QasmInstruction::GateCall(definition, _, qubits) => {
+                let name = self.names.iter().find(|(defined, _)| defined == definition.as_ref()).map(|(_, name)| name.as_str());
+                (name.expect("Writer::gates defines every gate it calls"), qubits.clone())
+            }
+            QasmInstruction::Controlled(inner, controls, _) => {
+                let name = match (inner.as_ref(), controls.len()) {
+                    (QasmInstruction::X(_), 3) => "c3x",
+                    (QasmInstruction::X(_), 4) => "c4x",
+                    (QasmInstruction::Y(_), 1) => "cy",
+                    (QasmInstruction::H(_), 1) => "ch",
+                    (QasmInstruction::Rx(_, _), 1) => "crx",
+                    (QasmInstruction::Ry(_, _), 1) => "cry",
+                    (QasmInstruction::Rz(_, _), 1) => "crz",
+                    (QasmInstruction::Phase(_, _), 1) => "cu1",
+                    (QasmInstruction::U(_, _, _, _), 1) => "cu3",
+                    (QasmInstruction::Swap(_, _), 1) => "cswap",
+                    _ => unreachable!("Writer::controlled leaves no `{}`", gate),
+                };
+                (name, controls.iter().copied().chain(inner.qubits()).collect())
+            }
+            _ => return WithLayout(gate, layout).to_string(),
+        };
+        let angles: Vec<String> = angles.iter().map(|a| a.to_string()).collect();
+        match angles.is_empty() {
+            true => format!("{} {};", name, layout.operands(&qubits)),
+            false => format!("{}({}) {};", name, angles.join(", "), layout.operands(&qubits)),
+        }
+    }
+}
+
+/// the register, its bits and the value of a `register == value` condition
+fn guard<'a>(condition: &ClassicalExpr, layout: &'a Layout) -> Result<(&'a str, std::ops::Range<usize>, i64), QucomError> {
+    let compared = match condition {
+        ClassicalExpr::Binary(BinaryOp::Eq, lhs, rhs) => match (lhs.as_ref(), rhs.as_ref()) {
+            (ClassicalExpr::Register(offset, size), ClassicalExpr::Int(value))
+            | (ClassicalExpr::Int(value), ClassicalExpr::Register(offset, size)) => Some((*offset, *size, *value)),
+            (ClassicalExpr::Bit(bit), ClassicalExpr::Int(value))
+            | (ClassicalExpr::Int(value), ClassicalExpr::Bit(bit)) => Some((*bit, 1, *value)),
+            _ => None,
+        },
+        _ => None,
+    };
+    match compared {
+        Some((offset, size, value)) if value >= 0 => match layout.register(offset, size) {
+            Some(register) => Ok((register, offset..offset + size, value)),
+            None => Err(QucomError::Unsupported(format!(
+                "OpenQASM 2.0 conditions compare a whole classical register, not part of one: `{}`",
+                layout.classical(condition)
+            ))),
+        },
+        _ => Err(QucomError::Unsupported(format!(
+            "OpenQASM 2.0 conditions compare a classical register with a non-negative integer, not `{}`",
+            layout.classical(condition)
+        ))),
+    }
+}
+
+/// the gate inside `gate`'s parameterization, and its angles as expressions
+fn expressions(gate: &QasmInstruction) -> (&QasmInstruction, Vec<ParameterExpression>) {
+    let (base, slots) = match gate {
+        QasmInstruction::Parameterized(base, slots) => (base.as_ref(), slots.as_slice()),
+        gate => (gate, &[][..]),
+    };
+    let mut angles: Vec<ParameterExpression> = base.angles().into_iter().map(ParameterExpression::constant).collect();
+    for (slot, expr) in slots {
+        angles[*slot] = expr.clone();
+    }
+    (base, angles)
+}
+
+fn is_gate(instr: &QasmInstruction) -> bool {
+    !matches!(
+        instr,
+        QasmInstruction::Reset(_) | QasmInstruction::ResetAll | QasmInstruction::Barrier(_) | QasmInstruction::BarrierAll
+        | QasmInstruction::Delay(_, _, _) | QasmInstruction::Measure(_, _) | QasmInstruction::MeasureAll
+        | QasmInstruction::If(_, _) | QasmInstruction::IfElse(_, _, _) | QasmInstruction::While(_, _)
+        | QasmInstruction::For(_, _, _) | QasmInstruction::Switch(_, _, _) | QasmInstruction::Break
+        | QasmInstruction::Continue | QasmInstruction::End | QasmInstruction::Assign(_, _)
+    )
+}
+
+/// whether qelib1 names `gate` under a single control
+fn single_controlled(gate: &QasmInstruction) -> bool {
+    matches!(
+        gate,
+        QasmInstruction::Y(_) | QasmInstruction::Z(_) | QasmInstruction::H(_) | QasmInstruction::Rx(_, _)
+        | QasmInstruction::Ry(_, _) | QasmInstruction::Rz(_, _) | QasmInstruction::Phase(_, _) | QasmInstruction::U(_, _, _, _)
+    )
+}
+
+/// V with V² = `gate` for a single-qubit gate: half the angle of a rotation or phase gate,
+/// otherwise the matrix square root
+fn root(gate: &QasmInstruction) -> Result<QasmInstruction, QucomError> {
+    if let Some(root) = gate.scaled(0.5) {
+        return Ok(root);
+    }
+    match GateOperation::from_instruction(gate) {
+        Some(operation) if !matches!(gate, QasmInstruction::Parameterized(..)) => {
+            Ok(QasmInstruction::Unitary(square_root(&operation.matrix), operation.targets))
+        }
+        _ => Err(QucomError::Unsupported(format!(
+            "OpenQASM 2.0 export cannot put more than one control on `{}`", gate
+        ))),
+    }
+}
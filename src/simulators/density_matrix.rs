@@ -0,0 +1,217 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use rand_distr::weighted::WeightedIndex;
+use rand_distr::Distribution;
+use crate::gates::GateOperation;
+use crate::kernels;
+use crate::error::QucomError;
+use crate::qasm::generator::QasmInstruction;
+use crate::qasm::QasmExecutor;
+use crate::noise::NoiseModel;
+use crate::simulators::{NoisySimulator, Simulator, SimulatorRng};
+use crate::states::zero_state;
+
+/// density matrix simulator, evolves ρ → UρU† for every instruction
+///
+/// ρ is stored row-major as a column of 4^n entries, i.e. a "statevector" over 2n qubits
+/// where qubits 0..n index the rows and n..2n index the columns. UρU† is then U on the row
+/// qubits followed by U* on the column qubits, so the statevector kernels are reused as is.
+#[derive(Debug, Clone)]
+pub struct DensityMatrixSimulator {
+    n_qubits: usize,
+    rho: Array2<Complex64>,
+    rng: SimulatorRng,
+}
+
+impl DensityMatrixSimulator {
+    /// start in |0...0⟩⟨0...0|
+    pub fn new(n_qubits: usize) -> Self {
+        Self::from_statevector(&zero_state(n_qubits))
+    }
+
+    /// start in the pure state |ψ⟩⟨ψ|
+    pub fn from_statevector(state: &Array2<Complex64>) -> Self {
+        let psi: Vec<Complex64> = state.iter().copied().collect();
+        let dim = psi.len();
+        let n_qubits = Self::qubits_for_dim(dim);
+
+        let mut rho = Array2::<Complex64>::zeros((dim * dim, 1));
+        for (r, a) in psi.iter().enumerate() {
+            for (c, b) in psi.iter().enumerate() {
+                rho[[r * dim + c, 0]] = a * b.conj();
+            }
+        }
+
+        Self { n_qubits, rho, rng: SimulatorRng::default() }
+    }
+
+    /// start in an arbitrary (possibly mixed) 2^n x 2^n density matrix
+    pub fn from_density_matrix(rho: &Array2<Complex64>) -> Self {
+        let (rows, cols) = rho.dim();
+        assert_eq!(rows, cols, "density matrix must be square");
+        let n_qubits = Self::qubits_for_dim(rows);
+
+        let flat: Vec<Complex64> = rho.iter().copied().collect();
+        let rho = Array2::from_shape_vec((rows * cols, 1), flat).unwrap();
+
+        Self { n_qubits, rho, rng: SimulatorRng::default() }
+    }
+
+    /// start in the maximally mixed state I / 2^n
+    pub fn maximally_mixed(n_qubits: usize) -> Self {
+        let dim = 1 << n_qubits;
+        let rho = Array2::<Complex64>::eye(dim) / Complex64::new(dim as f64, 0.0);
+        Self::from_density_matrix(&rho)
+    }
+
+    /// execute QASM instructions, returning the measurement results
+    pub fn execute(&mut self, instructions: &[QasmInstruction]) -> Result<Vec<String>, QucomError> {
+        QasmExecutor::run(instructions, self)
+    }
+
+    /// execute QASM instructions with the channels and readout errors of `noise`
+    pub fn execute_with_noise(&mut self, instructions: &[QasmInstruction], noise: &NoiseModel) -> Result<Vec<String>, QucomError> {
+        QasmExecutor::run(instructions, &mut NoisySimulator::new(self, noise))
+    }
+
+    /// current density matrix as a 2^n x 2^n array
+    pub fn density_matrix(&self) -> Array2<Complex64> {
+        let dim = 1 << self.n_qubits;
+        Array2::from_shape_vec((dim, dim), self.rho.iter().copied().collect()).unwrap()
+    }
+
+    /// probability of every basis state (the diagonal of ρ)
+    pub fn probabilities(&self) -> Vec<f64> {
+        let dim = 1 << self.n_qubits;
+        (0..dim).map(|i| self.rho[[i * dim + i, 0]].re).collect()
+    }
+
+    /// 2x2 reduced density matrix of `qubit`, tracing out every other qubit
+    pub fn reduced_density_matrix(&self, qubit: usize) -> Array2<Complex64> {
+        let dim = 1 << self.n_qubits;
+        let mask = 1 << kernels::bit_position(qubit, self.n_qubits);
+        let mut reduced = Array2::<Complex64>::zeros((2, 2));
+        for i in (0..dim).filter(|i| i & mask == 0) {
+            for ((a, b), entry) in reduced.indexed_iter_mut() {
+                *entry += self.rho[[(i | (a * mask)) * dim + (i | (b * mask)), 0]];
+            }
+        }
+        reduced
+    }
+
+    /// Tr(ρP) for a Pauli string given by its masks (see `PauliString::masks`)
+    /// P|k⟩ = i^ny (-1)^popcount(k & z_mask) |k ^ x_mask⟩, so Tr(ρP) = Σ_k ρ[k, k ^ x] · phase(k)
+    pub fn pauli_expectation(&self, x_mask: usize, z_mask: usize, n_y: usize) -> f64 {
+        let dim = 1 << self.n_qubits;
+        let sum: Complex64 = (0..dim)
+            .map(|k| {
+                let sign = if (k & z_mask).count_ones().is_multiple_of(2) { 1.0 } else { -1.0 };
+                self.rho[[k * dim + (k ^ x_mask), 0]] * sign
+            })
+            .sum();
+        (sum * Complex64::i().powu(n_y as u32)).re
+    }
+
+    /// Tr(ρ²), 1 for pure states and 1 / 2^n for the maximally mixed state
+    pub fn purity(&self) -> f64 {
+        // ρ is hermitian, so Tr(ρ²) = Σ |ρ_ij|²
+        self.rho.iter().map(|c| c.norm_sqr()).sum()
+    }
+
+    /// apply a unitary operation, ρ → UρU†
+    pub fn apply_operation(&mut self, op: &GateOperation) {
+        let total = 2 * self.n_qubits;
+        op.apply(&mut self.rho, total);
+        op.conjugate().shifted(self.n_qubits).apply(&mut self.rho, total);
+    }
+
+    /// apply a channel given by its Kraus operators on `qubits`, ρ → Σ KρK†
+    pub fn apply_kraus(&mut self, kraus: &[Array2<Complex64>], qubits: &[usize]) {
+        let total = 2 * self.n_qubits;
+        let columns: Vec<usize> = qubits.iter().map(|q| q + self.n_qubits).collect();
+        let mut result = Array2::<Complex64>::zeros(self.rho.dim());
+
+        for k in kraus {
+            let mut term = self.rho.clone();
+            kernels::apply_multi_qubit(&mut term, k, qubits, total);
+            kernels::apply_multi_qubit(&mut term, &k.mapv(|c| c.conj()), &columns, total);
+            result += &term;
+        }
+
+        self.rho = result;
+    }
+
+    fn qubits_for_dim(dim: usize) -> usize {
+        assert!(dim.is_power_of_two(), "dimension must be a power of two");
+        dim.trailing_zeros() as usize
+    }
+}
+
+impl Simulator for DensityMatrixSimulator {
+    fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    fn rng(&mut self) -> &mut SimulatorRng {
+        &mut self.rng
+    }
+
+    fn apply_gate(&mut self, instr: &QasmInstruction) {
+        let op = GateOperation::from_instruction(instr)
+            .unwrap_or_else(|| panic!("not a gate instruction: {}", instr));
+        self.apply_operation(&op);
+    }
+
+    /// selective measurement: sample an outcome and project ρ onto it
+    fn measure(&mut self, qubit: usize) -> usize {
+        let mask = 1 << (self.n_qubits - 1 - qubit);
+        let (p0, p1) = self
+            .probabilities()
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(p0, p1), (i, p)| if i & mask == 0 { (p0 + p, p1) } else { (p0, p1 + p) });
+
+        let dist = WeightedIndex::new([p0.max(0.0), p1.max(0.0)]).unwrap();
+        let outcome = dist.sample(&mut self.rng);
+
+        // project rows and columns, each scaled by 1/√p so ρ is divided by p overall
+        let probability = if outcome == 0 { p0 } else { p1 };
+        let total = 2 * self.n_qubits;
+        kernels::collapse(&mut self.rho, qubit, outcome, probability, total);
+        kernels::collapse(&mut self.rho, qubit + self.n_qubits, outcome, probability, total);
+
+        outcome
+    }
+
+    fn measure_all(&mut self) -> String {
+        let probs: Vec<f64> = self.probabilities().iter().map(|p| p.max(0.0)).collect();
+        let dist = WeightedIndex::new(&probs).unwrap();
+        let idx = dist.sample(&mut self.rng);
+        format!("{:0width$b}", idx, width = self.n_qubits)
+    }
+
+    fn sample(&mut self, shots: usize) -> Vec<String> {
+        let probs: Vec<f64> = self.probabilities().iter().map(|p| p.max(0.0)).collect();
+        let dist = WeightedIndex::new(&probs).unwrap();
+        (0..shots)
+            .map(|_| format!("{:0width$b}", dist.sample(&mut self.rng), width = self.n_qubits))
+            .collect()
+    }
+
+    /// non-selective reset channel with Kraus operators |0⟩⟨0| and |0⟩⟨1|
+    fn reset(&mut self, qubit: usize) {
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        let k0 = Array2::from_shape_vec((2, 2), vec![one, zero, zero, zero]).unwrap();
+        let k1 = Array2::from_shape_vec((2, 2), vec![zero, one, zero, zero]).unwrap();
+        self.apply_kraus(&[k0, k1], &[qubit]);
+    }
+
+    fn reset_all(&mut self) {
+        self.rho = Self::new(self.n_qubits).rho;
+    }
+
+    fn apply_channel(&mut self, kraus: &[Array2<Complex64>], qubits: &[usize]) {
+        self.apply_kraus(kraus, qubits);
+    }
+}
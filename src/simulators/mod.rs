@@ -0,0 +1,135 @@
+pub mod density_matrix;
+pub mod mps;
+pub mod noisy;
+pub mod rng;
+pub mod stabilizer;
+pub mod statevector;
+
+pub use density_matrix::DensityMatrixSimulator;
+pub use mps::{MpsConfig, MpsSimulator};
+pub use noisy::NoisySimulator;
+pub use rng::SimulatorRng;
+pub use stabilizer::StabilizerSimulator;
+pub use statevector::StatevectorSimulator;
+
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::error::QucomError;
+use crate::qasm::generator::QasmInstruction;
+
+/// simulation backend used by `QuantumCircuit::execute`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// pure statevector, 2^n amplitudes
+    #[default]
+    Statevector,
+    /// density matrix, 4^n entries, supports mixed states and channels
+    DensityMatrix,
+    /// Clifford tableau, polynomial in n but limited to Clifford gates
+    Stabilizer,
+    /// matrix product state, cheap for low-entanglement circuits on many qubits
+    MatrixProductState,
+    /// stabilizer for noiseless Clifford-only programs, statevector otherwise
+    Automatic,
+}
+
+/// quantum-state operations a backend provides to `QasmExecutor::run`
+/// classical bits and control flow are handled by the executor, so every backend shares them
+pub trait Simulator {
+    fn n_qubits(&self) -> usize;
+
+    /// the stream measurement outcomes and noise trajectories are drawn from
+    fn rng(&mut self) -> &mut SimulatorRng;
+
+    /// draw from `rng` from now on, e.g. a seeded stream for reproducible results
+    fn set_rng(&mut self, rng: SimulatorRng) {
+        *self.rng() = rng;
+    }
+
+    /// apply a gate instruction (anything that isn't measurement, reset, barrier, delay or control flow)
+    fn apply_gate(&mut self, instr: &QasmInstruction);
+
+    /// measure one qubit, collapse it and return the outcome (0 or 1)
+    fn measure(&mut self, qubit: usize) -> usize;
+
+    /// sample a bitstring over all qubits
+    fn measure_all(&mut self) -> String;
+
+    /// draw `shots` bitstrings from the current state without collapsing it
+    fn sample(&mut self, shots: usize) -> Vec<String> {
+        (0..shots).map(|_| self.measure_all()).collect()
+    }
+
+    /// whether gates evolve the state deterministically, so shots can share one pre-measurement state
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    /// reset one qubit to |0⟩
+    fn reset(&mut self, qubit: usize);
+
+    /// reset every qubit to |0⟩
+    fn reset_all(&mut self);
+
+    /// apply a channel given by its Kraus operators on `qubits`
+    fn apply_channel(&mut self, kraus: &[Array2<Complex64>], qubits: &[usize]);
+
+    /// idle `qubit` for `duration`; ideal backends do nothing
+    fn delay(&mut self, _duration: f64, _unit: &str, _qubit: usize) {}
+
+    /// error if the backend cannot run one of `instructions`, checked before anything is applied
+    fn check_support(&self, _instructions: &[QasmInstruction]) -> Result<(), QucomError> {
+        Ok(())
+    }
+}
+
+/// lets wrappers like `NoisySimulator` borrow a backend instead of owning it
+impl<S: Simulator + ?Sized> Simulator for &mut S {
+    fn n_qubits(&self) -> usize {
+        (**self).n_qubits()
+    }
+
+    fn rng(&mut self) -> &mut SimulatorRng {
+        (**self).rng()
+    }
+
+    fn apply_gate(&mut self, instr: &QasmInstruction) {
+        (**self).apply_gate(instr)
+    }
+
+    fn measure(&mut self, qubit: usize) -> usize {
+        (**self).measure(qubit)
+    }
+
+    fn measure_all(&mut self) -> String {
+        (**self).measure_all()
+    }
+
+    fn sample(&mut self, shots: usize) -> Vec<String> {
+        (**self).sample(shots)
+    }
+
+    fn is_deterministic(&self) -> bool {
+        (**self).is_deterministic()
+    }
+
+    fn reset(&mut self, qubit: usize) {
+        (**self).reset(qubit)
+    }
+
+    fn reset_all(&mut self) {
+        (**self).reset_all()
+    }
+
+    fn apply_channel(&mut self, kraus: &[Array2<Complex64>], qubits: &[usize]) {
+        (**self).apply_channel(kraus, qubits)
+    }
+
+    fn check_support(&self, instructions: &[QasmInstruction]) -> Result<(), QucomError> {
+        (**self).check_support(instructions)
+    }
+
+    fn delay(&mut self, duration: f64, unit: &str, qubit: usize) {
+        (**self).delay(duration, unit, qubit)
+    }
+}
@@ -0,0 +1,383 @@
+use ndarray::{s, Array2, Array3, ArrayView2};
+use num_complex::Complex64;
+use rand::Rng;
+use crate::gates::{swap, GateOperation};
+use crate::error::QucomError;
+use crate::qasm::generator::QasmInstruction;
+use crate::qasm::QasmExecutor;
+use crate::simulators::{Simulator, SimulatorRng};
+use crate::utils::svd;
+
+/// bond dimension and truncation settings for `MpsSimulator`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MpsConfig {
+    /// largest bond dimension kept after a two-qubit gate
+    pub max_bond_dimension: usize,
+    /// Schmidt weights (σ²) below this are discarded
+    pub truncation_threshold: f64,
+}
+
+impl Default for MpsConfig {
+    fn default() -> Self {
+        Self { max_bond_dimension: 64, truncation_threshold: 1e-12 }
+    }
+}
+
+/// matrix product state simulator for low-entanglement circuits
+///
+/// site q holds a (left bond, 2, right bond) tensor for qubit q. the state is kept in mixed
+/// canonical form around `center`, so the singular values of a two-qubit update are the true
+/// Schmidt coefficients and the discarded weight is the exact truncation error.
+/// non-adjacent two-qubit gates are routed through a swap network.
+#[derive(Debug, Clone)]
+pub struct MpsSimulator {
+    n_qubits: usize,
+    tensors: Vec<Array3<Complex64>>,
+    center: usize,
+    config: MpsConfig,
+    truncation_error: f64,
+    rng: SimulatorRng,
+}
+
+impl MpsSimulator {
+    /// start in |0...0⟩ with the default configuration
+    pub fn new(n_qubits: usize) -> Self {
+        Self::with_config(n_qubits, MpsConfig::default())
+    }
+
+    pub fn with_config(n_qubits: usize, config: MpsConfig) -> Self {
+        assert!(config.max_bond_dimension >= 1, "bond dimension must be at least 1");
+        let mut site = Array3::<Complex64>::zeros((1, 2, 1));
+        site[[0, 0, 0]] = Complex64::new(1.0, 0.0);
+        Self {
+            n_qubits,
+            tensors: vec![site; n_qubits],
+            center: 0,
+            config,
+            truncation_error: 0.0,
+            rng: SimulatorRng::default(),
+        }
+    }
+
+    /// execute QASM instructions, returning the measurement results
+    pub fn execute(&mut self, instructions: &[QasmInstruction]) -> Result<Vec<String>, QucomError> {
+        QasmExecutor::run(instructions, self)
+    }
+
+    pub fn config(&self) -> MpsConfig {
+        self.config
+    }
+
+    /// total Schmidt weight discarded so far (a bound on 1 - fidelity)
+    pub fn truncation_error(&self) -> f64 {
+        self.truncation_error
+    }
+
+    /// bond dimension between every pair of neighbouring qubits
+    pub fn bond_dimensions(&self) -> Vec<usize> {
+        self.tensors.iter().skip(1).map(|t| t.dim().0).collect()
+    }
+
+    /// amplitude ⟨bits|ψ⟩ for a bitstring with qubit 0 first
+    pub fn amplitude(&self, bits: &str) -> Complex64 {
+        assert_eq!(bits.len(), self.n_qubits, "bitstring length must match number of qubits");
+        let mut row = Array2::<Complex64>::ones((1, 1));
+        for (tensor, bit) in self.tensors.iter().zip(bits.chars()) {
+            let s = if bit == '1' { 1 } else { 0 };
+            row = row.dot(&physical(tensor, s));
+        }
+        row[[0, 0]]
+    }
+
+    /// contract into a dense 2^n statevector (only sensible for small n)
+    pub fn to_statevector(&self) -> Array2<Complex64> {
+        let mut state = Array2::<Complex64>::ones((1, 1));
+        for tensor in &self.tensors {
+            let (l, _, r) = tensor.dim();
+            let rows = state.nrows();
+            let mut next = Array2::<Complex64>::zeros((rows * 2, r));
+            for s in 0..2 {
+                let part = state.dot(&physical(tensor, s));
+                debug_assert_eq!(part.ncols(), r);
+                for i in 0..rows {
+                    next.row_mut(i * 2 + s).assign(&part.row(i));
+                }
+            }
+            debug_assert_eq!(l, state.ncols());
+            state = next;
+        }
+        state
+    }
+
+    fn apply_single(&mut self, gate: &Array2<Complex64>, q: usize) {
+        let tensor = &self.tensors[q];
+        let mut updated = Array3::<Complex64>::zeros(tensor.dim());
+        for s in 0..2 {
+            for t in 0..2 {
+                let g = gate[[s, t]];
+                let mut out = updated.slice_mut(s![.., s, ..]);
+                out.scaled_add(g, &tensor.slice(s![.., t, ..]));
+            }
+        }
+        self.tensors[q] = updated;
+    }
+
+    /// move the orthogonality center to `site` with exact (untruncated) splits
+    fn move_center(&mut self, site: usize) {
+        while self.center < site {
+            let i = self.center;
+            let (l, _, r) = self.tensors[i].dim();
+            let matrix = self.tensors[i].clone().into_shape_with_order((l * 2, r)).unwrap();
+            let (u, sigma, vt) = svd(&matrix);
+            let k = Self::nonzero(&sigma);
+            self.tensors[i] = u.slice(s![.., ..k]).to_owned().into_shape_with_order((l, 2, k)).unwrap();
+            let carry = Self::scale_rows(&vt.slice(s![..k, ..]).to_owned(), &sigma[..k]);
+            self.tensors[i + 1] = Self::contract_left(&carry, &self.tensors[i + 1]);
+            self.center += 1;
+        }
+        while self.center > site {
+            let i = self.center;
+            let (l, _, r) = self.tensors[i].dim();
+            let matrix = self.tensors[i].clone().into_shape_with_order((l, 2 * r)).unwrap();
+            let (u, sigma, vt) = svd(&matrix);
+            let k = Self::nonzero(&sigma);
+            self.tensors[i] = vt.slice(s![..k, ..]).to_owned().into_shape_with_order((k, 2, r)).unwrap();
+            let carry = Self::scale_columns(&u.slice(s![.., ..k]).to_owned(), &sigma[..k]);
+            self.tensors[i - 1] = Self::contract_right(&self.tensors[i - 1], &carry);
+            self.center -= 1;
+        }
+    }
+
+    /// apply a 4x4 gate to sites (left, left + 1), indexed |left, left+1⟩
+    fn apply_adjacent(&mut self, gate: &Array2<Complex64>, left: usize) {
+        self.move_center(left);
+        let (l, _, _) = self.tensors[left].dim();
+        let (_, _, r) = self.tensors[left + 1].dim();
+
+        // θ[a, s1 s2, b] = Σ_m A[a, s1, m] B[m, s2, b], then apply the gate on (s1 s2)
+        let mut theta = Array3::<Complex64>::zeros((l, 4, r));
+        for s1 in 0..2 {
+            for s2 in 0..2 {
+                let pair = physical(&self.tensors[left], s1).dot(&physical(&self.tensors[left + 1], s2));
+                theta.slice_mut(s![.., s1 * 2 + s2, ..]).assign(&pair);
+            }
+        }
+        let mut gated = Array3::<Complex64>::zeros((l, 4, r));
+        for out in 0..4 {
+            for inp in 0..4 {
+                let g = gate[[out, inp]];
+                if g != Complex64::new(0.0, 0.0) {
+                    gated.slice_mut(s![.., out, ..]).scaled_add(g, &theta.slice(s![.., inp, ..]));
+                }
+            }
+        }
+
+        let matrix = gated.into_shape_with_order((l * 2, 2 * r)).unwrap();
+        let (u, sigma, vt) = svd(&matrix);
+
+        // keep the largest Schmidt values allowed by the bond dimension and threshold
+        let total: f64 = sigma.iter().map(|s| s * s).sum();
+        let mut k = sigma
+            .iter()
+            .take(self.config.max_bond_dimension)
+            .take_while(|s| *s * *s / total >= self.config.truncation_threshold)
+            .count()
+            .max(1);
+        k = k.min(sigma.len());
+        let kept: f64 = sigma[..k].iter().map(|s| s * s).sum();
+        self.truncation_error += (total - kept) / total;
+
+        let norm = kept.sqrt();
+        let sigma: Vec<f64> = sigma[..k].iter().map(|s| s / norm).collect();
+        self.tensors[left] = u.slice(s![.., ..k]).to_owned().into_shape_with_order((l, 2, k)).unwrap();
+        let right = Self::scale_rows(&vt.slice(s![..k, ..]).to_owned(), &sigma);
+        self.tensors[left + 1] = right.into_shape_with_order((k, 2, r)).unwrap();
+        self.center = left + 1;
+    }
+
+    /// apply a 4x4 gate indexed |a b⟩ to any two qubits, swapping a next to b and back
+    fn apply_two(&mut self, gate: &Array2<Complex64>, a: usize, b: usize) {
+        let swap_gate = swap();
+        if a < b {
+            for site in a..b - 1 {
+                self.apply_adjacent(&swap_gate, site);
+            }
+            self.apply_adjacent(gate, b - 1);
+            for site in (a..b - 1).rev() {
+                self.apply_adjacent(&swap_gate, site);
+            }
+        } else {
+            for site in (b + 1..a).rev() {
+                self.apply_adjacent(&swap_gate, site);
+            }
+            // a now sits to the right of b, so reorder the gate to |b a⟩
+            let reordered = swap_gate.dot(gate).dot(&swap_gate);
+            self.apply_adjacent(&reordered, b);
+            for site in b + 1..a {
+                self.apply_adjacent(&swap_gate, site);
+            }
+        }
+    }
+
+    fn qubit_probabilities(&mut self, q: usize) -> (f64, f64) {
+        self.move_center(q);
+        let weight = |s: usize| self.tensors[q].slice(s![.., s, ..]).iter().map(|c| c.norm_sqr()).sum::<f64>();
+        (weight(0), weight(1))
+    }
+
+    fn measure_qubit<R: Rng + ?Sized>(&mut self, q: usize, rng: &mut R) -> usize {
+        let (p0, p1) = self.qubit_probabilities(q);
+        let outcome = if rng.random::<f64>() * (p0 + p1) < p0 { 0 } else { 1 };
+        let probability = if outcome == 0 { p0 } else { p1 };
+
+        // project the center tensor, which keeps the state normalized and canonical
+        let scale = Complex64::new(1.0 / probability.sqrt(), 0.0);
+        let tensor = &mut self.tensors[q];
+        tensor.slice_mut(s![.., 1 - outcome, ..]).fill(Complex64::new(0.0, 0.0));
+        tensor.slice_mut(s![.., outcome, ..]).mapv_inplace(|c| c * scale);
+        outcome
+    }
+
+    fn nonzero(sigma: &[f64]) -> usize {
+        let largest = sigma.first().copied().unwrap_or(0.0);
+        sigma.iter().take_while(|s| **s > largest * 1e-14).count().max(1)
+    }
+
+    fn scale_rows(matrix: &Array2<Complex64>, sigma: &[f64]) -> Array2<Complex64> {
+        let mut scaled = matrix.clone();
+        for (mut row, s) in scaled.rows_mut().into_iter().zip(sigma) {
+            row.mapv_inplace(|c| c * *s);
+        }
+        scaled
+    }
+
+    fn scale_columns(matrix: &Array2<Complex64>, sigma: &[f64]) -> Array2<Complex64> {
+        let mut scaled = matrix.clone();
+        for (mut column, s) in scaled.columns_mut().into_iter().zip(sigma) {
+            column.mapv_inplace(|c| c * *s);
+        }
+        scaled
+    }
+
+    /// M · T over T's left bond
+    fn contract_left(matrix: &Array2<Complex64>, tensor: &Array3<Complex64>) -> Array3<Complex64> {
+        let (_, _, r) = tensor.dim();
+        let mut out = Array3::<Complex64>::zeros((matrix.nrows(), 2, r));
+        for s in 0..2 {
+            out.slice_mut(s![.., s, ..]).assign(&matrix.dot(&physical(tensor, s)));
+        }
+        out
+    }
+
+    /// T · M over T's right bond
+    fn contract_right(tensor: &Array3<Complex64>, matrix: &Array2<Complex64>) -> Array3<Complex64> {
+        let (l, _, _) = tensor.dim();
+        let mut out = Array3::<Complex64>::zeros((l, 2, matrix.ncols()));
+        for s in 0..2 {
+            out.slice_mut(s![.., s, ..]).assign(&physical(tensor, s).dot(matrix));
+        }
+        out
+    }
+}
+
+/// the (left bond, right bond) matrix of a site tensor for physical index `s`
+fn physical(tensor: &Array3<Complex64>, s: usize) -> ArrayView2<'_, Complex64> {
+    tensor.slice(s![.., s, ..])
+}
+
+impl Simulator for MpsSimulator {
+    fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    /// every gate must touch at most two qubits once custom gates are expanded (CCX is decomposed)
+    fn check_support(&self, instructions: &[QasmInstruction]) -> Result<(), QucomError> {
+        for instr in instructions {
+            match instr {
+                QasmInstruction::If(_, body) | QasmInstruction::While(_, body)
+                | QasmInstruction::For(_, _, body) => self.check_support(body)?,
+                QasmInstruction::Switch(_, cases, default) => {
+                    for (_, body) in cases {
+                        self.check_support(body)?;
+                    }
+                    self.check_support(default)?;
+                }
+                QasmInstruction::IfElse(_, if_block, else_block) => {
+                    self.check_support(if_block)?;
+                    self.check_support(else_block)?;
+                }
+                QasmInstruction::CCX(_) => {}
+                other => match other.expand() {
+                    Some(body) => self.check_support(&body)?,
+                    None => {
+                        if GateOperation::from_instruction(other).is_some_and(|op| op.qubits().len() > 2) {
+                            return Err(QucomError::Unsupported(format!(
+                                "the MPS backend runs gates on at most two qubits, got `{}`", other
+                            )));
+                        }
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn rng(&mut self) -> &mut SimulatorRng {
+        &mut self.rng
+    }
+
+    fn apply_gate(&mut self, instr: &QasmInstruction) {
+        if let QasmInstruction::CCX(qs) = instr {
+            // standard 6-CNOT Toffoli decomposition, so only 1- and 2-qubit gates reach the MPS
+            let (a, b, c) = (qs[0], qs[1], qs[2]);
+            let decomposition = [
+                QasmInstruction::H(c), QasmInstruction::CX(b, c), QasmInstruction::T(c, true),
+                QasmInstruction::CX(a, c), QasmInstruction::T(c, false), QasmInstruction::CX(b, c),
+                QasmInstruction::T(c, true), QasmInstruction::CX(a, c), QasmInstruction::T(b, false),
+                QasmInstruction::T(c, false), QasmInstruction::H(c), QasmInstruction::CX(a, b),
+                QasmInstruction::T(a, false), QasmInstruction::T(b, true), QasmInstruction::CX(a, b),
+            ];
+            for gate in &decomposition {
+                self.apply_gate(gate);
+            }
+            return;
+        }
+
+        let op = GateOperation::from_instruction(instr)
+            .unwrap_or_else(|| panic!("not a gate instruction: {}", instr));
+        match op.qubits().as_slice() {
+            [q] => self.apply_single(&op.matrix, *q),
+            [a, b] => self.apply_two(&op.full_matrix(), *a, *b),
+            _ => panic!("MPS backend supports gates on at most two qubits, got: {}", instr),
+        }
+    }
+
+    fn measure(&mut self, qubit: usize) -> usize {
+        let mut rng = self.rng.clone();
+        self.measure_qubit(qubit, &mut rng)
+    }
+
+    /// sample qubit by qubit on a copy, so like the statevector backend the state is not collapsed
+    fn measure_all(&mut self) -> String {
+        let mut copy = self.clone();
+        (0..self.n_qubits)
+            .map(|q| if copy.measure_qubit(q, &mut self.rng) == 1 { '1' } else { '0' })
+            .collect()
+    }
+
+    fn reset(&mut self, qubit: usize) {
+        if self.measure(qubit) == 1 {
+            self.apply_gate(&QasmInstruction::X(qubit));
+        }
+    }
+
+    fn reset_all(&mut self) {
+        let rng = self.rng.clone();
+        *self = Self::with_config(self.n_qubits, self.config);
+        self.rng = rng;
+    }
+
+    fn apply_channel(&mut self, _kraus: &[Array2<Complex64>], _qubits: &[usize]) {
+        panic!("MPS backend does not support noise channels");
+    }
+}
@@ -0,0 +1,100 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::error::QucomError;
+use crate::noise::NoiseModel;
+use crate::qasm::generator::QasmInstruction;
+use crate::simulators::{Simulator, SimulatorRng};
+
+/// wraps a backend and applies the errors of a `NoiseModel` around every instruction
+/// on a statevector this samples one trajectory, on a density matrix it is exact
+#[derive(Clone)]
+pub struct NoisySimulator<'a, S: Simulator> {
+    inner: S,
+    noise: &'a NoiseModel,
+}
+
+impl<'a, S: Simulator> NoisySimulator<'a, S> {
+    pub fn new(inner: S, noise: &'a NoiseModel) -> Self {
+        Self { inner, noise }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn apply_errors(&mut self, name: &str, qubits: &[usize]) {
+        for (channel, targets) in self.noise.channels_for(name, qubits) {
+            self.inner.apply_channel(channel.kraus(), &targets);
+        }
+    }
+
+    fn read_out(&mut self, qubit: usize, outcome: usize) -> usize {
+        match self.noise.readout_error_for(qubit) {
+            Some(error) => error.apply(outcome, self.inner.rng()),
+            None => outcome,
+        }
+    }
+}
+
+impl<S: Simulator> Simulator for NoisySimulator<'_, S> {
+    fn n_qubits(&self) -> usize {
+        self.inner.n_qubits()
+    }
+
+    fn rng(&mut self) -> &mut SimulatorRng {
+        self.inner.rng()
+    }
+
+    fn apply_gate(&mut self, instr: &QasmInstruction) {
+        self.inner.apply_gate(instr);
+        self.apply_errors(instr.name(), &instr.qubits());
+    }
+
+    fn measure(&mut self, qubit: usize) -> usize {
+        self.apply_errors("measure", &[qubit]);
+        let outcome = self.inner.measure(qubit);
+        self.read_out(qubit, outcome)
+    }
+
+    fn measure_all(&mut self) -> String {
+        for q in 0..self.n_qubits() {
+            self.apply_errors("measure", &[q]);
+        }
+        self.inner
+            .measure_all()
+            .chars()
+            .enumerate()
+            .map(|(q, ch)| if self.read_out(q, (ch == '1') as usize) == 1 { '1' } else { '0' })
+            .collect()
+    }
+
+    /// channels and readout errors are drawn per shot, so shots never share a state
+    fn is_deterministic(&self) -> bool {
+        self.noise.is_ideal()
+    }
+
+    fn reset(&mut self, qubit: usize) {
+        self.inner.reset(qubit);
+        self.apply_errors("reset", &[qubit]);
+    }
+
+    fn reset_all(&mut self) {
+        self.inner.reset_all();
+        for q in 0..self.n_qubits() {
+            self.apply_errors("reset", &[q]);
+        }
+    }
+
+    fn apply_channel(&mut self, kraus: &[Array2<Complex64>], qubits: &[usize]) {
+        self.inner.apply_channel(kraus, qubits);
+    }
+
+    fn delay(&mut self, duration: f64, unit: &str, qubit: usize) {
+        self.inner.delay(duration, unit, qubit);
+        self.apply_errors("delay", &[qubit]);
+    }
+
+    fn check_support(&self, instructions: &[QasmInstruction]) -> Result<(), QucomError> {
+        self.inner.check_support(instructions)
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use rand::rngs::StdRng;
+use rand::{rng, RngCore, SeedableRng};
+
+/// handle to the random stream a simulator draws measurement outcomes and trajectories from
+///
+/// clones share one stream, so copies of a simulator (the per-shot copies in
+/// `QasmExecutor::run_shots`, the sampling copies in `measure_all`) keep consuming a single
+/// sequence. seeded streams use `StdRng`, which gives the same outcomes on native and wasm32
+#[derive(Clone)]
+pub struct SimulatorRng(Arc<Mutex<dyn RngCore + Send>>);
+
+impl SimulatorRng {
+    /// fresh stream seeded from the thread-local generator
+    pub fn from_entropy() -> Self {
+        Self::from_rng(StdRng::from_rng(&mut rng()))
+    }
+
+    /// reproducible stream: equal seeds give equal measurement results
+    pub fn seed_from_u64(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    /// draw from a user-supplied generator
+    pub fn from_rng<R: RngCore + Send + 'static>(rng: R) -> Self {
+        Self(Arc::new(Mutex::new(rng)))
+    }
+}
+
+impl Default for SimulatorRng {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+impl fmt::Debug for SimulatorRng {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SimulatorRng")
+    }
+}
+
+impl RngCore for SimulatorRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.lock().unwrap().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.lock().unwrap().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.lock().unwrap().fill_bytes(dest)
+    }
+}
@@ -0,0 +1,289 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use rand::Rng;
+use crate::error::QucomError;
+use crate::qasm::generator::QasmInstruction;
+use crate::qasm::QasmExecutor;
+use crate::simulators::{Simulator, SimulatorRng};
+
+/// Clifford simulator on an Aaronson–Gottesman tableau, O(n) per gate and O(n²) per measurement
+///
+/// rows 0..n are destabilizers, rows n..2n stabilizers and row 2n is scratch space.
+/// every row is a Pauli string stored as packed x and z bits plus a sign bit.
+#[derive(Debug, Clone)]
+pub struct StabilizerSimulator {
+    n_qubits: usize,
+    words: usize,
+    x: Vec<Vec<u64>>,
+    z: Vec<Vec<u64>>,
+    r: Vec<bool>,
+    rng: SimulatorRng,
+}
+
+impl StabilizerSimulator {
+    /// start in |0...0⟩, stabilized by Z on every qubit
+    pub fn new(n_qubits: usize) -> Self {
+        let words = n_qubits.div_ceil(64).max(1);
+        let rows = 2 * n_qubits + 1;
+        let mut sim = Self {
+            n_qubits,
+            words,
+            x: vec![vec![0; words]; rows],
+            z: vec![vec![0; words]; rows],
+            r: vec![false; rows],
+            rng: SimulatorRng::default(),
+        };
+        for q in 0..n_qubits {
+            sim.set_x(q, q, true);
+            sim.set_z(q + n_qubits, q, true);
+        }
+        sim
+    }
+
+    /// whether every instruction (including control-flow bodies) is a Clifford operation
+    pub fn supports(instructions: &[QasmInstruction]) -> bool {
+        instructions.iter().all(|instr| match instr {
+            QasmInstruction::H(_) | QasmInstruction::X(_) | QasmInstruction::Y(_) | QasmInstruction::Z(_)
+            | QasmInstruction::S(_, _) | QasmInstruction::CX(_, _) | QasmInstruction::CZ(_, _)
+            | QasmInstruction::Swap(_, _) | QasmInstruction::Measure(_, _) | QasmInstruction::MeasureAll
+            | QasmInstruction::Reset(_) | QasmInstruction::ResetAll | QasmInstruction::Barrier(_)
+            | QasmInstruction::BarrierAll | QasmInstruction::Delay(_, _, _) | QasmInstruction::Assign(_, _)
+            | QasmInstruction::Break | QasmInstruction::Continue | QasmInstruction::End => true,
+            QasmInstruction::If(_, body) | QasmInstruction::While(_, body)
+            | QasmInstruction::For(_, _, body) => Self::supports(body),
+            QasmInstruction::Switch(_, cases, default) => {
+                cases.iter().all(|(_, body)| Self::supports(body)) && Self::supports(default)
+            }
+            QasmInstruction::IfElse(_, if_block, else_block) => {
+                Self::supports(if_block) && Self::supports(else_block)
+            }
+            QasmInstruction::GateCall(definition, args, qubits) => {
+                Self::supports(&definition.instantiate(args, qubits))
+            }
+            _ => false,
+        })
+    }
+
+    /// execute QASM instructions, returning the measurement results
+    pub fn execute(&mut self, instructions: &[QasmInstruction]) -> Result<Vec<String>, QucomError> {
+        QasmExecutor::run(instructions, self)
+    }
+
+    /// current stabilizer generators as signed Pauli strings, e.g. "+XX" and "+ZZ" for a Bell pair
+    pub fn stabilizers(&self) -> Vec<String> {
+        (self.n_qubits..2 * self.n_qubits)
+            .map(|row| {
+                let sign = if self.r[row] { '-' } else { '+' };
+                let paulis = (0..self.n_qubits).map(|q| match (self.x_bit(row, q), self.z_bit(row, q)) {
+                    (false, false) => 'I',
+                    (true, false) => 'X',
+                    (true, true) => 'Y',
+                    (false, true) => 'Z',
+                });
+                std::iter::once(sign).chain(paulis).collect()
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn x_bit(&self, row: usize, q: usize) -> bool {
+        (self.x[row][q / 64] >> (q % 64)) & 1 == 1
+    }
+
+    #[inline]
+    fn z_bit(&self, row: usize, q: usize) -> bool {
+        (self.z[row][q / 64] >> (q % 64)) & 1 == 1
+    }
+
+    #[inline]
+    fn set_x(&mut self, row: usize, q: usize, value: bool) {
+        let mask = 1u64 << (q % 64);
+        if value { self.x[row][q / 64] |= mask } else { self.x[row][q / 64] &= !mask }
+    }
+
+    #[inline]
+    fn set_z(&mut self, row: usize, q: usize, value: bool) {
+        let mask = 1u64 << (q % 64);
+        if value { self.z[row][q / 64] |= mask } else { self.z[row][q / 64] &= !mask }
+    }
+
+    fn rows(&self) -> std::ops::Range<usize> {
+        0..2 * self.n_qubits
+    }
+
+    fn hadamard(&mut self, q: usize) {
+        for row in self.rows() {
+            let (x, z) = (self.x_bit(row, q), self.z_bit(row, q));
+            self.r[row] ^= x && z;
+            self.set_x(row, q, z);
+            self.set_z(row, q, x);
+        }
+    }
+
+    fn phase(&mut self, q: usize) {
+        for row in self.rows() {
+            let (x, z) = (self.x_bit(row, q), self.z_bit(row, q));
+            self.r[row] ^= x && z;
+            self.set_z(row, q, z ^ x);
+        }
+    }
+
+    fn phase_dagger(&mut self, q: usize) {
+        for row in self.rows() {
+            let (x, z) = (self.x_bit(row, q), self.z_bit(row, q));
+            self.r[row] ^= x && !z;
+            self.set_z(row, q, z ^ x);
+        }
+    }
+
+    /// Pauli gates only flip signs: X anticommutes with Z, Z with X and Y with both
+    fn pauli(&mut self, q: usize, flip_on_x: bool, flip_on_z: bool) {
+        for row in self.rows() {
+            self.r[row] ^= (flip_on_x && self.x_bit(row, q)) ^ (flip_on_z && self.z_bit(row, q));
+        }
+    }
+
+    fn cnot(&mut self, control: usize, target: usize) {
+        for row in self.rows() {
+            let (xc, zc) = (self.x_bit(row, control), self.z_bit(row, control));
+            let (xt, zt) = (self.x_bit(row, target), self.z_bit(row, target));
+            self.r[row] ^= xc && zt && !(xt ^ zc);
+            self.set_x(row, target, xt ^ xc);
+            self.set_z(row, control, zc ^ zt);
+        }
+    }
+
+    fn swap_qubits(&mut self, a: usize, b: usize) {
+        for row in self.rows() {
+            let (xa, za) = (self.x_bit(row, a), self.z_bit(row, a));
+            let (xb, zb) = (self.x_bit(row, b), self.z_bit(row, b));
+            self.set_x(row, a, xb);
+            self.set_z(row, a, zb);
+            self.set_x(row, b, xa);
+            self.set_z(row, b, za);
+        }
+    }
+
+    /// row h ← row i · row h, tracking the sign through the Pauli products
+    fn rowsum(&mut self, h: usize, i: usize) {
+        // sum of the i^k phase exponents, taken mod 4 at the end
+        let mut phase: i64 = 2 * (self.r[h] as i64) + 2 * (self.r[i] as i64);
+        for w in 0..self.words {
+            let (x1, z1) = (self.x[i][w], self.z[i][w]);
+            let (x2, z2) = (self.x[h][w], self.z[h][w]);
+            let y1 = x1 & z1;
+            let x_only = x1 & !z1;
+            let z_only = !x1 & z1;
+            let plus = (y1 & z2 & !x2) | (x_only & z2 & x2) | (z_only & x2 & !z2);
+            let minus = (y1 & x2 & !z2) | (x_only & z2 & !x2) | (z_only & x2 & z2);
+            phase += plus.count_ones() as i64 - minus.count_ones() as i64;
+            self.x[h][w] ^= x1;
+            self.z[h][w] ^= z1;
+        }
+        self.r[h] = phase.rem_euclid(4) == 2;
+    }
+
+    fn copy_row(&mut self, to: usize, from: usize) {
+        self.x[to] = self.x[from].clone();
+        self.z[to] = self.z[from].clone();
+        self.r[to] = self.r[from];
+    }
+
+    fn measure_qubit<R: Rng + ?Sized>(&mut self, q: usize, rng: &mut R) -> usize {
+        let n = self.n_qubits;
+
+        // random outcome when some stabilizer anticommutes with Z_q
+        if let Some(p) = (n..2 * n).find(|&row| self.x_bit(row, q)) {
+            for row in self.rows() {
+                if row != p && self.x_bit(row, q) {
+                    self.rowsum(row, p);
+                }
+            }
+            self.copy_row(p - n, p);
+            self.x[p].iter_mut().for_each(|w| *w = 0);
+            self.z[p].iter_mut().for_each(|w| *w = 0);
+            self.set_z(p, q, true);
+            let outcome = rng.random::<bool>();
+            self.r[p] = outcome;
+            return outcome as usize;
+        }
+
+        // deterministic outcome, accumulated in the scratch row
+        let scratch = 2 * n;
+        self.x[scratch].iter_mut().for_each(|w| *w = 0);
+        self.z[scratch].iter_mut().for_each(|w| *w = 0);
+        self.r[scratch] = false;
+        for row in 0..n {
+            if self.x_bit(row, q) {
+                self.rowsum(scratch, row + n);
+            }
+        }
+        self.r[scratch] as usize
+    }
+}
+
+impl Simulator for StabilizerSimulator {
+    fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    fn check_support(&self, instructions: &[QasmInstruction]) -> Result<(), QucomError> {
+        match instructions.iter().find(|instr| !Self::supports(std::slice::from_ref(*instr))) {
+            Some(instr) => Err(QucomError::Unsupported(format!("the stabilizer backend only runs Clifford gates, got `{}`", instr))),
+            None => Ok(()),
+        }
+    }
+
+    fn rng(&mut self) -> &mut SimulatorRng {
+        &mut self.rng
+    }
+
+    fn apply_gate(&mut self, instr: &QasmInstruction) {
+        match instr {
+            QasmInstruction::H(q) => self.hadamard(*q),
+            QasmInstruction::X(q) => self.pauli(*q, false, true),
+            QasmInstruction::Y(q) => self.pauli(*q, true, true),
+            QasmInstruction::Z(q) => self.pauli(*q, true, false),
+            QasmInstruction::S(q, false) => self.phase(*q),
+            QasmInstruction::S(q, true) => self.phase_dagger(*q),
+            QasmInstruction::CX(c, t) => self.cnot(*c, *t),
+            QasmInstruction::CZ(c, t) => {
+                self.hadamard(*t);
+                self.cnot(*c, *t);
+                self.hadamard(*t);
+            }
+            QasmInstruction::Swap(a, b) => self.swap_qubits(*a, *b),
+            other => panic!("stabilizer backend only supports Clifford gates, got: {}", other),
+        }
+    }
+
+    fn measure(&mut self, qubit: usize) -> usize {
+        let mut rng = self.rng.clone();
+        self.measure_qubit(qubit, &mut rng)
+    }
+
+    /// sample every qubit on a copy of the tableau, so like the statevector backend
+    /// the state is not collapsed
+    fn measure_all(&mut self) -> String {
+        let mut copy = self.clone();
+        (0..self.n_qubits)
+            .map(|q| if copy.measure_qubit(q, &mut self.rng) == 1 { '1' } else { '0' })
+            .collect()
+    }
+
+    fn reset(&mut self, qubit: usize) {
+        if self.measure(qubit) == 1 {
+            self.pauli(qubit, false, true);
+        }
+    }
+
+    fn reset_all(&mut self) {
+        let rng = self.rng.clone();
+        *self = Self::new(self.n_qubits);
+        self.rng = rng;
+    }
+
+    fn apply_channel(&mut self, _kraus: &[Array2<Complex64>], _qubits: &[usize]) {
+        panic!("stabilizer backend does not support noise channels");
+    }
+}
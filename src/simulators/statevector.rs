@@ -0,0 +1,193 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use rand_distr::weighted::WeightedIndex;
+use rand_distr::Distribution;
+use crate::gates::*;
+use crate::kernels;
+use crate::error::QucomError;
+use crate::qasm::generator::QasmInstruction;
+use crate::qasm::QasmExecutor;
+use crate::simulators::{Simulator, SimulatorRng};
+use crate::states::multi_qubit::zero_state;
+
+/// pure statevector backend, 2^n amplitudes updated in place by the strided kernels
+#[derive(Debug, Clone)]
+pub struct StatevectorSimulator {
+    state: Array2<Complex64>,
+    n_qubits: usize,
+    rng: SimulatorRng,
+}
+
+impl StatevectorSimulator {
+    /// start in |0...0⟩
+    pub fn new(n_qubits: usize) -> Self {
+        Self::from_state(zero_state(n_qubits), n_qubits)
+    }
+
+    /// start from an existing 2^n x 1 statevector
+    pub fn from_state(state: Array2<Complex64>, n_qubits: usize) -> Self {
+        assert_eq!(state.dim(), (1 << n_qubits, 1), "statevector must be 2^n x 1");
+        Self { state, n_qubits, rng: SimulatorRng::default() }
+    }
+
+    /// execute QASM instructions, returning the measurement results
+    pub fn execute(&mut self, instructions: &[QasmInstruction]) -> Result<Vec<String>, QucomError> {
+        QasmExecutor::run(instructions, self)
+    }
+
+    pub fn state(&self) -> &Array2<Complex64> {
+        &self.state
+    }
+
+    pub fn into_state(self) -> Array2<Complex64> {
+        self.state
+    }
+}
+
+impl Simulator for StatevectorSimulator {
+    fn n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    fn rng(&mut self) -> &mut SimulatorRng {
+        &mut self.rng
+    }
+
+    fn apply_gate(&mut self, instr: &QasmInstruction) {
+        match instr {
+            QasmInstruction::H(q) => {
+                kernels::apply_single_qubit(&mut self.state, &hadamard(), *q, self.n_qubits);
+            }
+            QasmInstruction::X(q) => {
+                kernels::apply_single_qubit(&mut self.state, &pauli_x(), *q, self.n_qubits);
+            }
+            QasmInstruction::Y(q) => {
+                kernels::apply_single_qubit(&mut self.state, &pauli_y(), *q, self.n_qubits);
+            }
+            QasmInstruction::Z(q) => {
+                kernels::apply_single_qubit(&mut self.state, &pauli_z(), *q, self.n_qubits);
+            }
+            QasmInstruction::S(q, false) => {
+                kernels::apply_single_qubit(&mut self.state, &s(), *q, self.n_qubits);
+            }
+            QasmInstruction::S(q, true) => {
+                kernels::apply_single_qubit(&mut self.state, &sdg(), *q, self.n_qubits);
+            }
+            QasmInstruction::T(q, false) => {
+                kernels::apply_single_qubit(&mut self.state, &t(), *q, self.n_qubits);
+            }
+            QasmInstruction::T(q, true) => {
+                kernels::apply_single_qubit(&mut self.state, &tdg(), *q, self.n_qubits);
+            }
+            QasmInstruction::CX(control, target) => {
+                kernels::apply_controlled(&mut self.state, &pauli_x(), &[*control], &[*target], self.n_qubits);
+            }
+            QasmInstruction::CCX(qs) => {
+                if qs.len() != 3 {
+                    panic!("CCX instruction requires exactly 3 qubits");
+                }
+                kernels::apply_controlled(&mut self.state, &pauli_x(), &qs[..2], &qs[2..], self.n_qubits);
+            }
+            QasmInstruction::CZ(control, target) => {
+                kernels::apply_controlled(&mut self.state, &pauli_z(), &[*control], &[*target], self.n_qubits);
+            }
+            QasmInstruction::Swap(q1, q2) => {
+                kernels::apply_swap(&mut self.state, *q1, *q2, self.n_qubits);
+            }
+            QasmInstruction::Rx(angle, q) => {
+                kernels::apply_single_qubit(&mut self.state, &rx(*angle), *q, self.n_qubits);
+            }
+            QasmInstruction::Ry(angle, q) => {
+                kernels::apply_single_qubit(&mut self.state, &ry(*angle), *q, self.n_qubits);
+            }
+            QasmInstruction::Rz(angle, q) => {
+                kernels::apply_single_qubit(&mut self.state, &rz(*angle), *q, self.n_qubits);
+            }
+            QasmInstruction::Phase(angle, q) => {
+                kernels::apply_single_qubit(&mut self.state, &phase(*angle), *q, self.n_qubits);
+            }
+            QasmInstruction::U(theta, phi, lambda, q) => {
+                kernels::apply_single_qubit(&mut self.state, &u_gate(*theta, *phi, *lambda), *q, self.n_qubits);
+            }
+            QasmInstruction::Unitary(matrix, qs) => match qs.as_slice() {
+                [q] => kernels::apply_single_qubit(&mut self.state, matrix, *q, self.n_qubits),
+                [q0, q1] => kernels::apply_two_qubit(&mut self.state, matrix, *q0, *q1, self.n_qubits),
+                qubits => kernels::apply_multi_qubit(&mut self.state, matrix, qubits, self.n_qubits),
+            },
+            QasmInstruction::Controlled(_, _, _) => {
+                let op = GateOperation::from_instruction(instr)
+                    .unwrap_or_else(|| panic!("not a gate instruction: {}", instr));
+                op.apply(&mut self.state, self.n_qubits);
+            }
+            other => panic!("not a gate instruction: {}", other),
+        }
+    }
+
+    /// measure a single qubit
+    fn measure(&mut self, qubit_index: usize) -> usize {
+        let (p0, p1) = kernels::qubit_probabilities(&self.state, qubit_index, self.n_qubits);
+
+        let dist = WeightedIndex::new([p0, p1]).unwrap();
+        let outcome = dist.sample(&mut self.rng);
+
+        // collapse state
+        let probability = if outcome == 0 { p0 } else { p1 };
+        kernels::collapse(&mut self.state, qubit_index, outcome, probability, self.n_qubits);
+
+        outcome
+    }
+
+    /// measure all qubits
+    fn measure_all(&mut self) -> String {
+        let probs = kernels::probabilities(&self.state);
+        let dist = WeightedIndex::new(&probs).unwrap();
+        let idx = dist.sample(&mut self.rng);
+        format!("{:0width$b}", idx, width = self.n_qubits)
+    }
+
+    /// one probability vector shared by every sample
+    fn sample(&mut self, shots: usize) -> Vec<String> {
+        let probs = kernels::probabilities(&self.state);
+        let dist = WeightedIndex::new(&probs).unwrap();
+        (0..shots)
+            .map(|_| format!("{:0width$b}", dist.sample(&mut self.rng), width = self.n_qubits))
+            .collect()
+    }
+    
+    /// reset qubit to |0⟩ state
+    fn reset(&mut self, qubit_index: usize) {
+        // measure the qubit
+        let measurement = self.measure(qubit_index);
+        
+        // if measured |1⟩, apply X to flip it to |0⟩
+        if measurement == 1 {
+            kernels::apply_single_qubit(&mut self.state, &pauli_x(), qubit_index, self.n_qubits);
+        }
+    }
+
+    fn reset_all(&mut self) {
+        self.state = crate::states::multi_qubit::zero_state(self.n_qubits);
+    }
+
+    /// pick one Kraus operator with probability ‖Kψ‖² and renormalize (quantum trajectory)
+    fn apply_channel(&mut self, kraus: &[Array2<Complex64>], qubits: &[usize]) {
+        let branches: Vec<Array2<Complex64>> = kraus
+            .iter()
+            .map(|k| {
+                let mut branch = self.state.clone();
+                kernels::apply_multi_qubit(&mut branch, k, qubits, self.n_qubits);
+                branch
+            })
+            .collect();
+        let weights: Vec<f64> = branches
+            .iter()
+            .map(|b| kernels::probabilities(b).iter().sum())
+            .collect();
+
+        let dist = WeightedIndex::new(&weights).unwrap();
+        let choice = dist.sample(&mut self.rng);
+
+        let norm = weights[choice].sqrt();
+        self.state = branches[choice].mapv(|c| c / norm);
+    }
+}
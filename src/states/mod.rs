@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod states;
 pub mod superpositions;
 pub mod multi_qubit;
@@ -1,3 +1,5 @@
 pub mod kronecker;
+pub mod svd;
 
 pub use kronecker::kron;
+pub use svd::svd;
@@ -0,0 +1,77 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+
+/// thin singular value decomposition A = U Σ V†, singular values in descending order
+/// one-sided Jacobi: rotate column pairs of A until they are mutually orthogonal
+/// returns (U: m x k, σ: k, V†: k x n) with k = min(m, n)
+pub fn svd(a: &Array2<Complex64>) -> (Array2<Complex64>, Vec<f64>, Array2<Complex64>) {
+    let (m, n) = a.dim();
+    if m < n {
+        // work on A† so there are never more columns than rows
+        let adjoint = |m: &Array2<Complex64>| m.t().as_standard_layout().mapv(|c| c.conj());
+        let (u, s, vt) = svd(&adjoint(a));
+        return (adjoint(&vt), s, adjoint(&u));
+    }
+
+    let mut w = a.clone();
+    let mut v = Array2::<Complex64>::eye(n);
+    let eps = 1e-15;
+
+    for _ in 0..100 {
+        let mut rotated = false;
+        for i in 0..n {
+            for j in i + 1..n {
+                let alpha: f64 = w.column(i).iter().map(|c| c.norm_sqr()).sum();
+                let beta: f64 = w.column(j).iter().map(|c| c.norm_sqr()).sum();
+                let gamma: Complex64 = w.column(i).iter().zip(w.column(j).iter()).map(|(x, y)| x.conj() * y).sum();
+                let g = gamma.norm();
+                if g <= eps * (alpha * beta).sqrt() || g < 1e-300 {
+                    continue;
+                }
+                rotated = true;
+
+                // real Jacobi rotation on (a_i, e^{-iφ} a_j) where γ = |γ| e^{iφ}
+                let phase = gamma / g;
+                let zeta = (beta - alpha) / (2.0 * g);
+                let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                let c = 1.0 / (1.0 + t * t).sqrt();
+                let s = c * t;
+
+                for mat in [&mut w, &mut v] {
+                    for r in 0..mat.nrows() {
+                        let (x, y) = (mat[[r, i]], mat[[r, j]]);
+                        mat[[r, i]] = x * c - y * phase.conj() * s;
+                        mat[[r, j]] = x * phase * s + y * c;
+                    }
+                }
+            }
+        }
+        if !rotated {
+            break;
+        }
+    }
+
+    let norms: Vec<f64> = (0..n)
+        .map(|j| w.column(j).iter().map(|c| c.norm_sqr()).sum::<f64>().sqrt())
+        .collect();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&x, &y| norms[y].total_cmp(&norms[x]));
+
+    let mut u = Array2::<Complex64>::zeros((m, n));
+    let mut vt = Array2::<Complex64>::zeros((n, n));
+    let mut sigma = Vec::with_capacity(n);
+    for (k, &j) in order.iter().enumerate() {
+        let s = norms[j];
+        sigma.push(s);
+        if s > 1e-300 {
+            for r in 0..m {
+                u[[r, k]] = w[[r, j]] / s;
+            }
+        }
+        for c in 0..n {
+            vt[[k, c]] = v[[c, j]].conj();
+        }
+    }
+
+    (u, sigma, vt)
+}
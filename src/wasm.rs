@@ -1,6 +1,9 @@
 use wasm_bindgen::prelude::*;
 use crate::circuits::QuantumCircuit as RustQuantumCircuit;
+use crate::observables::SparsePauliOp;
+use crate::simulators::Backend;
 
+/// every fallible method throws a JS `Error` carrying the `QucomError` message
 #[wasm_bindgen]
 pub struct QuantumCircuit {
     inner: RustQuantumCircuit,
@@ -9,185 +12,171 @@ pub struct QuantumCircuit {
 #[wasm_bindgen]
 impl QuantumCircuit {
     #[wasm_bindgen(constructor)]
-    pub fn new(n_qubits: usize) -> Result<QuantumCircuit, JsValue> {
+    pub fn new(n_qubits: usize) -> Result<QuantumCircuit, JsError> {
         if n_qubits == 0 {
-            return Err(JsValue::from_str("Number of qubits must be greater than 0"));
+            return Err(JsError::new("Number of qubits must be greater than 0"));
         }
         if n_qubits > 30 {
-            return Err(JsValue::from_str("Number of qubits exceeds maximum (30) for simulation"));
+            return Err(JsError::new("Number of qubits exceeds maximum (30) for simulation"));
         }
         Ok(Self {
             inner: RustQuantumCircuit::new(n_qubits),
         })
     }
-    pub fn h(&mut self, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.h(qubit);
+    pub fn h(&mut self, qubit: usize) -> Result<(), JsError> {
+        self.inner.h(qubit)?;
         Ok(())
     }
-    pub fn x(&mut self, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.x(qubit);
+    pub fn x(&mut self, qubit: usize) -> Result<(), JsError> {
+        self.inner.x(qubit)?;
         Ok(())
     }
-    pub fn y(&mut self, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.y(qubit);
+    pub fn y(&mut self, qubit: usize) -> Result<(), JsError> {
+        self.inner.y(qubit)?;
         Ok(())
     }
-    pub fn z(&mut self, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.z(qubit);
+    pub fn z(&mut self, qubit: usize) -> Result<(), JsError> {
+        self.inner.z(qubit)?;
         Ok(())
     }
-    pub fn s(&mut self, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.s(qubit);
+    pub fn s(&mut self, qubit: usize) -> Result<(), JsError> {
+        self.inner.s(qubit)?;
         Ok(())
     }
-    pub fn sdg(&mut self, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.sdg(qubit);
+    pub fn sdg(&mut self, qubit: usize) -> Result<(), JsError> {
+        self.inner.sdg(qubit)?;
         Ok(())
     }
-    pub fn t(&mut self, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.t(qubit);
+    pub fn t(&mut self, qubit: usize) -> Result<(), JsError> {
+        self.inner.t(qubit)?;
         Ok(())
     }
-    pub fn tdg(&mut self, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.tdg(qubit);
+    pub fn tdg(&mut self, qubit: usize) -> Result<(), JsError> {
+        self.inner.tdg(qubit)?;
         Ok(())
     }
-    pub fn rx(&mut self, angle: f64, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.rx(angle, qubit);
+    pub fn rx(&mut self, angle: f64, qubit: usize) -> Result<(), JsError> {
+        self.inner.rx(angle, qubit)?;
         Ok(())
     }
-    pub fn ry(&mut self, angle: f64, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.ry(angle, qubit);
+    pub fn ry(&mut self, angle: f64, qubit: usize) -> Result<(), JsError> {
+        self.inner.ry(angle, qubit)?;
         Ok(())
     }
-    pub fn rz(&mut self, angle: f64, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.rz(angle, qubit);
+    pub fn rz(&mut self, angle: f64, qubit: usize) -> Result<(), JsError> {
+        self.inner.rz(angle, qubit)?;
         Ok(())
     }
-    pub fn phase(&mut self, angle: f64, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.phase(angle, qubit);
+    pub fn phase(&mut self, angle: f64, qubit: usize) -> Result<(), JsError> {
+        self.inner.phase(angle, qubit)?;
         Ok(())
     }
-    pub fn u(&mut self, theta: f64, phi: f64, lambda: f64, qubit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit)?;
-        self.inner.u(theta, phi, lambda, qubit);
+    pub fn u(&mut self, theta: f64, phi: f64, lambda: f64, qubit: usize) -> Result<(), JsError> {
+        self.inner.u(theta, phi, lambda, qubit)?;
         Ok(())
     }
-    pub fn cx(&mut self, control: usize, target: usize) -> Result<(), JsValue> {
-        self.validate_qubit(control)?;
-        self.validate_qubit(target)?;
-        if control == target {
-            return Err(JsValue::from_str("Control and target qubits must be different"));
-        }
-        self.inner.cx(control, target);
+    pub fn cx(&mut self, control: usize, target: usize) -> Result<(), JsError> {
+        self.inner.cx(control, target)?;
         Ok(())
     }
-    pub fn cz(&mut self, control: usize, target: usize) -> Result<(), JsValue> {
-        self.validate_qubit(control)?;
-        self.validate_qubit(target)?;
-        if control == target {
-            return Err(JsValue::from_str("Control and target qubits must be different"));
-        }
-        self.inner.cz(control, target);
+    pub fn cz(&mut self, control: usize, target: usize) -> Result<(), JsError> {
+        self.inner.cz(control, target)?;
         Ok(())
     }
-    pub fn swap(&mut self, qubit1: usize, qubit2: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit1)?;
-        self.validate_qubit(qubit2)?;
-        if qubit1 == qubit2 {
-            return Err(JsValue::from_str("SWAP qubits must be different"));
-        }
-        self.inner.swap(qubit1, qubit2);
+    pub fn swap(&mut self, qubit1: usize, qubit2: usize) -> Result<(), JsError> {
+        self.inner.swap(qubit1, qubit2)?;
         Ok(())
     }
-    pub fn toffoli(&mut self, qubits: Vec<usize>) -> Result<(), JsValue> {
-        if qubits.len() != 3 {
-            return Err(JsValue::from_str("Toffoli gate requires exactly 3 qubits"));
-        }
-        for &q in &qubits {
-            self.validate_qubit(q)?;
-        }
-        if qubits[0] == qubits[1] || qubits[1] == qubits[2] || qubits[0] == qubits[2] {
-            return Err(JsValue::from_str("Toffoli qubits must be distinct"));
-        }
-        self.inner.toffoli(&qubits);
+    pub fn toffoli(&mut self, qubits: Vec<usize>) -> Result<(), JsError> {
+        self.inner.toffoli(&qubits)?;
         Ok(())
     }
-    pub fn mcx(&mut self, controls: Vec<usize>, target: usize, ancillas: Vec<usize>) -> Result<(), JsValue> {
-        if controls.is_empty() {
-            return Err(JsValue::from_str("MCX requires at least one control qubit"));
-        }
-        self.validate_qubit(target)?;
-        for &c in &controls {
-            self.validate_qubit(c)?;
-            if c == target {
-                return Err(JsValue::from_str("Control qubits cannot be the same as target"));
-            }
-        }
-        for &a in &ancillas {
-            self.validate_qubit(a)?;
-            if a == target {
-                return Err(JsValue::from_str("Ancilla qubits cannot be the same as target"));
-            }
-        }
+    pub fn mcx(&mut self, controls: Vec<usize>, target: usize, ancillas: Vec<usize>) -> Result<(), JsError> {
         let mut ancilla_vec = ancillas;
-        self.inner.mcx(&controls, target, &mut ancilla_vec);
+        self.inner.mcx(&controls, target, &mut ancilla_vec)?;
         Ok(())
     }
-    pub fn mcz(&mut self) -> Result<(), JsValue> {
+    pub fn mcz(&mut self) -> Result<(), JsError> {
         self.inner.mcz();
         Ok(())
     }
-    pub fn x_all(&mut self) -> Result<(), JsValue> {
+    pub fn x_all(&mut self) -> Result<(), JsError> {
         self.inner.x_all();
         Ok(())
     }
-    pub fn measure(&mut self) -> Result<(), JsValue> {
-        self.inner.measure();
+    pub fn measure(&mut self) -> Result<(), JsError> {
+        self.inner.measure()?;
         Ok(())
     }
-    pub fn measure_qubit(&mut self, qubit_index: usize, classical_bit: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit_index)?;
-        self.inner.measure_qubit(qubit_index, classical_bit);
+    pub fn measure_qubit(&mut self, qubit_index: usize, classical_bit: usize) -> Result<(), JsError> {
+        self.inner.measure_qubit(qubit_index, classical_bit)?;
         Ok(())
     }
-    pub fn reset_qubit(&mut self, qubit_index: usize) -> Result<(), JsValue> {
-        self.validate_qubit(qubit_index)?;
-        self.inner.reset_qubit(qubit_index);
+    pub fn reset_qubit(&mut self, qubit_index: usize) -> Result<(), JsError> {
+        self.inner.reset_qubit(qubit_index)?;
         Ok(())
     }
-    pub fn reset_all_qubits(&mut self) -> Result<(), JsValue> {
-        self.inner.reset_all_qubits();
+    pub fn reset_all_qubits(&mut self) -> Result<(), JsError> {
+        self.inner.reset_all_qubits()?;
         Ok(())
     }
-    pub fn barrier(&mut self, qubits: Vec<usize>) -> Result<(), JsValue> {
-        for &q in &qubits {
-            self.validate_qubit(q)?;
-        }
-        self.inner.barrier(&qubits);
+    pub fn barrier(&mut self, qubits: Vec<usize>) -> Result<(), JsError> {
+        self.inner.barrier(&qubits)?;
+        Ok(())
+    }
+    pub fn barrier_all(&mut self) -> Result<(), JsError> {
+        self.inner.barrier_all()?;
         Ok(())
     }
-    pub fn barrier_all(&mut self) -> Result<(), JsValue> {
-        self.inner.barrier_all();
+    pub fn set_backend(&mut self, backend: &str) -> Result<(), JsError> {
+        let backend = match backend {
+            "statevector" => Backend::Statevector,
+            "density_matrix" => Backend::DensityMatrix,
+            "stabilizer" => Backend::Stabilizer,
+            "mps" => Backend::MatrixProductState,
+            "automatic" => Backend::Automatic,
+            other => return Err(JsError::new(&format!("Unknown backend '{}'", other))),
+        };
+        self.inner.set_backend(backend);
         Ok(())
     }
+    /// seed measurement outcomes; the same seed gives the same results as a native build
+    pub fn set_seed(&mut self, seed: u64) {
+        self.inner.set_seed(seed);
+    }
     pub fn to_qasm(&self) -> String {
         self.inner.to_qasm()
     }
-    pub fn execute(&mut self) -> Vec<String> {
-        self.inner.execute()
+    pub fn execute(&mut self) -> Result<Vec<String>, JsError> {
+        Ok(self.inner.execute()?)
+    }
+    /// counts as a JSON object, e.g. {"00": 503, "11": 497}
+    pub fn execute_shots(&mut self, shots: usize) -> Result<String, JsError> {
+        Ok(self.inner.execute_shots(shots)?.to_string())
+    }
+    /// every shot's outcome in order
+    pub fn execute_shots_memory(&mut self, shots: usize) -> Result<Vec<String>, JsError> {
+        Ok(self.inner.execute_shots_with_memory(shots)?.memory().unwrap_or_default().to_vec())
+    }
+    pub fn probabilities(&self) -> Option<Vec<f64>> {
+        self.inner.probabilities()
+    }
+    pub fn marginal_probabilities(&self, qubits: Vec<usize>) -> Result<Option<Vec<f64>>, JsError> {
+        Ok(self.inner.marginal_probabilities(&qubits)?)
+    }
+    /// [re, im] of the amplitude of `bits`
+    pub fn amplitude(&self, bits: &str) -> Result<Option<Vec<f64>>, JsError> {
+        Ok(self.inner.amplitude(bits)?.map(|a| vec![a.re, a.im]))
+    }
+    pub fn bloch_vector(&self, qubit: usize) -> Result<Option<Vec<f64>>, JsError> {
+        Ok(self.inner.bloch_vector(qubit)?.map(|v| v.to_vec()))
+    }
+    /// ⟨Σ c_k P_k⟩ for dense Pauli labels such as "XZI" (qubit 0 first)
+    pub fn expectation(&self, labels: Vec<String>, coefficients: Vec<f64>) -> Result<Option<f64>, JsError> {
+        Ok(self.inner.expectation(&Self::pauli_op(&labels, &coefficients)?)?)
+    }
+    pub fn estimate_expectation(&self, labels: Vec<String>, coefficients: Vec<f64>, shots: usize) -> Result<f64, JsError> {
+        Ok(self.inner.estimate_expectation(&Self::pauli_op(&labels, &coefficients)?, shots)?)
     }
     pub fn reset(&mut self) {
         self.inner.reset();
@@ -199,19 +188,15 @@ impl QuantumCircuit {
     pub fn n_qubits(&self) -> usize {
         self.inner.n
     }
-    pub fn from_qasm(qasm_string: &str) -> Result<Vec<String>, JsValue> {
-        RustQuantumCircuit::from_qasm(qasm_string)
-            .map_err(|e| JsValue::from_str(&e))
-    }
-    fn validate_qubit(&self, qubit: usize) -> Result<(), JsValue> {
-        if qubit >= self.inner.n {
-            Err(JsValue::from_str(&format!(
-                "Qubit index {} out of bounds (circuit has {} qubits)",
-                qubit, self.inner.n
-            )))
-        } else {
-            Ok(())
+    pub fn from_qasm(qasm_string: &str) -> Result<Vec<String>, JsError> {
+        Ok(RustQuantumCircuit::from_qasm(qasm_string)?)
+    }
+    fn pauli_op(labels: &[String], coefficients: &[f64]) -> Result<SparsePauliOp, JsError> {
+        if labels.len() != coefficients.len() {
+            return Err(JsError::new("Need one coefficient per Pauli label"));
         }
+        let terms: Vec<(f64, &str)> = coefficients.iter().copied().zip(labels.iter().map(String::as_str)).collect();
+        Ok(SparsePauliOp::from_list(&terms)?)
     }
 }
 #[wasm_bindgen]
@@ -1,4 +1,5 @@
 use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::QucomError;
 
 #[test]
 fn circuit_creation() {
@@ -8,23 +9,25 @@ fn circuit_creation() {
 }
 
 #[test]
-fn circuit_reset() {
+fn circuit_reset() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(2);
-    circuit.h(0).cx(0, 1);
-    circuit.execute();
+    circuit.h(0)?.cx(0, 1)?;
+    circuit.execute()?;
     
     assert!(circuit.is_executed());
     circuit.reset();
     assert!(!circuit.is_executed());
+    Ok(())
 }
 
 #[test]
-fn multi_gate_application() {
+fn multi_gate_application() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(3);
-    circuit.h(0).h(1).h(2);
-    circuit.cx(0, 1).cx(1, 2);
+    circuit.h(0)?.h(1)?.h(2)?;
+    circuit.cx(0, 1)?.cx(1, 2)?;
     
     let qasm = circuit.to_qasm();
     assert!(qasm.contains("h q[0]"));
     assert!(qasm.contains("cx q[0], q[1]"));
+    Ok(())
 }
@@ -0,0 +1,115 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::parameters::Parameter;
+use qucom_rs::qasm::generator::QasmInstruction;
+use std::f64::consts::PI;
+use qucom_rs::QucomError;
+
+fn assert_states_eq(a: &QuantumCircuit, b: &QuantumCircuit) {
+    for (x, y) in a.state().unwrap().iter().zip(b.state().unwrap().iter()) {
+        crate::assert_complex_eq(*x, *y, 1e-12);
+    }
+}
+
+/// a bit of everything that can be inverted
+fn block() -> Result<QuantumCircuit, QucomError> {
+    let mut sub = QuantumCircuit::new(2);
+    sub.h(0)?.t(1)?.cx(0, 1)?.ry(Parameter::new("a")?, 0)?;
+
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.define_gate(sub.to_gate("sub")?)?;
+    circuit
+        .h(0)?
+        .s(1)?
+        .u(0.3, Parameter::new("phi")?, -0.8, 2)?
+        .cu(0.4, 1.1, 2.0 * &Parameter::new("phi")?, 0, 2)?
+        .crx(0.9, 1, 0)?
+        .gate("sub", &[Parameter::new("a")?.into()], &[2, 1])?
+        .cswap(2, 0, 1)?
+        .controlled(QasmInstruction::T(0, false), &[1], &[false])?;
+    Ok(circuit)
+}
+
+#[test]
+fn inverse_undoes_the_circuit() -> Result<(), QucomError> {
+    let circuit = block()?;
+    let inverse = circuit.inverse()?;
+    assert_eq!(inverse.parameters().len(), 2);
+    let qasm = inverse.to_qasm();
+    assert!(qasm.contains("gate sub(a) q0, q1 {") && qasm.contains("inv @ sub(a) q[2], q[1];"));
+
+    let mut identity = circuit.compose(&inverse, &[0, 1, 2])?.bind_parameter_values(&[0.7, -1.3])?;
+    identity.execute()?;
+    crate::assert_float_eq(identity.probability("000")?.unwrap(), 1.0, 1e-12);
+
+    let mut measured = QuantumCircuit::new(1);
+    measured.h(0)?.measure_qubit(0, 0)?;
+    assert!(measured.inverse().is_err());
+    Ok(())
+}
+
+#[test]
+fn power_repeats_and_negative_powers_invert() -> Result<(), QucomError> {
+    let mut rotation = QuantumCircuit::new(1);
+    rotation.rx(0.4, 0)?.rz(0.0, 0)?;
+
+    let mut cubed = rotation.power(3)?;
+    cubed.execute()?;
+    let mut expected = QuantumCircuit::new(1);
+    expected.rx(1.2, 0)?;
+    expected.execute()?;
+    assert_states_eq(&cubed, &expected);
+
+    let mut back = rotation.power(-2)?;
+    back.execute()?;
+    let mut expected = QuantumCircuit::new(1);
+    expected.rx(-0.8, 0)?;
+    expected.execute()?;
+    assert_states_eq(&back, &expected);
+    assert!(rotation.power(0)?.instructions().is_empty());
+    // |i32::MIN| does not fit an i32
+    assert!(QuantumCircuit::new(1).power(i32::MIN)?.instructions().is_empty());
+    Ok(())
+}
+
+#[test]
+fn phase_estimation_from_blocks() -> Result<(), QucomError> {
+    // U = P(π/2) has eigenvalue e^{2πi/4} on |1⟩, two counting qubits read 01
+    let mut u = QuantumCircuit::new(1);
+    u.phase(PI / 2.0, 0)?;
+    let mut qft = QuantumCircuit::new(2);
+    qft.h(0)?.cp(PI / 2.0, 1, 0)?.h(1)?.swap(0, 1)?;
+
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.define_gate(u.power(2)?.to_gate("u2")?)?;
+    circuit.define_gate(u.to_gate("u1")?)?;
+    circuit.x(2)?.h(0)?.h(1)?;
+    for (control, name) in [(0, "u2"), (1, "u1")] {
+        let call = QasmInstruction::GateCall(circuit.gate_definition(name).unwrap().clone().into(), Vec::new(), vec![2]);
+        circuit.controlled(call, &[control], &[true])?;
+    }
+    let mut circuit = circuit.compose(&qft.inverse()?, &[0, 1])?;
+    circuit.execute()?;
+    crate::assert_float_eq(circuit.probability("011")?.unwrap(), 1.0, 1e-12);
+
+    assert!(circuit.compose(&qft, &[0, 0]).is_err());
+    assert!(circuit.compose(&qft, &[0, 3]).is_err());
+    assert!(circuit.compose(&qft, &[0]).is_err());
+    Ok(())
+}
+
+#[test]
+fn tensor_places_circuits_side_by_side() -> Result<(), QucomError> {
+    let mut bell = QuantumCircuit::new(2);
+    bell.h(0)?.cx(0, 1)?.measure_qubit(1, 1)?;
+    let mut flip = QuantumCircuit::new(1);
+    flip.x(0)?.measure()?;
+
+    let mut both = bell.tensor(&flip)?;
+    assert_eq!(both.n, 3);
+    assert!(both.to_qasm().contains("measure q[2] -> c[2];"));
+    both.set_seed(5);
+    let counts = both.execute_shots(200)?;
+    assert_eq!(counts.get("01") + counts.get("11"), 200);
+    assert!(counts.get("01") > 0 && counts.get("11") > 0);
+    Ok(())
+}
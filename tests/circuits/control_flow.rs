@@ -1,30 +1,111 @@
 use qucom_rs::circuits::QuantumCircuit;
-use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::qasm::ClassicalExpr;
+use qucom_rs::qasm::generator::{LoopRange, QasmInstruction};
+use qucom_rs::QucomError;
 
 #[test]
-fn barrier() {
+fn barrier() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(3);
-    circuit.h(0).barrier(&[0, 1]).x(1);
+    circuit.h(0)?.barrier(&[0, 1])?.x(1)?;
     
     let qasm = circuit.to_qasm();
     assert!(qasm.contains("barrier"));
+    Ok(())
 }
 
 #[test]
-fn reset_qubit() {
+fn reset_qubit() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(2);
-    circuit.x(0).reset_qubit(0);
+    circuit.x(0)?.reset_qubit(0)?;
     let qasm = circuit.to_qasm();
     
     assert!(qasm.contains("reset q[0]"));
+    Ok(())
 }
 
 #[test]
-fn conditional_gate() {
+fn conditional_gate() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(2);
     let condition_body = vec![QasmInstruction::X(1)];
-    circuit.measure_qubit(0, 0).if_eq(0, 1, condition_body);
+    circuit.measure_qubit(0, 0)?.if_eq(0, 1, condition_body)?;
     
     let qasm = circuit.to_qasm();
     assert!(qasm.contains("if"));
+    Ok(())
+}
+
+#[test]
+fn register_condition_and_variables() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(0);
+    circuit.add_quantum_register("q", 2)?;
+    let c = circuit.add_classical_register("c", 2)?;
+    circuit.x(1)?.measure_qubit(0, c.bit(0)?)?.measure_qubit(1, c.bit(1)?)?;
+    circuit.assign("n", ClassicalExpr::register(&c) + 1)?;
+    circuit.if_condition(ClassicalExpr::var("n").equals(3), vec![QasmInstruction::X(0)])?;
+    circuit.measure_qubit(0, c.bit(0)?)?;
+
+    assert_eq!(circuit.execute()?, vec!["0", "1", "1"]);
+    let qasm = circuit.to_qasm_version(3)?;
+    assert!(qasm.contains("int[64] n;"));
+    assert!(qasm.contains("n = c + 1;"));
+    assert!(qasm.contains("if (n == 3) {"));
+    Ok(())
+}
+
+#[test]
+fn switch_stepped_loop_and_end() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.assign("n", ClassicalExpr::Int(0))?;
+    circuit.for_values("i", (0..10).step_by(3).collect(), vec![
+        QasmInstruction::Assign("n".to_string(), ClassicalExpr::var("n") + ClassicalExpr::var("i")),
+    ])?;
+    circuit.switch(ClassicalExpr::var("n"), vec![(vec![17, 18], vec![QasmInstruction::X(0)])], vec![QasmInstruction::X(1)])?;
+    circuit.measure_qubit(0, 0)?.end()?.measure_qubit(1, 1)?;
+
+    // 0 + 3 + 6 + 9
+    assert_eq!(circuit.execute()?, vec!["1"]);
+    let qasm = circuit.to_qasm_version(3)?;
+    assert!(qasm.contains("for i in [0:3:9] {"));
+    assert!(qasm.contains("case 17, 18 {"));
+    assert!(qasm.contains("end;"));
+    Ok(())
+}
+
+#[test]
+fn break_and_continue_need_a_loop() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(1);
+    let outside = circuit.switch(ClassicalExpr::Int(0), vec![(vec![0], vec![QasmInstruction::Break])], vec![]);
+    assert!(matches!(outside, Err(QucomError::InvalidArgument(message)) if message.contains("break must be inside a loop")));
+    assert!(circuit.if_condition(ClassicalExpr::Int(1), vec![QasmInstruction::Continue]).is_err());
+    assert!(circuit.instructions().is_empty());
+
+    // a switch or condition inside the loop body can still leave it
+    circuit.for_values("i", vec![0, 1], vec![
+        QasmInstruction::Switch(ClassicalExpr::var("i"), vec![(vec![1], vec![QasmInstruction::Break])], vec![]),
+        QasmInstruction::If(ClassicalExpr::var("i").equals(0), vec![QasmInstruction::Continue]),
+    ])?;
+    Ok(())
+}
+
+#[test]
+fn loop_ranges_are_stored_by_their_ends() -> Result<(), QucomError> {
+    let circuit = QuantumCircuit::parse_qasm("qubit q;\nfor i in [0:1000000000] { rx(i * 0.5) q; }")?;
+    let QasmInstruction::For(_, values, _) = &circuit.instructions()[0] else { panic!("expected a for loop") };
+    assert_eq!(*values, LoopRange::Range { start: 0, step: 1, end: 1_000_000_000 });
+    assert!(values.iter().take(3).eq([0, 1, 2]));
+    assert!(LoopRange::Range { start: i64::MAX - 1, step: 2, end: i64::MAX }.iter().eq([i64::MAX - 1]));
+    Ok(())
+}
+
+#[test]
+fn empty_loops_export_as_empty_ranges() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(1);
+    circuit.for_loop("i", 3, 3, vec![QasmInstruction::X(0)])?;
+    circuit.for_values("j", vec![], vec![QasmInstruction::X(0)])?;
+    circuit.measure_qubit(0, 0)?;
+
+    let qasm = circuit.to_qasm();
+    assert!(qasm.contains("for i in [3:2] {") && qasm.contains("for j in [0:-1] {"), "{}", qasm);
+    assert_eq!(QuantumCircuit::from_qasm(&qasm)?, vec!["0"]);
+    Ok(())
 }
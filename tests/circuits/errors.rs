@@ -0,0 +1,53 @@
+use ndarray::array;
+use num_complex::Complex64;
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::parameters::Parameter;
+use qucom_rs::qasm::QasmParser;
+use qucom_rs::simulators::Backend;
+use qucom_rs::QucomError;
+
+#[test]
+fn builders_reject_bad_qubits() {
+    let mut circuit = QuantumCircuit::new(3);
+    assert_eq!(circuit.h(99).err(), Some(QucomError::InvalidQubit { qubit: 99, n_qubits: 3 }));
+    assert_eq!(circuit.cx(1, 1).err(), Some(QucomError::DuplicateQubit(1)));
+    assert_eq!(circuit.toffoli(&[0, 1]).err().map(|e| matches!(e, QucomError::InvalidArgument(_))), Some(true));
+    assert_eq!(circuit.measure_qubit(0, 5).err(), Some(QucomError::InvalidClassicalBit { bit: 5, n_bits: 3 }));
+    // nothing was added by the failed calls
+    assert_eq!(circuit.instructions().len(), 0);
+}
+
+#[test]
+fn parse_errors_carry_their_position() {
+    let qasm = "OPENQASM 3.0;\nqubit[2] q;\n\n  h q[0];\n  frobnicate q[1];\n";
+    match QasmParser::parse(qasm) {
+        Err(QucomError::ParseError { line, column, message }) => {
+            assert_eq!((line, column), (5, 3));
+            assert!(message.contains("frobnicate"));
+        }
+        other => panic!("expected a parse error, got {:?}", other.map(|i| i.len())),
+    }
+}
+
+#[test]
+fn non_unitary_matrix_is_rejected() {
+    let zero = Complex64::new(0.0, 0.0);
+    let one = Complex64::new(1.0, 0.0);
+    let mut circuit = QuantumCircuit::new(1);
+    assert!(matches!(circuit.unitary(array![[one, one], [zero, one]], &[0]), Err(QucomError::NonUnitary(_))));
+}
+
+#[test]
+fn execution_errors() -> Result<(), QucomError> {
+    let mut unbound = QuantumCircuit::new(1);
+    unbound.rx(Parameter::new("theta")?, 0)?;
+    assert_eq!(unbound.execute(), Err(QucomError::UnboundParameter("theta".to_string())));
+    assert!(!unbound.is_executed());
+
+    let mut clifford_only = QuantumCircuit::new(1);
+    clifford_only.set_backend(Backend::Stabilizer);
+    clifford_only.h(0)?.t(0)?;
+    assert!(matches!(clifford_only.execute(), Err(QucomError::Unsupported(_))));
+    assert!(matches!(clifford_only.execute_shots(10), Err(QucomError::Unsupported(_))));
+    Ok(())
+}
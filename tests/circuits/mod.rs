@@ -2,3 +2,9 @@ mod basic_operations;
 mod measurement;
 mod control_flow;
 mod multiple_qubits;
+mod shots;
+mod seed;
+mod queries;
+mod compose;
+mod errors;
+mod registers;
@@ -1,15 +1,16 @@
 use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::QucomError;
 
 #[test]
-fn multiple_qubit_hadamard() {
+fn multiple_qubit_hadamard() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(10);
 
     for i in 0..10 {
-        circuit.h(i);
+        circuit.h(i)?;
     }
 
-    circuit.measure();
-    let results = circuit.execute();
+    circuit.measure()?;
+    let results = circuit.execute()?;
 
     // there should be 1 result string of length 10
     assert_eq!(results.len(), 1);
@@ -18,18 +19,19 @@ fn multiple_qubit_hadamard() {
     for c in results[0].chars() {
         assert!(c == '0' || c == '1');
     }
+    Ok(())
 }
 
 #[test]
-fn multiple_qubit_x() {
+fn multiple_qubit_x() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(10);
 
     for i in (0..10).step_by(2) {
-        circuit.x(i);
+        circuit.x(i)?;
     }
 
-    circuit.measure();
-    let results = circuit.execute();
+    circuit.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].len(), 10);
@@ -41,17 +43,19 @@ fn multiple_qubit_x() {
             assert_eq!(c, '0');
         }
     }
+    Ok(())
 }
 
 #[test]
-fn multiple_qubit_math() {
+fn multiple_qubit_math() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(10);
 
-    circuit.x(0).x(1).x(2);
+    circuit.x(0)?.x(1)?.x(2)?;
 
-    circuit.measure();
-    let results = circuit.execute();
+    circuit.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].len(), 10);
+    Ok(())
 }
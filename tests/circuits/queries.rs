@@ -0,0 +1,94 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::observables::SparsePauliOp;
+use qucom_rs::simulators::Backend;
+use std::f64::consts::{FRAC_1_SQRT_2, PI};
+use qucom_rs::QucomError;
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-10
+}
+
+#[test]
+fn probabilities_and_amplitudes_of_bell_state() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(2);
+    assert!(circuit.probabilities().is_none());
+
+    circuit.h(0)?.cx(0, 1)?;
+    circuit.execute()?;
+
+    let probs = circuit.probabilities().unwrap();
+    assert!(close(probs[0], 0.5) && close(probs[3], 0.5) && close(probs[1], 0.0));
+    assert!(close(circuit.amplitude("11")?.unwrap().re, FRAC_1_SQRT_2));
+    assert!(close(circuit.probability("01")?.unwrap(), 0.0));
+
+    // querying twice gives the same answer, nothing collapsed
+    assert_eq!(circuit.probabilities().unwrap(), probs);
+    Ok(())
+}
+
+#[test]
+fn marginals_follow_requested_qubit_order() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.x(2)?.ry(PI / 3.0, 0)?;
+    circuit.execute()?;
+
+    let marginal = circuit.marginal_probabilities(&[2, 0])?.unwrap();
+    // qubit 2 is always 1, qubit 0 is 1 with probability sin²(π/6)
+    assert!(close(marginal[0b10], 0.75));
+    assert!(close(marginal[0b11], 0.25));
+    assert!(close(marginal[0b00] + marginal[0b01], 0.0));
+    Ok(())
+}
+
+#[test]
+fn bloch_vectors() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.h(0)?.h(1)?.s(1)?.x(2)?;
+    circuit.execute()?;
+
+    let plus = circuit.bloch_vector(0)?.unwrap();
+    assert!(close(plus[0], 1.0) && close(plus[1], 0.0) && close(plus[2], 0.0));
+    let plus_i = circuit.bloch_vector(1)?.unwrap();
+    assert!(close(plus_i[0], 0.0) && close(plus_i[1], 1.0));
+    let one = circuit.bloch_vector(2)?.unwrap();
+    assert!(close(one[2], -1.0));
+
+    // half of a Bell pair sits at the centre of the sphere
+    let mut bell = QuantumCircuit::new(2);
+    bell.h(0)?.cx(0, 1)?;
+    bell.execute()?;
+    assert!(bell.bloch_vector(1)?.unwrap().iter().all(|c| close(*c, 0.0)));
+    Ok(())
+}
+
+#[test]
+fn queries_agree_across_backends() -> Result<(), QucomError> {
+    for backend in [Backend::Statevector, Backend::DensityMatrix, Backend::MatrixProductState] {
+        let mut circuit = QuantumCircuit::new(3);
+        circuit.set_backend(backend).h(0)?.s(0)?.rx(0.4, 1)?.cx(1, 2)?;
+        circuit.execute()?;
+
+        let y = circuit.bloch_vector(0)?.unwrap();
+        assert!(close(y[1], 1.0), "{:?}", backend);
+        assert!(close(circuit.marginal_probabilities(&[1])?.unwrap()[1], (0.2f64).sin().powi(2)));
+    }
+    Ok(())
+}
+
+#[test]
+fn queries_reject_qubits_and_bitstrings_outside_the_circuit() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.h(0)?;
+    circuit.execute()?;
+
+    assert_eq!(circuit.marginal_probabilities(&[2]), Err(QucomError::InvalidQubit { qubit: 2, n_qubits: 2 }));
+    assert_eq!(circuit.marginal_probabilities(&[1, 1]), Err(QucomError::DuplicateQubit(1)));
+    assert!(matches!(circuit.bloch_vector(5), Err(QucomError::InvalidQubit { qubit: 5, .. })));
+    let wide = SparsePauliOp::from_list(&[(1.0, "ZZZ")])?;
+    assert_eq!(circuit.expectation(&wide), Err(QucomError::InvalidQubit { qubit: 2, n_qubits: 2 }));
+    for bits in ["0", "012", "0a"] {
+        assert!(matches!(circuit.amplitude(bits), Err(QucomError::InvalidArgument(_))), "{}", bits);
+        assert!(circuit.probability(bits).is_err(), "{}", bits);
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::QucomError;
+
+/// two data qubits, an ancilla and a one-bit flag register
+fn syndrome_circuit() -> Result<QuantumCircuit, QucomError> {
+    let mut circuit = QuantumCircuit::new(0);
+    let data = circuit.add_quantum_register("data", 2)?;
+    let ancilla = circuit.add_quantum_register("ancilla", 1)?;
+    let flag = circuit.add_classical_register("flag", 1)?;
+    circuit
+        .x(data.qubit(0)?)?
+        .cx(data.qubit(0)?, ancilla.qubit(0)?)?
+        .cx(data.qubit(1)?, ancilla.qubit(0)?)?
+        .measure_qubit(ancilla.qubit(0)?, flag.bit(0)?)?;
+    Ok(circuit)
+}
+
+#[test]
+fn registers_lay_out_qubits_and_bits() -> Result<(), QucomError> {
+    let mut circuit = syndrome_circuit()?;
+    assert_eq!((circuit.n, circuit.n_bits()), (3, 1));
+    assert_eq!(circuit.quantum_register("ancilla").map(|r| r.qubits()), Some(vec![2]));
+    assert_eq!(circuit.measure_qubit(0, 1).err(), Some(QucomError::InvalidClassicalBit { bit: 1, n_bits: 1 }));
+    assert!(matches!(circuit.add_classical_register("data", 1), Err(QucomError::InvalidArgument(_))));
+    assert!(matches!(circuit.add_quantum_register("2q", 1), Err(QucomError::InvalidArgument(_))));
+    let ancilla = circuit.quantum_register("ancilla").unwrap();
+    assert!(matches!(ancilla.qubit(1), Err(QucomError::InvalidArgument(m)) if m == "qubit 1 is outside register 'ancilla' of size 1"));
+    assert!(matches!(circuit.classical_register("flag").unwrap().bit(1), Err(QucomError::InvalidArgument(_))));
+
+    assert_eq!(circuit.execute()?, vec!["1".to_string()]);
+    // the state is sized now, so no more qubits
+    assert!(circuit.add_quantum_register("more", 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn qasm_names_operands_by_register() -> Result<(), QucomError> {
+    let circuit = syndrome_circuit()?;
+    let qasm = circuit.to_qasm();
+    assert!(qasm.starts_with("OPENQASM 3.0;\nqubit[2] data;\nqubit[1] ancilla;\nbit[1] flag;\n"));
+    assert!(qasm.contains("cx data[1], ancilla[0];"));
+    assert!(qasm.contains("measure ancilla[0] -> flag[0];"));
+
+    let qasm2 = circuit.to_qasm_version(2)?;
+    assert!(qasm2.contains("qreg data[2];\nqreg ancilla[1];\ncreg flag[1];\n"));
+    Ok(())
+}
+
+#[test]
+fn multi_register_programs_round_trip() -> Result<(), QucomError> {
+    let circuit = syndrome_circuit()?;
+    let mut parsed = QuantumCircuit::parse_qasm(&circuit.to_qasm())?;
+    assert_eq!(parsed.quantum_registers(), circuit.quantum_registers());
+    assert_eq!(parsed.classical_registers(), circuit.classical_registers());
+    assert_eq!(parsed.to_qasm(), circuit.to_qasm());
+    assert_eq!(parsed.execute()?, vec!["1".to_string()]);
+
+    // a lone qubit is a register of one; registers may be declared between statements
+    let parsed = QuantumCircuit::parse_qasm("qubit a; bit[2] c;\nh a;\nqubit[2] b;\ncx a, b[1];\nc[1] = measure b[1];")?;
+    assert_eq!(parsed.n, 3);
+    assert!(matches!(parsed.instructions()[1], QasmInstruction::CX(0, 2)));
+    assert!(matches!(parsed.instructions()[2], QasmInstruction::Measure(2, 1)));
+    Ok(())
+}
+
+#[test]
+fn classical_bits_are_independent_of_qubits() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(1);
+    let results = circuit.add_classical_register("results", 3)?;
+    assert_eq!(circuit.n_bits(), 4);
+    circuit
+        .x(0)?
+        .measure_qubit(0, results.bit(2)?)?
+        .if_eq(results.bit(2)?, 1, vec![QasmInstruction::X(0)])?
+        .measure_qubit(0, 0)?;
+    assert_eq!(circuit.execute()?, vec!["1".to_string(), "0".to_string()]);
+    assert_eq!(circuit.execute_shots(5)?.get("10"), 5);
+
+    // tensor keeps both circuits' registers and shifts the second's bits past the first's
+    let tensor = circuit.tensor(&syndrome_circuit()?)?;
+    let names: Vec<&str> = tensor.quantum_registers().iter().map(|r| r.name()).collect();
+    assert_eq!(names, ["q", "data", "ancilla"]);
+    assert_eq!(tensor.n_bits(), 5);
+    assert!(tensor.to_qasm().contains("measure ancilla[0] -> flag[0];"));
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::noise::{NoiseModel, QuantumChannel, ReadoutError};
+use qucom_rs::qasm::QasmExecutor;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::simulators::{Backend, SimulatorRng};
+use qucom_rs::states::multi_qubit::zero_state;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use qucom_rs::QucomError;
+
+fn random_bits(backend: Backend, seed: u64) -> Result<Vec<String>, QucomError> {
+    let mut circuit = QuantumCircuit::new(4);
+    circuit.set_backend(backend).set_seed(seed);
+    for q in 0..4 {
+        circuit.h(q)?;
+    }
+    for q in 0..4 {
+        circuit.measure_qubit(q, q)?;
+    }
+    circuit.measure()?;
+    Ok(circuit.execute_shots_with_memory(32)?.memory().unwrap().to_vec())
+}
+
+#[test]
+fn same_seed_same_results_on_every_backend() -> Result<(), QucomError> {
+    for backend in [Backend::Statevector, Backend::DensityMatrix, Backend::Stabilizer, Backend::MatrixProductState] {
+        assert_eq!(random_bits(backend, 7)?, random_bits(backend, 7)?);
+        assert_ne!(random_bits(backend, 7)?, random_bits(backend, 8)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn seeded_noisy_trajectories_repeat() -> Result<(), QucomError> {
+    let run = || -> Result<Vec<Vec<String>>, QucomError> {
+        let mut noise = NoiseModel::new();
+        noise
+            .add_all_qubit_quantum_error(QuantumChannel::depolarizing(0.3, 1)?, &["h", "x"])
+            .add_all_qubit_readout_error(ReadoutError::symmetric(0.2)?);
+        let mut circuit = QuantumCircuit::new(3);
+        circuit.set_seed(11).set_noise_model(noise).h(0)?.x(1)?.h(2)?.measure()?;
+        (0..20).map(|_| circuit.execute()).collect()
+    };
+    assert_eq!(run()?, run()?);
+    Ok(())
+}
+
+#[test]
+fn executor_accepts_a_seeded_stream() -> Result<(), QucomError> {
+    let instructions = [QasmInstruction::H(0), QasmInstruction::H(1), QasmInstruction::MeasureAll];
+    let run = |rng: SimulatorRng| {
+        (0..16)
+            .map(|_| {
+                let mut state = zero_state(2);
+                QasmExecutor::execute_with_rng(&instructions, &mut state, 2, rng.clone()).unwrap().concat()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(run(SimulatorRng::seed_from_u64(3)), run(SimulatorRng::seed_from_u64(3)));
+    assert_eq!(run(SimulatorRng::from_rng(StdRng::seed_from_u64(3))), run(SimulatorRng::seed_from_u64(3)));
+
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.set_rng(StdRng::seed_from_u64(3)).h(0)?.h(1)?.measure()?;
+    let counts = circuit.execute_shots_with_memory(16)?;
+    assert_eq!(counts.memory().unwrap(), run(SimulatorRng::seed_from_u64(3)).as_slice());
+    Ok(())
+}
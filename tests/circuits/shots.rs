@@ -0,0 +1,67 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::qasm::QasmExecutor;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::simulators::Backend;
+use qucom_rs::QucomError;
+
+#[test]
+fn bell_counts() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.h(0)?.cx(0, 1)?.measure()?;
+    let counts = circuit.execute_shots(2000)?;
+
+    assert_eq!(counts.shots(), 2000);
+    assert_eq!(counts.get("00") + counts.get("11"), 2000);
+    assert!(counts.get("00") > 850 && counts.get("11") > 850);
+    assert!(counts.memory().is_none());
+    // the stored state is untouched
+    assert!(circuit.state().is_none());
+    Ok(())
+}
+
+#[test]
+fn memory_keeps_every_shot() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.x(1)?.measure_qubit(1, 0)?.measure_qubit(2, 1)?;
+    let counts = circuit.execute_shots_with_memory(50)?;
+
+    let memory = counts.memory().unwrap();
+    assert_eq!(memory.len(), 50);
+    assert!(memory.iter().all(|outcome| outcome == "10"));
+    assert_eq!(counts.most_frequent(), Some("10"));
+    assert_eq!(counts.to_string(), "{\"10\": 50}");
+    Ok(())
+}
+
+#[test]
+fn mid_circuit_feedback_resimulates_each_shot() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(2);
+    circuit
+        .h(0)?
+        .measure_qubit(0, 0)?
+        .if_eq(0, 1, vec![QasmInstruction::X(1)])?
+        .measure_qubit(1, 1)?;
+    let counts = circuit.execute_shots(400)?;
+
+    assert_eq!(counts.get("00") + counts.get("11"), 400);
+    assert!(counts.get("00") > 0 && counts.get("11") > 0);
+    Ok(())
+}
+
+#[test]
+fn terminal_measurement_detection() -> Result<(), QucomError> {
+    use QasmInstruction::*;
+
+    assert_eq!(QasmExecutor::terminal_measurements(&[H(0), CX(0, 1), BarrierAll, Measure(0, 0), MeasureAll]), Some(2));
+    assert_eq!(QasmExecutor::terminal_measurements(&[H(0), Measure(0, 0), X(0), Measure(0, 0)]), None);
+    assert_eq!(QasmExecutor::terminal_measurements(&[H(0), MeasureAll, MeasureAll]), None);
+    assert_eq!(QasmExecutor::terminal_measurements(&[Reset(0), Measure(0, 0)]), None);
+
+    // every backend agrees on a deterministic program
+    for backend in [Backend::Statevector, Backend::DensityMatrix, Backend::Stabilizer, Backend::MatrixProductState] {
+        let mut circuit = QuantumCircuit::new(3);
+        circuit.set_backend(backend).x(0)?.cx(0, 2)?.measure()?;
+        assert_eq!(circuit.execute_shots(20)?.get("101"), 20);
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::QucomError;
+
+#[test]
+fn gates_on_each_qubit() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(4);
+    let last: &[usize] = &[3];
+    circuit.h_multi([0, 2])?.h_multi(vec![0, 2])?;
+    circuit.on_each(1..4, |c, q| c.x(q))?;
+    circuit.on_each(last, |c, q| c.rx(std::f64::consts::PI, q))?;
+    circuit.measure()?;
+    assert_eq!(circuit.execute()?, vec!["0110"]);
+    Ok(())
+}
+
+#[test]
+fn register_pairs() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(0);
+    let a = circuit.add_quantum_register("a", 2)?;
+    let b = circuit.add_quantum_register("b", 2)?;
+    let c = circuit.add_classical_register("c", 2)?;
+    circuit.x(a.qubit(1)?)?;
+    circuit.on_pairs(a.qubits(), b.qubits(), |c, q, t| c.cx(q, t))?;
+    circuit.on_pairs(b.qubits(), c.bits(), |c, q, bit| c.measure_qubit(q, bit))?;
+    assert!(matches!(circuit.instructions()[1..3], [QasmInstruction::CX(0, 2), QasmInstruction::CX(1, 3)]));
+    assert_eq!(circuit.execute()?.concat(), "01");
+    Ok(())
+}
+
+#[test]
+fn failed_broadcasts_add_nothing() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(3);
+    assert!(matches!(circuit.on_pairs([0, 1], [2], |c, q, t| c.cx(q, t)), Err(QucomError::InvalidArgument(_))));
+    assert!(circuit.instructions().is_empty());
+    assert!(matches!(circuit.on_each([0, 1, 5], |c, q| c.h(q)), Err(QucomError::InvalidQubit { qubit: 5, .. })));
+    assert!(circuit.on_pairs([0, 1], [2, 1], |c, q, t| c.cx(q, t)).is_err());
+    // the gates before the failing one are taken back too
+    assert!(circuit.instructions().is_empty());
+
+    circuit.x(0)?;
+    assert!(circuit.on_each([1, 2, 3], |c, q| c.x(q)).is_err());
+    assert_eq!(circuit.instructions().len(), 1);
+    Ok(())
+}
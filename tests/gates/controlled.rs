@@ -0,0 +1,132 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::observables::SparsePauliOp;
+use qucom_rs::parameters::Parameter;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::qasm::QasmParser;
+use qucom_rs::simulators::Backend;
+use qucom_rs::QucomError;
+
+fn assert_states_eq(a: &QuantumCircuit, b: &QuantumCircuit) {
+    for (x, y) in a.state().unwrap().iter().zip(b.state().unwrap().iter()) {
+        crate::assert_complex_eq(*x, *y, 1e-12);
+    }
+}
+
+#[test]
+fn open_controls_match_x_conjugation() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.h(0)?.h(1)?.ry(0.4, 2)?;
+    circuit.controlled(QasmInstruction::Ry(1.1, 2), &[0, 1], &[false, true])?;
+    circuit.execute()?;
+
+    let mut expected = QuantumCircuit::new(3);
+    expected.h(0)?.h(1)?.ry(0.4, 2)?.x(0)?;
+    expected.controlled(QasmInstruction::Ry(1.1, 2), &[0, 1], &[true, true])?;
+    expected.x(0)?;
+    expected.execute()?;
+    assert_states_eq(&circuit, &expected);
+
+    // a single closed control on X is a plain CX
+    let cx = QasmInstruction::controlled(QasmInstruction::X(1), &[0], &[true]).unwrap();
+    assert!(matches!(cx, QasmInstruction::CX(0, 1)));
+    assert!(QasmInstruction::controlled(QasmInstruction::X(1), &[1], &[true]).is_err());
+    assert!(QasmInstruction::controlled(QasmInstruction::Measure(1, 0), &[0], &[true]).is_err());
+    assert!(circuit.controlled(QasmInstruction::H(1), &[3], &[true]).is_err());
+    Ok(())
+}
+
+#[test]
+fn named_controlled_gates_act_on_the_control_one_subspace() -> Result<(), QucomError> {
+    for backend in [Backend::Statevector, Backend::DensityMatrix] {
+        // control |0⟩: nothing happens
+        let mut idle = QuantumCircuit::new(3);
+        idle.set_backend(backend).x(1)?.ch(0, 1)?.cy(0, 2)?.crx(0.3, 0, 2)?.cswap(0, 1, 2)?;
+        idle.execute()?;
+        crate::assert_float_eq(idle.probability("010")?.unwrap(), 1.0, 1e-12);
+
+        // control |1⟩: the target gates apply
+        let mut active = QuantumCircuit::new(3);
+        active.set_backend(backend).x(0)?.x(1)?.cswap(0, 1, 2)?.cy(0, 1)?.ch(0, 2)?;
+        active.execute()?;
+        crate::assert_float_eq(active.probability("111")?.unwrap(), 0.5, 1e-12);
+        crate::assert_float_eq(active.probability("110")?.unwrap(), 0.5, 1e-12);
+    }
+
+    // CP and CRz differ only by a phase on the control |1⟩ subspace
+    let mut cp = QuantumCircuit::new(2);
+    cp.h(0)?.h(1)?.cp(0.8, 0, 1)?.cu(0.6, 0.2, -0.4, 1, 0)?;
+    cp.execute()?;
+    let mut expected = QuantumCircuit::new(2);
+    expected.h(0)?.h(1)?.crz(0.8, 0, 1)?.phase(0.4, 0)?;
+    expected.controlled(QasmInstruction::U(0.6, 0.2, -0.4, 0), &[1], &[true])?;
+    expected.execute()?;
+    assert_states_eq(&cp, &expected);
+    Ok(())
+}
+
+#[test]
+fn controlled_custom_gate() -> Result<(), QucomError> {
+    let mut bell = QuantumCircuit::new(2);
+    bell.h(0)?.cx(0, 1)?;
+    let definition = bell.to_gate("bell")?;
+
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.define_gate(definition)?;
+    let call = QasmInstruction::GateCall(circuit.gate_definition("bell").unwrap().clone().into(), Vec::new(), vec![1, 2]);
+    circuit.h(0)?.controlled(call, &[0], &[false])?;
+    circuit.execute()?;
+
+    let mut expected = QuantumCircuit::new(3);
+    expected.h(0)?.x(0)?.ch(0, 1)?.controlled(QasmInstruction::X(2), &[0, 1], &[true, true])?;
+    expected.x(0)?;
+    expected.execute()?;
+    assert_states_eq(&circuit, &expected);
+    crate::assert_float_eq(circuit.probability("011")?.unwrap(), 0.25, 1e-12);
+    Ok(())
+}
+
+#[test]
+fn qasm_modifiers_round_trip() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.h(0)?.h(1)?.crx(0.7, 0, 2)?.cy(1, 2)?;
+    circuit.controlled(QasmInstruction::Swap(1, 2), &[0], &[false])?;
+    circuit.controlled(QasmInstruction::Rz(0.3, 0), &[1, 2], &[true, false])?;
+
+    let qasm = circuit.to_qasm();
+    assert!(qasm.contains("ctrl @ rx(0.7) q[0], q[2];"));
+    assert!(qasm.contains("negctrl @ swap q[0], q[1], q[2];"));
+    assert!(qasm.contains("ctrl @ negctrl @ rz(0.3) q[1], q[2], q[0];"));
+
+    let parsed = QasmParser::parse(&qasm).unwrap();
+    let printed: Vec<String> = parsed.iter().map(|instr| instr.to_string()).collect();
+    let expected: Vec<String> = circuit.instructions().iter().map(|instr| instr.to_string()).collect();
+    assert_eq!(printed, expected);
+    let grouped = QasmParser::parse("ctrl(2) @ x q[0], q[1], q[2];").unwrap();
+    assert!(matches!(grouped[0], QasmInstruction::CCX(_)));
+
+    // a controlled unitary is written as its full matrix, the block of an open control first
+    let mut circuit = QuantumCircuit::new(2);
+    let x = ndarray::array![[0.0, 1.0], [1.0, 0.0]].mapv(|v: f64| v.into());
+    circuit.controlled(QasmInstruction::Unitary(x, vec![1]), &[0], &[false])?.measure()?;
+    assert_eq!(QuantumCircuit::from_qasm(&circuit.to_qasm())?, vec!["01"]);
+    Ok(())
+}
+
+#[test]
+fn adjoint_gradient_of_controlled_rotation() -> Result<(), QucomError> {
+    let theta = Parameter::new("theta")?;
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.h(0)?.cry(2.0 * &theta, 0, 1)?.crz(theta.clone(), 0, 1)?;
+    let op = SparsePauliOp::from_list(&[(0.5, "XY"), (1.0, "ZZ")]).unwrap();
+    assert!(circuit.parameter_shift_gradient(&op, &[0.4], None).is_err());
+
+    let expectation = |value: f64| -> Result<f64, QucomError> {
+        let mut bound = circuit.bind_parameter_values(&[value])?;
+        bound.execute()?;
+        Ok(bound.expectation(&op)?.unwrap())
+    };
+    let eps = 1e-6;
+    let finite = (expectation(0.4 + eps)? - expectation(0.4 - eps)?) / (2.0 * eps);
+    crate::assert_float_eq(circuit.adjoint_gradient(&op, &[0.4])?[0], finite, 1e-6);
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use ndarray::{array, Array2};
+use num_complex::Complex64;
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::parameters::Parameter;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::qasm::{GateDefinition, QasmExecutor, QasmGenerator, QasmParser};
+use qucom_rs::states::multi_qubit::zero_state;
+use qucom_rs::simulators::Backend;
+use std::f64::consts::FRAC_1_SQRT_2;
+use std::sync::Arc;
+use qucom_rs::QucomError;
+
+fn c(re: f64, im: f64) -> Complex64 {
+    Complex64::new(re, im)
+}
+
+fn assert_states_eq(a: &QuantumCircuit, b: &QuantumCircuit) {
+    for (x, y) in a.state().unwrap().iter().zip(b.state().unwrap().iter()) {
+        crate::assert_complex_eq(*x, *y, 1e-12);
+    }
+}
+
+/// body h, cx, rz(theta) on two local qubits
+fn entangler() -> Result<QuantumCircuit, QucomError> {
+    let mut sub = QuantumCircuit::new(2);
+    sub.h(0)?.cx(0, 1)?.rz(Parameter::new("theta")?, 1)?;
+    Ok(sub)
+}
+
+#[test]
+fn custom_gate_expands_onto_its_operands() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.define_gate(entangler()?.to_gate("entangle")?)?;
+    circuit.x(1)?.gate("entangle", &[0.7.into()], &[2, 0])?;
+    circuit.execute()?;
+
+    let mut expected = QuantumCircuit::new(3);
+    expected.x(1)?.h(2)?.cx(2, 0)?.rz(0.7, 0)?;
+    expected.execute()?;
+    assert_states_eq(&circuit, &expected);
+
+    // symbolic arguments bind like any other angle
+    let phi = Parameter::new("phi")?;
+    let mut template = QuantumCircuit::new(3);
+    template.define_gate(entangler()?.to_gate("entangle")?)?;
+    template.x(1)?.gate("entangle", &[2.0 * &phi], &[2, 0])?;
+    let mut bound = template.bind_parameter_values(&[0.35])?;
+    bound.execute()?;
+    assert_states_eq(&bound, &expected);
+    Ok(())
+}
+
+#[test]
+fn definition_checks() -> Result<(), QucomError> {
+    let mut measured = QuantumCircuit::new(1);
+    measured.h(0)?.measure_qubit(0, 0)?;
+    assert!(measured.to_gate("m").is_err());
+    assert!(entangler()?.to_gate("cx").is_err());
+    // every name a call would resolve to something else, or that would not parse back
+    let builtins = [
+        "h", "x", "y", "z", "s", "sdg", "t", "tdg", "cx", "CX", "cnot", "cz", "ccx", "toffoli", "swap", "rx", "ry",
+        "rz", "p", "phase", "u", "u3", "U",
+    ];
+    let keywords = [
+        "switch", "case", "default", "def", "return", "end", "break", "continue", "const", "opaque", "include", "let",
+        "float", "angle",
+    ];
+    for name in builtins.iter().chain(&keywords).chain(&["pi", "tau", "euler"]) {
+        assert!(GateDefinition::new(name, vec![], 1, vec![QasmInstruction::H(0)]).is_err(), "{}", name);
+    }
+
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.define_gate(entangler()?.to_gate("entangle")?)?;
+    assert!(circuit.gate("entangle", &[], &[0, 1]).is_err());
+    assert!(circuit.gate("entangle", &[0.1.into()], &[0, 0]).is_err());
+    assert!(circuit.gate("missing", &[], &[0]).is_err());
+
+    let mut other = QuantumCircuit::new(2);
+    other.h(0)?;
+    assert!(circuit.define_gate(other.to_gate("entangle")?).is_err());
+    Ok(())
+}
+
+#[test]
+fn unitary_instruction_is_checked_and_executed() -> Result<(), QucomError> {
+    // √iSWAP
+    let h = FRAC_1_SQRT_2;
+    let sqrt_iswap: Array2<Complex64> = array![
+        [c(1.0, 0.0), c(0.0, 0.0), c(0.0, 0.0), c(0.0, 0.0)],
+        [c(0.0, 0.0), c(h, 0.0), c(0.0, h), c(0.0, 0.0)],
+        [c(0.0, 0.0), c(0.0, h), c(h, 0.0), c(0.0, 0.0)],
+        [c(0.0, 0.0), c(0.0, 0.0), c(0.0, 0.0), c(1.0, 0.0)],
+    ];
+    for backend in [Backend::Statevector, Backend::DensityMatrix, Backend::MatrixProductState] {
+        let mut circuit = QuantumCircuit::new(3);
+        circuit.set_backend(backend).x(2)?.unitary(sqrt_iswap.clone(), &[2, 0])?;
+        circuit.execute()?;
+        crate::assert_float_eq(circuit.probability("100")?.unwrap(), 0.5, 1e-12);
+        crate::assert_float_eq(circuit.probability("001")?.unwrap(), 0.5, 1e-12);
+    }
+
+    let mut circuit = QuantumCircuit::new(2);
+    let not_unitary = array![[c(1.0, 0.0), c(1.0, 0.0)], [c(0.0, 0.0), c(1.0, 0.0)]];
+    assert!(circuit.unitary(not_unitary, &[0]).is_err());
+    assert!(circuit.unitary(sqrt_iswap.clone(), &[0]).is_err());
+    assert!(circuit.unitary(sqrt_iswap, &[1, 1]).is_err());
+    Ok(())
+}
+
+#[test]
+fn qasm_round_trip() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.define_gate(entangler()?.to_gate("entangle")?)?;
+    let mut outer = QuantumCircuit::new(2);
+    outer.define_gate(entangler()?.to_gate("entangle")?)?;
+    outer.gate("entangle", &[0.25.into()], &[1, 0])?.x(0)?;
+    circuit.define_gate(outer.to_gate("nested")?)?;
+
+    let i = c(0.0, 1.0);
+    circuit
+        .gate("nested", &[], &[0, 2])?
+        .gate("entangle", &[(-0.5).into()], &[1, 2])?
+        .unitary(array![[c(0.0, 0.0), i], [i, c(0.0, 0.0)]], &[0])?;
+
+    let qasm = circuit.to_qasm();
+    // dependencies come first and each gate is defined once
+    let entangle = qasm.find("gate entangle(theta) q0, q1 {").unwrap();
+    assert!(entangle < qasm.find("gate nested q0, q1 {").unwrap());
+    assert_eq!(qasm.matches("gate entangle").count(), 1);
+    assert!(qasm.contains("#pragma qucom unitary"));
+
+    let parsed = QasmParser::parse(&qasm).unwrap();
+    assert_eq!(parsed.len(), 3);
+    let mut generator = QasmGenerator::new(3);
+    for instr in &parsed {
+        generator.add_instruction(instr.clone());
+    }
+    assert_eq!(generator.to_qasm(), qasm);
+
+    circuit.execute()?;
+    let mut state = zero_state(3);
+    QasmExecutor::execute(&parsed, &mut state, 3)?;
+    for (x, y) in circuit.state().unwrap().iter().zip(state.iter()) {
+        crate::assert_complex_eq(*x, *y, 1e-12);
+    }
+    Ok(())
+}
+
+#[test]
+fn gates_sharing_a_name_are_renamed_on_export() -> Result<(), QucomError> {
+    // two different gates called `g`, as raw instructions can have
+    let flip = Arc::new(GateDefinition::new("g", vec![], 1, vec![QasmInstruction::X(0)])?);
+    let turn = Arc::new(GateDefinition::new("g", vec![], 1, vec![QasmInstruction::H(0)])?);
+    let mut generator = QasmGenerator::new(2);
+    generator.add_instruction(QasmInstruction::GateCall(flip, vec![], vec![0]));
+    generator.add_instruction(QasmInstruction::GateCall(turn, vec![], vec![1]));
+
+    let qasm = generator.to_qasm();
+    assert!(qasm.contains("gate g q0 { x q0; }") && qasm.contains("gate g_1 q0 { h q0; }"), "{}", qasm);
+    assert!(qasm.contains("g q[0];\ng_1 q[1];"), "{}", qasm);
+    let mut expected = QuantumCircuit::new(2);
+    expected.x(0)?.h(1)?.execute()?;
+    let mut parsed = QuantumCircuit::parse_qasm(&qasm)?;
+    parsed.execute()?;
+    assert_states_eq(&parsed, &expected);
+
+    // the inverse of `g` is `g_dg` in OpenQASM 2.0, which a program's own `g_dg` must not replace
+    let qasm = "OPENQASM 3.0;\ngate g a { s a; }\ngate g_dg a { x a; }\nqubit[2] q;\nh q[0];\ninv @ g q[0];\ng_dg q[1];";
+    let mut circuit = QuantumCircuit::parse_qasm(qasm)?;
+    let exported = circuit.to_qasm_version(2)?;
+    assert!(exported.contains("gate g_dg q0 { sdg q0; }") && exported.contains("gate g_dg_1 q0 { x q0; }"), "{}", exported);
+    let mut parsed = QuantumCircuit::parse_qasm(&exported)?;
+    parsed.execute()?;
+    circuit.execute()?;
+    assert_states_eq(&parsed, &circuit);
+    Ok(())
+}
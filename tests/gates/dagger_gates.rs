@@ -1,44 +1,49 @@
 use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::QucomError;
 
 #[test]
-fn s_then_dagger() {
+fn s_then_dagger() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(1);
     
     // should remain |0>
-    circuit.s(0).sdg(0).measure();
-    let results = circuit.execute();
+    circuit.s(0)?.sdg(0)?.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results[0], "0");
+    Ok(())
 }
 
 #[test]
-fn dagger_then_s() {
+fn dagger_then_s() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(1);
     
-    circuit.sdg(0).s(0).measure();
-    let results = circuit.execute();
+    circuit.sdg(0)?.s(0)?.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results[0], "0");
+    Ok(())
 }
 
 #[test]
-fn s_dagger_on_x_state() {
+fn s_dagger_on_x_state() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(1);
     
-    circuit.x(0).sdg(0).s(0).measure();
-    let results = circuit.execute();
+    circuit.x(0)?.sdg(0)?.s(0)?.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results[0], "1");
+    Ok(())
 }
 
 #[test]
-fn s_dagger_interference() {
+fn s_dagger_interference() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(1);
     
     // apply H, then S†, then H, to test phase effect
-    circuit.h(0).sdg(0).h(0).measure();
-    let results = circuit.execute();
+    circuit.h(0)?.sdg(0)?.h(0)?.measure()?;
+    let results = circuit.execute()?;
     
     // expect |0> or |1> due to constructive interference
     assert!(results[0] == "0" || results[0] == "1");
+    Ok(())
 }
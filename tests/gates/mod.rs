@@ -1,4 +1,7 @@
 mod single_qubit;
 mod two_qubit;
 mod multi_qubit;
-mod dagger_gates;
\ No newline at end of file
+mod dagger_gates;
+mod custom;
+mod controlled;
+mod broadcast;
@@ -1,36 +1,40 @@
 use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::QucomError;
 
 #[test]
-fn toffoli_gate() {
+fn toffoli_gate() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(3);
-    circuit.x(0).x(1).toffoli(&[0, 1, 2]).measure();
-    let results = circuit.execute();
+    circuit.x(0)?.x(1)?.toffoli(&[0, 1, 2])?.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results.len(), 1);
     assert_eq!(results[0], "111");
+    Ok(())
 }
 
 #[test]
-fn mcx_with_two_controls() {
+fn mcx_with_two_controls() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(3);
-    circuit.x(0).x(1);
-    circuit.mcx(&[0, 1], 2, &mut vec![]);
-    circuit.measure();
-    let results = circuit.execute();
+    circuit.x(0)?.x(1)?;
+    circuit.mcx(&[0, 1], 2, &mut vec![])?;
+    circuit.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results.len(), 1);
     assert_eq!(results[0], "111");
+    Ok(())
 }
 
 #[test]
-fn mcx_with_ancilla() {
+fn mcx_with_ancilla() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(5);
-    circuit.x(0).x(1).x(2);
-    circuit.mcx(&[0, 1, 2], 3, &mut vec![4]);
-    circuit.measure();
-    let results = circuit.execute();
+    circuit.x(0)?.x(1)?.x(2)?;
+    circuit.mcx(&[0, 1, 2], 3, &mut vec![4])?;
+    circuit.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results.len(), 1);
     // Check that target qubit (3) is flipped
     assert_eq!(results[0].chars().nth(3).unwrap(), '1');
+    Ok(())
 }
@@ -1,31 +1,35 @@
 use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::QucomError;
 
 #[test]
-fn cnot_gate() {
+fn cnot_gate() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(2);
-    circuit.x(1).cx(1, 0).measure();
-    let results = circuit.execute();
+    circuit.x(1)?.cx(1, 0)?.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results.len(), 1);
     assert_eq!(results[0], "11");
+    Ok(())
 }
 
 #[test]
-fn cz_gate() {
+fn cz_gate() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(2);
-    circuit.h(0).h(1).cz(0, 1).measure();
-    let results = circuit.execute();
+    circuit.h(0)?.h(1)?.cz(0, 1)?.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].len(), 2);
+    Ok(())
 }
 
 #[test]
-fn swap_gate() {
+fn swap_gate() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(2);
-    circuit.x(0).swap(0, 1).measure();
-    let results = circuit.execute();
+    circuit.x(0)?.swap(0, 1)?.measure()?;
+    let results = circuit.execute()?;
     
     assert_eq!(results.len(), 1);
     assert_eq!(results[0], "01");
+    Ok(())
 }
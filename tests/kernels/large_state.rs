@@ -0,0 +1,52 @@
+// these states are above the parallel threshold, so with `--features parallel`
+// they run through the threaded loops
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::gates::{hadamard, pauli_x, u_gate};
+use qucom_rs::kernels::{apply_controlled, apply_single_qubit, apply_swap, collapse, qubit_probabilities};
+use qucom_rs::states::zero_state;
+use num_complex::Complex64;
+use crate::{assert_complex_eq, assert_float_eq};
+use qucom_rs::QucomError;
+
+#[test]
+fn ghz_on_sixteen_qubits() -> Result<(), QucomError> {
+    let n = 16;
+    let mut circuit = QuantumCircuit::new(n);
+    circuit.h(0)?;
+    for q in 0..n - 1 {
+        circuit.cx(q, q + 1)?;
+    }
+    circuit.measure()?;
+    let results = circuit.execute()?;
+
+    assert!(results[0] == "0".repeat(n) || results[0] == "1".repeat(n));
+    Ok(())
+}
+
+#[test]
+fn kernels_preserve_norm_on_large_state() {
+    let n = 15;
+    let mut state = zero_state(n);
+    for q in 0..n {
+        apply_single_qubit(&mut state, &u_gate(0.1 * q as f64, 0.4, -0.2), q, n);
+    }
+    apply_controlled(&mut state, &pauli_x(), &[0, 3], &[n - 1], n);
+    apply_swap(&mut state, 2, n - 2, n);
+
+    let norm: f64 = state.iter().map(|c| c.norm_sqr()).sum();
+    assert_float_eq(norm, 1.0, 1e-10);
+}
+
+#[test]
+fn probabilities_and_collapse_on_large_state() {
+    let n = 15;
+    let mut state = zero_state(n);
+    apply_single_qubit(&mut state, &hadamard(), 7, n);
+
+    let (p0, p1) = qubit_probabilities(&state, 7, n);
+    assert_float_eq(p0, 0.5, 1e-12);
+    assert_float_eq(p1, 0.5, 1e-12);
+
+    collapse(&mut state, 7, 1, p1, n);
+    assert_complex_eq(state[[1 << (n - 1 - 7), 0]], Complex64::new(1.0, 0.0), 1e-12);
+}
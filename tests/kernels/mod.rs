@@ -0,0 +1,2 @@
+mod statevector;
+mod large_state;
@@ -0,0 +1,62 @@
+use qucom_rs::gates::{hadamard, pauli_x, swap, u_gate};
+use qucom_rs::kernels::{apply_controlled, apply_multi_qubit, apply_single_qubit, apply_swap, apply_two_qubit};
+use qucom_rs::states::{basis_state, superposition};
+use qucom_rs::utils::kron;
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::assert_complex_eq;
+
+// reference: full kronecker operator for a single-qubit gate
+fn kron_operator(gate: &Array2<Complex64>, qubit: usize, n_qubits: usize) -> Array2<Complex64> {
+    let eye = Array2::<Complex64>::eye(2);
+    let mut op = Array2::<Complex64>::eye(1);
+    for j in 0..n_qubits {
+        op = kron(&op, if j == qubit { gate } else { &eye });
+    }
+    op
+}
+
+#[test]
+fn single_qubit_kernel_matches_kronecker() {
+    let n = 4;
+    let gate = u_gate(0.3, 1.1, -0.7);
+    let initial = superposition(&["0001", "0110", "1011", "1100"]);
+
+    for q in 0..n {
+        let expected = kron_operator(&gate, q, n).dot(&initial);
+        let mut state = initial.clone();
+        apply_single_qubit(&mut state, &gate, q, n);
+        for i in 0..(1 << n) {
+            assert_complex_eq(state[[i, 0]], expected[[i, 0]], 1e-10);
+        }
+    }
+}
+
+#[test]
+fn two_qubit_kernel_matches_swap() {
+    let initial = superposition(&["100", "110", "011"]);
+
+    let mut by_matrix = initial.clone();
+    apply_two_qubit(&mut by_matrix, &swap(), 0, 2, 3);
+    let mut by_multi = initial.clone();
+    apply_multi_qubit(&mut by_multi, &swap(), &[2, 0], 3);
+    let mut by_permutation = initial.clone();
+    apply_swap(&mut by_permutation, 0, 2, 3);
+
+    for i in 0..8 {
+        assert_complex_eq(by_matrix[[i, 0]], by_permutation[[i, 0]], 1e-12);
+        assert_complex_eq(by_multi[[i, 0]], by_permutation[[i, 0]], 1e-12);
+    }
+}
+
+#[test]
+fn controlled_kernel_only_touches_control_subspace() {
+    // |110⟩ -> CCX -> |111⟩, |010⟩ is left alone
+    let mut state = basis_state("110");
+    apply_controlled(&mut state, &pauli_x(), &[0, 1], &[2], 3);
+    assert_complex_eq(state[[0b111, 0]], Complex64::new(1.0, 0.0), 1e-12);
+
+    let mut state = basis_state("010");
+    apply_controlled(&mut state, &hadamard(), &[0], &[2], 3);
+    assert_complex_eq(state[[0b010, 0]], Complex64::new(1.0, 0.0), 1e-12);
+}
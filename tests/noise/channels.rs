@@ -0,0 +1,54 @@
+use qucom_rs::noise::QuantumChannel;
+use qucom_rs::simulators::DensityMatrixSimulator;
+use qucom_rs::qasm::generator::QasmInstruction;
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::{assert_complex_eq, assert_float_eq};
+use qucom_rs::QucomError;
+
+#[test]
+fn full_depolarizing_gives_maximally_mixed_state() -> Result<(), QucomError> {
+    let mut sim = DensityMatrixSimulator::new(2);
+    let channel = QuantumChannel::depolarizing(1.0, 2)?;
+    sim.apply_kraus(channel.kraus(), &[0, 1]);
+
+    assert_eq!(channel.kraus().len(), 16);
+    assert_float_eq(sim.purity(), 0.25, 1e-12);
+    Ok(())
+}
+
+#[test]
+fn amplitude_damping_relaxes_to_ground() -> Result<(), QucomError> {
+    let mut sim = DensityMatrixSimulator::new(1);
+    sim.execute(&[QasmInstruction::X(0)])?;
+    sim.apply_kraus(QuantumChannel::amplitude_damping(1.0)?.kraus(), &[0]);
+
+    let rho = sim.density_matrix();
+    assert_complex_eq(rho[[0, 0]], Complex64::new(1.0, 0.0), 1e-12);
+    Ok(())
+}
+
+#[test]
+fn thermal_relaxation_decays_coherence_with_t2() -> Result<(), QucomError> {
+    let (t1, t2, time) = (50.0, 30.0, 10.0);
+    let mut sim = DensityMatrixSimulator::new(1);
+    sim.execute(&[QasmInstruction::H(0)])?;
+    sim.apply_kraus(QuantumChannel::thermal_relaxation(t1, t2, time)?.kraus(), &[0]);
+
+    let rho = sim.density_matrix();
+    assert_float_eq(rho[[0, 1]].re, 0.5 * (-time / t2).exp(), 1e-12);
+    assert_float_eq(rho[[1, 1]].re, 0.5 * (-time / t1).exp(), 1e-12);
+    Ok(())
+}
+
+#[test]
+fn invalid_channels_are_rejected() {
+    let error = QuantumChannel::from_kraus(vec![Array2::<Complex64>::eye(2) * Complex64::new(0.5, 0.0)]).unwrap_err();
+    assert!(error.to_string().contains("trace preserving"), "{}", error);
+    assert!(QuantumChannel::from_kraus(vec![]).is_err());
+    assert!(QuantumChannel::depolarizing(1.5, 1).is_err());
+    assert!(QuantumChannel::pauli(0.6, 0.6, 0.0).is_err());
+    assert!(QuantumChannel::thermal_relaxation(10.0, 30.0, 1.0).is_err());
+    let two_qubit = QuantumChannel::depolarizing(0.1, 2).unwrap();
+    assert!(QuantumChannel::amplitude_damping(0.1).unwrap().compose(&two_qubit).is_err());
+}
@@ -0,0 +1,2 @@
+mod channels;
+mod noise_model;
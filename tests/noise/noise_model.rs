@@ -0,0 +1,61 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::noise::{NoiseModel, QuantumChannel, ReadoutError};
+use qucom_rs::simulators::Backend;
+use num_complex::Complex64;
+use crate::assert_complex_eq;
+use qucom_rs::QucomError;
+
+#[test]
+fn readout_error_flips_recorded_bits() -> Result<(), QucomError> {
+    let mut noise = NoiseModel::new();
+    noise.add_readout_error(ReadoutError::symmetric(1.0)?, 1);
+
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.set_noise_model(noise).x(0)?.measure_qubit(0, 0)?.measure()?;
+    let results = circuit.execute()?;
+
+    assert_eq!(results, vec!["1", "11"]);
+    Ok(())
+}
+
+#[test]
+fn trajectory_applies_gate_errors() -> Result<(), QucomError> {
+    // full amplitude damping after every x brings the qubit straight back to |0⟩
+    let mut noise = NoiseModel::new();
+    noise.add_all_qubit_quantum_error(QuantumChannel::amplitude_damping(1.0)?, &["x"]);
+
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.set_noise_model(noise).x(0)?.x(1)?.measure()?;
+
+    assert_eq!(circuit.execute()?, vec!["00"]);
+    Ok(())
+}
+
+#[test]
+fn local_errors_only_hit_their_qubits() -> Result<(), QucomError> {
+    let mut noise = NoiseModel::new();
+    noise.add_quantum_error(QuantumChannel::pauli(1.0, 0.0, 0.0)?, &["cx"], &[0, 1]);
+
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.set_noise_model(noise).cx(1, 2)?.cx(0, 1)?.measure()?;
+
+    // only cx(0, 1) picks up X on both of its qubits
+    assert_eq!(circuit.execute()?, vec!["110"]);
+    Ok(())
+}
+
+#[test]
+fn density_matrix_backend_applies_channels_exactly() -> Result<(), QucomError> {
+    let mut noise = NoiseModel::new();
+    noise.add_all_qubit_quantum_error(QuantumChannel::depolarizing(0.5, 1)?, &["h"]);
+
+    let mut circuit = QuantumCircuit::new(1);
+    circuit.set_backend(Backend::DensityMatrix).set_noise_model(noise).h(0)?;
+    circuit.execute()?;
+
+    // off-diagonal coherence shrinks by (1 - p)
+    let rho = circuit.density_matrix().unwrap();
+    assert_complex_eq(rho[[0, 1]], Complex64::new(0.25, 0.0), 1e-12);
+    assert_complex_eq(rho[[0, 0]], Complex64::new(0.5, 0.0), 1e-12);
+    Ok(())
+}
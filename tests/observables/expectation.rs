@@ -0,0 +1,48 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::observables::SparsePauliOp;
+use qucom_rs::simulators::Backend;
+use crate::assert_float_eq;
+use qucom_rs::QucomError;
+
+#[test]
+fn bell_state_correlations() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.h(0)?.cx(0, 1)?;
+    circuit.execute()?;
+
+    let value = |label: &str| circuit.expectation(&SparsePauliOp::from_list(&[(1.0, label)]).unwrap()).unwrap().unwrap();
+    assert_float_eq(value("ZZ"), 1.0, 1e-10);
+    assert_float_eq(value("XX"), 1.0, 1e-10);
+    assert_float_eq(value("YY"), -1.0, 1e-10);
+    assert_float_eq(value("ZI"), 0.0, 1e-10);
+    Ok(())
+}
+
+#[test]
+fn weighted_sum_matches_on_every_backend() -> Result<(), QucomError> {
+    let op = SparsePauliOp::from_list(&[(0.5, "ZIZ"), (-0.3, "YXI"), (1.5, "III"), (0.7, "IYZ")]).unwrap();
+    let value = |backend: Backend| -> Result<f64, QucomError> {
+        let mut circuit = QuantumCircuit::new(3);
+        circuit.set_backend(backend).ry(0.4, 0)?.rx(1.1, 1)?.cx(0, 2)?.h(1)?.s(0)?;
+        circuit.execute()?;
+        Ok(circuit.expectation(&op)?.unwrap())
+    };
+
+    let exact = value(Backend::Statevector)?;
+    assert_float_eq(value(Backend::DensityMatrix)?, exact, 1e-10);
+    assert_float_eq(value(Backend::MatrixProductState)?, exact, 1e-10);
+    Ok(())
+}
+
+#[test]
+fn sampled_estimate_converges() -> Result<(), QucomError> {
+    let op = SparsePauliOp::from_list(&[(0.5, "ZZ"), (-0.8, "XI"), (0.3, "IY"), (1.0, "II")]).unwrap();
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.set_seed(5).ry(0.9, 0)?.rx(0.6, 1)?.cx(0, 1)?;
+
+    let estimate = circuit.estimate_expectation(&op, 20000)?;
+    circuit.execute()?;
+    let exact = circuit.expectation(&op)?.unwrap();
+    assert!((estimate - exact).abs() < 0.05, "estimate {} vs exact {}", estimate, exact);
+    Ok(())
+}
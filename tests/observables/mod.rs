@@ -0,0 +1,2 @@
+mod pauli;
+mod expectation;
@@ -0,0 +1,41 @@
+use qucom_rs::observables::{Pauli, PauliString, SparsePauliOp};
+use qucom_rs::QucomError;
+
+#[test]
+fn parse_dense_label() {
+    let pauli: PauliString = "XIzY".parse().unwrap();
+
+    assert_eq!(pauli.paulis(), &[(0, Pauli::X), (2, Pauli::Z), (3, Pauli::Y)]);
+    assert_eq!(pauli.get(1), Pauli::I);
+    assert_eq!(pauli.to_string(), "X0 Z2 Y3");
+    assert!("XQ".parse::<PauliString>().is_err());
+}
+
+#[test]
+fn sparse_strings_are_sorted() -> Result<(), QucomError> {
+    let pauli = PauliString::from_sparse(&[(5, Pauli::Z), (1, Pauli::X), (3, Pauli::I)])?;
+
+    assert_eq!(pauli.qubits(), vec![1, 5]);
+    assert_eq!(pauli.min_qubits(), 6);
+    // qubit 0 is the most significant bit
+    assert_eq!(pauli.masks(6), (0b010000, 0b000001, 0));
+    assert_eq!(PauliString::from_sparse(&[(2, Pauli::X), (2, Pauli::Z)]), Err(QucomError::DuplicateQubit(2)));
+    Ok(())
+}
+
+#[test]
+fn terms_merge_and_group() {
+    let mut op = SparsePauliOp::from_list(&[(0.5, "ZZ"), (0.25, "ZI"), (-1.0, "XX"), (2.0, "II")]).unwrap();
+    op.add_term(0.5, "ZZ".parse().unwrap());
+
+    assert_eq!(op.terms().len(), 4);
+    assert_eq!(op.terms()[0].0, 1.0);
+
+    let groups = op.measurement_groups();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].1, vec![0, 1]);
+    assert_eq!(groups[1].1, vec![2]);
+
+    let error = SparsePauliOp::from_list(&[(1.0, "ZZ"), (1.0, "ZQ")]);
+    assert!(matches!(error, Err(QucomError::InvalidArgument(message)) if message == "Invalid Pauli 'Q' in 'ZQ'"));
+}
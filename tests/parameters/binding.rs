@@ -0,0 +1,88 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::parameters::Parameter;
+use qucom_rs::qasm::QasmParser;
+use qucom_rs::qasm::generator::QasmInstruction;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use qucom_rs::QucomError;
+
+#[test]
+fn bound_circuit_matches_concrete_circuit() -> Result<(), QucomError> {
+    let theta = Parameter::new("theta")?;
+    let phi = Parameter::new("phi")?;
+
+    let mut template = QuantumCircuit::new(2);
+    template.h(0)?.rx(&theta, 0)?.cx(0, 1)?.rz(2.0 * &theta - &phi, 1)?.u(&phi, 0.3, &theta / 2.0, 0)?;
+    assert_eq!(template.parameters(), vec![theta.clone(), phi.clone()]);
+
+    for (t, p) in [(0.4, 1.1), (-2.0, 0.25)] {
+        let mut bound = template.bind_parameter_values(&[t, p])?;
+        assert!(bound.parameters().is_empty());
+        bound.execute()?;
+
+        let mut expected = QuantumCircuit::new(2);
+        expected.h(0)?.rx(t, 0)?.cx(0, 1)?.rz(2.0 * t - p, 1)?.u(p, 0.3, t / 2.0, 0)?;
+        expected.execute()?;
+
+        for (a, b) in bound.state().unwrap().iter().zip(expected.state().unwrap().iter()) {
+            crate::assert_complex_eq(*a, *b, 1e-12);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn binding_requires_every_value() -> Result<(), QucomError> {
+    let mut template = QuantumCircuit::new(1);
+    template.ry(Parameter::new("theta")?, 0)?.rz(Parameter::new("phi")?, 0)?;
+
+    let partial = HashMap::from([("theta".to_string(), 1.0)]);
+    assert_eq!(template.bind_parameters(&partial).err(), Some(QucomError::UnboundParameter("phi".to_string())));
+    assert!(template.bind_parameter_values(&[1.0]).is_err());
+    Ok(())
+}
+
+#[test]
+fn parameters_cannot_share_a_register_name() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(1);
+    assert!(matches!(circuit.rx(Parameter::new("q")?, 0), Err(QucomError::InvalidArgument(_))));
+    assert!(circuit.ry(Parameter::new("c")?, 0).is_err());
+    assert!(circuit.instructions().is_empty());
+
+    circuit.rz(Parameter::new("ancilla")?, 0)?;
+    assert!(circuit.add_quantum_register("ancilla", 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn qasm_round_trip_keeps_inputs() -> Result<(), QucomError> {
+    let theta = Parameter::new("theta")?;
+    let mut template = QuantumCircuit::new(1);
+    template.rx(2.0 * &theta + PI / 2.0, 0)?.phase(&theta, 0)?;
+
+    let qasm = template.to_qasm();
+    assert!(qasm.contains("input float[64] theta;"));
+
+    let instructions = QasmParser::parse(&qasm).unwrap();
+    assert_eq!(instructions.len(), 2);
+    assert_eq!(instructions[0].to_string(), template.to_qasm().lines().rev().nth(1).unwrap());
+    assert_eq!(instructions[1].parameters(), vec![theta]);
+
+    let values = HashMap::from([("theta".to_string(), 0.25)]);
+    let bound = instructions[0].bind(&values).unwrap();
+    assert!(matches!(bound, QasmInstruction::Rx(angle, 0) if (angle - (0.5 + PI / 2.0)).abs() < 1e-12));
+    Ok(())
+}
+
+#[test]
+fn parser_accepts_expressions_over_declared_inputs_only() -> Result<(), QucomError> {
+    let qasm = "OPENQASM 3.0;\nqubit[1] q;\ninput float[64] a;\nrz(-(a - pi) / 2) q[0];\nry(2*pi/3) q[0];";
+    let instructions = QasmParser::parse(qasm).unwrap();
+    assert_eq!(instructions[0].parameters(), vec![Parameter::new("a")?]);
+    assert!(matches!(instructions[1], QasmInstruction::Ry(angle, 0) if (angle - 2.0 * PI / 3.0).abs() < 1e-12));
+
+    // undeclared names and non-linear products are rejected
+    assert!(QasmParser::parse("qubit[1] q;\nrz(b) q[0];").is_err());
+    assert!(QasmParser::parse("qubit[1] q;\ninput float[64] a;\nrz(a*a) q[0];").is_err());
+    Ok(())
+}
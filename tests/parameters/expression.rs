@@ -0,0 +1,43 @@
+use qucom_rs::parameters::{Parameter, ParameterExpression};
+use std::collections::HashMap;
+use qucom_rs::QucomError;
+
+#[test]
+fn linear_arithmetic_and_display() -> Result<(), QucomError> {
+    let theta = Parameter::new("theta")?;
+    let phi = Parameter::new("phi")?;
+
+    let expr = 2.0 * &theta - &phi + 0.5;
+    assert_eq!(expr.to_string(), "2*theta - phi + 0.5");
+    assert_eq!(expr.coefficient(&theta), 2.0);
+    assert_eq!(expr.coefficient(&phi), -1.0);
+
+    // terms with the same parameter merge, cancelled terms disappear
+    let cancelled = expr + &phi - 0.5;
+    assert_eq!(cancelled.to_string(), "2*theta");
+    assert_eq!((&theta - &theta).value(), Some(0.0));
+    Ok(())
+}
+
+#[test]
+fn bind_evaluates_and_reports_missing_values() -> Result<(), QucomError> {
+    let theta = Parameter::new("theta")?;
+    let expr = (&theta + 1.0) / 2.0;
+
+    let values = HashMap::from([("theta".to_string(), 3.0)]);
+    assert_eq!(expr.bind(&values).unwrap(), 2.0);
+
+    let err = expr.bind(&HashMap::new()).unwrap_err();
+    assert_eq!(err, QucomError::UnboundParameter("theta".to_string()));
+    assert_eq!(ParameterExpression::constant(1.5).bind(&HashMap::new()).unwrap(), 1.5);
+    Ok(())
+}
+
+#[test]
+fn parameter_names_must_read_back_from_qasm() {
+    assert!(Parameter::new("2theta").is_err());
+    // constants and keywords would read back as something else
+    for name in ["pi", "tau", "euler", "input", "float", "for", "measure"] {
+        assert!(matches!(Parameter::new(name), Err(QucomError::InvalidArgument(_))), "{}", name);
+    }
+}
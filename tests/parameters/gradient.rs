@@ -0,0 +1,131 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::observables::SparsePauliOp;
+use qucom_rs::parameters::Parameter;
+use qucom_rs::simulators::Backend;
+use qucom_rs::QucomError;
+
+fn ansatz() -> Result<QuantumCircuit, QucomError> {
+    let theta = Parameter::new("theta")?;
+    let phi = Parameter::new("phi")?;
+    let mut circuit = QuantumCircuit::new(2);
+    circuit
+        .ry(&theta, 0)?
+        .h(1)?
+        .cx(0, 1)?
+        .rx(2.0 * &phi - 0.3, 1)?
+        .u(&theta, &phi, -&theta, 0)?
+        .phase(&phi + &theta, 1)?
+        .cx(1, 0)?
+        .rz(0.5 * &theta, 0)?;
+    Ok(circuit)
+}
+
+fn finite_difference(circuit: &QuantumCircuit, op: &SparsePauliOp, values: &[f64], i: usize) -> Result<f64, QucomError> {
+    let eps = 1e-6;
+    let at = |delta: f64| -> Result<f64, QucomError> {
+        let mut shifted = values.to_vec();
+        shifted[i] += delta;
+        let mut bound = circuit.bind_parameter_values(&shifted)?;
+        bound.execute()?;
+        Ok(bound.expectation(op)?.unwrap())
+    };
+    Ok((at(eps)? - at(-eps)?) / (2.0 * eps))
+}
+
+#[test]
+fn single_rotation_gradient() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(1);
+    circuit.ry(Parameter::new("theta")?, 0)?;
+    let z = SparsePauliOp::from_list(&[(1.0, "Z")]).unwrap();
+
+    // ⟨Z⟩ = cos θ
+    for theta in [0.0, 0.7, 2.5] {
+        let shift = circuit.parameter_shift_gradient(&z, &[theta], None)?;
+        let adjoint = circuit.adjoint_gradient(&z, &[theta])?;
+        crate::assert_float_eq(shift[0], -theta.sin(), 1e-10);
+        crate::assert_float_eq(adjoint[0], -theta.sin(), 1e-10);
+    }
+    Ok(())
+}
+
+#[test]
+fn methods_agree_with_finite_differences() -> Result<(), QucomError> {
+    let circuit = ansatz()?;
+    let op = SparsePauliOp::from_list(&[(0.7, "ZZ"), (-1.2, "XY"), (0.4, "IX")]).unwrap();
+    let values = [0.37, -1.1];
+
+    let shift = circuit.parameter_shift_gradient(&op, &values, None)?;
+    let adjoint = circuit.adjoint_gradient(&op, &values)?;
+    for i in 0..values.len() {
+        let expected = finite_difference(&circuit, &op, &values, i)?;
+        crate::assert_float_eq(shift[i], expected, 1e-6);
+        crate::assert_float_eq(adjoint[i], expected, 1e-6);
+    }
+    Ok(())
+}
+
+#[test]
+fn sampled_parameter_shift_is_close() -> Result<(), QucomError> {
+    let mut circuit = ansatz()?;
+    circuit.set_seed(11);
+    let op = SparsePauliOp::from_list(&[(1.0, "ZI"), (0.5, "XX")]).unwrap();
+    let values = [0.9, 0.2];
+
+    let exact = circuit.adjoint_gradient(&op, &values)?;
+    let sampled = circuit.parameter_shift_gradient(&op, &values, Some(20000))?;
+    for (s, e) in sampled.iter().zip(&exact) {
+        crate::assert_float_eq(*s, *e, 0.05);
+    }
+    Ok(())
+}
+
+#[test]
+fn adjoint_rejects_non_unitary_circuits() -> Result<(), QucomError> {
+    let z = SparsePauliOp::from_list(&[(1.0, "Z")]).unwrap();
+    let mut circuit = QuantumCircuit::new(1);
+    circuit.rx(Parameter::new("theta")?, 0)?.measure_qubit(0, 0)?;
+
+    assert!(circuit.adjoint_gradient(&z, &[0.1]).is_err());
+    assert!(circuit.adjoint_gradient(&z, &[]).is_err());
+    Ok(())
+}
+
+#[test]
+fn controlled_custom_gates() -> Result<(), QucomError> {
+    let op = SparsePauliOp::from_list(&[(1.0, "ZI"), (0.6, "XZ")]).unwrap();
+    let values = [0.45];
+
+    // with constant arguments the controlled body is differentiated like any other gates
+    let qasm = "input float theta;\nqubit[2] q;\ngate g(a) t { ry(a) t; rz(0.4) t; }\n\
+                h q[0];\nry(theta) q[1];\nctrl @ g(0.8) q[0], q[1];\nrx(theta) q[0];";
+    let circuit = QuantumCircuit::parse_qasm(qasm)?;
+    let adjoint = circuit.adjoint_gradient(&op, &values)?;
+    let shift = circuit.parameter_shift_gradient(&op, &values, None)?;
+    let expected = finite_difference(&circuit, &op, &values, 0)?;
+    crate::assert_float_eq(adjoint[0], expected, 1e-6);
+    crate::assert_float_eq(shift[0], expected, 1e-6);
+
+    // a symbolic argument would need the body's angles as expressions, so it is rejected
+    let qasm = "input float theta;\nqubit[2] q;\ngate g(a) t { ry(a) t; rz(0.4) t; }\n\
+                h q[0];\nctrl @ g(theta) q[0], q[1];";
+    let circuit = QuantumCircuit::parse_qasm(qasm)?;
+    let op = SparsePauliOp::from_list(&[(1.0, "ZI"), (1.0, "IZ")]).unwrap();
+    assert!(finite_difference(&circuit, &op, &values, 0)?.abs() > 0.1);
+    assert!(matches!(circuit.adjoint_gradient(&op, &values), Err(QucomError::Unsupported(_))));
+    assert!(matches!(circuit.parameter_shift_gradient(&op, &values, None), Err(QucomError::Unsupported(_))));
+    Ok(())
+}
+
+#[test]
+fn adjoint_needs_the_statevector_backend() -> Result<(), QucomError> {
+    let z = SparsePauliOp::from_list(&[(1.0, "Z")]).unwrap();
+    let mut circuit = QuantumCircuit::new(1);
+    circuit.ry(Parameter::new("theta")?, 0)?;
+    for backend in [Backend::DensityMatrix, Backend::Stabilizer, Backend::MatrixProductState] {
+        circuit.set_backend(backend);
+        assert!(matches!(circuit.adjoint_gradient(&z, &[0.3]), Err(QucomError::Unsupported(_))));
+    }
+    circuit.set_backend(Backend::Automatic);
+    crate::assert_float_eq(circuit.adjoint_gradient(&z, &[0.3])?[0], -0.3f64.sin(), 1e-10);
+    Ok(())
+}
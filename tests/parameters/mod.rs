@@ -0,0 +1,3 @@
+mod expression;
+mod binding;
+mod gradient;
@@ -0,0 +1,47 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::qasm::QasmParser;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::QucomError;
+
+#[test]
+fn gates_broadcast_over_registers() -> Result<(), QucomError> {
+    let qasm = "qubit[3] a; qubit[3] b; qubit t;\n\
+                h a;\n\
+                cx a, b;\n\
+                ccx a[0], b, t;";
+    let instructions = QasmParser::parse(qasm)?;
+    assert_eq!(instructions.len(), 9);
+    assert!(matches!(instructions[..3], [QasmInstruction::H(0), QasmInstruction::H(1), QasmInstruction::H(2)]));
+    assert!(matches!(instructions[3..6], [QasmInstruction::CX(0, 3), QasmInstruction::CX(1, 4), QasmInstruction::CX(2, 5)]));
+    assert!(matches!(&instructions[8], QasmInstruction::CCX(qubits) if qubits == &[0, 5, 6]));
+    Ok(())
+}
+
+#[test]
+fn broadcast_programs_simulate() -> Result<(), QucomError> {
+    let qasm = "OPENQASM 2.0;\n\
+                include \"qelib1.inc\";\n\
+                qreg a[2];\n\
+                qreg b[2];\n\
+                creg c[2];\n\
+                creg d[2];\n\
+                x a;\n\
+                cx a, b;\n\
+                u1(pi) b;\n\
+                reset a;\n\
+                measure a -> c;\n\
+                measure b -> d;";
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?.concat(), "0011");
+
+    // modifiers broadcast too: every qubit of `b` controlled by `t`
+    let mut circuit = QuantumCircuit::parse_qasm("qubit t; qubit[2] b; x t; ctrl @ x t, b;")?;
+    circuit.execute()?;
+    crate::assert_float_eq(circuit.probability("111")?.unwrap(), 1.0, 1e-12);
+    Ok(())
+}
+
+#[test]
+fn broadcast_registers_must_match() {
+    let error = QasmParser::parse("qubit[3] a; qubit[2] b;\ncx a, b;").unwrap_err().to_string();
+    assert!(error.contains("line 2, column 7") && error.contains("must all have 3 qubits"), "{}", error);
+}
@@ -0,0 +1,62 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::qasm::QasmParser;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::QucomError;
+
+#[test]
+fn condition_on_a_whole_register() -> Result<(), QucomError> {
+    // c[0] is the least significant bit, so measuring 101 gives c == 5
+    let qasm = "qubit[3] q; bit[3] c; bit r;\n\
+                x q[0]; x q[2];\n\
+                c = measure q;\n\
+                if (c == 5) { x q[1]; }\n\
+                r = measure q[1];";
+    let results = QuantumCircuit::from_qasm(qasm)?;
+    assert_eq!(results, vec!["101", "1"]);
+
+    let exported = QuantumCircuit::parse_qasm(qasm)?.to_qasm_version(3)?;
+    assert!(exported.contains("if (c == 5) {"));
+    // with two classical registers the export measures qubit by qubit
+    assert_eq!(QuantumCircuit::from_qasm(&exported)?.concat(), results.concat());
+    Ok(())
+}
+
+#[test]
+fn comparisons_and_boolean_operators() -> Result<(), QucomError> {
+    let qasm = "qubit[2] q; bit[2] c; bit[2] r;\n\
+                x q[1];\n\
+                c = measure q;\n\
+                if (c > 1 && c < 3) { x q[0]; }\n\
+                if (!(c == 2) || c[0] != 0) { x q[1]; }\n\
+                r = measure q;";
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?, vec!["01", "1", "1"]);
+    Ok(())
+}
+
+#[test]
+fn integer_variables_count_loop_iterations() -> Result<(), QucomError> {
+    let qasm = "qubit q; bit c; int[32] n = 0;\n\
+                while (n < 3) { x q; n += 1; }\n\
+                int m = (n << 2) | 1;\n\
+                if (m == 13 && (m ^ 12) == 1) { x q; }\n\
+                c = measure q;";
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?, vec!["0"]);
+
+    let err = QasmParser::parse("qubit q; undeclared = 1;").unwrap_err();
+    assert!(matches!(err, QucomError::ParseError { line: 1, column: 10, .. }));
+    Ok(())
+}
+
+#[test]
+fn loop_variables_as_indices_and_angles() -> Result<(), QucomError> {
+    // indexing by the variable unrolls the loop, using it as an angle keeps the loop
+    let qasm = "qubit[3] q; bit[3] c;\n\
+                for i in [0:1] { x q[i + 1]; }\n\
+                for uint k in [0:1] { rx(k * pi) q[0]; }\n\
+                c = measure q;";
+    let instructions = QasmParser::parse(qasm)?;
+    assert!(matches!(instructions[..2], [QasmInstruction::X(1), QasmInstruction::X(2)]));
+    assert!(matches!(&instructions[2], QasmInstruction::For(var, values, _) if var == "k" && values.iter().eq([0, 1])));
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?, vec!["111"]);
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use ndarray::array;
+use num_complex::Complex64;
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::qasm::QasmParser;
+use qucom_rs::QucomError;
+
+fn c(re: f64, im: f64) -> Complex64 {
+    Complex64::new(re, im)
+}
+
+#[test]
+fn switch_runs_the_matching_case() -> Result<(), QucomError> {
+    let qasm = "qubit[2] q; bit[2] c; int n = 2; int m = 7;\n\
+                switch (n) { case 0 { x q[0]; } case 1, 2 { x q[1]; } default { x q; } }\n\
+                switch (m) { case 0 { x q[1]; } default { x q[0]; } }\n\
+                c = measure q;";
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?.concat(), "11");
+
+    // no matching case and no default does nothing
+    let qasm = "qubit q; bit c; int n = 3;\nswitch (n + 1) { case 1 { x q; } }\nc = measure q;";
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?.concat(), "0");
+    Ok(())
+}
+
+#[test]
+fn loops_break_continue_and_step() -> Result<(), QucomError> {
+    let qasm = "qubit q; bit c; int n = 0; int k = 0;\n\
+                while (n < 10) { n += 1; if (n == 3) { continue; } if (n == 5) { break; } k += 1; }\n\
+                for int i in [0:2:6] { if (i == 4) { continue; } k += i; }\n\
+                for i in {1, 3, 5} { switch (i) { case 5 { break; } default { k += 10 * i; } } }\n\
+                for i in [5:-2:1] { k += i; }\n\
+                if (k == 60) { x q; }\n\
+                c = measure q;";
+    // k: 3 from the while loop, 0 + 2 + 6, 10 + 30, then 5 + 3 + 1
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?.concat(), "1");
+    Ok(())
+}
+
+#[test]
+fn ranges_include_their_end() -> Result<(), QucomError> {
+    let qasm = "qubit[2] q; bit[2] c; int n = 0;\n\
+                for i in [0:2] { n += 1; }\n\
+                for i in [4:-2:0] { n += 1; }\n\
+                if (n == 6) { x q[0]; }\n\
+                c = measure q;";
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?.concat(), "10");
+
+    let exported = QuantumCircuit::parse_qasm(qasm)?.to_qasm();
+    assert!(exported.contains("for i in [0:2] {") && exported.contains("for i in [4:-2:0] {"), "{}", exported);
+    assert_eq!(QuantumCircuit::from_qasm(&exported)?.concat(), "10");
+    Ok(())
+}
+
+#[test]
+fn stepped_and_set_loops_unroll_over_qubits_and_end_stops() -> Result<(), QucomError> {
+    let qasm = "qubit[6] q; bit[6] c;\n\
+                for i in [0:2:4] { x q[i]; }\n\
+                for i in {5, 1} { x q[i]; }\n\
+                c = measure q;\n\
+                if (c[0] == 1) { end; }\n\
+                x q[3];\n\
+                c[3] = measure q[3];";
+    // the second measurement of q[3] never runs
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?.concat(), "111011");
+    Ok(())
+}
+
+#[test]
+fn loops_whose_ranges_or_case_labels_use_an_outer_variable_unroll() -> Result<(), QucomError> {
+    let qasm = "qubit[4] q; bit[4] c; int n = 1;\n\
+                for int i in [0:2] { for int j in [0:i] { x q[j]; } }\n\
+                for int i in [0:1] { switch (n) { case i { x q[3]; } } }\n\
+                c = measure q;";
+    // q[0] flips three times, q[1] twice and q[2] once; only the case for i == 1 runs
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?.concat(), "1011");
+    Ok(())
+}
+
+#[test]
+fn runaway_while_loops_stop_at_the_limit() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::parse_qasm("qubit q; int n = 0;\nwhile (n >= 0) { n += 1; }")?;
+    circuit.set_iteration_limit(100);
+    assert!(matches!(circuit.execute(), Err(QucomError::IterationLimit(100))));
+    assert!(matches!(circuit.execute_shots(5), Err(QucomError::IterationLimit(100))));
+    Ok(())
+}
+
+#[test]
+fn misused_control_flow_errors() {
+    for (qasm, expected) in [
+        ("qubit q;\nbreak;", "line 2, column 1"),
+        ("qubit[2] q;\nfor i in [0:1] { x q[i]; continue; }", "cannot break or continue"),
+        ("qubit q;\nfor i in [0:0:4] { }", "step cannot be 0"),
+        ("qubit q; int n;\nswitch (n) { case 1 { } case 2, 1 { } }", "case 1 is already listed"),
+        ("qubit q; int n;\nswitch (n) { case 1 x q; }", "expected '{'"),
+    ] {
+        let error = QasmParser::parse(qasm).unwrap_err().to_string();
+        assert!(error.contains(expected), "{}: {}", qasm, error);
+    }
+}
+
+#[test]
+fn control_flow_exports_and_re_imports() -> Result<(), QucomError> {
+    let qasm = "qubit[2] q; bit[2] c; int n = 0;\n\
+                for i in {5, 1, 3} { n += i; }\n\
+                for i in [0:2:6] { if (i == 4) { break; } n += 1; }\n\
+                switch (n) { case 11 { x q[0]; } default { x q[1]; } }\n\
+                c = measure q;\n\
+                end;";
+    let circuit = QuantumCircuit::parse_qasm(qasm)?;
+    let exported = circuit.to_qasm_version(3)?;
+    for expected in ["for i in {5, 1, 3} {", "for i in [0:2:6] {", "break;", "switch (n) {", "case 11 {", "default {", "end;"] {
+        assert!(exported.contains(expected), "{} missing from\n{}", expected, exported);
+    }
+    assert_eq!(QuantumCircuit::from_qasm(&exported)?.concat(), "10");
+    Ok(())
+}
+
+#[test]
+fn gates_called_in_loops_are_exported() -> Result<(), QucomError> {
+    let qasm = "qubit q; bit c;\ngate flip a { x a; }\nfor i in [0:2] { flip q; }\nc = measure q;";
+    let exported = QuantumCircuit::parse_qasm(qasm)?.to_qasm();
+    assert!(exported.contains("gate flip q0 { x q0; }"), "{}", exported);
+    assert_eq!(QuantumCircuit::from_qasm(&exported)?, vec!["1"]);
+    Ok(())
+}
+
+#[test]
+fn unitaries_in_blocks_export_and_re_import() -> Result<(), QucomError> {
+    let x = array![[c(0.0, 0.0), c(1.0, 0.0)], [c(1.0, 0.0), c(0.0, 0.0)]];
+    let mut flip = QuantumCircuit::new(1);
+    flip.unitary(x.clone(), &[0])?;
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.define_gate(flip.to_gate("flip")?)?;
+    circuit
+        .measure_qubit(0, 0)?
+        .if_eq(0, 0, vec![QasmInstruction::unitary(x, &[1])?])?
+        .gate("flip", &[], &[2])?
+        .measure_qubit(1, 1)?
+        .measure_qubit(2, 2)?;
+    let exported = circuit.to_qasm();
+    assert!(exported.contains("#pragma qucom unitary"), "{}", exported);
+    assert_eq!(QuantumCircuit::from_qasm(&exported)?, circuit.execute()?);
+    Ok(())
+}
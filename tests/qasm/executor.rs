@@ -1,13 +1,15 @@
 use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::QucomError;
 
 #[test]
-fn execute_simple_circuit() {
+fn execute_simple_circuit() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(2);
-    circuit.h(0).measure();
-    let results = circuit.execute();
+    circuit.h(0)?.measure()?;
+    let results = circuit.execute()?;
     
     assert!(!results.is_empty());
     assert!(results[0].starts_with('0') || results[0].starts_with('1'));
+    Ok(())
 }
 
 #[test]
@@ -19,10 +21,11 @@ fn from_qasm_execution() {
 }
 
 #[test]
-fn bell_state_execution() {
+fn bell_state_execution() -> Result<(), QucomError> {
     let mut circuit = QuantumCircuit::new(2);
-    circuit.h(0).cx(0, 1).measure();
-    let results = circuit.execute();
+    circuit.h(0)?.cx(0, 1)?.measure()?;
+    let results = circuit.execute()?;
     
     assert!(results[0] == "00" || results[0] == "11");
+    Ok(())
 }
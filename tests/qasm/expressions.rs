@@ -0,0 +1,54 @@
+use std::f64::consts::PI;
+use qucom_rs::qasm::QasmParser;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::QucomError;
+
+/// the angle of each instruction in a one-qubit program
+fn angles(body: &str) -> Result<Vec<f64>, QucomError> {
+    let instructions = QasmParser::parse(&format!("qubit q;\n{}", body))?;
+    Ok(instructions.iter().flat_map(|instr| instr.angles()).collect())
+}
+
+#[test]
+fn arithmetic_follows_precedence() -> Result<(), QucomError> {
+    let expected = [-PI / 4.0, PI / 2.0 + 0.1, 3.0 * PI / 8.0, PI / 4.0, -4.0, 2.0 * PI - 1.0, 1.5e-3];
+    let found = angles("rz(-pi/4) q; rz(pi/2 + 0.1) q; rz(3*pi/8) q; rz((pi/2)/2) q;\n\
+                        rz(-2 ** 2) q; rz(tau - (3 % 2)) q; rz(1.5e-3) q;")?;
+    assert_eq!(found.len(), expected.len());
+    for (a, b) in found.iter().zip(expected) {
+        crate::assert_float_eq(*a, b, 1e-12);
+    }
+    Ok(())
+}
+
+#[test]
+fn math_functions() -> Result<(), QucomError> {
+    let found = angles("rx(sin(0.5)) q; ry(arccos(0)) q; rz(sqrt(2) * ln(euler)) q; p(pow(2, 3) - exp(0)) q;")?;
+    let expected = [0.5f64.sin(), PI / 2.0, 2f64.sqrt(), 7.0];
+    for (a, b) in found.iter().zip(expected) {
+        crate::assert_float_eq(*a, b, 1e-12);
+    }
+    assert!(matches!(angles("rz(sqrt(-1)) q;"), Err(QucomError::ParseError { .. })));
+    assert!(matches!(angles("rz(frob(1)) q;"), Err(QucomError::ParseError { column: 4, .. })));
+    Ok(())
+}
+
+#[test]
+fn const_and_input_variables() -> Result<(), QucomError> {
+    let found = angles("const float[64] half = pi / 2;\nconst float quarter = half / 2;\nrz(half + quarter) q;")?;
+    crate::assert_float_eq(found[0], 3.0 * PI / 4.0, 1e-12);
+
+    let instructions = QasmParser::parse("input float theta; qubit q;\nrz(2*theta - 0.3) q;")?;
+    match &instructions[0] {
+        QasmInstruction::Parameterized(_, angles) => assert_eq!(angles[0].1.to_string(), "2*theta - 0.3"),
+        other => panic!("expected a parameterized gate, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn non_constant_functions_and_consts_are_rejected() {
+    assert!(QasmParser::parse("input float theta; qubit q;\nrz(sin(theta)) q;").is_err());
+    assert!(QasmParser::parse("input float theta; const float c = theta;").is_err());
+    assert!(QasmParser::parse("const float c = 1; const float c = 2;").is_err());
+}
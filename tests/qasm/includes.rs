@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::qasm::{FileResolver, IncludeResolver, QasmParser};
+use qucom_rs::QucomError;
+
+#[test]
+fn bundled_libraries_define_standard_gates() -> Result<(), QucomError> {
+    // sx² = X, cy flips the target back, and cu(π, 0, π, 0) is a CNOT
+    let qasm = "include \"stdgates.inc\";\n\
+                qubit[2] q;\n\
+                x q[0]; sx q[1]; sx q[1];\n\
+                cy q[0], q[1];\n\
+                cu(pi, 0, pi, 0) q[0], q[1];";
+    let mut circuit = QuantumCircuit::parse_qasm(qasm)?;
+    circuit.execute()?;
+    crate::assert_float_eq(circuit.probability("11")?.unwrap(), 1.0, 1e-12);
+
+    // u2(0, π) is a Hadamard; the second include is skipped and a program may redefine a library gate
+    let qasm = "include \"qelib1.inc\";\ninclude \"qelib1.inc\";\n\
+                gate sx a { x a; }\n\
+                qubit[3] q;\n\
+                u2(0, pi) q[0]; u2(0, pi) q[0];\n\
+                sx q[1];\n\
+                cu3(pi, 0, pi) q[1], q[2];";
+    let mut circuit = QuantumCircuit::parse_qasm(qasm)?;
+    circuit.execute()?;
+    crate::assert_float_eq(circuit.probability("011")?.unwrap(), 1.0, 1e-12);
+    Ok(())
+}
+
+#[test]
+fn resolver_reads_other_files() -> Result<(), QucomError> {
+    let files: HashMap<String, String> = [
+        ("flip.inc".to_string(), "gate flip a { x a; }".to_string()),
+        ("pair.inc".to_string(), "include \"flip.inc\";\ngate pair a, b { flip a; flip b; }".to_string()),
+    ]
+    .into();
+    let qasm = "include \"pair.inc\";\ninclude \"flip.inc\";\nqubit[3] q;\npair q[0], q[2];";
+    let mut circuit = QuantumCircuit::parse_qasm_with_includes(qasm, &files)?;
+    circuit.execute()?;
+    crate::assert_float_eq(circuit.probability("101")?.unwrap(), 1.0, 1e-12);
+    assert_eq!(QasmParser::parse_with_includes(qasm, &files)?.len(), 1);
+
+    // without a resolver only the bundled libraries are found
+    let error = QasmParser::parse(qasm).unwrap_err().to_string();
+    assert!(error.contains("cannot find include file 'pair.inc'"), "{}", error);
+
+    // errors inside a file point at the include and name the position in the file
+    let files: HashMap<String, String> = [("bad.inc".to_string(), "gate g a {\n  nope a;\n}".to_string())].into();
+    let error = QasmParser::parse_with_includes("qubit q;\ninclude \"bad.inc\";", &files).unwrap_err().to_string();
+    assert!(error.contains("in 'bad.inc' at 2:3"), "{}", error);
+    Ok(())
+}
+
+#[test]
+fn subroutines_take_qubits_registers_and_values() -> Result<(), QucomError> {
+    let qasm = "def prepare(qubit[2] r, float theta) { ry(theta) r[0]; cx r[0], r[1]; }\n\
+                def flip(qubit a, int n) { if (n == 2) { x a; } }\n\
+                def check(qubit a) -> bit { return measure a; }\n\
+                def twice(int n) -> int { return 2 * n; }\n\
+                qubit[2] p; qubit r; bit[3] c;\n\
+                prepare(p, pi);\n\
+                flip(r, 2);\n\
+                flip(p[0], 3);\n\
+                int m = twice(3);\n\
+                if (m == 6) { flip(p[1], 2); }\n\
+                c[0] = check(p[0]);\n\
+                c[1] = check(p[1]);\n\
+                c[2] = check(r);";
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?.concat(), "101");
+    Ok(())
+}
+
+#[test]
+fn subroutine_misuse_is_reported() {
+    let cases = [
+        ("def f(qubit a) { f(a); }\nqubit q; f(q);", "nest too deeply"),
+        ("def f(qubit a) -> bit { return measure a; x a; }", "return must be the last statement"),
+        ("def f(qubit a) { x a; }\nqubit[2] q; f(q);", "expected a single qubit"),
+        ("def f(qubit a) { x a; }\nqubit q; bit c; c = f(q);", "'f' does not return a value"),
+        ("def f(qubit a) { qubit b; }\nqubit q; f(q);", "cannot include files or declare qubits"),
+        ("qubit q; return;", "return outside a subroutine"),
+    ];
+    for (qasm, expected) in cases {
+        let error = QasmParser::parse(qasm).unwrap_err().to_string();
+        assert!(error.contains(expected), "{}: {}", qasm, error);
+    }
+}
+
+#[test]
+fn file_resolver_stays_under_its_root() -> Result<(), QucomError> {
+    let dir = std::env::temp_dir().join(format!("qucom-includes-{}", std::process::id()));
+    let root = dir.join("root");
+    std::fs::create_dir_all(root.join("lib")).unwrap();
+    std::fs::write(root.join("lib/flip.inc"), "gate flip a { x a; }").unwrap();
+    std::fs::write(dir.join("outside.inc"), "gate outside a { x a; }").unwrap();
+    let resolver = FileResolver::new(&root);
+
+    assert_eq!(resolver.resolve("lib/flip.inc").as_deref(), Some("gate flip a { x a; }"));
+    assert_eq!(resolver.resolve("./lib/flip.inc").as_deref(), Some("gate flip a { x a; }"));
+    assert_eq!(resolver.resolve("../outside.inc"), None);
+    assert_eq!(resolver.resolve("lib/../../outside.inc"), None);
+    assert_eq!(resolver.resolve(dir.join("outside.inc").to_str().unwrap()), None);
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(dir.join("outside.inc"), root.join("link.inc")).unwrap();
+        assert_eq!(resolver.resolve("link.inc"), None);
+    }
+
+    let error = QasmParser::parse_with_includes("include \"../outside.inc\";\nqubit q;", &resolver).unwrap_err().to_string();
+    assert!(error.contains("cannot find include file '../outside.inc'"), "{}", error);
+    std::fs::remove_dir_all(&dir).unwrap();
+    Ok(())
+}
@@ -1,3 +1,11 @@
 mod generator;
 mod parser;
+mod syntax;
+mod expressions;
 mod executor;
+mod classical;
+mod modifiers;
+mod includes;
+mod qasm2;
+mod broadcast;
+mod control_flow;
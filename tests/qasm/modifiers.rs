@@ -0,0 +1,101 @@
+use std::f64::consts::PI;
+use ndarray::array;
+use num_complex::Complex64;
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::qasm::QasmParser;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::QucomError;
+
+#[test]
+fn inv_and_pow_on_builtin_gates() -> Result<(), QucomError> {
+    let qasm = "qubit[2] q;\n\
+                inv @ s q[0];\n\
+                pow(2) @ t q[1];\n\
+                pow(-1) @ t q[0];\n\
+                pow(0.5) @ z q[1];\n\
+                ctrl @ inv @ rx(0.3) q[0], q[1];";
+    let instructions = QasmParser::parse(qasm)?;
+    assert_eq!(instructions.len(), 6);
+    assert!(matches!(instructions[0], QasmInstruction::S(0, true)));
+    assert!(matches!(instructions[1..3], [QasmInstruction::T(1, false), QasmInstruction::T(1, false)]));
+    assert!(matches!(instructions[3], QasmInstruction::T(0, true)));
+    assert!(matches!(instructions[4], QasmInstruction::Phase(angle, 1) if (angle - PI / 2.0).abs() < 1e-12));
+    assert!(matches!(&instructions[5], QasmInstruction::Controlled(gate, controls, _)
+        if matches!(gate.as_ref(), QasmInstruction::Rx(angle, 1) if *angle == -0.3) && controls == &[0]));
+    Ok(())
+}
+
+#[test]
+fn modifiers_apply_exactly() -> Result<(), QucomError> {
+    // S² = Z and (√Z)² = Z, so both sandwiches between Hadamards act as X
+    for gate in ["pow(2) @ s q[0];", "pow(0.5) @ z q[0]; pow(0.5) @ z q[0];"] {
+        let mut circuit = QuantumCircuit::parse_qasm(&format!("qubit[2] q; h q[0]; {} h q[0];", gate))?;
+        circuit.execute()?;
+        crate::assert_float_eq(circuit.probability("10")?.unwrap(), 1.0, 1e-12);
+    }
+
+    // controls apply to every repetition: (ctrl-√X)² with √X = H S H is a CNOT
+    let qasm = "gate sx a { h a; s a; h a; }\nqubit[2] q; x q[0]; ctrl @ pow(2) @ sx q[0], q[1];";
+    let mut circuit = QuantumCircuit::parse_qasm(qasm)?;
+    circuit.execute()?;
+    crate::assert_float_eq(circuit.probability("11")?.unwrap(), 1.0, 1e-12);
+    Ok(())
+}
+
+#[test]
+fn inverted_custom_gates_round_trip() -> Result<(), QucomError> {
+    let qasm = "gate g(a) x, y { rx(a) x; cx x, y; t y; }\n\
+                qubit[2] q;\n\
+                h q[1];\n\
+                g(0.4) q[0], q[1];\n\
+                inv @ g(0.4) q[0], q[1];\n\
+                h q[1];";
+    let mut circuit = QuantumCircuit::parse_qasm(qasm)?;
+    let exported = circuit.to_qasm();
+    assert!(exported.contains("gate g(a) q0, q1 {"));
+    assert!(exported.contains("inv @ g(0.4) q[0], q[1];"));
+    assert!(!exported.contains("g_dg"));
+
+    circuit.execute()?;
+    crate::assert_float_eq(circuit.probability("00")?.unwrap(), 1.0, 1e-12);
+    let mut reparsed = QuantumCircuit::parse_qasm(&exported)?;
+    reparsed.execute()?;
+    crate::assert_float_eq(reparsed.probability("00")?.unwrap(), 1.0, 1e-12);
+    Ok(())
+}
+
+#[test]
+fn unsupported_modifiers_are_parse_errors() {
+    let error = |qasm: &str| QasmParser::parse(qasm).unwrap_err();
+    assert!(matches!(error("qubit q;\npow(0.5) @ h q;"), QucomError::ParseError { line: 2, column: 1, .. }));
+    assert!(matches!(error("input float[64] k; qubit q;\npow(k) @ x q;"), QucomError::ParseError { line: 2, column: 5, .. }));
+    assert!(matches!(error("qubit q;\ninv @ q;"), QucomError::ParseError { line: 2, .. }));
+    assert!(matches!(error("qubit[2] q;\nctrl(0) @ x q[0];"), QucomError::ParseError { line: 2, column: 1, .. }));
+    assert!(matches!(error("qubit[2] q;\ninv @ negctrl(0) @ x q[0];"), QucomError::ParseError { line: 2, column: 7, .. }));
+}
+
+#[test]
+fn whole_powers_do_not_repeat_gates() -> Result<(), QucomError> {
+    let qasm = "qubit[2] q;\n\
+                pow(1000000000) @ rz(0.1) q[0];\n\
+                pow(4) @ x q[0];\n\
+                pow(1000000003) @ s q[1];\n\
+                pow(-3) @ t q[1];";
+    let instructions = QasmParser::parse(qasm)?;
+    assert_eq!(instructions.len(), 5);
+    assert!(matches!(instructions[0], QasmInstruction::Rz(angle, 0) if (angle - 1e8).abs() < 1e-6));
+    assert!(matches!(instructions[1], QasmInstruction::S(1, true)));
+    assert!(matches!(instructions[2..], [QasmInstruction::T(1, true), QasmInstruction::T(1, true), QasmInstruction::T(1, true)]));
+
+    // a unitary's matrix is raised instead: X³ = X
+    let (zero, one) = (Complex64::new(0.0, 0.0), Complex64::new(1.0, 0.0));
+    let cubed = QasmInstruction::Unitary(array![[zero, one], [one, zero]], vec![0]).power(3.0)?;
+    assert!(matches!(&cubed[..], [QasmInstruction::Unitary(matrix, _)] if matrix[[0, 1]] == one && matrix[[0, 0]] == zero));
+
+    // custom gates have no closed form, so only a bounded number of copies is allowed
+    let custom = "gate g a { h a; t a; }\nqubit q;\n";
+    assert_eq!(QasmParser::parse(&format!("{}pow(-64) @ g q;", custom))?.len(), 64);
+    let error = QasmParser::parse(&format!("{}pow(1000000000) @ g q;", custom)).unwrap_err().to_string();
+    assert!(error.contains("line 3") && error.contains("more than 64 times"), "{}", error);
+    Ok(())
+}
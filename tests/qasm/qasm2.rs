@@ -0,0 +1,193 @@
+use ndarray::Array2;
+use num_complex::Complex64;
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::gates::u_gate;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::qasm::QasmParser;
+use qucom_rs::simulators::Backend;
+use crate::assert_complex_eq;
+use qucom_rs::QucomError;
+
+#[test]
+fn qelib1_gates_simulate() -> Result<(), QucomError> {
+    let qasm = "OPENQASM 2.0;\n\
+                include \"qelib1.inc\";\n\
+                qreg q[4];\n\
+                creg c[4];\n\
+                x q[0];\n\
+                u2(0,pi) q[1]; u2(0,pi) q[1]; u3(pi,0,pi) q[1];\n\
+                ch q[0],q[2]; ch q[0],q[2];\n\
+                h q[2]; cu1(pi) q[0],q[2]; h q[2];\n\
+                cswap q[0],q[2],q[3];\n\
+                cu3(pi,0,pi) q[1],q[2];\n\
+                h q[3]; crz(pi) q[0],q[3]; h q[3];\n\
+                measure q -> c;";
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?, vec!["1110"]);
+    Ok(())
+}
+
+#[test]
+fn conditions_compare_whole_registers() -> Result<(), QucomError> {
+    // c[0] is the least significant bit, so c == 1 holds and c == 2 does not
+    let qasm = "OPENQASM 2.0;\n\
+                include \"qelib1.inc\";\n\
+                qreg q[2];\n\
+                creg c[2];\n\
+                creg r[2];\n\
+                x q[0];\n\
+                measure q -> c;\n\
+                if(c==1) x q[1];\n\
+                if(c==2) x q[0];\n\
+                measure q -> r;";
+    assert_eq!(QuantumCircuit::from_qasm(qasm)?.concat(), "1011");
+
+    let error = QasmParser::parse("OPENQASM 2.0;\nqreg q[1];\ncreg c[1];\nif(c[0]==1) x q[0];").unwrap_err();
+    assert!(error.to_string().contains("line 4, column 5"), "{}", error);
+    let error = QasmParser::parse("OPENQASM 2.0;\nqreg q[1];\ncreg c[1];\nif(c==1) { x q[0]; }").unwrap_err();
+    assert!(error.to_string().contains("line 4, column 10"), "{}", error);
+    Ok(())
+}
+
+#[test]
+fn exports_re_import_as_qasm2() -> Result<(), QucomError> {
+    let qasm = "OPENQASM 2.0;\n\
+                include \"qelib1.inc\";\n\
+                gate majority a,b,c { cx c,b; cx c,a; ccx a,b,c; }\n\
+                qreg q[3];\n\
+                creg c[3];\n\
+                x q[1]; x q[2];\n\
+                sx q[0]; sxdg q[0];\n\
+                cu1(pi/2) q[1],q[2];\n\
+                majority q[0],q[1],q[2];\n\
+                measure q -> c;";
+    let circuit = QuantumCircuit::parse_qasm(qasm)?;
+    let exported = circuit.to_qasm_version(2)?;
+    // qelib1 gates come from the include, only the program's own gate is written out
+    assert!(exported.contains("gate majority q0, q1, q2 {"));
+    assert!(!exported.contains("gate sx") && !exported.contains("gate cu1"));
+    assert_eq!(QuantumCircuit::from_qasm(&exported)?, QuantumCircuit::from_qasm(qasm)?);
+    Ok(())
+}
+
+#[test]
+fn qasm2_declarations_and_keywords() -> Result<(), QucomError> {
+    // qelib1 gates a program defines itself are the built-in ones; opaque gates declare without a body
+    let qasm = "OPENQASM 2.0;\n\
+                gate h a { U(pi/2,0,pi) a; }\n\
+                gate cx c,t { CX c,t; }\n\
+                opaque magic(theta) a,b;\n\
+                qreg q[2];\n\
+                h q[0]; cx q[0],q[1];";
+    assert_eq!(QasmParser::parse(qasm)?.len(), 2);
+
+    let error = QasmParser::parse(&format!("{}\nmagic(0.1) q[0],q[1];", qasm)).unwrap_err().to_string();
+    assert!(error.contains("opaque gate 'magic' has no definition"), "{}", error);
+    let error = QasmParser::parse("OPENQASM 2.0;\nqubit q;").unwrap_err().to_string();
+    assert!(error.contains("'qubit' is not part of OpenQASM 2.0"), "{}", error);
+    let error = QasmParser::parse("OPENQASM 2.0;\nqreg q[2];\nctrl @ x q[0], q[1];").unwrap_err().to_string();
+    assert!(error.contains("'ctrl' is not part of OpenQASM 2.0"), "{}", error);
+    Ok(())
+}
+
+/// final density matrix of `qasm`, which ignores the global phase an export may change
+fn density(qasm: &str) -> Result<Array2<Complex64>, QucomError> {
+    let mut circuit = QuantumCircuit::parse_qasm(qasm)?;
+    circuit.set_backend(Backend::DensityMatrix).execute()?;
+    Ok(circuit.density_matrix().unwrap())
+}
+
+#[test]
+fn controlled_gates_export_under_qelib1_names() -> Result<(), QucomError> {
+    let qasm = "OPENQASM 3.0;\n\
+                include \"stdgates.inc\";\n\
+                gate g(a) x, y { h x; crz(a) x, y; t y; }\n\
+                qubit[4] q;\n\
+                h q[0]; h q[1]; h q[2]; ry(0.3) q[3];\n\
+                ctrl @ h q[0], q[1];\n\
+                negctrl @ p(0.4) q[1], q[2];\n\
+                ctrl @ rz(0.7) q[2], q[3];\n\
+                ctrl @ u(0.1, 0.2, 0.3) q[0], q[3];\n\
+                ctrl @ swap q[0], q[1], q[2];\n\
+                ctrl @ ctrl @ u(0.5, 0.6, 0.7) q[0], q[1], q[3];\n\
+                ctrl(2) @ swap q[3], q[2], q[0], q[1];\n\
+                ctrl @ ctrl @ s q[1], q[2], q[0];\n\
+                ctrl(3) @ x q[0], q[1], q[2], q[3];\n\
+                ctrl @ t q[0], q[2];\n\
+                ctrl @ g(0.9) q[3], q[0], q[1];";
+    let exported = QuantumCircuit::parse_qasm(qasm)?.to_qasm_version(2)?;
+    for statement in ["ch q[0], q[1];", "x q[1];\ncu1(0.4) q[1], q[2];\nx q[1];", "crz(0.7) q[2], q[3];", "cswap q[0], q[1], q[2];", "c3x q[0], q[1], q[2], q[3];"] {
+        assert!(exported.contains(statement), "{}", exported);
+    }
+    assert!(!exported.contains("ctrl") && !exported.contains("@"), "{}", exported);
+
+    let expected = density(qasm)?;
+    let actual = density(&exported)?;
+    for (a, b) in actual.iter().zip(expected.iter()) {
+        assert_complex_eq(*a, *b, 1e-9);
+    }
+    Ok(())
+}
+
+#[test]
+fn conditions_export_as_register_comparisons() -> Result<(), QucomError> {
+    let qasm = "OPENQASM 3.0;\n\
+                include \"stdgates.inc\";\n\
+                qubit[3] q;\n\
+                bit[2] c;\n\
+                bit[3] r;\n\
+                x q[0];\n\
+                c[0] = measure q[0];\n\
+                c[1] = measure q[1];\n\
+                if (c == 1) { x q[1]; cx q[1], q[2]; }\n\
+                if (c == 2) x q[0];\n\
+                r = measure q;";
+    let exported = QuantumCircuit::parse_qasm(qasm)?.to_qasm_version(2)?;
+    assert!(exported.contains("if(c==1) x q[1];\nif(c==1) cx q[1], q[2];\nif(c==2) x q[0];"), "{}", exported);
+    assert_eq!(QuantumCircuit::from_qasm(&exported)?, QuantumCircuit::from_qasm(qasm)?);
+    Ok(())
+}
+
+#[test]
+fn qasm2_export_rejects_what_it_cannot_express() -> Result<(), QucomError> {
+    let header = "OPENQASM 3.0;\ninclude \"stdgates.inc\";\nqubit[2] q;\nbit[2] c;\n";
+    for (body, message) in [
+        ("while (c[0] == 0) { h q[0]; c[0] = measure q[0]; }", "cannot express `while"),
+        ("int[64] n;\nn = 2;", "cannot express"),
+        ("if (c[0] == 1) x q[1];", "whole classical register"),
+        ("if (c > 1) x q[1];", "compare a classical register with a non-negative integer"),
+        ("if (c == 1) { c[0] = measure q[0]; x q[1]; }", "must come last"),
+        ("input float[64] theta;\nrx(theta) q[0];", "bind 'theta'"),
+    ] {
+        let circuit = QuantumCircuit::parse_qasm(&format!("{}{}", header, body))?;
+        let error = circuit.to_qasm_version(2).unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", body, error);
+    }
+    assert!(QuantumCircuit::new(1).to_qasm_version(4).is_err());
+    Ok(())
+}
+
+#[test]
+fn single_qubit_unitaries_export_as_u3() -> Result<(), QucomError> {
+    let matrix = u_gate(0.7, 0.2, -0.4) * Complex64::from_polar(1.0, 0.3);
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.h(0)?.h(1)?.ry(0.4, 2)?.unitary(matrix.clone(), &[2])?;
+    circuit.controlled(QasmInstruction::Unitary(matrix.clone(), vec![2]), &[0], &[true])?;
+    circuit.controlled(QasmInstruction::Unitary(matrix.clone(), vec![2]), &[0, 1], &[true, false])?;
+
+    // the OpenQASM 3.0 export keeps the matrices in a pragma only this crate reads
+    let qasm = circuit.to_qasm();
+    assert!(qasm.contains("#pragma qucom unitary"));
+    let exported = circuit.to_qasm_version(2)?;
+    assert!(!exported.contains("pragma") && exported.contains("u3(") && exported.contains("cu3("), "{}", exported);
+    let expected = density(&qasm)?;
+    let actual = density(&exported)?;
+    for (a, b) in actual.iter().zip(expected.iter()) {
+        assert_complex_eq(*a, *b, 1e-9);
+    }
+
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.unitary(Array2::eye(4), &[0, 1])?;
+    let error = circuit.to_qasm_version(2).unwrap_err().to_string();
+    assert!(error.contains("no statement for a 2-qubit unitary"), "{}", error);
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use qucom_rs::qasm::{ClassicalExpr, QasmParser};
+use qucom_rs::qasm::ast::StatementKind;
+use qucom_rs::qasm::generator::{LoopRange, QasmInstruction};
+use qucom_rs::QucomError;
+
+#[test]
+fn free_form_layout_and_comments() -> Result<(), QucomError> {
+    let qasm = "OPENQASM 3.0; qubit[2] q; bit[2] c;\n\
+                h q[0]; /* a block\n comment */ cx q[0],\n   q[1];\n\
+                // line comment\n\
+                c[1] = measure q[1];";
+    let instructions = QasmParser::parse(qasm)?;
+    assert_eq!(instructions.len(), 3);
+    assert!(matches!(instructions[0], QasmInstruction::H(0)));
+    assert!(matches!(instructions[1], QasmInstruction::CX(0, 1)));
+    assert!(matches!(instructions[2], QasmInstruction::Measure(1, 1)));
+    Ok(())
+}
+
+#[test]
+fn registers_are_laid_out_in_order() -> Result<(), QucomError> {
+    let qasm = "qubit[2] data; qubit ancilla; qubit[2] more;\ncx data[1], ancilla; swap ancilla, more[1];";
+    let instructions = QasmParser::parse(qasm)?;
+    assert!(matches!(instructions[0], QasmInstruction::CX(1, 2)));
+    assert!(matches!(instructions[1], QasmInstruction::Swap(2, 4)));
+
+    let err = QasmParser::parse("qubit[2] q;\nh q[2];").unwrap_err();
+    assert!(matches!(err, QucomError::ParseError { line: 2, column: 3, .. }));
+    Ok(())
+}
+
+#[test]
+fn nested_blocks_build_an_ast_with_spans() -> Result<(), QucomError> {
+    let qasm = "qubit[1] q; bit[1] c;\nfor i in [0:1] {\n  if (c[0] == 1) { x q[0]; } else { h q[0]; }\n}";
+    let program = QasmParser::parse_program(qasm)?;
+    assert_eq!(program.statements.len(), 3);
+    let StatementKind::For { body, .. } = &program.statements[2].kind else { panic!("expected a for loop") };
+    assert_eq!((body[0].span.line, body[0].span.column), (3, 3));
+    assert!(matches!(&body[0].kind, StatementKind::If { else_body: Some(_), .. }));
+
+    let instructions = QasmParser::parse(qasm)?;
+    assert!(matches!(&instructions[0], QasmInstruction::For(_, values, body) if *values == LoopRange::Range { start: 0, step: 1, end: 1 } && matches!(&body[0], QasmInstruction::IfElse(condition, _, _) if *condition == ClassicalExpr::bit_eq(0, 1))));
+    Ok(())
+}
+
+#[test]
+fn errors_point_at_the_offending_token() {
+    let position = |qasm: &str| match QasmParser::parse(qasm) {
+        Err(QucomError::ParseError { line, column, message }) => (line, column, message),
+        other => panic!("expected a parse error, got {:?}", other.map(|i| i.len())),
+    };
+    let (line, column, message) = position("qubit[2] q;\ncx q[0] q[1];");
+    assert_eq!((line, column), (2, 9));
+    assert!(message.contains("expected"));
+    assert_eq!(position("qubit q;\n/* never closed\nh q;").0, 2);
+    let (_, column, message) = position("qubit q; rz(b) q;");
+    assert_eq!((column, message.contains("'b'")), (13, true));
+    let (_, column, message) = position("qubit[2] q; cx q[0];");
+    assert_eq!((column, message.contains("2 qubits")), (13, true));
+}
+
+#[test]
+fn deep_nesting_is_an_error_not_a_stack_overflow() -> Result<(), QucomError> {
+    let angle = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+    assert_eq!(QasmParser::parse(&format!("qubit q;\nrx({}) q;", angle(40)))?.len(), 1);
+
+    let err = QasmParser::parse(&format!("qubit q;\nrx({}) q;", angle(800))).unwrap_err();
+    assert!(matches!(&err, QucomError::ParseError { line: 2, column: 68, message } if message.contains("nesting is limited to 64 levels")), "{}", err);
+    let err = QasmParser::parse(&format!("qubit q;\nrx({}1) q;", "-".repeat(5000))).unwrap_err();
+    assert!(matches!(err, QucomError::ParseError { line: 2, .. }));
+    let err = QasmParser::parse(&format!("qubit q; bit c;\n{}x q;{}", "if (c == 0) { ".repeat(1000), " }".repeat(1000))).unwrap_err();
+    assert!(matches!(err, QucomError::ParseError { line: 2, .. }));
+    Ok(())
+}
@@ -3,6 +3,11 @@ mod circuits;
 mod qasm;
 mod states;
 mod utils;
+mod kernels;
+mod simulators;
+mod observables;
+mod parameters;
+mod noise;
 
 pub fn assert_complex_eq(a: num_complex::Complex64, b: num_complex::Complex64, epsilon: f64) {
     let diff_re = (a.re - b.re).abs();
@@ -0,0 +1,64 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::simulators::{Backend, DensityMatrixSimulator};
+use qucom_rs::qasm::generator::QasmInstruction;
+use ndarray::Array2;
+use num_complex::Complex64;
+use crate::{assert_complex_eq, assert_float_eq};
+use qucom_rs::QucomError;
+
+#[test]
+fn bell_state_density_matrix() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(2);
+    circuit.set_backend(Backend::DensityMatrix).h(0)?.cx(0, 1)?;
+    circuit.execute()?;
+
+    let rho = circuit.density_matrix().unwrap();
+    for (r, c) in [(0, 0), (0, 3), (3, 0), (3, 3)] {
+        assert_complex_eq(rho[[r, c]], Complex64::new(0.5, 0.0), 1e-12);
+    }
+    assert_complex_eq(rho[[1, 1]], Complex64::new(0.0, 0.0), 1e-12);
+    Ok(())
+}
+
+#[test]
+fn reset_is_a_non_selective_channel() -> Result<(), QucomError> {
+    // resetting half of a bell pair leaves |0⟩⟨0| ⊗ I/2, a mixed state
+    let mut sim = DensityMatrixSimulator::new(2);
+    sim.execute(&[
+        QasmInstruction::H(0),
+        QasmInstruction::CX(0, 1),
+        QasmInstruction::Reset(0),
+    ])?;
+
+    let rho = sim.density_matrix();
+    assert_complex_eq(rho[[0, 0]], Complex64::new(0.5, 0.0), 1e-12);
+    assert_complex_eq(rho[[1, 1]], Complex64::new(0.5, 0.0), 1e-12);
+    assert_complex_eq(rho[[0, 1]], Complex64::new(0.0, 0.0), 1e-12);
+    assert_float_eq(sim.purity(), 0.5, 1e-12);
+    Ok(())
+}
+
+#[test]
+fn mixed_initial_state() -> Result<(), QucomError> {
+    // X leaves the maximally mixed state unchanged
+    let rho = Array2::<Complex64>::eye(2) * Complex64::new(0.5, 0.0);
+    let mut circuit = QuantumCircuit::new(1);
+    circuit.initialize_density_matrix(&rho)?.x(0)?;
+    circuit.execute()?;
+
+    let rho = circuit.density_matrix().unwrap();
+    assert_complex_eq(rho[[0, 0]], Complex64::new(0.5, 0.0), 1e-12);
+    assert_complex_eq(rho[[1, 1]], Complex64::new(0.5, 0.0), 1e-12);
+    assert_eq!(circuit.backend(), Backend::DensityMatrix);
+    Ok(())
+}
+
+#[test]
+fn measurement_matches_statevector() -> Result<(), QucomError> {
+    let mut circuit = QuantumCircuit::new(3);
+    circuit.set_backend(Backend::DensityMatrix).x(0)?.cx(0, 2)?.measure_qubit(2, 0)?.measure()?;
+    let results = circuit.execute()?;
+
+    assert_eq!(results, vec!["1", "101"]);
+    Ok(())
+}
@@ -0,0 +1,3 @@
+mod density_matrix;
+mod stabilizer;
+mod mps;
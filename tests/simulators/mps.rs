@@ -0,0 +1,80 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::qasm::QasmExecutor;
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::simulators::{Backend, MpsConfig, MpsSimulator};
+use qucom_rs::states::multi_qubit::zero_state;
+use qucom_rs::QucomError;
+
+#[test]
+fn matches_statevector_with_non_adjacent_gates() -> Result<(), QucomError> {
+    let n = 5;
+    let instructions = vec![
+        QasmInstruction::H(0),
+        QasmInstruction::Ry(0.7, 3),
+        QasmInstruction::CX(0, 4),
+        QasmInstruction::U(0.3, 1.1, -0.4, 2),
+        QasmInstruction::CZ(3, 1),
+        QasmInstruction::Swap(4, 1),
+        QasmInstruction::CCX(vec![4, 0, 2]),
+        QasmInstruction::T(2, true),
+        QasmInstruction::CX(2, 0),
+        QasmInstruction::Rx(1.3, 4),
+    ];
+
+    let mut state = zero_state(n);
+    QasmExecutor::execute(&instructions, &mut state, n)?;
+    let mut mps = MpsSimulator::new(n);
+    mps.execute(&instructions)?;
+
+    let dense = mps.to_statevector();
+    for (a, b) in dense.iter().zip(state.iter()) {
+        assert!((a - b).norm() < 1e-10);
+    }
+    assert!(mps.truncation_error() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn ghz_on_a_hundred_qubits() -> Result<(), QucomError> {
+    let n = 100;
+    let mut circuit = QuantumCircuit::new(n);
+    circuit.set_backend(Backend::MatrixProductState).h(0)?;
+    for q in 0..n - 1 {
+        circuit.cx(q, q + 1)?;
+    }
+    circuit.rz(0.4, 50)?.measure()?;
+    let results = circuit.execute()?;
+
+    assert!(results[0] == "0".repeat(n) || results[0] == "1".repeat(n));
+    assert!(circuit.truncation_error().unwrap() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn bond_dimension_limit_reports_truncation() -> Result<(), QucomError> {
+    let config = MpsConfig { max_bond_dimension: 1, ..MpsConfig::default() };
+    let mut mps = MpsSimulator::with_config(2, config);
+    mps.execute(&[QasmInstruction::H(0), QasmInstruction::CX(0, 1)])?;
+
+    assert_eq!(mps.bond_dimensions(), vec![1]);
+    assert!((mps.truncation_error() - 0.5).abs() < 1e-10);
+    // the remaining product state is still normalized
+    let norm: f64 = mps.to_statevector().iter().map(|c| c.norm_sqr()).sum();
+    assert!((norm - 1.0).abs() < 1e-10);
+    Ok(())
+}
+
+#[test]
+fn mid_circuit_measurement_collapses() -> Result<(), QucomError> {
+    let mut mps = MpsSimulator::new(3);
+    let results = mps.execute(&[
+        QasmInstruction::H(0),
+        QasmInstruction::CX(0, 2),
+        QasmInstruction::Measure(0, 0),
+        QasmInstruction::MeasureAll,
+    ])?;
+
+    let bit = &results[0];
+    assert_eq!(results[1], format!("{}0{}", bit, bit));
+    Ok(())
+}
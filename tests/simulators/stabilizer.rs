@@ -0,0 +1,60 @@
+use qucom_rs::circuits::QuantumCircuit;
+use qucom_rs::simulators::{Backend, StabilizerSimulator};
+use qucom_rs::qasm::generator::QasmInstruction;
+use qucom_rs::QucomError;
+
+#[test]
+fn bell_pair_stabilizers() -> Result<(), QucomError> {
+    let mut sim = StabilizerSimulator::new(2);
+    sim.execute(&[QasmInstruction::H(0), QasmInstruction::CX(0, 1)])?;
+
+    let mut stabilizers = sim.stabilizers();
+    stabilizers.sort();
+    assert_eq!(stabilizers, vec!["+XX", "+ZZ"]);
+    Ok(())
+}
+
+#[test]
+fn ghz_on_a_thousand_qubits() -> Result<(), QucomError> {
+    let n = 1000;
+    let mut circuit = QuantumCircuit::new(n);
+    circuit.set_backend(Backend::Stabilizer).h(0)?;
+    for q in 0..n - 1 {
+        circuit.cx(q, q + 1)?;
+    }
+    circuit.measure()?;
+    let results = circuit.execute()?;
+
+    assert!(results[0] == "0".repeat(n) || results[0] == "1".repeat(n));
+    Ok(())
+}
+
+#[test]
+fn deterministic_outcomes_match_statevector() -> Result<(), QucomError> {
+    // Y, S, Sdg, CZ and swap, with a mid-circuit measurement
+    let build = |backend: Backend| -> Result<Vec<String>, QucomError> {
+        let mut circuit = QuantumCircuit::new(3);
+        circuit.set_backend(backend);
+        circuit.h(0)?.s(0)?.s(0)?.h(0)?.y(1)?.cz(0, 1)?.sdg(2)?.swap(1, 2)?.measure_qubit(2, 0)?.measure()?;
+        circuit.execute()
+    };
+
+    assert_eq!(build(Backend::Stabilizer)?, vec!["1", "101"]);
+    assert_eq!(build(Backend::Statevector)?, vec!["1", "101"]);
+    Ok(())
+}
+
+#[test]
+fn automatic_backend_selection() -> Result<(), QucomError> {
+    let mut clifford = QuantumCircuit::new(2);
+    clifford.set_backend(Backend::Automatic).h(0)?.cx(0, 1)?.measure()?;
+    assert_eq!(clifford.resolved_backend(), Backend::Stabilizer);
+    clifford.execute()?;
+    assert!(clifford.stabilizers().is_some());
+    assert!(clifford.state().is_none());
+
+    let mut non_clifford = QuantumCircuit::new(2);
+    non_clifford.set_backend(Backend::Automatic).h(0)?.t(0)?.measure()?;
+    assert_eq!(non_clifford.resolved_backend(), Backend::Statevector);
+    Ok(())
+}
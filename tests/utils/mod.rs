@@ -1 +1,2 @@
 mod kronecker;
+mod svd;
@@ -0,0 +1,40 @@
+use qucom_rs::utils::svd;
+use ndarray::Array2;
+use num_complex::Complex64;
+
+#[test]
+fn svd_reconstructs_complex_matrix() {
+    let a = Array2::from_shape_fn((3, 5), |(i, j)| {
+        Complex64::new((i * 5 + j) as f64 * 0.3 - 1.0, ((i + 2 * j) % 4) as f64 * 0.7)
+    });
+    let (u, sigma, vt) = svd(&a);
+
+    assert_eq!(u.dim(), (3, 3));
+    assert_eq!(vt.dim(), (3, 5));
+    assert!(sigma.windows(2).all(|w| w[0] >= w[1]));
+
+    let mut scaled = u.clone();
+    for (mut column, s) in scaled.columns_mut().into_iter().zip(&sigma) {
+        column.mapv_inplace(|c| c * *s);
+    }
+    let rebuilt = scaled.dot(&vt);
+    for (x, y) in rebuilt.iter().zip(a.iter()) {
+        assert!((x - y).norm() < 1e-10);
+    }
+
+    // U has orthonormal columns
+    let gram = u.t().mapv(|c| c.conj()).dot(&u);
+    for ((i, j), value) in gram.indexed_iter() {
+        let expected = if i == j { 1.0 } else { 0.0 };
+        assert!((value - Complex64::new(expected, 0.0)).norm() < 1e-10);
+    }
+}
+
+#[test]
+fn svd_of_rank_one_matrix() {
+    let a = Array2::from_shape_fn((4, 4), |(i, j)| Complex64::new((i + 1) as f64 * (j + 1) as f64, 0.0));
+    let (_, sigma, _) = svd(&a);
+
+    assert!((sigma[0] - 30.0).abs() < 1e-10);
+    assert!(sigma[1..].iter().all(|s| s.abs() < 1e-10));
+}